@@ -1,6 +1,18 @@
-use clap::builder::styling::{AnsiColor, Effects};
 use clap::builder::Styles;
+use clap::builder::styling::{AnsiColor, Effects};
 use clap::{Parser, Subcommand};
+use palladin_server::build::{SizeBudget, SsrFormat};
+use palladin_server::playground::DEFAULT_PLAYGROUND_ENTRY;
+use palladin_server::preview::DEFAULT_RUNTIME_CONFIG_PATH;
+use palladin_server::server::{
+    AppType, CacheRule, ChunkAddon, ChunkGroup, DEFAULT_ASSET_INLINE_LIMIT, DEFAULT_BUILD_HISTORY,
+    DEFAULT_CHUNK_FILENAMES, DEFAULT_CONNECT_TIMEOUT_SECS, DEFAULT_CSS_MODULES_PATTERN,
+    DEFAULT_DEBOUNCE_MS, DEFAULT_HTTP_TIMEOUT_SECS, DEFAULT_MAX_DIR_ENTRIES, Entrypoint, Framework,
+    HmrRule, Inject, MetaTag, Minify, NoIndex, NonCanonical, ResourceHint, ResponseHeader, Rewrite,
+    SourceEntry, Tenant, TrailingSlash,
+};
+use palladin_server::transform::Loader;
+use palladin_shared::{DEFAULT_LOG_TARGET_WIDTH, Locale, LogTime};
 use std::path::PathBuf;
 
 #[derive(Parser)]
@@ -14,10 +26,31 @@ pub struct Cli {
     #[arg(short, long, global = true, action = clap::ArgAction::Count)]
     pub verbose: u8,
 
+    /// Time log lines start with (none, elapsed, clock)
+    #[arg(long, global = true, value_name = "TIME", default_value_t = LogTime::None)]
+    pub log_time: LogTime,
+
+    /// Width log targets are padded to, so messages line up
+    #[arg(long, global = true, value_name = "N", default_value_t = DEFAULT_LOG_TARGET_WIDTH)]
+    pub log_target_width: usize,
+
+    /// Locale of the CLI's messages (en, ids); ids prints stable message ids with their
+    /// arguments instead, for snapshot tests
+    #[arg(
+        long,
+        global = true,
+        value_name = "LOCALE",
+        env = "PALLADIN_LOCALE",
+        default_value_t = Locale::En
+    )]
+    pub locale: Locale,
+
     #[command(subcommand)]
     pub command: Commands,
 }
 
+// Parsed once at startup, boxing the dev options wouldn't buy anything
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand)]
 pub enum Commands {
     /// Run the development server
@@ -30,13 +63,523 @@ pub enum Commands {
         #[arg(short, long, default_value = "8080")]
         port: u16,
 
-        /// Root directory for serving files
-        #[arg(long, default_value = ".")]
-        root: PathBuf,
+        /// Root directory for serving files (defaults to the nearest directory with a package.json)
+        #[arg(long)]
+        root: Option<PathBuf>,
+
+        /// Entrypoint file to bundle (e.g., src/index.tsx), inferred from the module scripts of
+        /// index.html when omitted.
+        /// Repeatable; use NAME=PATH to name the emitted chunk
+        #[arg(short, long, value_name = "[NAME=]PATH")]
+        entrypoint: Vec<Entrypoint>,
+
+        /// Additional `host:port` addresses to serve on (repeatable)
+        #[arg(long = "listen", value_name = "ADDR")]
+        listen: Vec<String>,
+
+        /// Framework preset (react, vue, svelte, solid, vanilla), detected from package.json when omitted
+        #[arg(long)]
+        framework: Option<Framework>,
+
+        /// Transpile targets (e.g., es2017,chrome80), read from browserslist when omitted
+        #[arg(long, value_delimiter = ',')]
+        target: Vec<String>,
+
+        /// Pattern of the scoped class names of *.module.css files, built from [name], [local] and [hash]
+        #[arg(long, value_name = "PATTERN", default_value = DEFAULT_CSS_MODULES_PATTERN)]
+        css_modules_pattern: String,
+
+        /// Inline imported assets smaller than this many bytes as data: URLs, 0 to never inline
+        #[arg(long, value_name = "BYTES", default_value_t = DEFAULT_ASSET_INLINE_LIMIT)]
+        asset_inline_limit: usize,
+
+        /// Cache-Control header of served files matching a glob, e.g. "assets/**=max-age=3600"
+        /// (repeatable, the first matching rule wins)
+        #[arg(long, value_name = "PATTERN=VALUE")]
+        cache_control: Vec<CacheRule>,
+
+        /// How routes without a file are answered (spa, mpa, custom): spa falls back to
+        /// index.html, mpa only serves existing pages and custom never answers with HTML
+        #[arg(long, value_name = "TYPE", default_value_t = AppType::Spa)]
+        app_type: AppType,
+
+        /// File served for routes matching a glob, checked before the app type, e.g.
+        /// "app/**=app/index.html" (repeatable, the first matching rule wins)
+        #[arg(long, value_name = "PATTERN=PATH")]
+        rewrite: Vec<Rewrite>,
+
+        /// Header added to every response, e.g. "Cross-Origin-Opener-Policy: same-origin"
+        /// (repeatable)
+        #[arg(long, value_name = "NAME: VALUE")]
+        header: Vec<ResponseHeader>,
+
+        /// Record all requests into a HAR file
+        #[arg(long, value_name = "FILE")]
+        har: Option<PathBuf>,
+
+        /// Include request and response bodies in the HAR file
+        #[arg(long, requires = "har")]
+        har_bodies: bool,
+
+        /// Glob of files whose changes are ignored, relative to the root (repeatable)
+        #[arg(long = "watch-ignore", value_name = "GLOB")]
+        watch_ignore: Vec<String>,
+
+        /// Additional directory to watch for changes, e.g. a workspace package (repeatable)
+        #[arg(long = "watch-root", value_name = "DIR")]
+        watch_root: Vec<PathBuf>,
+
+        /// Watch packages symlinked into node_modules, e.g. by `pnpm link`
+        #[arg(long)]
+        follow_symlinks: bool,
+
+        /// Milliseconds to wait for more changes before rebuilding
+        #[arg(long, value_name = "MS", default_value_t = DEFAULT_DEBOUNCE_MS)]
+        watch_debounce: u64,
+
+        /// Poll for changes every MS milliseconds (default 1000) instead of using OS events,
+        /// e.g. in Docker or WSL2
+        #[arg(long, value_name = "MS", num_args = 0..=1)]
+        watch_poll: Option<Option<u64>>,
+
+        /// Directory relative to the root that is neither watched nor served, e.g. large fixtures (repeatable)
+        #[arg(long, value_name = "DIR")]
+        exclude: Vec<PathBuf>,
+
+        /// Skip watching directories with more entries than this
+        #[arg(long, value_name = "N", default_value_t = DEFAULT_MAX_DIR_ENTRIES)]
+        max_dir_entries: usize,
+
+        /// How pages are updated when matching files outside the bundle change, ACTION being
+        /// refresh, reload, ignore or event:NAME (repeatable, checked before the defaults)
+        #[arg(long, value_name = "GLOB=ACTION")]
+        hmr_rule: Vec<HmrRule>,
+
+        /// Vite or Rollup plugin module to run through node, a path or package name (repeatable)
+        #[arg(long, value_name = "MODULE")]
+        js_plugin: Vec<String>,
+
+        /// Import workspace packages from their sources instead of their build output
+        #[arg(long)]
+        workspace_sources: bool,
+
+        /// Source entry of a workspace package, relative to it, when it's neither the `source` of
+        /// its package.json nor src/index.* (repeatable)
+        #[arg(long, value_name = "NAME=PATH", requires = "workspace_sources")]
+        source_entry: Vec<SourceEntry>,
+
+        /// Install packages reported missing with the project's package manager, asking first
+        #[arg(long)]
+        install: bool,
+
+        /// Install missing packages without asking
+        #[arg(short, long, requires = "install")]
+        yes: bool,
+
+        /// Accept unsaved editor buffers at /__buffers and serve them over the files on disk
+        #[arg(long)]
+        hot_typing: bool,
+
+        /// Answer /favicon.ico and Chrome devtools probes with a 404 like any missing file
+        #[arg(long)]
+        no_probe_routes: bool,
+
+        /// List the files of directories without an index.html, with their size and modification
+        /// time, instead of treating them as routes
+        #[arg(long)]
+        directory_listing: bool,
+
+        /// HTML page answering page loads of missing files and routes, relative to the root, e.g.
+        /// 404.html; {{status}} and {{message}} are replaced with the error
+        #[arg(long, value_name = "FILE")]
+        not_found_page: Option<PathBuf>,
+
+        /// HTML page answering page loads that failed with a server error, relative to the root,
+        /// with the same placeholders as --not-found-page
+        #[arg(long, value_name = "FILE")]
+        error_page: Option<PathBuf>,
+
+        /// Serve a deny-all robots.txt and X-Robots-Tag: noindex (auto, always, never); auto does
+        /// so when bound to a non-loopback address or reached through a proxy or tunnel
+        #[arg(long, value_name = "MODE", default_value_t = NoIndex::Auto)]
+        noindex: NoIndex,
+
+        /// Import added to modules using NAME without declaring it, as NAME=MODULE for the
+        /// default export, NAME=MODULE#EXPORT or NAME=MODULE#* (repeatable), e.g. React=react
+        #[arg(long, value_name = "NAME=MODULE")]
+        inject: Vec<Inject>,
+
+        /// External origin every HTML page preconnects to, as ORIGIN, dns-prefetch=ORIGIN or
+        /// crossorigin=ORIGIN (repeatable)
+        #[arg(long, value_name = "[KIND=]ORIGIN")]
+        resource_hint: Vec<ResourceHint>,
+
+        /// Language of every HTML page, set as <html lang>
+        #[arg(long, value_name = "LANG", env = "PALLADIN_HTML_LANG")]
+        html_lang: Option<String>,
+
+        /// Text appended to the <title> of every HTML page, e.g. " (staging)"
+        #[arg(long, value_name = "TEXT", env = "PALLADIN_TITLE_SUFFIX")]
+        title_suffix: Option<String>,
+
+        /// <meta> tag set on every HTML page, replacing the page's own, e.g. theme-color=#0f172a
+        /// (repeatable, separated by ; in the environment variable)
+        #[arg(
+            long,
+            value_name = "NAME=CONTENT",
+            env = "PALLADIN_META",
+            value_delimiter = ';'
+        )]
+        meta: Vec<MetaTag>,
+
+        /// Canonical form of page URLs (always, never): whether /about ends with a slash. Every
+        /// form is served when omitted
+        #[arg(long, value_name = "MODE")]
+        trailing_slash: Option<TrailingSlash>,
+
+        /// How page URLs other than the canonical one are answered (redirect, serve, 404)
+        #[arg(long, value_name = "ACTION", default_value_t = NonCanonical::Redirect)]
+        non_canonical_urls: NonCanonical,
+
+        /// Server entry to bundle for SSR, served at /__palladin/ssr for the framework's JS runtime to
+        /// execute, stack traces posted to /__palladin/ssr/stacktrace are mapped to the sources
+        #[arg(long, value_name = "ENTRY")]
+        ssr: Option<PathBuf>,
+
+        /// Don't answer API requests with the files in mocks/, e.g. mocks/api/users.json for
+        /// /api/users
+        #[arg(long)]
+        no_mocks: bool,
+
+        /// Number of builds kept to roll back to through /__history
+        #[arg(long, value_name = "N", default_value_t = DEFAULT_BUILD_HISTORY)]
+        build_history: usize,
+
+        /// Serve the project in DIR under /NAME/, named after the directory by default.
+        /// Repeatable, each project gets its own bundler, caches and HMR channel
+        #[arg(long, value_name = "[NAME=]DIR", conflicts_with = "root")]
+        tenant: Vec<Tenant>,
+
+        /// Serve every git worktree of the current repository under /BRANCH/
+        #[arg(long, conflicts_with = "root")]
+        worktrees: bool,
+    },
+    /// Bundle the project for production
+    Build {
+        /// Project root directory (defaults to the nearest directory with a package.json)
+        #[arg(long)]
+        root: Option<PathBuf>,
+
+        /// Entrypoint file to bundle (e.g., src/index.tsx), inferred from the module scripts of
+        /// index.html when omitted.
+        /// Repeatable; use NAME=PATH to name the emitted chunk
+        #[arg(short, long, value_name = "[NAME=]PATH")]
+        entrypoint: Vec<Entrypoint>,
+
+        /// Framework preset (react, vue, svelte, solid, vanilla), detected from package.json when omitted
+        #[arg(long)]
+        framework: Option<Framework>,
+
+        /// Transpile targets (e.g., es2017,chrome80), read from browserslist when omitted
+        #[arg(long, value_delimiter = ',')]
+        target: Vec<String>,
+
+        /// Pattern of the scoped class names of *.module.css files, built from [name], [local] and [hash]
+        #[arg(long, value_name = "PATTERN", default_value = DEFAULT_CSS_MODULES_PATTERN)]
+        css_modules_pattern: String,
+
+        /// Inline imported assets smaller than this many bytes as data: URLs, 0 to never inline
+        #[arg(long, value_name = "BYTES", default_value_t = DEFAULT_ASSET_INLINE_LIMIT)]
+        asset_inline_limit: usize,
+
+        /// Sign checksums.txt with the HMAC-SHA256 key read from this file
+        #[arg(long, value_name = "FILE")]
+        sign_key: Option<PathBuf>,
+
+        /// Fail on problems that only show up on other platforms, like imports with the wrong casing
+        #[arg(long)]
+        strict: bool,
+
+        /// Minify the output (true, false, whitespace-only)
+        #[arg(long, value_name = "MODE", default_value_t = Minify::On)]
+        minify: Minify,
+
+        /// Split modules whose id matches REGEX into a chunk of their own, replacing the built-in
+        /// vendor groups (repeatable)
+        #[arg(long, value_name = "NAME[:PRIORITY[:MIN_SIZE]]=REGEX")]
+        chunk_group: Vec<ChunkGroup>,
+
+        /// Filename pattern of entry chunks, built from [name] and [hash]; defaults to
+        /// [name].js, or [name]-[hash].js with --manifest
+        #[arg(long, value_name = "PATTERN")]
+        entry_filenames: Option<String>,
+
+        /// Filename pattern of shared and lazily loaded chunks, built from [name] and [hash]
+        #[arg(long, value_name = "PATTERN", default_value = DEFAULT_CHUNK_FILENAMES)]
+        chunk_filenames: String,
+
+        /// Filename pattern of every emitted asset, built from [name], [hash] and [extname];
+        /// images and fonts get directories of their own when omitted
+        #[arg(long, value_name = "PATTERN")]
+        asset_filenames: Option<String>,
+
+        /// Code prepended to every emitted chunk, e.g. a license comment
+        #[arg(long, value_name = "CODE")]
+        banner: Option<String>,
+
+        /// Code appended to every emitted chunk
+        #[arg(long, value_name = "CODE")]
+        footer: Option<String>,
+
+        /// Banner of the chunks named like CHUNK (a glob) in place of --banner, e.g.
+        /// "vendor=/*! Licenses: vendor.LICENSE.txt */" (repeatable)
+        #[arg(long, value_name = "CHUNK=CODE")]
+        chunk_banner: Vec<ChunkAddon>,
+
+        /// Footer of the chunks named like CHUNK (a glob) in place of --footer (repeatable)
+        #[arg(long, value_name = "CHUNK=CODE")]
+        chunk_footer: Vec<ChunkAddon>,
+
+        /// Import added to modules using NAME without declaring it, as NAME=MODULE for the
+        /// default export, NAME=MODULE#EXPORT or NAME=MODULE#* (repeatable), e.g. React=react
+        #[arg(long, value_name = "NAME=MODULE")]
+        inject: Vec<Inject>,
+
+        /// External origin every HTML page preconnects to, as ORIGIN, dns-prefetch=ORIGIN or
+        /// crossorigin=ORIGIN (repeatable)
+        #[arg(long, value_name = "[KIND=]ORIGIN")]
+        resource_hint: Vec<ResourceHint>,
+
+        /// Language of every HTML page, set as <html lang>
+        #[arg(long, value_name = "LANG", env = "PALLADIN_HTML_LANG")]
+        html_lang: Option<String>,
+
+        /// Text appended to the <title> of every HTML page, e.g. " (staging)"
+        #[arg(long, value_name = "TEXT", env = "PALLADIN_TITLE_SUFFIX")]
+        title_suffix: Option<String>,
+
+        /// <meta> tag set on every HTML page, replacing the page's own, e.g. theme-color=#0f172a
+        /// (repeatable, separated by ; in the environment variable)
+        #[arg(
+            long,
+            value_name = "NAME=CONTENT",
+            env = "PALLADIN_META",
+            value_delimiter = ';'
+        )]
+        meta: Vec<MetaTag>,
+
+        /// Canonical form of page URLs (always, never): whether /about ends with a slash. Every
+        /// form is served when omitted
+        #[arg(long, value_name = "MODE")]
+        trailing_slash: Option<TrailingSlash>,
+
+        /// How page URLs other than the canonical one are answered (redirect, serve, 404)
+        #[arg(long, value_name = "ACTION", default_value_t = NonCanonical::Redirect)]
+        non_canonical_urls: NonCanonical,
+
+        /// Vite or Rollup plugin module to run through node, a path or package name (repeatable)
+        #[arg(long, value_name = "MODULE")]
+        js_plugin: Vec<String>,
+
+        /// Import workspace packages from their sources instead of their build output
+        #[arg(long)]
+        workspace_sources: bool,
+
+        /// Source entry of a workspace package, relative to it, when it's neither the `source` of
+        /// its package.json nor src/index.* (repeatable)
+        #[arg(long, value_name = "NAME=PATH", requires = "workspace_sources")]
+        source_entry: Vec<SourceEntry>,
+
+        /// Install packages reported missing with the project's package manager, asking first
+        #[arg(long)]
+        install: bool,
+
+        /// Install missing packages without asking
+        #[arg(short, long, requires = "install")]
+        yes: bool,
+
+        /// Size limit of the emitted files matching GLOB, in bytes or with a kb or mb suffix,
+        /// checked in build-report.json (repeatable)
+        #[arg(long, value_name = "GLOB=SIZE")]
+        budget: Vec<SizeBudget>,
+
+        /// Record the modules of each chunk with their gzip sizes in build-report.json, for
+        /// `palladin analyze`
+        #[arg(long)]
+        analyze: bool,
+
+        /// Write chunk sizes, module lists and phase timings as JSON to FILE, implies --analyze
+        #[arg(long, value_name = "FILE")]
+        stats: Option<PathBuf>,
+
+        /// Write a manifest.json mapping source files to the emitted ones, for backend templates
+        #[arg(long)]
+        manifest: bool,
+
+        /// Server entry to bundle for Node into <dir>-server next to the build directory, relative
+        /// to the root. Also writes an ssr-manifest.json listing the files to preload for each
+        /// source module
+        #[arg(long, value_name = "ENTRY")]
+        ssr: Option<PathBuf>,
+
+        /// Module format of the server bundle (esm, cjs)
+        #[arg(long, value_name = "FORMAT", default_value_t = SsrFormat::Esm)]
+        ssr_format: SsrFormat,
+    },
+    /// Serve a production build locally
+    Preview {
+        /// Host address to bind the server to
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+
+        /// Port to serve on, each additional profile uses the next port
+        #[arg(short, long, default_value = "4173")]
+        port: u16,
+
+        /// Project root directory (defaults to the nearest directory with a package.json)
+        #[arg(long)]
+        root: Option<PathBuf>,
+
+        /// Build directory to serve, relative to the root
+        #[arg(long, default_value = "dist")]
+        dir: PathBuf,
+
+        /// Serve `runtime-config.<PROFILE>.json` from the root as the app's runtime config (repeatable)
+        #[arg(long, value_name = "PROFILE")]
+        profile: Vec<String>,
+
+        /// URL path the app fetches its runtime config from
+        #[arg(long, value_name = "PATH", default_value = DEFAULT_RUNTIME_CONFIG_PATH)]
+        runtime_config_path: String,
+
+        /// Send X-Content-Type-Options, Referrer-Policy and a report-only Content-Security-Policy
+        /// suggested from the build, like a hardened production host
+        #[arg(long)]
+        security_headers: bool,
+
+        /// Canonical form of page URLs (always, never): whether /about ends with a slash. Every
+        /// form is served when omitted
+        #[arg(long, value_name = "MODE")]
+        trailing_slash: Option<TrailingSlash>,
+
+        /// How page URLs other than the canonical one are answered (redirect, serve, 404)
+        #[arg(long, value_name = "ACTION", default_value_t = NonCanonical::Redirect)]
+        non_canonical_urls: NonCanonical,
+    },
+    /// Serve an in-browser editor whose files are kept in memory
+    Playground {
+        /// Host address to bind the server to
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+
+        /// Port to serve on
+        #[arg(short, long, default_value = "5174")]
+        port: u16,
+
+        /// Directory to copy the initial files from, a starter file is used when omitted
+        #[arg(long, value_name = "DIR")]
+        from: Option<PathBuf>,
+
+        /// Entry module among the files
+        #[arg(short, long, default_value = DEFAULT_PLAYGROUND_ENTRY)]
+        entry: String,
+
+        /// Filename pattern of lazily loaded chunks, built from [name] and [hash]
+        #[arg(long, value_name = "PATTERN", default_value = DEFAULT_CHUNK_FILENAMES)]
+        chunk_filenames: String,
+    },
+    /// Upload the files that changed since the last deploy
+    Deploy {
+        /// Where to deploy: `s3://bucket/prefix` or an rsync destination such as `user@host:/var/www`
+        target: String,
+
+        /// Project root directory (defaults to the nearest directory with a package.json)
+        #[arg(long)]
+        root: Option<PathBuf>,
+
+        /// Build directory to deploy, relative to the root
+        #[arg(long, default_value = "dist")]
+        dir: PathBuf,
+
+        /// Seconds a request to the target may take
+        #[arg(long, value_name = "SECS", default_value_t = DEFAULT_HTTP_TIMEOUT_SECS)]
+        timeout: u64,
+
+        /// Seconds connecting to the target may take
+        #[arg(long, value_name = "SECS", default_value_t = DEFAULT_CONNECT_TIMEOUT_SECS)]
+        connect_timeout: u64,
+
+        /// Proxy to send requests through, overriding HTTPS_PROXY, HTTP_PROXY and ALL_PROXY
+        #[arg(long, value_name = "URL")]
+        proxy: Option<String>,
+
+        /// PEM file of a certificate authority to trust in addition to the built-in ones
+        /// (repeatable)
+        #[arg(long, value_name = "FILE")]
+        ca_cert: Vec<PathBuf>,
+
+        /// Accept invalid TLS certificates, only meant for debugging
+        #[arg(long)]
+        insecure: bool,
+    },
+    /// Work with the build-report.json files written by builds
+    Report {
+        #[command(subcommand)]
+        command: ReportCommands,
+    },
+    /// Explain what the last build is made of
+    Analyze {
+        #[command(subcommand)]
+        command: AnalyzeCommands,
+    },
+    /// Compile a single module read from stdin and print it to stdout, e.g.
+    /// `palladin transform --loader tsx < input.tsx > output.js`
+    Transform {
+        /// How the input is parsed (js, jsx, ts, tsx, json)
+        #[arg(long)]
+        loader: Loader,
+
+        /// Minify the output (true, false, whitespace-only)
+        #[arg(long, value_name = "MODE", default_value_t = Minify::Off)]
+        minify: Minify,
+
+        /// Transpile targets (e.g., es2017,chrome80)
+        #[arg(long, value_delimiter = ',')]
+        target: Vec<String>,
+
+        /// Write a source map of the output to FILE and reference it from the output
+        #[arg(long, value_name = "FILE")]
+        sourcemap: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ReportCommands {
+    /// Print what got worse between two builds, failing if anything did
+    Compare {
+        /// Report of the build compared against, e.g. from the base branch
+        before: PathBuf,
+
+        /// Report of the new build
+        after: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum AnalyzeCommands {
+    /// List the heaviest packages bundled into the last build
+    Deps {
+        /// Project root directory (defaults to the nearest directory with a package.json)
+        #[arg(long)]
+        root: Option<PathBuf>,
+
+        /// Build directory holding build-report.json, relative to the root
+        #[arg(long, default_value = "dist")]
+        dir: PathBuf,
 
-        /// Entrypoint file to bundle (e.g., src/index.tsx)
-        #[arg(short, long)]
-        entrypoint: PathBuf,
+        /// Number of packages to list
+        #[arg(short = 'n', long, default_value = "10")]
+        top: usize,
     },
 }
 
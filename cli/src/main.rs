@@ -1,19 +1,42 @@
 mod cli;
-mod logger;
+mod messages;
 
-use crate::cli::{Cli, Commands};
-use crate::logger::LOGGER;
+use crate::cli::{AnalyzeCommands, Cli, Commands, ReportCommands};
+use crate::messages::Message;
 use clap::Parser;
-use log::{info, LevelFilter};
-use palladin_server::server::{Server, ServerConfig};
-use palladin_shared::{canonicalize_with_strip, PalladinResult};
-use std::env::set_current_dir;
-use std::path::PathBuf;
+use log::{LevelFilter, info, warn};
+use palladin_server::build::{
+    BUILD_REPORT_FILE, BuildOptions, BuildReport, SsrOptions, build, compare_reports,
+};
+use palladin_server::deploy::{RsyncAdapter, S3Adapter, S3Options, deploy};
+use palladin_server::playground::{PlaygroundOptions, load_files, playground};
+use palladin_server::preview::{PreviewOptions, RuntimeConfig, preview};
+use palladin_server::server::{
+    AutoInstall, CachePolicy, DEFAULT_CHUNK_FILENAMES, DEFAULT_ENTRY_FILENAMES,
+    DEFAULT_POLL_INTERVAL_MS, Entrypoint, ErrorPages, HarOptions, HmrPolicy, HtmlMeta, HttpOptions,
+    JsPlugins, Mdx, Mocks, NonCanonical, PackageManager, PostCss, Server, ServerConfig, Svelte,
+    Tailwind, Tenant, ToolRequirement, TrailingSlash, UrlPolicy, WatchOptions, check_toolchain,
+    find_mdx, find_mocks, find_postcss_config, find_project_root, find_svelte, find_tailwind,
+    git_worktrees, infer_entrypoints, on_terminal_line, serve_tenants,
+};
+use palladin_server::transform::{TransformOptions, transform};
+use palladin_shared::{
+    ColoredLogger, LogFormat, PalladinError, PalladinResult, canonicalize_with_strip, set_locale,
+    set_message_root,
+};
+use std::collections::BTreeMap;
+use std::env::{current_dir, set_current_dir};
+use std::fs;
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinSet;
 
 #[tokio::main]
 async fn main() -> PalladinResult {
     let cli = Cli::parse();
+    set_locale(cli.locale);
 
     // Map verbosity count (-v, -vv, -vvv) to log levels
     let log_level = match cli.verbose {
@@ -23,8 +46,8 @@ async fn main() -> PalladinResult {
         _ => LevelFilter::Trace,
     };
 
-    log::set_logger(&LOGGER)
-        .map(|()| log::set_max_level(log_level))
+    ColoredLogger::new(LogFormat::new(cli.log_time, cli.log_target_width))
+        .init(log_level)
         .expect("Failed to set logger");
 
     match cli.command {
@@ -33,20 +56,638 @@ async fn main() -> PalladinResult {
             port,
             root,
             entrypoint,
+            listen,
+            framework,
+            target,
+            css_modules_pattern,
+            asset_inline_limit,
+            cache_control,
+            app_type,
+            rewrite,
+            header,
+            har,
+            har_bodies,
+            watch_ignore,
+            watch_root,
+            follow_symlinks,
+            watch_debounce,
+            watch_poll,
+            exclude,
+            max_dir_entries,
+            hmr_rule,
+            js_plugin,
+            workspace_sources,
+            source_entry,
+            install,
+            yes,
+            hot_typing,
+            no_probe_routes,
+            directory_listing,
+            not_found_page,
+            error_page,
+            noindex,
+            inject,
+            resource_hint,
+            html_lang,
+            title_suffix,
+            meta,
+            trailing_slash,
+            non_canonical_urls,
+            ssr,
+            no_mocks,
+            build_history,
+            tenant,
+            worktrees,
         } => {
-            let root = canonicalize_with_strip(&root)?;
-            set_current_dir(&root)?;
-
-            let mut config = ServerConfig::new()
+            let config = ServerConfig::new()
                 .with_host(host)
                 .with_port(port)
-                .with_root(root)
-                .with_entrypoint(entrypoint);
+                .with_listen(listen)
+                .with_framework(framework)
+                .with_target(target)
+                .with_css_modules_pattern(css_modules_pattern)
+                .with_asset_inline_limit(asset_inline_limit)
+                .with_cache_policy(CachePolicy {
+                    rules: cache_control,
+                })
+                .with_app_type(app_type)
+                .with_rewrites(rewrite)
+                .with_headers(
+                    header
+                        .into_iter()
+                        .map(|header| (header.name, header.value))
+                        .collect(),
+                )
+                .with_hot_typing(hot_typing)
+                .with_probe_routes(!no_probe_routes)
+                .with_directory_listing(directory_listing)
+                .with_error_pages(ErrorPages {
+                    not_found: not_found_page,
+                    server_error: error_page,
+                })
+                .with_workspace_sources(workspace_sources)
+                .with_source_entries(source_entry)
+                .with_noindex(noindex)
+                .with_inject(inject)
+                .with_resource_hints(resource_hint)
+                .with_html_meta(HtmlMeta {
+                    lang: html_lang,
+                    title_suffix,
+                    tags: meta,
+                })
+                .with_url_policy(url_policy(trailing_slash, non_canonical_urls))
+                .with_ssr_entry(ssr)
+                .with_build_history(build_history)
+                .with_har(har.map(|path| HarOptions {
+                    path,
+                    capture_bodies: har_bodies,
+                }))
+                .with_watch(WatchOptions {
+                    ignored: watch_ignore,
+                    follow_symlinks,
+                    roots: watch_root,
+                    debounce_ms: watch_debounce,
+                    poll_interval_ms: watch_poll
+                        .map(|interval| interval.unwrap_or(DEFAULT_POLL_INTERVAL_MS)),
+                    excluded: exclude,
+                    max_dir_entries,
+                })
+                .with_hmr_policy(HmrPolicy::default().with_overrides(hmr_rule));
+            let auto_install = auto_install(install, yes);
+
+            let mut tenants = tenant;
+            if worktrees {
+                tenants.extend(git_worktrees(&current_dir()?)?);
+            }
+            if !tenants.is_empty() {
+                let mut configs: Vec<(String, ServerConfig)> = Vec::new();
+                for Tenant { name, root } in tenants {
+                    if configs.iter().any(|(existing, _)| *existing == name) {
+                        return Err(PalladinError::InvalidAddress(format!(
+                            "tenant /{name}/ is given more than once"
+                        )));
+                    }
+
+                    let root = canonicalize_with_strip(&root)?;
+                    let entrypoints = tenant_entrypoints(&root, &entrypoint)?;
+                    let config = config
+                        .clone()
+                        .with_root(root.clone())
+                        .with_entrypoints(entrypoints);
+                    let config = with_tools(config, &root, js_plugin.clone(), auto_install)?;
+                    let config = with_mocks(config, &root, no_mocks);
+                    configs.push((name, config));
+                }
+
+                let address = config.address();
+                let message = Message::ServingProjects {
+                    count: configs.len(),
+                    address: &address,
+                };
+                info!(target: "server", "{message}");
+                return serve_tenants(&address, configs).await;
+            }
+
+            let (root, entrypoints) = resolve_project(root, entrypoint)?;
+            let config = config.with_root(root.clone()).with_entrypoints(entrypoints);
+            let config = with_tools(config, &root, js_plugin, auto_install)?;
+            let config = with_mocks(config, &root, no_mocks);
 
             let server = Arc::new(Server::new(config)?);
-            info!(target: "server", "server running on http://{}", server.context().address());
+            for address in server.context().addresses() {
+                info!(target: "server", "{}", Message::ServerRunning { address: &address });
+            }
 
+            restart_on_key(&server);
             server.serve().await
         }
+        Commands::Build {
+            root,
+            entrypoint,
+            framework,
+            target,
+            css_modules_pattern,
+            asset_inline_limit,
+            sign_key,
+            strict,
+            minify,
+            chunk_group,
+            entry_filenames,
+            chunk_filenames,
+            asset_filenames,
+            banner,
+            footer,
+            chunk_banner,
+            chunk_footer,
+            inject,
+            resource_hint,
+            html_lang,
+            title_suffix,
+            meta,
+            trailing_slash,
+            non_canonical_urls,
+            js_plugin,
+            workspace_sources,
+            source_entry,
+            install,
+            yes,
+            budget,
+            analyze,
+            stats,
+            manifest,
+            ssr,
+            ssr_format,
+        } => {
+            // Resolve paths before changing into the project root
+            let sign_key = sign_key
+                .map(|key| canonicalize_with_strip(&key))
+                .transpose()?;
+            let stats = stats
+                .map(|stats| current_dir().map(|dir| dir.join(stats)))
+                .transpose()?;
+            let (root, entrypoints) = resolve_project(root, entrypoint)?;
+
+            // Templates reference entries through the manifest, so they can be
+            // hashed and cached like the other chunks
+            let entry_filenames = entry_filenames.unwrap_or_else(|| match manifest {
+                true => DEFAULT_CHUNK_FILENAMES.to_string(),
+                false => DEFAULT_ENTRY_FILENAMES.to_string(),
+            });
+
+            let config = ServerConfig::new()
+                .with_root(root.clone())
+                .with_entrypoints(entrypoints)
+                .with_framework(framework)
+                .with_target(target)
+                .with_css_modules_pattern(css_modules_pattern)
+                .with_asset_inline_limit(asset_inline_limit)
+                .with_strict(strict)
+                .with_minify(minify)
+                .with_chunk_groups(chunk_group)
+                .with_entry_filenames(entry_filenames)
+                .with_chunk_filenames(chunk_filenames)
+                .with_banner(banner)
+                .with_footer(footer)
+                .with_chunk_banners(chunk_banner)
+                .with_chunk_footers(chunk_footer)
+                .with_inject(inject)
+                .with_resource_hints(resource_hint)
+                .with_html_meta(HtmlMeta {
+                    lang: html_lang,
+                    title_suffix,
+                    tags: meta,
+                })
+                .with_url_policy(url_policy(trailing_slash, non_canonical_urls))
+                .with_workspace_sources(workspace_sources)
+                .with_source_entries(source_entry);
+            let config = match asset_filenames {
+                Some(pattern) => config.with_asset_filenames(pattern),
+                None => config,
+            };
+            let config = with_tools(config, &root, js_plugin, auto_install(install, yes))?;
+
+            let options = BuildOptions {
+                sign_key,
+                budgets: budget,
+                analyze,
+                stats,
+                manifest,
+                ssr: ssr.map(|entry| SsrOptions {
+                    entry,
+                    format: ssr_format,
+                }),
+            };
+            let output = build(config, options).await?;
+            info!(
+                "{}",
+                Message::Built {
+                    files: output.files.len(),
+                    dir: &output.dir
+                }
+            );
+            info!("{}", Message::SuggestedCsp { csp: &output.csp });
+            Ok(())
+        }
+        Commands::Preview {
+            host,
+            port,
+            root,
+            dir,
+            profile,
+            runtime_config_path,
+            security_headers,
+            trailing_slash,
+            non_canonical_urls,
+        } => {
+            let root = project_root(root)?;
+            let dir = canonicalize_with_strip(root.join(dir))?;
+
+            // Without profiles the runtime config is served from the build as-is
+            let profiles: Vec<Option<String>> = if profile.is_empty() {
+                vec![None]
+            } else {
+                profile.into_iter().map(Some).collect()
+            };
+
+            let mut servers = JoinSet::new();
+            let count = profiles.len();
+            for (offset, profile) in profiles.into_iter().enumerate() {
+                let profile_port = u16::try_from(offset)
+                    .ok()
+                    .and_then(|offset| port.checked_add(offset))
+                    .ok_or_else(|| {
+                        PalladinError::InvalidAddress(
+                            Message::PreviewPortsExhausted {
+                                port,
+                                profiles: count,
+                            }
+                            .to_string(),
+                        )
+                    })?;
+                let address = format!("{host}:{profile_port}");
+                let runtime_config = match &profile {
+                    Some(name) => Some(RuntimeConfig::new(
+                        runtime_config_path.clone(),
+                        canonicalize_with_strip(root.join(format!("runtime-config.{name}.json")))?,
+                    )),
+                    None => None,
+                };
+
+                let message = Message::PreviewRunning {
+                    profile: profile.as_deref(),
+                    address: &address,
+                };
+                info!(target: "server", "{message}");
+
+                let options = PreviewOptions::new(dir.clone(), address)
+                    .with_runtime_config(runtime_config)
+                    .with_security_headers(security_headers)
+                    .with_url_policy(url_policy(trailing_slash, non_canonical_urls));
+                servers.spawn(preview(options));
+            }
+
+            while let Some(result) = servers.join_next().await {
+                result.map_err(|e| PalladinError::Build(e.into()))??;
+            }
+            Ok(())
+        }
+        Commands::Playground {
+            host,
+            port,
+            from,
+            entry,
+            chunk_filenames,
+        } => {
+            let files = match from {
+                Some(dir) => load_files(&canonicalize_with_strip(dir)?)?,
+                None => BTreeMap::new(),
+            };
+
+            let address = format!("{host}:{port}");
+            info!(target: "server", "{}", Message::PlaygroundRunning { address: &address });
+            playground(PlaygroundOptions {
+                address,
+                entry,
+                files,
+                chunk_filenames,
+                plugins: Vec::new(),
+            })
+            .await
+        }
+        Commands::Deploy {
+            target,
+            root,
+            dir,
+            timeout,
+            connect_timeout,
+            proxy,
+            ca_cert,
+            insecure,
+        } => {
+            let root = project_root(root)?;
+            let dir = canonicalize_with_strip(root.join(dir))?;
+
+            let report = match target.strip_prefix("s3://") {
+                Some(location) => {
+                    let (bucket, prefix) = location.split_once('/').unwrap_or((location, ""));
+                    let config = ServerConfig::new().with_root(root).with_http(HttpOptions {
+                        timeout: Duration::from_secs(timeout),
+                        connect_timeout: Duration::from_secs(connect_timeout),
+                        proxy,
+                        ca_certs: ca_cert,
+                        accept_invalid_certs: insecure,
+                    });
+                    let adapter =
+                        S3Adapter::from_config(S3Options::from_env(bucket, prefix)?, &config)?;
+                    deploy(&adapter, &dir).await?
+                }
+                None => deploy(&RsyncAdapter::new(dir.clone(), target), &dir).await?,
+            };
+
+            info!(
+                "{}",
+                Message::Deployed {
+                    uploaded: report.uploaded.len(),
+                    unchanged: report.unchanged
+                }
+            );
+            Ok(())
+        }
+        Commands::Report {
+            command: ReportCommands::Compare { before, after },
+        } => {
+            let regressions =
+                compare_reports(&BuildReport::read(&before)?, &BuildReport::read(&after)?);
+            if regressions.is_empty() {
+                info!("{}", Message::NoRegressions);
+                return Ok(());
+            }
+
+            for regression in &regressions {
+                warn!("{}", Message::Regression { regression });
+            }
+            Err(PalladinError::Build(palladin_shared::anyhow!(
+                "{}",
+                Message::Regressions {
+                    count: regressions.len(),
+                    since: &before
+                }
+            )))
+        }
+        Commands::Analyze {
+            command: AnalyzeCommands::Deps { root, dir, top },
+        } => {
+            let path = project_root(root)?.join(dir).join(BUILD_REPORT_FILE);
+            let report = BuildReport::read(&path)?;
+            if report.chunks.is_empty() {
+                warn!("{}", Message::MissingChunkManifest { report: &path });
+                return Ok(());
+            }
+
+            let dependencies = report.dependency_sizes();
+            if dependencies.is_empty() {
+                info!("{}", Message::NoBundledPackages);
+                return Ok(());
+            }
+            for dependency in dependencies.iter().take(top) {
+                info!("{}", Message::BundledPackage { dependency });
+            }
+            Ok(())
+        }
+        Commands::Transform {
+            loader,
+            minify,
+            target,
+            sourcemap,
+        } => {
+            let mut source = String::new();
+            io::stdin().read_to_string(&mut source)?;
+
+            let mut options = TransformOptions::new(loader);
+            options.minify = minify;
+            options.target = target;
+            options.sourcemap = sourcemap.is_some();
+            let output = transform(source, &options).await?;
+
+            let mut code = output.code;
+            if let (Some(path), Some(map)) = (sourcemap, output.map) {
+                fs::write(&path, map)?;
+                let name = path.file_name().unwrap_or(path.as_os_str());
+                code.push_str(&format!(
+                    "\n//# sourceMappingURL={}\n",
+                    name.to_string_lossy()
+                ));
+            }
+            io::stdout().write_all(code.as_bytes())?;
+            Ok(())
+        }
+    }
+}
+
+/// Resolves the project root and entrypoints, inferring whichever wasn't given,
+/// and changes into the root.
+fn resolve_project(
+    root: Option<PathBuf>,
+    entrypoints: Vec<Entrypoint>,
+) -> PalladinResult<(PathBuf, Vec<Entrypoint>)> {
+    let root = canonicalize_with_strip(project_root(root)?)?;
+    set_current_dir(&root)?;
+
+    if !entrypoints.is_empty() {
+        return Ok((root, entrypoints));
+    }
+
+    let entrypoints = inferred_entrypoints(&root)?;
+    Ok((root, entrypoints))
+}
+
+/// Returns the entrypoints of the tenant project in `root`, the given ones
+/// taken relative to it.
+fn tenant_entrypoints(root: &Path, entrypoints: &[Entrypoint]) -> PalladinResult<Vec<Entrypoint>> {
+    if entrypoints.is_empty() {
+        return inferred_entrypoints(root);
+    }
+
+    Ok(entrypoints
+        .iter()
+        .map(|entry| Entrypoint {
+            name: entry.name.clone(),
+            path: root.join(&entry.path),
+        })
+        .collect())
+}
+
+/// Returns the entrypoints of the project in `root`, the module scripts of
+/// its `index.html` or a conventional `src/` entry.
+fn inferred_entrypoints(root: &Path) -> PalladinResult<Vec<Entrypoint>> {
+    let entrypoints = infer_entrypoints(root);
+    if entrypoints.is_empty() {
+        return Err(PalladinError::FileNotFound(
+            Message::NoEntrypoint { root }.to_string(),
+        ));
+    }
+
+    for entrypoint in &entrypoints {
+        info!("{}", Message::InferredEntrypoint { path: entrypoint });
+    }
+    Ok(entrypoints.into_iter().map(Entrypoint::from).collect())
+}
+
+/// Serves the API mocks of the project in `root`, unless disabled.
+fn with_mocks(config: ServerConfig, root: &Path, no_mocks: bool) -> ServerConfig {
+    if no_mocks {
+        return config;
+    }
+    match find_mocks(root) {
+        Some(dir) => {
+            info!("{}", Message::ServingMocks { dir: &dir });
+            config.with_mocks(Mocks::new(dir))
+        }
+        None => config,
+    }
+}
+
+/// Adds the features that shell out to external tools to `config`, after
+/// checking the tools are available. Features whose tools are missing are
+/// left out, with a warning.
+fn with_tools(
+    mut config: ServerConfig,
+    root: &Path,
+    js_plugins: Vec<String>,
+    auto_install: AutoInstall,
+) -> PalladinResult<ServerConfig> {
+    let manager = PackageManager::detect(root);
+    let postcss_config = find_postcss_config(root);
+    let tailwind = find_tailwind(root);
+    let svelte = find_svelte(root);
+    let mdx = find_mdx(root);
+
+    let mut node_features = Vec::new();
+    if !js_plugins.is_empty() {
+        node_features.push("--js-plugin".to_string());
+    }
+    if let Some(config) = &postcss_config {
+        node_features.push(config.display().to_string());
+    }
+    if tailwind {
+        node_features.push("Tailwind".to_string());
+    }
+    if svelte {
+        node_features.push("Svelte".to_string());
+    }
+    if mdx {
+        node_features.push("MDX".to_string());
+    }
+
+    let mut requirements = Vec::new();
+    if !node_features.is_empty() {
+        requirements.push(ToolRequirement::node(node_features.join(" and ")));
+    }
+    if auto_install != AutoInstall::Disabled {
+        requirements.push(ToolRequirement::package_manager(manager, "--install"));
+    }
+    let toolchain = check_toolchain(&requirements);
+
+    if !js_plugins.is_empty() && toolchain.is_usable("node") {
+        config = config.with_plugin(JsPlugins::spawn(root, js_plugins)?);
+    }
+    if let Some(postcss_config) = postcss_config.filter(|_| toolchain.is_usable("node")) {
+        info!(
+            "{}",
+            Message::PostCss {
+                config: &postcss_config
+            }
+        );
+        config = config.with_postcss(PostCss::spawn(root, postcss_config)?);
+    }
+    if tailwind && toolchain.is_usable("node") {
+        info!("{}", Message::Tailwind);
+        config = config.with_tailwind(Tailwind::spawn(root)?);
+    }
+    if svelte && toolchain.is_usable("node") {
+        info!("{}", Message::Svelte);
+        config = config.with_svelte(Svelte::spawn(root)?);
+    }
+    if mdx && toolchain.is_usable("node") {
+        info!("{}", Message::Mdx);
+        config = config.with_mdx(Mdx::spawn(root)?);
+    }
+    if toolchain.is_usable(&manager.to_string()) {
+        config = config.with_auto_install(auto_install);
+    }
+    Ok(config)
+}
+
+/// Restarts `server` whenever `r` is entered on the terminal.
+fn restart_on_key(server: &Arc<Server>) {
+    if !io::stdin().is_terminal() {
+        return;
+    }
+
+    info!(target: "server", "{}", Message::RestartHint);
+    let server = Arc::clone(server);
+    let runtime = tokio::runtime::Handle::current();
+    // Lines answering an install prompt aren't seen here
+    on_terminal_line(move |line| {
+        if line.trim() == "r" {
+            let server = Arc::clone(&server);
+            runtime.spawn(async move {
+                if let Err(err) = server.restart().await {
+                    warn!("{}", Message::RestartFailed { error: &err });
+                }
+            });
+        }
+    });
+}
+
+/// Maps the `--install` and `--yes` flags to an auto-install policy.
+fn auto_install(install: bool, yes: bool) -> AutoInstall {
+    match (install, yes) {
+        (false, _) => AutoInstall::Disabled,
+        (true, false) => AutoInstall::Confirm,
+        (true, true) => AutoInstall::Yes,
     }
 }
+
+/// Maps the `--trailing-slash` and `--non-canonical-urls` flags to a URL
+/// policy, `None` when no canonical form was chosen.
+fn url_policy(
+    trailing_slash: Option<TrailingSlash>,
+    non_canonical: NonCanonical,
+) -> Option<UrlPolicy> {
+    trailing_slash.map(|trailing_slash| UrlPolicy {
+        trailing_slash,
+        non_canonical,
+    })
+}
+
+/// Returns `root`, or the nearest directory with a package.json when omitted.
+/// Paths below it are printed relative to it in the `ids` locale.
+fn project_root(root: Option<PathBuf>) -> PalladinResult<PathBuf> {
+    let root = match root {
+        Some(root) => root,
+        None => find_project_root(&current_dir()?).unwrap_or_else(|| PathBuf::from(".")),
+    };
+    set_message_root(&root);
+    Ok(root)
+}
@@ -0,0 +1,285 @@
+use palladin_server::build::{DependencySize, Regression};
+use palladin_shared::{CatalogMessage, message_path, write_message};
+use std::fmt;
+use std::path::Path;
+
+/// Message printed by the CLI, displayed in the locale set at startup.
+#[derive(Clone, Copy)]
+pub enum Message<'a> {
+    ServingProjects {
+        count: usize,
+        address: &'a str,
+    },
+    ServerRunning {
+        address: &'a str,
+    },
+    RestartHint,
+    RestartFailed {
+        error: &'a dyn fmt::Display,
+    },
+    InferredEntrypoint {
+        path: &'a Path,
+    },
+    NoEntrypoint {
+        root: &'a Path,
+    },
+    ServingMocks {
+        dir: &'a Path,
+    },
+    PostCss {
+        config: &'a Path,
+    },
+    Tailwind,
+    Svelte,
+    Mdx,
+    Built {
+        files: usize,
+        dir: &'a Path,
+    },
+    SuggestedCsp {
+        csp: &'a str,
+    },
+    PreviewRunning {
+        profile: Option<&'a str>,
+        address: &'a str,
+    },
+    PreviewPortsExhausted {
+        port: u16,
+        profiles: usize,
+    },
+    PlaygroundRunning {
+        address: &'a str,
+    },
+    Deployed {
+        uploaded: usize,
+        unchanged: usize,
+    },
+    NoRegressions,
+    Regressions {
+        count: usize,
+        since: &'a Path,
+    },
+    MissingChunkManifest {
+        report: &'a Path,
+    },
+    NoBundledPackages,
+    Regression {
+        regression: &'a Regression,
+    },
+    BundledPackage {
+        dependency: &'a DependencySize,
+    },
+}
+
+impl CatalogMessage for Message<'_> {
+    fn id(&self) -> &'static str {
+        match self {
+            Message::ServingProjects { .. } => "dev.serving-projects",
+            Message::ServerRunning { .. } => "dev.running",
+            Message::RestartHint => "dev.restart-hint",
+            Message::RestartFailed { .. } => "dev.restart-failed",
+            Message::InferredEntrypoint { .. } => "dev.inferred-entrypoint",
+            Message::NoEntrypoint { .. } => "dev.no-entrypoint",
+            Message::ServingMocks { .. } => "dev.serving-mocks",
+            Message::PostCss { .. } => "tools.postcss",
+            Message::Tailwind => "tools.tailwind",
+            Message::Svelte => "tools.svelte",
+            Message::Mdx => "tools.mdx",
+            Message::Built { .. } => "build.done",
+            Message::SuggestedCsp { .. } => "build.csp",
+            Message::PreviewRunning { .. } => "preview.running",
+            Message::PreviewPortsExhausted { .. } => "preview.ports-exhausted",
+            Message::PlaygroundRunning { .. } => "playground.running",
+            Message::Deployed { .. } => "deploy.done",
+            Message::NoRegressions => "report.no-regressions",
+            Message::Regressions { .. } => "report.regressions",
+            Message::MissingChunkManifest { .. } => "analyze.no-chunk-manifest",
+            Message::NoBundledPackages => "analyze.no-packages",
+            Message::Regression { .. } => "report.regression",
+            Message::BundledPackage { .. } => "analyze.package",
+        }
+    }
+
+    fn args(&self) -> Vec<(&'static str, String)> {
+        match *self {
+            Message::ServingProjects { count, address } => {
+                vec![
+                    ("count", count.to_string()),
+                    ("address", address.to_string()),
+                ]
+            }
+            Message::ServerRunning { address }
+            | Message::PlaygroundRunning { address }
+            | Message::PreviewRunning {
+                profile: None,
+                address,
+            } => vec![("address", address.to_string())],
+            Message::PreviewRunning {
+                profile: Some(profile),
+                address,
+            } => vec![
+                ("profile", profile.to_string()),
+                ("address", address.to_string()),
+            ],
+            Message::PreviewPortsExhausted { port, profiles } => {
+                vec![
+                    ("port", port.to_string()),
+                    ("profiles", profiles.to_string()),
+                ]
+            }
+            Message::RestartFailed { error } => vec![("error", error.to_string())],
+            Message::InferredEntrypoint { path } => vec![("path", message_path(path))],
+            Message::NoEntrypoint { root } => vec![("root", message_path(root))],
+            Message::ServingMocks { dir } => vec![("dir", message_path(dir))],
+            Message::PostCss { config } => vec![("config", message_path(config))],
+            Message::Built { files, dir } => {
+                vec![("files", files.to_string()), ("dir", message_path(dir))]
+            }
+            Message::SuggestedCsp { csp } => vec![("csp", csp.to_string())],
+            Message::Deployed {
+                uploaded,
+                unchanged,
+            } => vec![
+                ("uploaded", uploaded.to_string()),
+                ("unchanged", unchanged.to_string()),
+            ],
+            Message::Regressions { count, since } => {
+                vec![("count", count.to_string()), ("since", message_path(since))]
+            }
+            Message::MissingChunkManifest { report } => {
+                vec![("report", message_path(report))]
+            }
+            Message::Regression { regression } => regression_args(regression),
+            Message::BundledPackage { dependency } => vec![
+                ("package", dependency.package.clone()),
+                ("size", dependency.size.to_string()),
+                ("gzip", dependency.gzip_size.to_string()),
+                ("chunks", dependency.chunks.to_string()),
+                ("entries", dependency.entries.join(",")),
+            ],
+            Message::RestartHint
+            | Message::Tailwind
+            | Message::Svelte
+            | Message::Mdx
+            | Message::NoRegressions
+            | Message::NoBundledPackages => Vec::new(),
+        }
+    }
+
+    fn fmt_en(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Message::ServingProjects { count, address } => {
+                write!(f, "serving {count} projects on http://{address}")
+            }
+            Message::ServerRunning { address } => write!(f, "server running on http://{address}"),
+            Message::RestartHint => f.write_str("press r + enter to restart"),
+            Message::RestartFailed { error } => write!(f, "restart failed: {error}"),
+            Message::InferredEntrypoint { path } => {
+                write!(f, "using inferred entrypoint {}", path.display())
+            }
+            Message::NoEntrypoint { root } => write!(
+                f,
+                "no entrypoint found in index.html or src/ of {}, pass --entrypoint",
+                root.display()
+            ),
+            Message::ServingMocks { dir } => {
+                write!(f, "serving API mocks from {}", dir.display())
+            }
+            Message::PostCss { config } => {
+                write!(f, "processing stylesheets with {}", config.display())
+            }
+            Message::Tailwind => {
+                f.write_str("generating Tailwind stylesheets as content files change")
+            }
+            Message::Svelte => f.write_str("compiling Svelte components"),
+            Message::Mdx => f.write_str("compiling MDX documents"),
+            Message::Built { files, dir } => {
+                write!(f, "built {files} files into {}", dir.display())
+            }
+            Message::SuggestedCsp { csp } => write!(f, "suggested Content-Security-Policy: {csp}"),
+            Message::PreviewRunning {
+                profile: Some(profile),
+                address,
+            } => write!(f, "preview [{profile}] running on http://{address}"),
+            Message::PreviewRunning {
+                profile: None,
+                address,
+            } => write!(f, "preview running on http://{address}"),
+            Message::PreviewPortsExhausted { port, profiles } => write!(
+                f,
+                "{profiles} profiles need ports from {port} past 65535, pass a lower --port"
+            ),
+            Message::PlaygroundRunning { address } => {
+                write!(f, "playground running on http://{address}")
+            }
+            Message::Deployed {
+                uploaded,
+                unchanged,
+            } => write!(f, "deployed {uploaded} files, {unchanged} unchanged"),
+            Message::NoRegressions => f.write_str("no regressions"),
+            Message::Regressions { count, since } => {
+                write!(f, "{count} regression(s) since {}", since.display())
+            }
+            Message::MissingChunkManifest { report } => {
+                write!(
+                    f,
+                    "{} has no chunk manifest, build again with --analyze",
+                    report.display()
+                )
+            }
+            Message::NoBundledPackages => f.write_str("no packages from node_modules are bundled"),
+            Message::Regression { regression } => write!(f, "{regression}"),
+            Message::BundledPackage { dependency } => write!(f, "{dependency}"),
+        }
+    }
+}
+
+impl fmt::Display for Message<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_message(self, f)
+    }
+}
+
+/// Returns the arguments of `regression`, its kind first.
+fn regression_args(regression: &Regression) -> Vec<(&'static str, String)> {
+    match regression {
+        Regression::SlowerBuild {
+            before_ms,
+            after_ms,
+        } => vec![
+            ("kind", "slower-build".to_string()),
+            ("before_ms", before_ms.to_string()),
+            ("after_ms", after_ms.to_string()),
+        ],
+        Regression::TotalSize { before, after } => vec![
+            ("kind", "total-size".to_string()),
+            ("before", before.to_string()),
+            ("after", after.to_string()),
+        ],
+        Regression::FileGrew {
+            name,
+            before,
+            after,
+        } => vec![
+            ("kind", "file-grew".to_string()),
+            ("name", name.clone()),
+            ("before", before.to_string()),
+            ("after", after.to_string()),
+        ],
+        Regression::FileAdded { name, size } => vec![
+            ("kind", "file-added".to_string()),
+            ("name", name.clone()),
+            ("size", size.to_string()),
+        ],
+        Regression::Warning(warning) => vec![
+            ("kind", "warning".to_string()),
+            ("warning", warning.clone()),
+        ],
+        Regression::BudgetExceeded { glob, limit, size } => vec![
+            ("kind", "budget-exceeded".to_string()),
+            ("glob", glob.clone()),
+            ("limit", limit.to_string()),
+            ("size", size.to_string()),
+        ],
+    }
+}
@@ -0,0 +1,199 @@
+use super::report::{BuildReport, ChunkReport, ModuleSize, format_size};
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use rolldown_common::Output;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::io::Write;
+use std::path::Path;
+
+/// What a package installed in `node_modules` adds to the build.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DependencySize {
+    /// Name of the package, e.g. `react-dom` or `@tanstack/query-core`
+    pub package: String,
+    /// Size of its bundled modules in bytes
+    pub size: u64,
+    /// Size of its bundled modules compressed with gzip, each on its own, so
+    /// it slightly overestimates what's sent over the wire
+    pub gzip_size: u64,
+    /// Number of chunks its modules were bundled into
+    pub chunks: usize,
+    /// Entrypoints whose chunks import it, directly or not
+    pub entries: Vec<String>,
+}
+
+impl fmt::Display for DependencySize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} ({} gzip) in {} chunk(s)",
+            self.package,
+            format_size(self.size),
+            format_size(self.gzip_size),
+            self.chunks
+        )?;
+        if !self.entries.is_empty() {
+            write!(f, ", pulled in by {}", self.entries.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl BuildReport {
+    /// Returns the packages bundled into the build, heaviest first.
+    ///
+    /// Empty for reports of builds that weren't analyzed.
+    pub fn dependency_sizes(&self) -> Vec<DependencySize> {
+        let entries = chunk_entries(&self.chunks);
+
+        let mut packages: BTreeMap<&str, DependencySize> = BTreeMap::new();
+        for chunk in &self.chunks {
+            for module in &chunk.modules {
+                let Some(package) = package_name(&module.id) else {
+                    continue;
+                };
+                let dependency = packages.entry(package).or_insert_with(|| DependencySize {
+                    package: package.to_string(),
+                    size: 0,
+                    gzip_size: 0,
+                    chunks: 0,
+                    entries: Vec::new(),
+                });
+                dependency.size += module.size;
+                dependency.gzip_size += module.gzip_size;
+            }
+        }
+
+        // Counted once per chunk, however many modules it holds
+        for (package, dependency) in &mut packages {
+            let mut pulled_by = BTreeSet::new();
+            for chunk in &self.chunks {
+                let bundled = chunk
+                    .modules
+                    .iter()
+                    .any(|module| package_name(&module.id) == Some(*package));
+                if bundled {
+                    dependency.chunks += 1;
+                    pulled_by.extend(entries.get(chunk.file.as_str()).into_iter().flatten());
+                }
+            }
+            dependency.entries = pulled_by.into_iter().map(str::to_string).collect();
+        }
+
+        let mut dependencies: Vec<DependencySize> = packages.into_values().collect();
+        dependencies.sort_by(|a, b| b.size.cmp(&a.size).then(a.package.cmp(&b.package)));
+        dependencies
+    }
+}
+
+/// Records the chunks of a bundle output, as emitted into `files`, and the
+/// modules bundled into them with ids relative to `root`.
+///
+/// Every chunk and module is compressed on its own, so it's only done for
+/// builds that are analyzed.
+pub(crate) fn chunk_reports(
+    assets: &[Output],
+    files: &[(String, &[u8])],
+    root: &Path,
+) -> Vec<ChunkReport> {
+    assets
+        .iter()
+        .filter_map(|asset| match asset {
+            Output::Chunk(chunk) => Some(chunk),
+            Output::Asset(_) => None,
+        })
+        .map(|chunk| {
+            let file = chunk.filename.to_string();
+            let content = files
+                .iter()
+                .find(|(name, _)| *name == file)
+                .map(|(_, content)| *content)
+                .unwrap_or_default();
+            ChunkReport {
+                entry: chunk.is_entry.then(|| chunk.name.to_string()),
+                imports: chunk
+                    .imports
+                    .iter()
+                    .chain(&chunk.dynamic_imports)
+                    .map(|import| import.to_string())
+                    .collect(),
+                size: content.len() as u64,
+                gzip_size: gzip_size(content),
+                modules: chunk
+                    .modules
+                    .keys
+                    .iter()
+                    .zip(&chunk.modules.values)
+                    .map(|(id, module)| {
+                        let code = module.code().unwrap_or_default();
+                        ModuleSize {
+                            id: module_id(id, root),
+                            size: code.len() as u64,
+                            gzip_size: gzip_size(code.as_bytes()),
+                        }
+                    })
+                    .collect(),
+                file,
+            }
+        })
+        .collect()
+}
+
+/// Returns `id` relative to `root` with `/` separators, so reports don't
+/// reveal where the project was built. Virtual modules are kept as they are.
+fn module_id(id: &str, root: &Path) -> String {
+    match Path::new(id).strip_prefix(root) {
+        Ok(relative) => relative.to_string_lossy().replace('\\', "/"),
+        Err(_) => id.to_string(),
+    }
+}
+
+fn gzip_size(content: &[u8]) -> u64 {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    match encoder.write_all(content).and_then(|()| encoder.finish()) {
+        Ok(compressed) => compressed.len() as u64,
+        Err(_) => content.len() as u64,
+    }
+}
+
+/// Maps every chunk to the entrypoints whose entry chunk imports it, directly
+/// or through other chunks.
+fn chunk_entries(chunks: &[ChunkReport]) -> HashMap<&str, BTreeSet<&str>> {
+    let by_file: HashMap<&str, &ChunkReport> = chunks
+        .iter()
+        .map(|chunk| (chunk.file.as_str(), chunk))
+        .collect();
+
+    let mut entries: HashMap<&str, BTreeSet<&str>> = HashMap::new();
+    for chunk in chunks {
+        let Some(entry) = &chunk.entry else {
+            continue;
+        };
+
+        let mut pending = vec![chunk.file.as_str()];
+        while let Some(file) = pending.pop() {
+            if !entries.entry(file).or_default().insert(entry) {
+                continue;
+            }
+            if let Some(chunk) = by_file.get(file) {
+                pending.extend(chunk.imports.iter().map(String::as_str));
+            }
+        }
+    }
+    entries
+}
+
+/// Returns the package a module id belongs to, `None` for the project's own
+/// modules.
+fn package_name(id: &str) -> Option<&str> {
+    let (_, path) = id
+        .rsplit_once("node_modules/")
+        .or_else(|| id.rsplit_once("node_modules\\"))?;
+    let end = match path.starts_with('@') {
+        true => path.match_indices(['/', '\\']).nth(1),
+        false => path.match_indices(['/', '\\']).next(),
+    }
+    .map_or(path.len(), |(end, _)| end);
+    Some(&path[..end]).filter(|package| !package.is_empty())
+}
@@ -0,0 +1,53 @@
+use anyhow::anyhow;
+use hmac::{Hmac, Mac};
+use palladin_shared::{PalladinError, PalladinResult};
+use sha2::{Digest, Sha256};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// Name of the checksum manifest written next to the build output.
+pub const CHECKSUMS_FILE: &str = "checksums.txt";
+
+/// Name of the file holding the manifest's HMAC-SHA256 signature.
+pub const SIGNATURE_FILE: &str = "checksums.txt.sig";
+
+/// Writes the SHA-256 of every file into `dir/checksums.txt`, one
+/// `<hex>  <path>` line per file so it can be checked with `sha256sum -c`.
+///
+/// # Errors
+///
+/// Returns an error if the manifest cannot be written.
+pub fn write_checksums(dir: &Path, files: &[(String, &[u8])]) -> PalladinResult<PathBuf> {
+    let mut manifest = String::new();
+    for (name, content) in files {
+        let _ = writeln!(manifest, "{:x}  {name}", Sha256::digest(content));
+    }
+
+    let path = dir.join(CHECKSUMS_FILE);
+    fs_err::write(&path, manifest)?;
+    Ok(path)
+}
+
+/// Signs the manifest at `path` with HMAC-SHA256 and writes the hex encoded
+/// signature next to it.
+///
+/// # Errors
+///
+/// Returns an error if the key is empty or a file cannot be read or written.
+pub fn sign_checksums(path: &Path, key: &[u8]) -> PalladinResult<PathBuf> {
+    if key.is_empty() {
+        return Err(PalladinError::Build(anyhow!("signing key is empty")));
+    }
+
+    let manifest = fs_err::read(path)?;
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(key).map_err(|err| PalladinError::Build(err.into()))?;
+    mac.update(&manifest);
+
+    let signature_path = path.with_file_name(SIGNATURE_FILE);
+    fs_err::write(
+        &signature_path,
+        format!("{:x}\n", mac.finalize().into_bytes()),
+    )?;
+    Ok(signature_path)
+}
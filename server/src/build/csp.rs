@@ -0,0 +1,171 @@
+use crate::html::{element_contents, opening_tags, tag_attribute};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// Directives of a suggested policy, in the order they're written.
+const DIRECTIVES: &[&str] = &[
+    "default-src",
+    "script-src",
+    "style-src",
+    "img-src",
+    "font-src",
+    "object-src",
+    "base-uri",
+];
+
+/// Extensions of font files, allowed through `font-src`.
+const FONT_EXTENSIONS: &[&str] = &["woff", "woff2", "ttf", "otf", "eot"];
+
+/// Suggests a Content-Security-Policy allowing what the built `files` load.
+///
+/// Everything served from the same origin is allowed. So are the inline
+/// scripts and styles of the HTML pages, by hash, and the external origins
+/// pages and stylesheets load scripts, styles, images and fonts from.
+/// Requests made by scripts can't be told from the output, `connect-src`
+/// falls back to the same origin.
+pub fn suggest_csp(files: &[(String, &[u8])]) -> String {
+    let mut policy: BTreeMap<&str, BTreeSet<String>> = BTreeMap::new();
+    let mut allow = |directive, source: String| {
+        policy.entry(directive).or_default().insert(source);
+    };
+
+    for directive in [
+        "default-src",
+        "script-src",
+        "style-src",
+        "img-src",
+        "base-uri",
+    ] {
+        allow(directive, "'self'".to_string());
+    }
+    allow("img-src", "data:".to_string());
+    allow("object-src", "'none'".to_string());
+
+    for (name, content) in files {
+        let extension = Path::new(name)
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        match extension.as_str() {
+            "html" => {
+                let html = String::from_utf8_lossy(content);
+                for (tag, script) in element_contents(&html, "script") {
+                    match tag_attribute(tag, "src") {
+                        Some(src) => external_origin(src)
+                            .into_iter()
+                            .for_each(|origin| allow("script-src", origin)),
+                        None if !script.trim().is_empty() => {
+                            allow("script-src", source_hash(script));
+                        }
+                        None => {}
+                    }
+                }
+                for (_, style) in element_contents(&html, "style") {
+                    allow("style-src", source_hash(style));
+                }
+                for tag in opening_tags(&html, "link") {
+                    let is_stylesheet = tag_attribute(tag, "rel") == Some("stylesheet");
+                    let origin = tag_attribute(tag, "href").and_then(external_origin);
+                    if let Some(origin) = origin.filter(|_| is_stylesheet) {
+                        allow("style-src", origin);
+                    }
+                }
+                for tag in opening_tags(&html, "img") {
+                    if let Some(origin) = tag_attribute(tag, "src").and_then(external_origin) {
+                        allow("img-src", origin);
+                    }
+                }
+            }
+            "css" => {
+                let css = String::from_utf8_lossy(content);
+                for (url, imported) in stylesheet_urls(&css) {
+                    let Some(origin) = external_origin(url) else {
+                        continue;
+                    };
+                    let directive = if imported {
+                        "style-src"
+                    } else if is_font(url) {
+                        "font-src"
+                    } else {
+                        "img-src"
+                    };
+                    allow(directive, origin);
+                }
+            }
+            "wasm" => allow("script-src", "'wasm-unsafe-eval'".to_string()),
+            _ => {}
+        }
+    }
+
+    DIRECTIVES
+        .iter()
+        .filter_map(|directive| {
+            let sources = policy.get(directive)?;
+            let sources: Vec<&str> = sources.iter().map(String::as_str).collect();
+            Some(format!("{directive} {}", sources.join(" ")))
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Returns the `'sha256-...'` source allowing the inline script or style
+/// `content`.
+fn source_hash(content: &str) -> String {
+    format!(
+        "'sha256-{}'",
+        STANDARD.encode(Sha256::digest(content.as_bytes()))
+    )
+}
+
+/// Returns the origin of `url` if it points at another host, e.g.
+/// `https://fonts.gstatic.com` for `https://fonts.gstatic.com/s/font.woff2`.
+fn external_origin(url: &str) -> Option<String> {
+    let (scheme, rest) = match url.split_once("://") {
+        Some((scheme, rest)) if matches!(scheme, "http" | "https") => (scheme, rest),
+        // Protocol-relative URLs use the scheme of the page
+        _ => ("https", url.strip_prefix("//")?),
+    };
+    let host = rest.split(['/', '?', '#']).next()?;
+    (!host.is_empty()).then(|| format!("{scheme}://{host}"))
+}
+
+/// Returns the URLs referenced by `url()` and `@import` in the stylesheet
+/// `css`, each with whether it is an imported stylesheet.
+fn stylesheet_urls(css: &str) -> Vec<(&str, bool)> {
+    let mut urls = Vec::new();
+    let mut rest = css;
+
+    while let Some(start) = rest.find("url(") {
+        let imported = rest[..start].trim_end().ends_with("@import");
+        rest = &rest[start + 4..];
+        let Some(end) = rest.find(')') else {
+            break;
+        };
+        urls.push((rest[..end].trim().trim_matches(['"', '\'']), imported));
+        rest = &rest[end..];
+    }
+
+    // `@import "..."` without `url()`
+    let mut rest = css;
+    while let Some(start) = rest.find("@import") {
+        rest = rest[start + 7..].trim_start();
+        if let Some(quote @ ('"' | '\'')) = rest.chars().next()
+            && let Some(end) = rest[1..].find(quote)
+        {
+            urls.push((&rest[1..=end], true));
+        }
+    }
+
+    urls
+}
+
+fn is_font(url: &str) -> bool {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    Path::new(path)
+        .extension()
+        .is_some_and(|ext| FONT_EXTENSIONS.contains(&ext.to_string_lossy().as_ref()))
+}
@@ -0,0 +1,139 @@
+use crate::rolldown::chunk_stylesheets;
+use crate::server::Context;
+use palladin_shared::{PalladinError, PalladinResult};
+use rolldown_common::{Output, OutputChunk};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Name of the manifest written next to the build output.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// An emitted file, as listed in `manifest.json`.
+///
+/// Follows the layout of Vite's manifest, so backend integrations written
+/// for it can read palladin's as well.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestEntry {
+    /// Path relative to the build directory
+    pub file: String,
+    /// Name of the chunk
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Source path relative to the root
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub src: Option<String>,
+    #[serde(skip_serializing_if = "is_false")]
+    pub is_entry: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    pub is_dynamic_entry: bool,
+    /// Keys of the chunks it imports statically
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub imports: Vec<String>,
+    /// Keys of the chunks it imports dynamically
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub dynamic_imports: Vec<String>,
+    /// Stylesheets of the chunk and of the chunks it imports, in load order
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub css: Vec<String>,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+/// Maps source files to the files the build emitted for them, keyed by
+/// source path relative to the root, e.g. `src/main.tsx`.
+///
+/// Chunks without a source module of their own are keyed by `_` and their
+/// file name, like Vite does.
+pub fn build_manifest(ctx: &Context, assets: &[Output]) -> BTreeMap<String, ManifestEntry> {
+    let chunk_src = |chunk: &OutputChunk| {
+        let id = chunk.facade_module_id.as_ref()?;
+        source_path(ctx.root(), Path::new(id.as_str()))
+    };
+    let keys: HashMap<&str, String> = assets
+        .iter()
+        .filter_map(|asset| match asset {
+            Output::Chunk(chunk) => {
+                let key = chunk_src(chunk).unwrap_or_else(|| format!("_{}", chunk.filename));
+                Some((chunk.filename.as_str(), key))
+            }
+            Output::Asset(_) => None,
+        })
+        .collect();
+    let key_of = |filename: &str| {
+        keys.get(filename)
+            .cloned()
+            .unwrap_or_else(|| format!("_{filename}"))
+    };
+
+    let mut manifest = BTreeMap::new();
+    for asset in assets {
+        match asset {
+            Output::Chunk(chunk) => {
+                manifest.insert(
+                    key_of(&chunk.filename),
+                    ManifestEntry {
+                        file: chunk.filename.to_string(),
+                        name: Some(chunk.name.to_string()),
+                        src: chunk_src(chunk),
+                        is_entry: chunk.is_entry,
+                        is_dynamic_entry: chunk.is_dynamic_entry,
+                        imports: chunk.imports.iter().map(|id| key_of(id)).collect(),
+                        dynamic_imports: chunk
+                            .dynamic_imports
+                            .iter()
+                            .map(|id| key_of(id))
+                            .collect(),
+                        css: chunk_stylesheets(assets, &chunk.filename),
+                    },
+                );
+            }
+            Output::Asset(asset) => {
+                // Stylesheets are listed with the chunks they belong to
+                let Some(src) = asset.original_file_names.first() else {
+                    continue;
+                };
+                manifest.insert(
+                    src.to_string(),
+                    ManifestEntry {
+                        file: asset.filename.to_string(),
+                        name: None,
+                        src: Some(src.to_string()),
+                        is_entry: false,
+                        is_dynamic_entry: false,
+                        imports: Vec::new(),
+                        dynamic_imports: Vec::new(),
+                        css: Vec::new(),
+                    },
+                );
+            }
+        }
+    }
+    manifest
+}
+
+/// Writes `manifest` into `dir/manifest.json`.
+///
+/// # Errors
+///
+/// Returns an error if the manifest cannot be written.
+pub fn write_manifest(
+    dir: &Path,
+    manifest: &BTreeMap<String, ManifestEntry>,
+) -> PalladinResult<PathBuf> {
+    let json =
+        serde_json::to_string_pretty(manifest).map_err(|e| PalladinError::Build(e.into()))?;
+    let path = dir.join(MANIFEST_FILE);
+    fs_err::write(&path, json)?;
+    Ok(path)
+}
+
+/// Returns `path` relative to `root` using `/` as separator, `None` for
+/// modules outside the root.
+pub(super) fn source_path(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    Some(relative.to_string_lossy().replace('\\', "/"))
+}
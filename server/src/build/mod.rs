@@ -0,0 +1,385 @@
+mod analyze;
+mod checksums;
+mod csp;
+mod manifest;
+mod report;
+mod ssr;
+mod stats;
+
+use crate::html::{inject_into_head, rewrite_module_script_src, stylesheet_links};
+use crate::messages::Message;
+use crate::rolldown::{
+    BundleMode, chunk_stylesheets, content_hashed_files, create_bundler, create_ssr_bundler,
+    install_missing_packages,
+};
+use crate::server::plugin::transform_index_html;
+use crate::server::{
+    Context, REDIRECTS_FILE, ServerConfig, Severity, filter_diagnostics, inject_html_meta,
+    inject_resource_hints,
+};
+use log::{info, warn};
+use palladin_shared::{PalladinResult, canonicalize_with_strip};
+use rolldown_common::Output;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+
+pub use analyze::DependencySize;
+use analyze::chunk_reports;
+pub use checksums::{CHECKSUMS_FILE, SIGNATURE_FILE, sign_checksums, write_checksums};
+pub use csp::suggest_csp;
+pub use manifest::{MANIFEST_FILE, ManifestEntry, build_manifest, write_manifest};
+pub(crate) use report::format_size;
+pub use report::{
+    BUILD_REPORT_FILE, BudgetReport, BuildReport, ChunkReport, FileSize, ModuleSize, Regression,
+    SizeBudget, compare_reports,
+};
+pub use ssr::{
+    SSR_BUILD_DIR_SUFFIX, SSR_MANIFEST_FILE, SsrFormat, SsrOptions, build_ssr_manifest,
+    ssr_build_dir, write_ssr_manifest,
+};
+use stats::PhaseTimer;
+pub use stats::{BuildStats, PhaseTiming};
+
+/// Name of the HTML document serving a directory.
+const INDEX_HTML: &str = "index.html";
+
+/// Directory never searched for HTML pages.
+const NODE_MODULES: &str = "node_modules";
+
+/// Options for a production build.
+#[derive(Debug, Clone, Default)]
+pub struct BuildOptions {
+    /// File holding the key `checksums.txt` is signed with, unsigned when `None`
+    pub sign_key: Option<PathBuf>,
+    /// Size limits recorded in the build report
+    pub budgets: Vec<SizeBudget>,
+    /// Record the modules bundled into each chunk with their gzip sizes in
+    /// the build report, for `palladin analyze`. Compressing every module
+    /// takes a while, so it's off by default
+    pub analyze: bool,
+    /// File the [`BuildStats`] are written to, not written when `None`.
+    /// Implies [`analyze`](Self::analyze), the stats list the chunks
+    pub stats: Option<PathBuf>,
+    /// Also write a `manifest.json` mapping source files to emitted files,
+    /// for backend templates to link them
+    pub manifest: bool,
+    /// Server entry bundled for Node into a sibling of the build directory,
+    /// e.g. `dist-server`, along with an SSR manifest
+    pub ssr: Option<SsrOptions>,
+}
+
+/// Files written by a production build.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct BuildOutput {
+    /// The build directory everything was written to
+    pub dir: PathBuf,
+    /// Emitted files, relative to `dir`
+    pub files: Vec<String>,
+    /// Path of the checksum manifest
+    pub checksums: PathBuf,
+    /// Path of the manifest signature, if it was signed
+    pub signature: Option<PathBuf>,
+    /// Path of the build report, see [`BuildReport`]
+    pub report: PathBuf,
+    /// Path of the build stats, if requested
+    pub stats: Option<PathBuf>,
+    /// Path of the manifest, if requested, see [`build_manifest`]
+    pub manifest: Option<PathBuf>,
+    /// Files of the server bundle, if requested
+    pub ssr_files: Vec<PathBuf>,
+    /// Path of the checksum manifest of the server bundle, if requested
+    pub ssr_checksums: Option<PathBuf>,
+    /// Path of the SSR manifest, if a server bundle was requested, see
+    /// [`build_ssr_manifest`]
+    pub ssr_manifest: Option<PathBuf>,
+    /// Content-Security-Policy allowing what the output loads, see
+    /// [`suggest_csp`]
+    pub csp: String,
+}
+
+/// Bundles the project into its build directory and writes a checksum
+/// manifest and a [`BuildReport`] for the emitted files.
+///
+/// With a [`UrlPolicy`](crate::server::UrlPolicy) configured, its rules for
+/// the HTML pages are written to `_redirects` for the host to apply.
+///
+/// # Errors
+///
+/// Returns an error if bundling fails or the output cannot be written.
+pub async fn build(config: ServerConfig, options: BuildOptions) -> PalladinResult<BuildOutput> {
+    let started = Instant::now();
+    let mut timer = PhaseTimer::start();
+    let ctx = Arc::new(Context::new(config)?);
+
+    let mut bundler = create_bundler(ctx.clone(), BundleMode::Build).build()?;
+    let mut output = bundler.write().await;
+    // Imports of missing packages resolve once they're installed
+    if output.is_err() && install_missing_packages(&ctx).await {
+        bundler.close().await?;
+        bundler = create_bundler(ctx.clone(), BundleMode::Build).build()?;
+        output = bundler.write().await;
+    }
+    let output = output?;
+    bundler.close().await?;
+    timer.finish("bundle");
+    let warnings: Vec<String> =
+        filter_diagnostics(&ctx.config().plugins, &output.warnings, Severity::Warning)
+            .into_iter()
+            .map(|warning| warning.message)
+            .collect();
+    for warning in &warnings {
+        warn!("{}", Message::BuildWarning { warning });
+    }
+
+    let dir = ctx.build_dir().clone();
+    let pages = write_html_pages(&ctx, &output.assets)?;
+    let redirects = match ctx.config().url_policy {
+        Some(policy) => {
+            let pages: Vec<String> = pages.iter().map(|(page, _)| page.clone()).collect();
+            let rules = policy.redirect_rules(&pages);
+            let path = dir.join(REDIRECTS_FILE);
+            fs_err::write(&path, &rules)?;
+            info!("{}", Message::Wrote { path: &path });
+            Some(rules)
+        }
+        None => None,
+    };
+    timer.finish("html");
+
+    let mut files: Vec<(String, &[u8])> = output
+        .assets
+        .iter()
+        .map(|asset| (asset.filename().to_string(), asset.content_as_bytes()))
+        .collect();
+    files.extend(
+        pages
+            .iter()
+            .map(|(page, html)| (page.clone(), html.as_bytes())),
+    );
+    files.extend(
+        redirects
+            .iter()
+            .map(|rules| (REDIRECTS_FILE.to_string(), rules.as_bytes())),
+    );
+    files.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let manifest = match options.manifest {
+        true => {
+            let path = write_manifest(&dir, &build_manifest(&ctx, &output.assets))?;
+            info!("{}", Message::Wrote { path: &path });
+            Some(path)
+        }
+        false => None,
+    };
+
+    let (ssr_output, ssr_manifest) = match &options.ssr {
+        Some(ssr) => {
+            let entry = ctx.resolve_path(&ssr.entry)?;
+            let mut bundler =
+                create_ssr_bundler(ctx.clone(), &entry, BundleMode::Build, ssr.format).build()?;
+            let ssr_output = bundler.write().await?;
+            bundler.close().await?;
+
+            let path = write_ssr_manifest(&dir, &build_ssr_manifest(&ctx, &output.assets))?;
+            info!("{}", Message::Wrote { path: &path });
+            timer.finish("ssr");
+            (Some(ssr_output), Some(path))
+        }
+        None => (None, None),
+    };
+    let ssr_dir = ssr_build_dir(&dir);
+    let mut ssr_contents: Vec<(String, &[u8])> = ssr_output
+        .iter()
+        .flat_map(|output| &output.assets)
+        .map(|asset| (asset.filename().to_string(), asset.content_as_bytes()))
+        .collect();
+    ssr_contents.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let ssr_files: Vec<PathBuf> = ssr_contents
+        .iter()
+        .map(|(name, _)| ssr_dir.join(name))
+        .collect();
+    for file in &ssr_files {
+        info!("{}", Message::Wrote { path: file });
+    }
+
+    let csp = suggest_csp(&files);
+    let checksums = write_checksums(&dir, &files)?;
+    info!("{}", Message::Wrote { path: &checksums });
+    // The server bundle is deployed on its own, so it gets its own manifest
+    let ssr_checksums = match ssr_output {
+        Some(_) => {
+            let path = write_checksums(&ssr_dir, &ssr_contents)?;
+            info!("{}", Message::Wrote { path: &path });
+            Some(path)
+        }
+        None => None,
+    };
+
+    let signature = match &options.sign_key {
+        Some(key) => {
+            let key = fs_err::read(key)?;
+            let signature = sign_checksums(&checksums, &key)?;
+            info!("{}", Message::SignedChecksums { path: &signature });
+            if let Some(path) = &ssr_checksums {
+                let signature = sign_checksums(path, &key)?;
+                info!("{}", Message::SignedChecksums { path: &signature });
+            }
+            Some(signature)
+        }
+        None => None,
+    };
+    timer.finish("checksums");
+
+    let duration_ms = started.elapsed().as_millis() as u64;
+    let chunks = match options.analyze || options.stats.is_some() {
+        true => chunk_reports(&output.assets, &files, ctx.root()),
+        false => Vec::new(),
+    };
+    let report = BuildReport::new(
+        duration_ms,
+        &files,
+        chunks.clone(),
+        warnings,
+        &options.budgets,
+    )
+    .with_immutable_files(&content_hashed_files(&ctx, &output.assets))
+    .with_ssr_files(&ssr_contents);
+    for budget in report.exceeded_budgets() {
+        warn!(
+            "{}",
+            Message::OverBudget {
+                glob: &budget.glob,
+                size: budget.size,
+                limit: budget.limit
+            }
+        );
+    }
+    let report = report.write(&dir)?;
+    info!("{}", Message::Wrote { path: &report });
+    timer.finish("report");
+
+    if let Some(path) = &options.stats {
+        BuildStats::new(timer.into_phases(), chunks).write(path)?;
+        info!("{}", Message::Wrote { path });
+    }
+
+    Ok(BuildOutput {
+        dir,
+        files: files.into_iter().map(|(name, _)| name).collect(),
+        checksums,
+        signature,
+        report,
+        stats: options.stats,
+        manifest,
+        ssr_files,
+        ssr_checksums,
+        ssr_manifest,
+        csp,
+    })
+}
+
+/// Copies the HTML pages of the project into the build directory, pointing
+/// their entrypoint scripts at the emitted entry chunks and linking the
+/// stylesheets bundled with them.
+///
+/// Pages are the `.html` files at the root and `index.html` files one
+/// directory below it. Returns the written documents by path relative to the
+/// build directory.
+fn write_html_pages(ctx: &Context, assets: &[Output]) -> PalladinResult<Vec<(String, String)>> {
+    let entries: HashMap<PathBuf, String> = assets
+        .iter()
+        .filter_map(|asset| match asset {
+            Output::Chunk(chunk) if chunk.is_entry => Some(chunk),
+            _ => None,
+        })
+        .filter_map(|chunk| {
+            let facade = chunk
+                .facade_module_id
+                .as_ref()
+                .map(|id| PathBuf::from(id.as_str()))
+                // Without a facade module the chunk belongs to the primary entrypoint
+                .unwrap_or_else(|| ctx.entrypoint().clone());
+            ctx.is_entrypoint(&facade)
+                .then(|| (facade, chunk.filename.to_string()))
+        })
+        .collect();
+
+    let mut pages = Vec::new();
+    for page in find_html_pages(ctx)? {
+        let html = fs_err::read_to_string(ctx.root().join(&page))?;
+        let html = transform_index_html(&ctx.config().plugins, &html);
+        let html = inject_resource_hints(&html, &ctx.config().resource_hints);
+        let html = inject_html_meta(&html, &ctx.config().html_meta);
+        let page_dir = ctx.root().join(&page);
+        let page_dir = page_dir.parent().unwrap_or(ctx.root());
+
+        let loaded = RefCell::new(Vec::new());
+        let html = rewrite_module_script_src(&html, |src| {
+            let path = match src.strip_prefix('/') {
+                Some(src) => ctx.root().join(src),
+                None => page_dir.join(src),
+            };
+            let path = canonicalize_with_strip(path).ok()?;
+            let entry = entries.get(&path)?;
+            loaded.borrow_mut().push(entry.clone());
+            Some(format!("/{entry}"))
+        });
+
+        // Stylesheets are linked so they load before the scripts run
+        let stylesheets: Vec<String> = loaded
+            .into_inner()
+            .iter()
+            .flat_map(|entry| chunk_stylesheets(assets, entry))
+            .map(|css| format!("/{css}"))
+            .collect();
+        let html = match stylesheets.is_empty() {
+            true => html,
+            false => inject_into_head(&html, &stylesheet_links(stylesheets)),
+        };
+
+        let out = ctx.build_dir().join(&page);
+        if let Some(parent) = out.parent() {
+            fs_err::create_dir_all(parent)?;
+        }
+        fs_err::write(out, &html)?;
+        pages.push((page, html));
+    }
+
+    Ok(pages)
+}
+
+/// Returns the HTML pages of the project, relative to the root and using `/`
+/// as separator.
+fn find_html_pages(ctx: &Context) -> PalladinResult<Vec<String>> {
+    let is_html = |path: &Path| path.extension().is_some_and(|ext| ext == "html");
+
+    let mut pages = Vec::new();
+    for entry in fs_err::read_dir(ctx.root())? {
+        let path = entry?.path();
+        let Some(name) = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+        else {
+            continue;
+        };
+
+        if path.is_file() && is_html(&path) {
+            pages.push(name);
+        } else if path.is_dir()
+            && !name.starts_with('.')
+            && name != NODE_MODULES
+            && !path.starts_with(ctx.build_dir())
+            && !ctx.is_excluded(&path)
+            && path.join(INDEX_HTML).is_file()
+        {
+            pages.push(format!("{name}/{INDEX_HTML}"));
+        }
+    }
+
+    pages.sort();
+    Ok(pages)
+}
@@ -0,0 +1,426 @@
+use anyhow::anyhow;
+use globset::{Glob, GlobMatcher};
+use palladin_shared::{PalladinError, PalladinResult};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Name of the build report written next to the build output.
+pub const BUILD_REPORT_FILE: &str = "build-report.json";
+
+/// Length of the content hash rolldown puts in file names.
+const HASH_LEN: usize = 8;
+
+/// Factor by which a build has to get slower to count as a regression, so
+/// the usual noise in timings isn't reported.
+const SLOWER_BUILD_FACTOR: f64 = 1.2;
+
+/// Limits the total size of the emitted files matching `glob`.
+#[derive(Debug, Clone)]
+pub struct SizeBudget {
+    /// Glob matched against paths relative to the build directory, e.g.
+    /// `assets/*.js`
+    pub glob: String,
+    /// Maximum size in bytes
+    pub limit: u64,
+    matcher: GlobMatcher,
+}
+
+impl SizeBudget {
+    /// Creates a budget of `limit` bytes for the files matching `glob`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `glob` isn't a valid glob.
+    pub fn new(glob: &str, limit: u64) -> Result<Self, String> {
+        let matcher = Glob::new(glob)
+            .map_err(|err| format!("invalid glob `{glob}`: {err}"))?
+            .compile_matcher();
+        Ok(Self {
+            glob: glob.to_string(),
+            limit,
+            matcher,
+        })
+    }
+
+    /// Checks the budget against the emitted `files`.
+    fn check(&self, files: &[FileSize]) -> BudgetReport {
+        let size = files
+            .iter()
+            .filter(|file| self.matcher.is_match(&file.name))
+            .map(|file| file.size)
+            .sum();
+        BudgetReport {
+            glob: self.glob.clone(),
+            limit: self.limit,
+            size,
+            exceeded: size > self.limit,
+        }
+    }
+}
+
+/// Parses `GLOB=SIZE`, the size in bytes or with a `kb` or `mb` suffix, e.g.
+/// `assets/*.js=150kb`.
+impl FromStr for SizeBudget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((glob, size)) = s.rsplit_once('=') else {
+            return Err(format!("invalid budget `{s}`, expected GLOB=SIZE"));
+        };
+        let limit = parse_size(size.trim())
+            .ok_or_else(|| format!("invalid size `{size}`, expected e.g. 5000, 150kb or 1.5mb"))?;
+        Self::new(glob.trim(), limit)
+    }
+}
+
+/// Parses a size in bytes, kilobytes (`kb`) or megabytes (`mb`), where a
+/// kilobyte is 1024 bytes.
+fn parse_size(size: &str) -> Option<u64> {
+    let size = size.to_ascii_lowercase();
+    let (number, unit) = match size.strip_suffix("mb") {
+        Some(number) => (number, 1024.0 * 1024.0),
+        None => match size.strip_suffix("kb") {
+            Some(number) => (number, 1024.0),
+            None => (size.strip_suffix('b').unwrap_or(&size), 1.0),
+        },
+    };
+
+    let number: f64 = number.trim().parse().ok()?;
+    (number.is_finite() && number >= 0.0).then(|| (number * unit).round() as u64)
+}
+
+/// A file emitted by the build.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileSize {
+    /// Path relative to the build directory
+    pub name: String,
+    /// Size in bytes
+    pub size: u64,
+    /// Whether the name carries a content hash, so the file can be cached
+    /// forever. Missing from reports of older builds
+    #[serde(default)]
+    pub immutable: bool,
+}
+
+/// A chunk emitted by the build, with the modules bundled into it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkReport {
+    /// Path relative to the build directory
+    pub file: String,
+    /// Name of the entrypoint, if it's an entry chunk
+    pub entry: Option<String>,
+    /// Chunks it imports statically or dynamically
+    pub imports: Vec<String>,
+    /// Size of the emitted file in bytes, missing from reports of older
+    /// builds
+    #[serde(default)]
+    pub size: u64,
+    /// Size of the emitted file compressed with gzip
+    #[serde(default)]
+    pub gzip_size: u64,
+    pub modules: Vec<ModuleSize>,
+}
+
+/// A module's share of the chunk it was bundled into.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModuleSize {
+    /// Module id relative to the project root, e.g. `src/main.ts` or
+    /// `node_modules/react/index.js`
+    pub id: String,
+    /// Size of the rendered code in bytes
+    pub size: u64,
+    /// Size of the rendered code compressed on its own with gzip
+    pub gzip_size: u64,
+}
+
+/// How the emitted files measure up to a [`SizeBudget`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BudgetReport {
+    pub glob: String,
+    pub limit: u64,
+    /// Total size of the matching files
+    pub size: u64,
+    pub exceeded: bool,
+}
+
+/// What a production build took and emitted, written to
+/// [`BUILD_REPORT_FILE`] so builds can be compared.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildReport {
+    /// Time spent bundling and writing the output, in milliseconds
+    pub duration_ms: u64,
+    /// Total size of the emitted files in bytes
+    pub total_size: u64,
+    pub files: Vec<FileSize>,
+    /// Warnings reported by the bundler
+    pub warnings: Vec<String>,
+    pub budgets: Vec<BudgetReport>,
+    /// Chunk manifest, only recorded by builds with
+    /// [`BuildOptions::analyze`](super::BuildOptions::analyze)
+    #[serde(default)]
+    pub chunks: Vec<ChunkReport>,
+    /// Files of the server bundle, relative to its directory
+    #[serde(default)]
+    pub ssr_files: Vec<FileSize>,
+}
+
+impl BuildReport {
+    /// Creates the report of a build emitting `files` and `chunks`, checking
+    /// the files against `budgets`.
+    pub fn new(
+        duration_ms: u64,
+        files: &[(String, &[u8])],
+        chunks: Vec<ChunkReport>,
+        warnings: Vec<String>,
+        budgets: &[SizeBudget],
+    ) -> Self {
+        let files = file_sizes(files);
+        Self {
+            duration_ms,
+            total_size: files.iter().map(|file| file.size).sum(),
+            budgets: budgets.iter().map(|budget| budget.check(&files)).collect(),
+            files,
+            warnings,
+            chunks,
+            ssr_files: Vec::new(),
+        }
+    }
+
+    /// Marks the files named in `immutable` as cacheable forever.
+    pub fn with_immutable_files(mut self, immutable: &HashSet<String>) -> Self {
+        for file in &mut self.files {
+            file.immutable = immutable.contains(&file.name);
+        }
+        self
+    }
+
+    /// Returns the files of the build that can be cached forever.
+    pub fn immutable_files(&self) -> HashSet<String> {
+        self.files
+            .iter()
+            .filter(|file| file.immutable)
+            .map(|file| file.name.clone())
+            .collect()
+    }
+
+    /// Adds the files of the server bundle to the report.
+    pub fn with_ssr_files(mut self, files: &[(String, &[u8])]) -> Self {
+        self.ssr_files = file_sizes(files);
+        self
+    }
+
+    /// Reads a report written by a previous build.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or isn't a build report.
+    pub fn read(path: &Path) -> PalladinResult<Self> {
+        let json = fs_err::read_to_string(path)?;
+        serde_json::from_str(&json).map_err(|err| {
+            PalladinError::Build(anyhow!("{} is not a build report: {err}", path.display()))
+        })
+    }
+
+    /// Writes the report into `dir/build-report.json`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the report cannot be written.
+    pub fn write(&self, dir: &Path) -> PalladinResult<PathBuf> {
+        let json =
+            serde_json::to_string_pretty(self).map_err(|e| PalladinError::Build(e.into()))?;
+        let path = dir.join(BUILD_REPORT_FILE);
+        fs_err::write(&path, json)?;
+        Ok(path)
+    }
+
+    /// Returns the budgets the build exceeded.
+    pub fn exceeded_budgets(&self) -> impl Iterator<Item = &BudgetReport> {
+        self.budgets.iter().filter(|budget| budget.exceeded)
+    }
+}
+
+fn file_sizes(files: &[(String, &[u8])]) -> Vec<FileSize> {
+    files
+        .iter()
+        .map(|(name, content)| FileSize {
+            name: name.clone(),
+            size: content.len() as u64,
+            immutable: false,
+        })
+        .collect()
+}
+
+/// Something that got worse between two builds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Regression {
+    /// The build got noticeably slower
+    SlowerBuild { before_ms: u64, after_ms: u64 },
+    /// The output got bigger as a whole
+    TotalSize { before: u64, after: u64 },
+    /// A file got bigger, named without its content hash
+    FileGrew {
+        name: String,
+        before: u64,
+        after: u64,
+    },
+    /// A file was added, named without its content hash
+    FileAdded { name: String, size: u64 },
+    /// The bundler reported a warning it didn't before
+    Warning(String),
+    /// A budget that was met is now exceeded
+    BudgetExceeded { glob: String, limit: u64, size: u64 },
+}
+
+impl fmt::Display for Regression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Regression::SlowerBuild {
+                before_ms,
+                after_ms,
+            } => write!(f, "build took {after_ms}ms, was {before_ms}ms"),
+            Regression::TotalSize { before, after } => write!(
+                f,
+                "output grew by {} to {}",
+                format_size(after - before),
+                format_size(*after)
+            ),
+            Regression::FileGrew {
+                name,
+                before,
+                after,
+            } => write!(
+                f,
+                "{name} grew by {} to {}",
+                format_size(after - before),
+                format_size(*after)
+            ),
+            Regression::FileAdded { name, size } => {
+                write!(f, "{name} was added ({})", format_size(*size))
+            }
+            Regression::Warning(warning) => write!(f, "new warning: {warning}"),
+            Regression::BudgetExceeded { glob, limit, size } => write!(
+                f,
+                "{glob} is {}, over its budget of {}",
+                format_size(*size),
+                format_size(*limit)
+            ),
+        }
+    }
+}
+
+/// Compares the build reported in `after` to the one in `before`, returning
+/// what got worse.
+///
+/// Files are matched by name with their content hash left out, so a chunk
+/// whose content changed is compared to its previous version.
+pub fn compare_reports(before: &BuildReport, after: &BuildReport) -> Vec<Regression> {
+    let mut regressions = Vec::new();
+
+    if after.duration_ms as f64 > before.duration_ms as f64 * SLOWER_BUILD_FACTOR {
+        regressions.push(Regression::SlowerBuild {
+            before_ms: before.duration_ms,
+            after_ms: after.duration_ms,
+        });
+    }
+    if after.total_size > before.total_size {
+        regressions.push(Regression::TotalSize {
+            before: before.total_size,
+            after: after.total_size,
+        });
+    }
+
+    let sizes_before = sizes_by_name(&before.files);
+    for (name, after_size) in sizes_by_name(&after.files) {
+        match sizes_before.get(&name) {
+            Some(&before_size) if after_size > before_size => {
+                regressions.push(Regression::FileGrew {
+                    name,
+                    before: before_size,
+                    after: after_size,
+                })
+            }
+            Some(_) => {}
+            None => regressions.push(Regression::FileAdded {
+                name,
+                size: after_size,
+            }),
+        }
+    }
+
+    let warnings_before: BTreeSet<&String> = before.warnings.iter().collect();
+    regressions.extend(
+        after
+            .warnings
+            .iter()
+            .filter(|warning| !warnings_before.contains(warning))
+            .map(|warning| Regression::Warning(warning.clone())),
+    );
+
+    let exceeded_before: BTreeSet<&str> = before
+        .exceeded_budgets()
+        .map(|budget| budget.glob.as_str())
+        .collect();
+    regressions.extend(
+        after
+            .exceeded_budgets()
+            .filter(|budget| !exceeded_before.contains(budget.glob.as_str()))
+            .map(|budget| Regression::BudgetExceeded {
+                glob: budget.glob.clone(),
+                limit: budget.limit,
+                size: budget.size,
+            }),
+    );
+
+    regressions
+}
+
+/// Sums the sizes of `files` by their name without content hash.
+fn sizes_by_name(files: &[FileSize]) -> BTreeMap<String, u64> {
+    let mut sizes = BTreeMap::new();
+    for file in files {
+        *sizes.entry(unhashed_name(&file.name)).or_default() += file.size;
+    }
+    sizes
+}
+
+/// Removes the `-[hash]` rolldown puts before the extension of chunk and
+/// asset names, e.g. `assets/logo-B1x2y3z4.svg` becomes `assets/logo.svg`.
+fn unhashed_name(name: &str) -> String {
+    let (dir, file) = name.rsplit_once('/').unwrap_or(("", name));
+    let (stem, ext) = file.split_once('.').unwrap_or((file, ""));
+
+    let stem = match stem.rsplit_once('-') {
+        Some((base, hash))
+            if hash.len() == HASH_LEN
+                && hash.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') =>
+        {
+            base
+        }
+        _ => stem,
+    };
+
+    let mut unhashed = String::with_capacity(name.len());
+    if !dir.is_empty() {
+        unhashed.push_str(dir);
+        unhashed.push('/');
+    }
+    unhashed.push_str(stem);
+    if !ext.is_empty() {
+        unhashed.push('.');
+        unhashed.push_str(ext);
+    }
+    unhashed
+}
+
+/// Formats a size in bytes for people, e.g. `1.5 kB`.
+pub(crate) fn format_size(bytes: u64) -> String {
+    match bytes {
+        0..1024 => format!("{bytes} B"),
+        1024..1_048_576 => format!("{:.1} kB", bytes as f64 / 1024.0),
+        _ => format!("{:.2} MB", bytes as f64 / 1_048_576.0),
+    }
+}
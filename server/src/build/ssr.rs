@@ -0,0 +1,119 @@
+use super::manifest::source_path;
+use crate::rolldown::chunk_stylesheets;
+use crate::server::Context;
+use palladin_shared::{PalladinError, PalladinResult};
+use rolldown_common::Output;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Suffix of the directory next to the build directory the server bundle is
+/// written to, e.g. `dist-server`.
+pub const SSR_BUILD_DIR_SUFFIX: &str = "-server";
+
+/// Name of the SSR manifest written next to the client build output.
+pub const SSR_MANIFEST_FILE: &str = "ssr-manifest.json";
+
+/// Module format of the server bundle.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SsrFormat {
+    /// ES modules, written as `.mjs`
+    #[default]
+    Esm,
+    /// CommonJS, written as `.cjs`
+    Cjs,
+}
+
+impl SsrFormat {
+    /// Returns the extension of the emitted files, which tells Node the
+    /// format regardless of the `type` of the project's package.json.
+    pub(crate) fn extension(&self) -> &'static str {
+        match self {
+            SsrFormat::Esm => "mjs",
+            SsrFormat::Cjs => "cjs",
+        }
+    }
+}
+
+palladin_shared::named_enum!(SsrFormat, "SSR format" {
+    Esm => "esm",
+    Cjs => "cjs",
+});
+
+/// Returns the directory the server bundle of the client build in
+/// `build_dir` is written to, a sibling so the server code is never deployed
+/// with the public files.
+pub fn ssr_build_dir(build_dir: &Path) -> PathBuf {
+    let name = build_dir
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    build_dir.with_file_name(format!("{name}{SSR_BUILD_DIR_SUFFIX}"))
+}
+
+/// Server entry bundled for Node next to the client build.
+#[derive(Debug, Clone)]
+pub struct SsrOptions {
+    /// Entry module, relative to the root
+    pub entry: PathBuf,
+    pub format: SsrFormat,
+}
+
+/// Maps the source modules of the client build to the files to preload when
+/// the server renders them, keyed by path relative to the root like Vite's
+/// `ssr-manifest.json`.
+///
+/// A module lists the chunk it was bundled into, the stylesheets of that
+/// chunk and the assets it imports, as URL paths including the base path.
+pub fn build_ssr_manifest(ctx: &Context, assets: &[Output]) -> BTreeMap<String, Vec<String>> {
+    let mut manifest: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut add = |src: String, file: String| {
+        let files = manifest.entry(src).or_default();
+        if !files.contains(&file) {
+            files.push(file);
+        }
+    };
+
+    for asset in assets {
+        match asset {
+            Output::Chunk(chunk) => {
+                let files: Vec<String> = std::iter::once(chunk.filename.to_string())
+                    .chain(chunk_stylesheets(assets, &chunk.filename))
+                    .map(|file| ctx.base_url(&format!("/{file}")))
+                    .collect();
+                for id in &chunk.modules.keys {
+                    let Some(src) = source_path(ctx.root(), Path::new(&id.to_string())) else {
+                        continue;
+                    };
+                    for file in &files {
+                        add(src.clone(), file.clone());
+                    }
+                }
+            }
+            Output::Asset(asset) => {
+                for src in &asset.original_file_names {
+                    add(
+                        src.to_string(),
+                        ctx.base_url(&format!("/{}", asset.filename)),
+                    );
+                }
+            }
+        }
+    }
+    manifest
+}
+
+/// Writes `manifest` into `dir/ssr-manifest.json`.
+///
+/// # Errors
+///
+/// Returns an error if the manifest cannot be written.
+pub fn write_ssr_manifest(
+    dir: &Path,
+    manifest: &BTreeMap<String, Vec<String>>,
+) -> PalladinResult<PathBuf> {
+    let json =
+        serde_json::to_string_pretty(manifest).map_err(|e| PalladinError::Build(e.into()))?;
+    let path = dir.join(SSR_MANIFEST_FILE);
+    fs_err::write(&path, json)?;
+    Ok(path)
+}
@@ -0,0 +1,71 @@
+use super::report::ChunkReport;
+use palladin_shared::{PalladinError, PalladinResult};
+use serde::Serialize;
+use std::path::Path;
+use std::time::Instant;
+
+/// Time spent in one phase of a build.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PhaseTiming {
+    /// Name of the phase, e.g. `bundle` or `checksums`
+    pub name: &'static str,
+    pub duration_ms: u64,
+}
+
+/// Measures the phases of a build one after the other.
+#[derive(Debug)]
+pub(crate) struct PhaseTimer {
+    phases: Vec<PhaseTiming>,
+    started: Instant,
+}
+
+impl PhaseTimer {
+    pub fn start() -> Self {
+        Self {
+            phases: Vec::new(),
+            started: Instant::now(),
+        }
+    }
+
+    /// Ends the running phase as `name` and starts the next one.
+    pub fn finish(&mut self, name: &'static str) {
+        let now = Instant::now();
+        self.phases.push(PhaseTiming {
+            name,
+            duration_ms: (now - self.started).as_millis() as u64,
+        });
+        self.started = now;
+    }
+
+    pub fn into_phases(self) -> Vec<PhaseTiming> {
+        self.phases
+    }
+}
+
+/// Machine-readable statistics of a build, written with
+/// [`BuildOptions::stats`](super::BuildOptions::stats) for dashboards and
+/// bundle size bots.
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildStats {
+    pub phases: Vec<PhaseTiming>,
+    /// The chunk manifest of the build report
+    pub chunks: Vec<ChunkReport>,
+}
+
+impl BuildStats {
+    pub fn new(phases: Vec<PhaseTiming>, chunks: Vec<ChunkReport>) -> Self {
+        Self { phases, chunks }
+    }
+
+    /// Writes the stats as JSON to `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn write(&self, path: &Path) -> PalladinResult {
+        let json =
+            serde_json::to_string_pretty(self).map_err(|e| PalladinError::Build(e.into()))?;
+        fs_err::write(path, json)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,123 @@
+mod rsync;
+mod s3;
+
+use crate::build::{BUILD_REPORT_FILE, BuildReport, CHECKSUMS_FILE, SIGNATURE_FILE};
+use crate::messages::Message;
+use crate::rolldown::guess_content_type;
+use crate::server::errors::cache_control;
+use log::info;
+use palladin_shared::{PalladinError, PalladinResult};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+
+pub use rsync::RsyncAdapter;
+pub use s3::{S3Adapter, S3Options};
+
+/// A file from the build directory that needs to be uploaded.
+#[derive(Debug, Clone)]
+pub struct DeployFile {
+    /// Path relative to the build directory, using forward slashes
+    pub path: String,
+    /// Absolute path on disk
+    pub source: PathBuf,
+    pub content_type: &'static str,
+    pub cache_control: &'static str,
+}
+
+impl DeployFile {
+    /// Creates the upload of `path`, cached forever when it's one of the
+    /// `immutable` files.
+    fn new(dir: &Path, path: &str, immutable: &HashSet<String>) -> Self {
+        Self {
+            path: path.to_string(),
+            source: dir.join(path),
+            content_type: guess_content_type(path),
+            cache_control: cache_control(immutable.contains(path)),
+        }
+    }
+}
+
+/// Destination a build can be deployed to.
+pub trait DeployAdapter {
+    /// Returns the checksum manifest of the current deployment, `None` if
+    /// nothing was deployed yet.
+    fn remote_manifest(&self) -> impl Future<Output = PalladinResult<Option<String>>> + Send;
+
+    /// Uploads `files`, overwriting existing ones.
+    fn upload(&self, files: &[DeployFile]) -> impl Future<Output = PalladinResult> + Send;
+}
+
+/// Outcome of a deployment.
+#[derive(Debug, Clone)]
+pub struct DeployReport {
+    /// Files that were uploaded, relative to the build directory
+    pub uploaded: Vec<String>,
+    /// Number of files skipped because the remote copy is up to date
+    pub unchanged: usize,
+}
+
+/// Uploads the files of the build in `dir` whose checksum differs from the
+/// current deployment.
+///
+/// The manifest is uploaded last, so an interrupted deploy is retried in full.
+///
+/// # Errors
+///
+/// Returns an error if `dir` has no checksum manifest or an upload fails.
+pub async fn deploy(adapter: &impl DeployAdapter, dir: &Path) -> PalladinResult<DeployReport> {
+    let manifest_path = dir.join(CHECKSUMS_FILE);
+    let manifest = fs_err::read_to_string(&manifest_path).map_err(|_| {
+        PalladinError::FileNotFound(format!(
+            "{}, run `palladin build` first",
+            manifest_path.display()
+        ))
+    })?;
+
+    let local = parse_manifest(&manifest);
+    let remote = adapter
+        .remote_manifest()
+        .await?
+        .map(|manifest| parse_manifest(&manifest))
+        .unwrap_or_default();
+
+    let mut changed: Vec<&str> = local
+        .iter()
+        .filter(|(path, checksum)| remote.get(*path) != Some(*checksum))
+        .map(|(path, _)| path.as_str())
+        .collect();
+    changed.sort_unstable();
+
+    // Builds without a report are revalidated as a whole
+    let immutable = BuildReport::read(&dir.join(BUILD_REPORT_FILE))
+        .map(|report| report.immutable_files())
+        .unwrap_or_default();
+    let files: Vec<DeployFile> = changed
+        .iter()
+        .map(|path| DeployFile::new(dir, path, &immutable))
+        .collect();
+    if !files.is_empty() {
+        info!("{}", Message::Uploading { count: files.len() });
+        adapter.upload(&files).await?;
+    }
+
+    let mut manifests = vec![DeployFile::new(dir, CHECKSUMS_FILE, &immutable)];
+    if dir.join(SIGNATURE_FILE).is_file() {
+        manifests.push(DeployFile::new(dir, SIGNATURE_FILE, &immutable));
+    }
+    adapter.upload(&manifests).await?;
+
+    Ok(DeployReport {
+        unchanged: local.len() - files.len(),
+        uploaded: changed.into_iter().map(str::to_string).collect(),
+    })
+}
+
+/// Parses `<hex>  <path>` lines into a map from path to checksum.
+fn parse_manifest(manifest: &str) -> HashMap<String, String> {
+    manifest
+        .lines()
+        .filter_map(|line| line.split_once("  "))
+        .map(|(checksum, path)| (path.to_string(), checksum.to_string()))
+        .collect()
+}
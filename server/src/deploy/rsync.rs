@@ -0,0 +1,83 @@
+use super::{DeployAdapter, DeployFile};
+use crate::build::CHECKSUMS_FILE;
+use anyhow::anyhow;
+use palladin_shared::{PalladinError, PalladinResult};
+use std::path::PathBuf;
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+/// Deploys with `rsync` to a local directory or an `[user@]host:path` target
+/// reachable over ssh.
+#[derive(Debug, Clone)]
+pub struct RsyncAdapter {
+    /// Directory uploaded files are relative to
+    dir: PathBuf,
+    /// Destination in rsync syntax
+    target: String,
+}
+
+impl RsyncAdapter {
+    pub fn new(dir: PathBuf, target: impl Into<String>) -> Self {
+        Self {
+            dir,
+            target: target.into(),
+        }
+    }
+
+    /// Splits an ssh target into host and path, `None` for local targets.
+    fn remote(&self) -> Option<(&str, &str)> {
+        // Windows drive letters like `C:\` aren't hosts
+        self.target
+            .split_once(':')
+            .filter(|(host, _)| host.len() > 1 && !host.contains('/'))
+    }
+}
+
+impl DeployAdapter for RsyncAdapter {
+    async fn remote_manifest(&self) -> PalladinResult<Option<String>> {
+        let Some((host, path)) = self.remote() else {
+            let path = PathBuf::from(&self.target).join(CHECKSUMS_FILE);
+            return Ok(fs_err::read_to_string(path).ok());
+        };
+
+        let output = Command::new("ssh")
+            .arg(host)
+            .arg(format!(
+                "cat '{}/{CHECKSUMS_FILE}'",
+                path.trim_end_matches('/')
+            ))
+            .stderr(Stdio::null())
+            .output()
+            .await?;
+
+        // A missing manifest just means nothing was deployed yet
+        Ok(output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).into_owned()))
+    }
+
+    async fn upload(&self, files: &[DeployFile]) -> PalladinResult {
+        let mut child = Command::new("rsync")
+            .args(["--archive", "--relative", "--files-from=-"])
+            .arg(format!("{}/", self.dir.display()))
+            .arg(&self.target)
+            .stdin(Stdio::piped())
+            .spawn()?;
+
+        let list: String = files
+            .iter()
+            .map(|file| format!("{}\n", file.path))
+            .collect();
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(list.as_bytes()).await?;
+        }
+
+        let status = child.wait().await?;
+        if !status.success() {
+            return Err(PalladinError::Build(anyhow!("rsync exited with {status}")));
+        }
+        Ok(())
+    }
+}
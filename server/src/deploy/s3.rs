@@ -0,0 +1,204 @@
+use super::{DeployAdapter, DeployFile};
+use crate::build::CHECKSUMS_FILE;
+use crate::server::ServerConfig;
+use anyhow::anyhow;
+use hmac::{Hmac, Mac};
+use log::debug;
+use palladin_shared::{PalladinError, PalladinResult, UtcDateTime};
+use reqwest::{Client, Method, RequestBuilder, StatusCode};
+use sha2::{Digest, Sha256};
+
+/// Connection settings for an S3-compatible bucket.
+#[derive(Debug, Clone)]
+pub struct S3Options {
+    /// Base URL of the service, e.g. `https://s3.eu-central-1.amazonaws.com`
+    pub endpoint: String,
+    pub region: String,
+    pub bucket: String,
+    /// Key prefix all files are uploaded under, e.g. `site/`
+    pub prefix: String,
+    pub access_key: String,
+    pub secret_key: String,
+}
+
+impl S3Options {
+    /// Creates options for `bucket`, reading credentials from the standard
+    /// `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY`, `AWS_REGION` and
+    /// `AWS_ENDPOINT_URL` environment variables.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the credentials are not set.
+    pub fn from_env(bucket: impl Into<String>, prefix: impl Into<String>) -> PalladinResult<Self> {
+        let var = |name: &str| {
+            std::env::var(name)
+                .map_err(|_| PalladinError::Build(anyhow!("{name} must be set to deploy to S3")))
+        };
+
+        let region = std::env::var("AWS_REGION").unwrap_or_else(|_| "us-east-1".to_string());
+        let endpoint = std::env::var("AWS_ENDPOINT_URL")
+            .unwrap_or_else(|_| format!("https://s3.{region}.amazonaws.com"));
+
+        let mut prefix = prefix.into();
+        if !prefix.is_empty() && !prefix.ends_with('/') {
+            prefix.push('/');
+        }
+
+        Ok(Self {
+            endpoint,
+            region,
+            bucket: bucket.into(),
+            prefix,
+            access_key: var("AWS_ACCESS_KEY_ID")?,
+            secret_key: var("AWS_SECRET_ACCESS_KEY")?,
+        })
+    }
+}
+
+/// Deploys to an S3-compatible bucket using path-style requests signed with
+/// AWS Signature Version 4.
+#[derive(Debug, Clone)]
+pub struct S3Adapter {
+    options: S3Options,
+    client: Client,
+}
+
+impl S3Adapter {
+    /// Creates an adapter sending its requests with `client`, e.g. one built
+    /// from [`HttpOptions`](crate::server::HttpOptions) so a stalled upload
+    /// times out.
+    pub fn new(options: S3Options, client: Client) -> Self {
+        Self { options, client }
+    }
+
+    /// Creates an adapter sending its requests with the HTTP settings of
+    /// `config`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP settings are invalid, see
+    /// [`HttpOptions::client`](crate::server::HttpOptions::client).
+    pub fn from_config(options: S3Options, config: &ServerConfig) -> PalladinResult<Self> {
+        Ok(Self::new(options, config.http.client()?))
+    }
+
+    fn key(&self, path: &str) -> String {
+        format!("{}{path}", self.options.prefix)
+    }
+
+    /// Builds a signed request for the object `key`.
+    fn request(&self, method: Method, key: &str, body: &[u8]) -> PalladinResult<RequestBuilder> {
+        let options = &self.options;
+        let endpoint = options.endpoint.trim_end_matches('/');
+        let host = endpoint
+            .split_once("://")
+            .map_or(endpoint, |(_, host)| host)
+            .to_string();
+        let path = format!("/{}/{}", options.bucket, uri_encode(key));
+
+        let now = UtcDateTime::now();
+        let date = format!("{:04}{:02}{:02}", now.year, now.month, now.day);
+        let timestamp = format!("{date}T{:02}{:02}{:02}Z", now.hour, now.minute, now.second);
+        let payload_hash = format!("{:x}", Sha256::digest(body));
+
+        let canonical_request = format!(
+            "{method}\n{path}\n\nhost:{host}\nx-amz-content-sha256:{payload_hash}\n\
+             x-amz-date:{timestamp}\n\nhost;x-amz-content-sha256;x-amz-date\n{payload_hash}"
+        );
+        let scope = format!("{date}/{}/s3/aws4_request", options.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{timestamp}\n{scope}\n{:x}",
+            Sha256::digest(canonical_request.as_bytes())
+        );
+
+        let mut key = format!("AWS4{}", options.secret_key).into_bytes();
+        for part in [date.as_str(), &options.region, "s3", "aws4_request"] {
+            key = hmac(&key, part.as_bytes())?;
+        }
+        let signature = hex(&hmac(&key, string_to_sign.as_bytes())?);
+
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{scope}, \
+             SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature={signature}",
+            options.access_key
+        );
+
+        Ok(self
+            .client
+            .request(method, format!("{endpoint}{path}"))
+            .header("x-amz-content-sha256", payload_hash)
+            .header("x-amz-date", timestamp)
+            .header("authorization", authorization))
+    }
+}
+
+impl DeployAdapter for S3Adapter {
+    async fn remote_manifest(&self) -> PalladinResult<Option<String>> {
+        let response = self
+            .request(Method::GET, &self.key(CHECKSUMS_FILE), &[])?
+            .send()
+            .await
+            .map_err(request_error)?;
+
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(None),
+            status if status.is_success() => {
+                Ok(Some(response.text().await.map_err(request_error)?))
+            }
+            status => Err(PalladinError::Build(anyhow!(
+                "fetching the remote manifest failed with {status}"
+            ))),
+        }
+    }
+
+    async fn upload(&self, files: &[DeployFile]) -> PalladinResult {
+        for file in files {
+            let body = fs_err::read(&file.source)?;
+            let response = self
+                .request(Method::PUT, &self.key(&file.path), &body)?
+                .header("content-type", file.content_type)
+                .header("cache-control", file.cache_control)
+                .body(body)
+                .send()
+                .await
+                .map_err(request_error)?;
+
+            if !response.status().is_success() {
+                return Err(PalladinError::Build(anyhow!(
+                    "uploading {} failed with {}",
+                    file.path,
+                    response.status()
+                )));
+            }
+            debug!("uploaded {}", file.path);
+        }
+        Ok(())
+    }
+}
+
+fn request_error(err: reqwest::Error) -> PalladinError {
+    PalladinError::Build(err.into())
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> PalladinResult<Vec<u8>> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(key).map_err(|err| PalladinError::Build(err.into()))?;
+    mac.update(data);
+    Ok(mac.finalize().into_bytes().to_vec())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Percent-encodes an object key as required by SigV4, keeping `/` separators.
+fn uri_encode(key: &str) -> String {
+    key.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
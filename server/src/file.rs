@@ -1,5 +1,5 @@
 use sha2::{Digest, Sha256};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone)]
 pub struct File {
@@ -14,9 +14,9 @@ impl File {
     /// Return MIME content type
     pub fn content_type(&self) -> &str {
         match self.ty {
-            FileType::CSS => "text/css",
-            FileType::JS | FileType::JSX | FileType::TS | FileType::TSX => "application/javascript",
-            FileType::HTML => "text/html",
+            FileType::Css => "text/css",
+            FileType::Js | FileType::Jsx | FileType::Ts | FileType::Tsx => "application/javascript",
+            FileType::Html => "text/html",
         }
     }
 }
@@ -29,15 +29,15 @@ pub struct FileContent {
 
 #[derive(Debug, Clone, Hash, Eq, PartialEq)]
 pub enum FileType {
-    CSS,
+    Css,
 
-    JS,
-    JSX,
+    Js,
+    Jsx,
 
-    TS,
-    TSX,
+    Ts,
+    Tsx,
 
-    HTML,
+    Html,
 }
 
 pub fn calculate_content_hash(content: &str) -> String {
@@ -46,7 +46,7 @@ pub fn calculate_content_hash(content: &str) -> String {
     format!("{:x}", hasher.finalize())
 }
 
-pub fn detect_file_type(path: &PathBuf) -> FileType {
+pub fn detect_file_type(path: &Path) -> FileType {
     match path
         .extension()
         .and_then(|s| s.to_str())
@@ -54,12 +54,12 @@ pub fn detect_file_type(path: &PathBuf) -> FileType {
         .to_ascii_lowercase()
         .as_str()
     {
-        "css" => FileType::CSS,
-        "js" => FileType::JS,
-        "jsx" => FileType::JSX,
-        "ts" => FileType::TS,
-        "tsx" => FileType::TSX,
-        "html" => FileType::HTML,
-        _ => FileType::JS,
+        "css" => FileType::Css,
+        "js" => FileType::Js,
+        "jsx" => FileType::Jsx,
+        "ts" => FileType::Ts,
+        "tsx" => FileType::Tsx,
+        "html" => FileType::Html,
+        _ => FileType::Js,
     }
 }
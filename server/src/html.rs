@@ -0,0 +1,157 @@
+/// Returns the value of attribute `name` in an opening tag such as
+/// `<script type="module" src="/src/main.tsx">`.
+pub fn tag_attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let mut offset = 0;
+
+    while let Some(index) = tag[offset..].find(name) {
+        let start = offset + index;
+        offset = start + name.len();
+
+        let preceded_by_space = tag[..start].chars().last().is_some_and(char::is_whitespace);
+        if !preceded_by_space {
+            continue;
+        }
+
+        let Some(value) = tag[offset..].trim_start().strip_prefix('=') else {
+            continue;
+        };
+        let value = value.trim_start();
+
+        return match value.chars().next()? {
+            quote @ ('"' | '\'') => value[1..].find(quote).map(|end| &value[1..=end]),
+            _ => value
+                .split(|c: char| c.is_whitespace() || c == '>' || c == '/')
+                .next(),
+        };
+    }
+
+    None
+}
+
+/// Returns every opening `<script ...>` tag in the document.
+pub fn script_tags(html: &str) -> Vec<&str> {
+    opening_tags(html, "script")
+}
+
+/// Returns every opening tag of the `name` elements in the document.
+pub fn opening_tags<'a>(html: &'a str, name: &str) -> Vec<&'a str> {
+    let open = format!("<{name}");
+    let mut tags = Vec::new();
+    let mut rest = html;
+
+    while let Some(start) = rest.find(&open) {
+        let Some(end) = rest[start..].find('>') else {
+            break;
+        };
+        tags.push(&rest[start..start + end + 1]);
+        rest = &rest[start + end + 1..];
+    }
+
+    tags
+}
+
+/// Returns the opening tag and the contents of every `name` element in the
+/// document, e.g. of inline `<script>` or `<style>` elements.
+pub fn element_contents<'a>(html: &'a str, name: &str) -> Vec<(&'a str, &'a str)> {
+    let close = format!("</{name}>");
+    let mut elements = Vec::new();
+    let mut rest = html;
+
+    for tag in opening_tags(html, name) {
+        let Some(start) = rest.find(tag) else {
+            break;
+        };
+        rest = &rest[start + tag.len()..];
+        let Some(end) = rest.find(&close) else {
+            break;
+        };
+        elements.push((tag, &rest[..end]));
+        rest = &rest[end + close.len()..];
+    }
+
+    elements
+}
+
+/// Returns the `src` of every `<script type="module">` tag in the document.
+pub fn module_script_sources(html: &str) -> Vec<String> {
+    script_tags(html)
+        .into_iter()
+        .filter(|tag| tag_attribute(tag, "type") == Some("module"))
+        .filter_map(|tag| tag_attribute(tag, "src"))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Rewrites the `src` of every module script, keeping it when `rewrite`
+/// returns `None`.
+pub fn rewrite_module_script_src(html: &str, rewrite: impl Fn(&str) -> Option<String>) -> String {
+    let mut result = html.to_string();
+    for tag in script_tags(html) {
+        if tag_attribute(tag, "type") != Some("module") {
+            continue;
+        }
+
+        let Some(old) = tag_attribute(tag, "src") else {
+            continue;
+        };
+        if let Some(src) = rewrite(old) {
+            let rewritten = tag.replacen(old, &src, 1);
+            result = result.replacen(tag, &rewritten, 1);
+        }
+    }
+    result
+}
+
+/// Inserts `tags` at the end of the document's `<head>`, or at its start when
+/// there is no `<head>`.
+pub fn inject_into_head(html: &str, tags: &str) -> String {
+    match html.find("</head>") {
+        Some(index) => format!("{}{tags}{}", &html[..index], &html[index..]),
+        None => format!("{tags}{html}"),
+    }
+}
+
+/// Returns a `<link rel="stylesheet">` tag for each of `hrefs`.
+pub fn stylesheet_links<S: AsRef<str>>(hrefs: impl IntoIterator<Item = S>) -> String {
+    hrefs
+        .into_iter()
+        .map(|href| format!("<link rel=\"stylesheet\" href=\"{}\">\n", href.as_ref()))
+        .collect()
+}
+
+/// Prefixes the root-relative `src` and `href` attributes of every tag with
+/// `base`, e.g. `/src/main.tsx` becomes `/app/src/main.tsx` for `/app/`.
+pub fn rebase_urls(html: &str, base: &str) -> String {
+    let prefix = base.trim_end_matches('/');
+    if prefix.is_empty() {
+        return html.to_string();
+    }
+
+    let mut result = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        let Some(end) = rest[start..].find('>') else {
+            break;
+        };
+        let tag = &rest[start..start + end + 1];
+        result.push_str(&rest[..start]);
+
+        let mut rewritten = tag.to_string();
+        let urls = ["src", "href"].map(|name| tag_attribute(tag, name));
+        for (index, url) in urls.iter().enumerate() {
+            let Some(url) = url else {
+                continue;
+            };
+            // Protocol-relative URLs point at other hosts
+            let root_relative = url.starts_with('/') && !url.starts_with("//");
+            if root_relative && !urls[..index].contains(&Some(*url)) {
+                rewritten = rewritten.replacen(url, &format!("{prefix}{url}"), 1);
+            }
+        }
+
+        result.push_str(&rewritten);
+        rest = &rest[start + end + 1..];
+    }
+    result.push_str(rest);
+    result
+}
@@ -1,3 +1,11 @@
+pub mod build;
+pub mod deploy;
 mod file;
+mod html;
+mod messages;
+pub mod playground;
+pub mod prelude;
+pub mod preview;
 mod rolldown;
 pub mod server;
+pub mod transform;
@@ -0,0 +1,698 @@
+use crate::server::{Framework, ToolRequirement};
+use palladin_shared::{CatalogMessage, message_path, write_message};
+use std::fmt;
+use std::path::Path;
+
+/// Message logged by the server, displayed in the locale set at startup.
+#[derive(Clone, Copy)]
+pub(crate) enum Message<'a> {
+    RestartRunning,
+    Restarting,
+    EngineStopped {
+        error: &'a dyn fmt::Display,
+    },
+    RestartFailed {
+        error: &'a dyn fmt::Display,
+    },
+    UnusableTools {
+        tools: &'a [(ToolRequirement, String)],
+    },
+    InvalidHeader {
+        name: &'a dyn fmt::Display,
+        value: &'a str,
+    },
+    ErrorPageUnreadable {
+        page: &'a Path,
+        error: &'a dyn fmt::Display,
+    },
+    ServingOldBuild {
+        id: u64,
+    },
+    ServingLatestBuild {
+        id: u64,
+    },
+    Watching {
+        path: &'a Path,
+    },
+    CannotWatch {
+        path: &'a Path,
+        error: &'a dyn fmt::Display,
+    },
+    IgnoreFileUnreadable {
+        path: &'a Path,
+        error: &'a dyn fmt::Display,
+    },
+    InvalidIgnorePatterns {
+        error: &'a dyn fmt::Display,
+    },
+    WatcherError {
+        error: &'a dyn fmt::Display,
+    },
+    WatcherStopped {
+        error: &'a dyn fmt::Display,
+    },
+    MissedBufferChanges {
+        count: u64,
+    },
+    MissedInvalidations {
+        count: u64,
+    },
+    InvalidationFailed {
+        id: &'a str,
+        error: &'a dyn fmt::Debug,
+    },
+    RebuildFailed {
+        error: &'a dyn fmt::Debug,
+    },
+    Scanning {
+        directories: usize,
+        files: usize,
+    },
+    Scanned {
+        directories: usize,
+        files: usize,
+        seconds: f64,
+    },
+    CannotScan {
+        dir: &'a Path,
+        error: &'a dyn fmt::Display,
+    },
+    OversizedDirectory {
+        dir: &'a Path,
+        entries: usize,
+        limit: usize,
+    },
+    MocksUnreadable {
+        dir: &'a Path,
+        error: &'a dyn fmt::Display,
+    },
+    MockShadowed {
+        route: &'a str,
+    },
+    Mocking {
+        route: &'a str,
+    },
+    InvalidClientFrame {
+        client: &'a str,
+        error: &'a dyn fmt::Display,
+    },
+    HmrError {
+        error: &'a dyn fmt::Debug,
+    },
+    InvalidNodeResponse {
+        error: &'a dyn fmt::Display,
+    },
+    PluginsFailed {
+        error: &'a dyn fmt::Display,
+        specifiers: &'a [String],
+    },
+    MissingRewriteTarget {
+        rewrite: &'a dyn fmt::Display,
+    },
+    DetectedFramework {
+        framework: Framework,
+    },
+    BrowserslistTargets {
+        targets: &'a [String],
+    },
+    SkippedBrowserslistQueries {
+        queries: &'a [&'a str],
+    },
+    ServingSsrEntry {
+        path: &'a str,
+    },
+    AcceptingBuffers,
+    NoIndex,
+    RecordingHar {
+        path: &'a Path,
+    },
+    HarWriteFailed {
+        error: &'a dyn fmt::Display,
+    },
+    BundleWarning {
+        warning: &'a dyn fmt::Display,
+    },
+    BuildWarning {
+        warning: &'a dyn fmt::Display,
+    },
+    BundleError {
+        error: &'a dyn fmt::Display,
+    },
+    BundleOutputFailed {
+        error: &'a dyn fmt::Display,
+    },
+    BrokenOutput {
+        problem: &'a dyn fmt::Display,
+    },
+    OrderHazard {
+        hazard: &'a dyn fmt::Display,
+    },
+    InvalidSideEffectGlobs {
+        error: &'a dyn fmt::Display,
+    },
+    CaseMismatch {
+        importer: &'a str,
+        specifier: &'a str,
+        expected: &'a str,
+        actual: &'a str,
+    },
+    TailwindFailed {
+        path: &'a str,
+        error: &'a dyn fmt::Display,
+    },
+    SkippedWorktree {
+        root: &'a Path,
+    },
+    ServingTenant {
+        root: &'a Path,
+        address: &'a str,
+        name: &'a str,
+    },
+    RunningCommand {
+        command: &'a str,
+    },
+    CommandFailed {
+        command: &'a str,
+        status: &'a dyn fmt::Display,
+    },
+    PromptSkipped {
+        question: &'a str,
+    },
+    InstallFailed {
+        error: &'a dyn fmt::Display,
+    },
+    Wrote {
+        path: &'a Path,
+    },
+    SignedChecksums {
+        path: &'a Path,
+    },
+    OverBudget {
+        glob: &'a str,
+        size: u64,
+        limit: u64,
+    },
+    Uploading {
+        count: usize,
+    },
+    ReportingCsp {
+        csp: &'a str,
+    },
+    PlaygroundUpdated {
+        path: &'a str,
+    },
+    PlaygroundRemoved {
+        path: &'a str,
+    },
+    StaleBuild,
+    ServerExposed {
+        action: &'a str,
+    },
+    CrossOriginRejected {
+        action: &'a str,
+    },
+    FirstBuildBroken {
+        problems: usize,
+    },
+    InvalidTenant {
+        tenant: &'a str,
+    },
+    GitFailed {
+        error: &'a dyn fmt::Display,
+    },
+    WorktreeListFailed {
+        error: &'a str,
+    },
+    NodeStartFailed {
+        error: &'a dyn fmt::Display,
+    },
+    NodeSetupFailed {
+        name: &'a str,
+        error: &'a dyn fmt::Display,
+    },
+    NoFeatureId {
+        name: &'a str,
+    },
+    NodeExited {
+        name: Option<&'a str>,
+    },
+    HookFailed {
+        hook: &'a str,
+        error: &'a str,
+    },
+}
+
+impl CatalogMessage for Message<'_> {
+    fn id(&self) -> &'static str {
+        match self {
+            Message::RestartRunning => "dev.restart-running",
+            Message::Restarting => "dev.restarting",
+            Message::EngineStopped { .. } => "dev.engine-stopped",
+            Message::RestartFailed { .. } => "dev.restart-failed",
+            Message::UnusableTools { .. } => "tools.unavailable",
+            Message::InvalidHeader { .. } => "dev.invalid-header",
+            Message::ErrorPageUnreadable { .. } => "dev.error-page-unreadable",
+            Message::ServingOldBuild { .. } => "history.serving-build",
+            Message::ServingLatestBuild { .. } => "history.serving-latest",
+            Message::Watching { .. } => "watch.watching",
+            Message::CannotWatch { .. } => "watch.cannot-watch",
+            Message::IgnoreFileUnreadable { .. } => "watch.ignore-file-unreadable",
+            Message::InvalidIgnorePatterns { .. } => "watch.invalid-ignore-patterns",
+            Message::WatcherError { .. } => "watch.error",
+            Message::WatcherStopped { .. } => "watch.stopped",
+            Message::MissedBufferChanges { .. } => "watch.missed-buffer-changes",
+            Message::MissedInvalidations { .. } => "watch.missed-invalidations",
+            Message::InvalidationFailed { .. } => "watch.invalidation-failed",
+            Message::RebuildFailed { .. } => "watch.rebuild-failed",
+            Message::Scanning { .. } => "watch.scanning",
+            Message::Scanned { .. } => "watch.scanned",
+            Message::CannotScan { .. } => "watch.cannot-scan",
+            Message::OversizedDirectory { .. } => "watch.oversized-directory",
+            Message::MocksUnreadable { .. } => "mocks.unreadable",
+            Message::MockShadowed { .. } => "mocks.shadowed",
+            Message::Mocking { .. } => "mocks.route",
+            Message::InvalidClientFrame { .. } => "hmr.invalid-message",
+            Message::HmrError { .. } => "hmr.error",
+            Message::InvalidNodeResponse { .. } => "node.invalid-response",
+            Message::PluginsFailed { .. } => "plugins.failed",
+            Message::MissingRewriteTarget { .. } => "dev.missing-rewrite-target",
+            Message::DetectedFramework { .. } => "dev.framework",
+            Message::BrowserslistTargets { .. } => "dev.browserslist-targets",
+            Message::SkippedBrowserslistQueries { .. } => "dev.browserslist-skipped",
+            Message::ServingSsrEntry { .. } => "dev.ssr-entry",
+            Message::AcceptingBuffers => "dev.buffers",
+            Message::NoIndex => "dev.noindex",
+            Message::RecordingHar { .. } => "dev.har",
+            Message::HarWriteFailed { .. } => "dev.har-failed",
+            Message::BundleWarning { .. } => "bundle.warning",
+            Message::BuildWarning { .. } => "build.warning",
+            Message::BundleError { .. } => "bundle.error",
+            Message::BundleOutputFailed { .. } => "bundle.output-failed",
+            Message::BrokenOutput { .. } => "bundle.broken-output",
+            Message::OrderHazard { .. } => "bundle.order-hazard",
+            Message::InvalidSideEffectGlobs { .. } => "bundle.invalid-side-effect-globs",
+            Message::CaseMismatch { .. } => "bundle.case-mismatch",
+            Message::TailwindFailed { .. } => "css.tailwind-failed",
+            Message::SkippedWorktree { .. } => "tenants.skipped-worktree",
+            Message::ServingTenant { .. } => "tenants.serving",
+            Message::RunningCommand { .. } => "install.running",
+            Message::CommandFailed { .. } => "install.failed",
+            Message::PromptSkipped { .. } => "install.prompt-skipped",
+            Message::InstallFailed { .. } => "install.error",
+            Message::Wrote { .. } => "build.wrote",
+            Message::SignedChecksums { .. } => "build.signed",
+            Message::OverBudget { .. } => "build.over-budget",
+            Message::Uploading { .. } => "deploy.uploading",
+            Message::ReportingCsp { .. } => "preview.csp-report",
+            Message::PlaygroundUpdated { .. } => "playground.updated",
+            Message::PlaygroundRemoved { .. } => "playground.removed",
+            Message::StaleBuild => "history.stale-build",
+            Message::ServerExposed { .. } => "dev.exposed",
+            Message::CrossOriginRejected { .. } => "dev.cross-origin",
+            Message::FirstBuildBroken { .. } => "bundle.first-build-broken",
+            Message::InvalidTenant { .. } => "tenants.invalid",
+            Message::GitFailed { .. } => "tenants.git-failed",
+            Message::WorktreeListFailed { .. } => "tenants.worktree-list-failed",
+            Message::NodeStartFailed { .. } => "node.start-failed",
+            Message::NodeSetupFailed { .. } => "node.setup-failed",
+            Message::NoFeatureId { .. } => "node.no-feature-id",
+            Message::NodeExited { .. } => "node.exited",
+            Message::HookFailed { .. } => "node.hook-failed",
+        }
+    }
+
+    fn args(&self) -> Vec<(&'static str, String)> {
+        match *self {
+            Message::EngineStopped { error }
+            | Message::RestartFailed { error }
+            | Message::InvalidIgnorePatterns { error }
+            | Message::WatcherError { error }
+            | Message::WatcherStopped { error }
+            | Message::InvalidNodeResponse { error }
+            | Message::HarWriteFailed { error }
+            | Message::BundleError { error }
+            | Message::InvalidSideEffectGlobs { error }
+            | Message::InstallFailed { error }
+            | Message::GitFailed { error }
+            | Message::NodeStartFailed { error } => vec![("error", error.to_string())],
+            Message::BundleOutputFailed { error } => vec![("error", format!("{error:#}"))],
+            Message::UnusableTools { tools } => {
+                let tools: Vec<_> = tools.iter().map(|(req, _)| req.tool.as_str()).collect();
+                vec![("tools", tools.join(","))]
+            }
+            Message::InvalidHeader { name, value } => {
+                vec![("name", name.to_string()), ("value", format!("{value:?}"))]
+            }
+            Message::ErrorPageUnreadable { page, error } => {
+                vec![("page", message_path(page)), ("error", error.to_string())]
+            }
+            Message::ServingOldBuild { id } | Message::ServingLatestBuild { id } => {
+                vec![("id", id.to_string())]
+            }
+            Message::Watching { path } => vec![("path", message_path(path))],
+            Message::CannotWatch { path, error }
+            | Message::IgnoreFileUnreadable { path, error } => {
+                vec![("path", message_path(path)), ("error", error.to_string())]
+            }
+            Message::MissedBufferChanges { count } | Message::MissedInvalidations { count } => {
+                vec![("count", count.to_string())]
+            }
+            Message::InvalidationFailed { id, error } => {
+                vec![("id", id.to_string()), ("error", format!("{error:?}"))]
+            }
+            Message::RebuildFailed { error } | Message::HmrError { error } => {
+                vec![("error", format!("{error:?}"))]
+            }
+            Message::Scanning { directories, files } => vec![
+                ("directories", directories.to_string()),
+                ("files", files.to_string()),
+            ],
+            Message::Scanned {
+                directories,
+                files,
+                seconds,
+            } => vec![
+                ("directories", directories.to_string()),
+                ("files", files.to_string()),
+                ("seconds", format!("{seconds:.1}")),
+            ],
+            Message::CannotScan { dir, error } | Message::MocksUnreadable { dir, error } => {
+                vec![("dir", message_path(dir)), ("error", error.to_string())]
+            }
+            Message::OversizedDirectory {
+                dir,
+                entries,
+                limit,
+            } => vec![
+                ("dir", message_path(dir)),
+                ("entries", entries.to_string()),
+                ("limit", limit.to_string()),
+            ],
+            Message::MockShadowed { route } | Message::Mocking { route } => {
+                vec![("route", route.to_string())]
+            }
+            Message::InvalidClientFrame { client, error } => {
+                vec![("client", client.to_string()), ("error", error.to_string())]
+            }
+            Message::PluginsFailed { error, specifiers } => vec![
+                ("error", error.to_string()),
+                ("plugins", specifiers.join(",")),
+            ],
+            Message::MissingRewriteTarget { rewrite } => vec![("rewrite", rewrite.to_string())],
+            Message::DetectedFramework { framework } => {
+                vec![("framework", framework.to_string())]
+            }
+            Message::BrowserslistTargets { targets } => vec![("targets", targets.join(","))],
+            Message::SkippedBrowserslistQueries { queries } => {
+                vec![("queries", queries.join(","))]
+            }
+            Message::ServingSsrEntry { path }
+            | Message::PlaygroundUpdated { path }
+            | Message::PlaygroundRemoved { path } => vec![("path", path.to_string())],
+            Message::RecordingHar { path }
+            | Message::Wrote { path }
+            | Message::SignedChecksums { path } => vec![("path", message_path(path))],
+            Message::BundleWarning { warning } | Message::BuildWarning { warning } => {
+                vec![("warning", warning.to_string())]
+            }
+            Message::BrokenOutput { problem } => vec![("problem", problem.to_string())],
+            Message::OrderHazard { hazard } => vec![("hazard", hazard.to_string())],
+            Message::CaseMismatch {
+                importer,
+                specifier,
+                expected,
+                actual,
+            } => vec![
+                ("importer", importer.to_string()),
+                ("specifier", specifier.to_string()),
+                ("expected", expected.to_string()),
+                ("actual", actual.to_string()),
+            ],
+            Message::TailwindFailed { path, error } => {
+                vec![("path", path.to_string()), ("error", error.to_string())]
+            }
+            Message::SkippedWorktree { root } => vec![("root", message_path(root))],
+            Message::ServingTenant {
+                root,
+                address,
+                name,
+            } => vec![
+                ("root", message_path(root)),
+                ("address", address.to_string()),
+                ("name", name.to_string()),
+            ],
+            Message::RunningCommand { command } => vec![("command", command.to_string())],
+            Message::CommandFailed { command, status } => vec![
+                ("command", command.to_string()),
+                ("status", status.to_string()),
+            ],
+            Message::PromptSkipped { question } => vec![("question", question.to_string())],
+            Message::OverBudget { glob, size, limit } => vec![
+                ("glob", glob.to_string()),
+                ("size", size.to_string()),
+                ("limit", limit.to_string()),
+            ],
+            Message::Uploading { count } => vec![("count", count.to_string())],
+            Message::ReportingCsp { csp } => vec![("csp", csp.to_string())],
+            Message::ServerExposed { action } | Message::CrossOriginRejected { action } => {
+                vec![("action", action.to_string())]
+            }
+            Message::FirstBuildBroken { problems } => vec![("problems", problems.to_string())],
+            Message::InvalidTenant { tenant } => vec![("tenant", tenant.to_string())],
+            Message::WorktreeListFailed { error } => vec![("error", error.to_string())],
+            Message::NodeSetupFailed { name, error } => {
+                vec![("name", name.to_string()), ("error", error.to_string())]
+            }
+            Message::NoFeatureId { name } | Message::NodeExited { name: Some(name) } => {
+                vec![("name", name.to_string())]
+            }
+            Message::HookFailed { hook, error } => {
+                vec![("hook", hook.to_string()), ("error", error.to_string())]
+            }
+            Message::RestartRunning
+            | Message::Restarting
+            | Message::AcceptingBuffers
+            | Message::NoIndex
+            | Message::StaleBuild
+            | Message::NodeExited { name: None } => Vec::new(),
+        }
+    }
+
+    fn fmt_en(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Message::RestartRunning => f.write_str("a restart is already running"),
+            Message::Restarting => f.write_str("restarting, bundling the project from scratch"),
+            Message::EngineStopped { error } => write!(f, "dev engine stopped: {error}"),
+            Message::RestartFailed { error } => write!(f, "restart failed: {error}"),
+            Message::UnusableTools { tools } => {
+                f.write_str("some features are unavailable:")?;
+                for (req, problem) in tools {
+                    write!(
+                        f,
+                        "\n  - {}: {problem}, needed for {}\n    {}",
+                        req.tool, req.needed_for, req.install_hint
+                    )?;
+                }
+                Ok(())
+            }
+            Message::InvalidHeader { name, value } => {
+                write!(f, "dropping invalid value for header {name}: {value:?}")
+            }
+            Message::ErrorPageUnreadable { page, error } => {
+                write!(f, "failed to read error page {}: {error}", page.display())
+            }
+            Message::ServingOldBuild { id } => write!(
+                f,
+                "serving build {id}, changes made since aren't shown until the next rebuild"
+            ),
+            Message::ServingLatestBuild { id } => write!(f, "serving the latest build {id} again"),
+            Message::Watching { path } => write!(f, "watching {}", path.display()),
+            Message::CannotWatch { path, error } => {
+                write!(f, "cannot watch {}: {error}", path.display())
+            }
+            Message::IgnoreFileUnreadable { path, error } => {
+                write!(f, "failed to read {}: {error}", path.display())
+            }
+            Message::InvalidIgnorePatterns { error } => {
+                write!(f, "invalid ignore patterns: {error}")
+            }
+            Message::WatcherError { error } => write!(f, "file watcher error: {error}"),
+            Message::WatcherStopped { error } => write!(f, "file watcher stopped: {error}"),
+            Message::MissedBufferChanges { count } => {
+                write!(f, "missed {count} in-memory file changes")
+            }
+            Message::MissedInvalidations { count } => {
+                write!(f, "missed {count} module invalidations")
+            }
+            Message::InvalidationFailed { id, error } => {
+                write!(f, "failed to invalidate {id}: {error:#?}")
+            }
+            Message::RebuildFailed { error } => write!(f, "rebuild failed: {error:#?}"),
+            Message::Scanning { directories, files } => write!(
+                f,
+                "scanning project: {directories} directories, {files} files so far"
+            ),
+            Message::Scanned {
+                directories,
+                files,
+                seconds,
+            } => write!(
+                f,
+                "scanned {directories} directories and {files} files in {seconds:.1}s"
+            ),
+            Message::CannotScan { dir, error } => {
+                write!(f, "cannot scan {}: {error}", dir.display())
+            }
+            Message::OversizedDirectory {
+                dir,
+                entries,
+                limit,
+            } => write!(
+                f,
+                "not watching {}, it has {entries} entries (limit {limit})",
+                dir.display()
+            ),
+            Message::MocksUnreadable { dir, error } => {
+                write!(f, "failed to read mocks from {}: {error}", dir.display())
+            }
+            Message::MockShadowed { route } => {
+                write!(f, "ignoring mock for {route}, the dev server answers it")
+            }
+            Message::Mocking { route } => write!(f, "mocking {route}"),
+            Message::InvalidClientFrame { client, error } => {
+                write!(f, "invalid message from HMR client {client}: {error}")
+            }
+            Message::HmrError { error } => write!(f, "HMR error: {error:#?}"),
+            Message::InvalidNodeResponse { error } => {
+                write!(f, "invalid response from node: {error}")
+            }
+            Message::PluginsFailed { error, specifiers } => {
+                write!(f, "{error} ({})", specifiers.join(", "))
+            }
+            Message::MissingRewriteTarget { rewrite } => {
+                write!(f, "rewrite {rewrite} points to a missing file")
+            }
+            Message::DetectedFramework { framework } => write!(
+                f,
+                "detected {framework} project, using its JSX and chunking defaults, \
+                 override with --framework <name>"
+            ),
+            Message::BrowserslistTargets { targets } => {
+                write!(
+                    f,
+                    "transpiling for {} from browserslist",
+                    targets.join(", ")
+                )
+            }
+            Message::SkippedBrowserslistQueries { queries } => write!(
+                f,
+                "ignoring browserslist queries without a browser version: {}, pass --target to \
+                 set the transpile target",
+                queries.join(", ")
+            ),
+            Message::ServingSsrEntry { path } => write!(f, "serving the SSR entry at {path}"),
+            Message::AcceptingBuffers => {
+                f.write_str("accepting unsaved editor buffers at /__buffers")
+            }
+            Message::NoIndex => f.write_str("asking crawlers not to index the server"),
+            Message::RecordingHar { path } => {
+                write!(f, "recording requests to {}", path.display())
+            }
+            Message::HarWriteFailed { error } => write!(f, "failed to write HAR file: {error}"),
+            Message::BundleWarning { warning } => write!(f, "rolldown warning: {warning}"),
+            Message::BuildWarning { warning } => write!(f, "{warning}"),
+            Message::BundleError { error } => write!(f, "rolldown build error: {error}"),
+            Message::BundleOutputFailed { error } => {
+                write!(f, "failed to process rolldown output: {error:#}")
+            }
+            Message::BrokenOutput { problem } => write!(f, "{problem}"),
+            Message::OrderHazard { hazard } => write!(f, "execution order hazard: {hazard}"),
+            Message::InvalidSideEffectGlobs { error } => {
+                write!(f, "ignoring side effect free globs: {error}")
+            }
+            Message::CaseMismatch {
+                importer,
+                specifier,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{importer} imports \"{specifier}\", but \"{expected}\" is named \"{actual}\" on \
+                 disk, which breaks on case-sensitive filesystems"
+            ),
+            Message::TailwindFailed { path, error } => {
+                write!(f, "failed to regenerate {path}: {error}")
+            }
+            Message::SkippedWorktree { root } => {
+                write!(f, "skipping worktree {}, no usable name", root.display())
+            }
+            Message::ServingTenant {
+                root,
+                address,
+                name,
+            } => write!(f, "serving {} at http://{address}/{name}/", root.display()),
+            Message::RunningCommand { command } => write!(f, "running `{command}`"),
+            Message::CommandFailed { command, status } => {
+                write!(f, "`{command}` failed with {status}")
+            }
+            Message::PromptSkipped { question } => write!(
+                f,
+                "{question} skipped, stdin is not a terminal, pass --yes to install anyway"
+            ),
+            Message::InstallFailed { error } => write!(f, "{error}"),
+            Message::Wrote { path } => write!(f, "wrote {}", path.display()),
+            Message::SignedChecksums { path } => {
+                write!(f, "signed checksums into {}", path.display())
+            }
+            Message::OverBudget { glob, size, limit } => {
+                write!(
+                    f,
+                    "{glob} is {size} bytes, over its budget of {limit} bytes"
+                )
+            }
+            Message::Uploading { count } => write!(f, "uploading {count} changed files"),
+            Message::ReportingCsp { csp } => {
+                write!(f, "reporting violations of Content-Security-Policy: {csp}")
+            }
+            Message::PlaygroundUpdated { path } => write!(f, "playground: updated {path}"),
+            Message::PlaygroundRemoved { path } => write!(f, "playground: removed {path}"),
+            Message::StaleBuild => f.write_str("stale build, reload the page"),
+            Message::ServerExposed { action } => write!(
+                f,
+                "{action} is disabled while the server is exposed to the network"
+            ),
+            Message::CrossOriginRejected { action } => write!(f, "cross-origin {action} rejected"),
+            Message::FirstBuildBroken { problems } => write!(
+                f,
+                "the first build is broken, {problems} problem(s) found in its output"
+            ),
+            Message::InvalidTenant { tenant } => write!(
+                f,
+                "invalid tenant '{tenant}', expected DIR or NAME=DIR with NAME made of letters, \
+                 digits, '-', '_' and '.'"
+            ),
+            Message::GitFailed { error } => write!(f, "failed to run git: {error}"),
+            Message::WorktreeListFailed { error } => {
+                write!(f, "git worktree list failed: {error}")
+            }
+            Message::NodeStartFailed { error } => write!(f, "failed to start node: {error}"),
+            Message::NodeSetupFailed { name, error } => {
+                write!(f, "failed to set up {name}: {error}")
+            }
+            Message::NoFeatureId { name } => write!(f, "node returned no feature id for {name}"),
+            Message::NodeExited { name: Some(name) } => {
+                write!(f, "node running {name} exited, check the output above")
+            }
+            Message::NodeExited { name: None } => {
+                f.write_str("node exited, check the output above")
+            }
+            Message::HookFailed { hook, error } => write!(f, "{hook} failed: {error}"),
+        }
+    }
+}
+
+impl fmt::Display for Message<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_message(self, f)
+    }
+}
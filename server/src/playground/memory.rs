@@ -0,0 +1,99 @@
+use crate::server::PalladinPlugin;
+use palladin_shared::PalladinResult;
+use std::collections::BTreeMap;
+
+/// Prefix of the ids of in-memory modules, keeping them apart from files on
+/// disk.
+pub const MEMORY_ROOT: &str = "/__playground__";
+
+/// Suffixes tried when an import leaves out the extension.
+const RESOLVE_SUFFIXES: &[&str] = &[
+    "",
+    ".tsx",
+    ".ts",
+    ".jsx",
+    ".js",
+    "/index.tsx",
+    "/index.ts",
+    "/index.jsx",
+    "/index.js",
+];
+
+/// Resolves and loads modules from a set of in-memory files, keyed by their
+/// path relative to the playground root (e.g. `src/App.tsx`).
+///
+/// Relative and root-relative imports of in-memory modules are resolved
+/// against the files, bare imports are left to the bundler.
+#[derive(Debug)]
+pub struct MemoryFiles {
+    files: BTreeMap<String, String>,
+}
+
+impl MemoryFiles {
+    pub fn new(files: BTreeMap<String, String>) -> Self {
+        Self { files }
+    }
+
+    /// Returns the file `path` refers to, trying the usual extensions.
+    fn find(&self, path: &str) -> Option<&str> {
+        RESOLVE_SUFFIXES.iter().find_map(|suffix| {
+            let (key, _) = self.files.get_key_value(&format!("{path}{suffix}"))?;
+            Some(key.as_str())
+        })
+    }
+}
+
+impl PalladinPlugin for MemoryFiles {
+    fn name(&self) -> &str {
+        "memory-files"
+    }
+
+    fn resolve_id(
+        &self,
+        specifier: &str,
+        importer: Option<&str>,
+    ) -> PalladinResult<Option<String>> {
+        let importer = importer.and_then(|importer| importer.strip_prefix(MEMORY_ROOT));
+        let path = if let Some(path) = specifier.strip_prefix(MEMORY_ROOT) {
+            path.to_string()
+        } else if specifier.starts_with("./") || specifier.starts_with("../") {
+            let Some(importer) = importer else {
+                return Ok(None);
+            };
+            let dir = importer.rsplit_once('/').map_or("", |(dir, _)| dir);
+            format!("{dir}/{specifier}")
+        } else if specifier.starts_with('/') && importer.is_some() {
+            specifier.to_string()
+        } else {
+            return Ok(None);
+        };
+
+        Ok(normalize(&path)
+            .and_then(|path| self.find(&path))
+            .map(|key| format!("{MEMORY_ROOT}/{key}")))
+    }
+
+    fn load(&self, id: &str) -> PalladinResult<Option<String>> {
+        Ok(id
+            .strip_prefix(MEMORY_ROOT)
+            .map(|path| path.trim_start_matches('/'))
+            .and_then(|path| self.files.get(path))
+            .cloned())
+    }
+}
+
+/// Resolves `.` and `..` segments of a `/` separated path, returning it
+/// without a leading slash. Returns `None` for paths leaving the root.
+pub fn normalize(path: &str) -> Option<String> {
+    let mut segments = Vec::new();
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop()?;
+            }
+            segment => segments.push(segment),
+        }
+    }
+    Some(segments.join("/"))
+}
@@ -0,0 +1,319 @@
+mod memory;
+
+use crate::html::{inject_into_head, rewrite_module_script_src, stylesheet_links};
+use crate::messages::Message;
+use crate::rolldown::{create_virtual_bundler, guess_content_type};
+use crate::server::errors::{HttpResponse, REVALIDATE};
+use crate::server::listener;
+use crate::server::{ModuleDependencies, PalladinPlugin};
+use axum::extract::{Path, State};
+use axum::http::Response;
+use axum::routing::get;
+use axum::{Json, Router};
+use ignore::WalkBuilder;
+use log::{debug, info};
+use memory::{MEMORY_ROOT, MemoryFiles, normalize};
+use palladin_shared::{PalladinError, PalladinResult};
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path as FsPath;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Editor page served at `/`.
+const EDITOR_HTML: &str = include_str!("editor.html");
+
+/// File name of the bundled entry chunk.
+const ENTRY_CHUNK: &str = "index.js";
+
+/// Entry module of the playground unless configured otherwise.
+pub const DEFAULT_PLAYGROUND_ENTRY: &str = "src/index.tsx";
+
+/// Entry module the playground starts with when no files are given.
+const STARTER_ENTRY: &str = r##"const root = document.querySelector("#root")!;
+root.textContent = "Hello from the Palladin playground";
+"##;
+
+/// Options for serving a playground.
+#[derive(Debug, Clone)]
+pub struct PlaygroundOptions {
+    /// The `host:port` address to bind to.
+    pub address: String,
+    /// Path of the entry module among the files, e.g. `src/index.tsx`.
+    pub entry: String,
+    /// Files the playground starts with, keyed by `/` separated path. A
+    /// starter entry module is used when empty.
+    pub files: BTreeMap<String, String>,
+    /// Filename pattern of the chunks besides the entry chunk, e.g.
+    /// `[name]-[hash].js`
+    pub chunk_filenames: String,
+    /// Plugins run after the ones loading the in-memory modules.
+    pub plugins: Vec<Arc<dyn PalladinPlugin>>,
+}
+
+struct Playground {
+    entry: String,
+    chunk_filenames: String,
+    plugins: Vec<Arc<dyn PalladinPlugin>>,
+    /// Dependencies the plugins declared for the modules they load
+    dependencies: Arc<ModuleDependencies>,
+    files: RwLock<BTreeMap<String, String>>,
+    bundle: RwLock<Bundle>,
+    /// Held while bundling so rebuilds don't overlap
+    building: Mutex<()>,
+}
+
+/// Output of the latest bundle.
+#[derive(Debug, Clone, Default)]
+struct Bundle {
+    revision: u64,
+    error: Option<String>,
+    /// Emitted files by name, kept from the last successful bundle on errors
+    assets: HashMap<String, String>,
+}
+
+/// State of the latest bundle as reported to the editor.
+#[derive(Debug, Serialize)]
+struct BundleStatus {
+    revision: u64,
+    error: Option<String>,
+}
+
+/// Serves an in-browser editor whose files are held in memory and bundled
+/// by the same pipeline as projects on disk, until the server is shut down.
+///
+/// Files are edited through `/api/files/{path}` (`GET` lists them, `PUT`
+/// writes one and `DELETE` removes one), each change rebuilds the bundle
+/// served at `/preview/`. Bare imports are resolved from `node_modules` of
+/// the working directory.
+///
+/// # Errors
+///
+/// Returns an error if the address cannot be bound.
+pub async fn playground(options: PlaygroundOptions) -> PalladinResult {
+    let mut files = options.files;
+    if files.is_empty() {
+        files.insert(options.entry.clone(), STARTER_ENTRY.to_string());
+    }
+
+    let playground = Arc::new(Playground {
+        entry: options.entry,
+        chunk_filenames: options.chunk_filenames,
+        plugins: options.plugins,
+        dependencies: Arc::default(),
+        files: RwLock::new(files),
+        bundle: RwLock::new(Bundle::default()),
+        building: Mutex::new(()),
+    });
+    playground.rebuild().await;
+
+    let tcp = listener::bind(&options.address).await?;
+    let app = Router::new()
+        .route("/", get(serve_editor_handler))
+        .route("/api/files", get(list_files_handler))
+        .route(
+            "/api/files/{*path}",
+            get(read_file_handler)
+                .put(write_file_handler)
+                .delete(remove_file_handler),
+        )
+        .route("/api/bundle", get(bundle_status_handler))
+        .route("/preview", get(serve_preview_handler))
+        .route("/preview/", get(serve_preview_handler))
+        .route("/preview/{*file}", get(serve_asset_handler))
+        .with_state(playground);
+
+    axum::serve(tcp, app).await?;
+    Ok(())
+}
+
+/// Reads the text files under `dir` into a set of playground files, skipping
+/// ignored files and `node_modules`.
+///
+/// # Errors
+///
+/// Returns an error if `dir` cannot be walked.
+pub fn load_files(dir: &FsPath) -> PalladinResult<BTreeMap<String, String>> {
+    let mut files = BTreeMap::new();
+    let walker = WalkBuilder::new(dir)
+        .filter_entry(|entry| entry.file_name() != "node_modules")
+        .build();
+
+    for entry in walker {
+        let entry = entry.map_err(|e| PalladinError::Build(e.into()))?;
+        if !entry.file_type().is_some_and(|ty| ty.is_file()) {
+            continue;
+        }
+
+        // Binary files can't be edited, leave them out
+        let Ok(content) = fs_err::read_to_string(entry.path()) else {
+            continue;
+        };
+        let Ok(relative) = entry.path().strip_prefix(dir) else {
+            continue;
+        };
+        files.insert(relative.to_string_lossy().replace('\\', "/"), content);
+    }
+
+    Ok(files)
+}
+
+impl Playground {
+    /// Bundles the current files, returning the state of the new bundle.
+    async fn rebuild(&self) -> BundleStatus {
+        let _building = self.building.lock().await;
+        let files = self.files.read().clone();
+        let result = self.bundle_files(files).await;
+
+        let mut bundle = self.bundle.write();
+        bundle.revision += 1;
+        match result {
+            Ok(assets) => {
+                debug!("bundled playground revision {}", bundle.revision);
+                bundle.error = None;
+                bundle.assets = assets;
+            }
+            Err(e) => bundle.error = Some(e.to_string()),
+        }
+        bundle.status()
+    }
+
+    async fn bundle_files(
+        &self,
+        files: BTreeMap<String, String>,
+    ) -> PalladinResult<HashMap<String, String>> {
+        let plugins: Vec<Arc<dyn PalladinPlugin>> =
+            std::iter::once(Arc::new(MemoryFiles::new(files)) as _)
+                .chain(self.plugins.iter().cloned())
+                .collect();
+
+        let entry = format!("{MEMORY_ROOT}/{}", self.entry);
+        let mut bundler =
+            create_virtual_bundler(entry, &self.chunk_filenames, &plugins, &self.dependencies)
+                .build()?;
+        let output = bundler.generate().await;
+        bundler.close().await?;
+
+        Ok(output?
+            .assets
+            .iter()
+            .map(|asset| {
+                let content = String::from_utf8_lossy(asset.content_as_bytes()).to_string();
+                (asset.filename().to_string(), content)
+            })
+            .collect())
+    }
+
+    /// Returns the page running the bundle, the `index.html` among the files
+    /// if there is one.
+    fn preview_html(&self) -> String {
+        let bundle = self.bundle.read();
+        let entry_src = format!("/preview/{ENTRY_CHUNK}?rev={}", bundle.revision);
+
+        let html = match self.files.read().get("index.html") {
+            Some(html) => rewrite_module_script_src(html, |src| {
+                (normalize(src).as_deref() == Some(self.entry.as_str())).then(|| entry_src.clone())
+            }),
+            None => format!(
+                "<!doctype html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n</head>\n<body>\n\
+                 <div id=\"root\"></div>\n<script type=\"module\" src=\"{entry_src}\"></script>\n\
+                 </body>\n</html>\n"
+            ),
+        };
+
+        let mut stylesheets: Vec<_> = bundle
+            .assets
+            .keys()
+            .filter(|name| name.ends_with(".css"))
+            .map(|name| format!("/preview/{name}"))
+            .collect();
+        stylesheets.sort();
+
+        inject_into_head(&html, &stylesheet_links(stylesheets))
+    }
+}
+
+impl Bundle {
+    fn status(&self) -> BundleStatus {
+        BundleStatus {
+            revision: self.revision,
+            error: self.error.clone(),
+        }
+    }
+}
+
+async fn serve_editor_handler() -> Response<String> {
+    HttpResponse::ok(EDITOR_HTML)
+        .content_type("text/html")
+        .cache_control(REVALIDATE)
+        .finish()
+}
+
+async fn list_files_handler(
+    State(playground): State<Arc<Playground>>,
+) -> Json<BTreeMap<String, String>> {
+    Json(playground.files.read().clone())
+}
+
+async fn read_file_handler(
+    State(playground): State<Arc<Playground>>,
+    Path(path): Path<String>,
+) -> PalladinResult<String> {
+    playground
+        .files
+        .read()
+        .get(&path)
+        .cloned()
+        .ok_or(PalladinError::FileNotFound(path))
+}
+
+async fn write_file_handler(
+    State(playground): State<Arc<Playground>>,
+    Path(path): Path<String>,
+    content: String,
+) -> PalladinResult<Json<BundleStatus>> {
+    let path = normalize(&path)
+        .filter(|path| !path.is_empty())
+        .ok_or_else(|| PalladinError::FileNotFound(path.clone()))?;
+
+    info!("{}", Message::PlaygroundUpdated { path: &path });
+    playground.files.write().insert(path, content);
+    Ok(Json(playground.rebuild().await))
+}
+
+async fn remove_file_handler(
+    State(playground): State<Arc<Playground>>,
+    Path(path): Path<String>,
+) -> PalladinResult<Json<BundleStatus>> {
+    if playground.files.write().remove(&path).is_none() {
+        return Err(PalladinError::FileNotFound(path));
+    }
+
+    info!("{}", Message::PlaygroundRemoved { path: &path });
+    Ok(Json(playground.rebuild().await))
+}
+
+async fn bundle_status_handler(State(playground): State<Arc<Playground>>) -> Json<BundleStatus> {
+    Json(playground.bundle.read().status())
+}
+
+async fn serve_preview_handler(State(playground): State<Arc<Playground>>) -> Response<String> {
+    HttpResponse::ok(playground.preview_html())
+        .content_type("text/html")
+        .cache_control("no-store")
+        .finish()
+}
+
+async fn serve_asset_handler(
+    State(playground): State<Arc<Playground>>,
+    Path(file): Path<String>,
+) -> PalladinResult<Response<String>> {
+    let content = playground.bundle.read().assets.get(&file).cloned();
+    let content = content.ok_or_else(|| PalladinError::FileNotFound(file.clone()))?;
+
+    Ok(HttpResponse::ok(content)
+        .content_type(guess_content_type(&file))
+        .cache_control("no-store")
+        .finish())
+}
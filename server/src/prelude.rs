@@ -0,0 +1,26 @@
+//! The stable interface for embedding palladin.
+//!
+//! Everything re-exported here follows semver: it only changes in breaking
+//! ways with a major release. Enums and output structs that are expected to
+//! grow are `#[non_exhaustive]`, and no type of the bundler palladin is built
+//! on is part of it, so upgrading the bundler doesn't break embedders.
+//!
+//! ```ignore
+//! use palladin_server::prelude::*;
+//!
+//! let server = Server::new(ServerConfig::new().with_root("app".into()))?;
+//! ```
+//!
+//! Items outside the prelude, e.g. the request handlers of
+//! [`server::files`](crate::server::files), may change in any release.
+//! `tests/public_api.rs` uses the prelude like embedders do, so breaking
+//! changes fail the tests.
+
+pub use crate::build::{BuildOptions, BuildOutput, build};
+pub use crate::preview::{PreviewOptions, RuntimeConfig, preview};
+pub use crate::server::hmr::{ClientMessage, HmrChannel, HmrMessage};
+pub use crate::server::{
+    Context, Dependency, Diagnostic, Entrypoint, Framework, Minify, ModuleDependencies,
+    PalladinPlugin, Server, ServerConfig, ServerHooks, Severity, SsrModule, WatchOptions,
+};
+pub use palladin_shared::{BuildDiagnostics, PalladinError, PalladinResult};
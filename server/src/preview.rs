@@ -0,0 +1,261 @@
+use crate::build::{BUILD_REPORT_FILE, BuildReport, suggest_csp};
+use crate::messages::Message;
+use crate::rolldown::guess_content_type;
+use crate::server::errors::{REVALIDATE, cache_control};
+use crate::server::{REDIRECT_STATUS, Routing, UrlPolicy, listener};
+use axum::Router;
+use axum::extract::State;
+use axum::http::header::{
+    CACHE_CONTROL, CONTENT_SECURITY_POLICY_REPORT_ONLY, CONTENT_TYPE, LOCATION, REFERRER_POLICY,
+    X_CONTENT_TYPE_OPTIONS,
+};
+use axum::http::{HeaderValue, Uri};
+use axum::middleware;
+use axum::response::{IntoResponse, Response};
+use log::info;
+use palladin_shared::{PalladinError, PalladinResult};
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+/// URL path the app fetches its runtime config from unless configured otherwise.
+pub const DEFAULT_RUNTIME_CONFIG_PATH: &str = "/runtime-config.json";
+
+/// Options for serving a production build, see [`PreviewOptions::new`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct PreviewOptions {
+    /// The build directory to serve.
+    pub dir: PathBuf,
+    /// The `host:port` address to bind to.
+    pub address: String,
+    /// Runtime config served in place of the one in the build, if any.
+    pub runtime_config: Option<RuntimeConfig>,
+    /// Sends the security headers of a typical production host with every
+    /// response, see [`preview`].
+    pub security_headers: bool,
+    /// Canonical form of page URLs, every form is served when `None`.
+    pub url_policy: Option<UrlPolicy>,
+}
+
+/// What the preview server answers requests with.
+struct PreviewState {
+    options: PreviewOptions,
+    /// Files of the build that can be cached forever, from its report
+    immutable: HashSet<String>,
+}
+
+impl PreviewOptions {
+    /// Creates options serving the build in `dir` on `address`, without a
+    /// runtime config, security headers or URL policy.
+    pub fn new(dir: PathBuf, address: impl Into<String>) -> Self {
+        Self {
+            dir,
+            address: address.into(),
+            runtime_config: None,
+            security_headers: false,
+            url_policy: None,
+        }
+    }
+
+    /// Returns new `PreviewOptions` with the specified runtime config.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_runtime_config(mut self, runtime_config: Option<RuntimeConfig>) -> Self {
+        self.runtime_config = runtime_config;
+        self
+    }
+
+    /// Returns new `PreviewOptions` sending security headers or not.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_security_headers(mut self, security_headers: bool) -> Self {
+        self.security_headers = security_headers;
+        self
+    }
+
+    /// Returns new `PreviewOptions` with the specified canonical form of
+    /// page URLs.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_url_policy(mut self, url_policy: Option<UrlPolicy>) -> Self {
+        self.url_policy = url_policy;
+        self
+    }
+}
+
+/// JSON file served at `url_path`, so the same build can be pointed at
+/// different backends.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct RuntimeConfig {
+    /// URL path the app fetches the config from, e.g. `/runtime-config.json`.
+    pub url_path: String,
+    /// File whose contents are served at `url_path`.
+    pub file: PathBuf,
+}
+
+impl RuntimeConfig {
+    /// Creates a runtime config serving `file` at `url_path`.
+    pub fn new(url_path: impl Into<String>, file: PathBuf) -> Self {
+        Self {
+            url_path: url_path.into(),
+            file,
+        }
+    }
+}
+
+/// Serves the build directory until the server is shut down.
+///
+/// Extensionless paths are served by the page `<path>.html` or
+/// `<path>/index.html` and fall back to `index.html` so client side routes
+/// work. Page URLs follow the `url_policy` like in the dev server.
+///
+/// With `security_headers`, responses carry `X-Content-Type-Options`,
+/// `Referrer-Policy` and the policy [`suggest_csp`] suggests for the build
+/// as `Content-Security-Policy-Report-Only`, so violations show up in the
+/// browser console without breaking the page.
+///
+/// # Errors
+///
+/// Returns an error if the address cannot be bound or the build cannot be
+/// read.
+pub async fn preview(options: PreviewOptions) -> PalladinResult {
+    let tcp = listener::bind(&options.address).await?;
+    let csp = match options.security_headers {
+        true => Some(build_csp(&options.dir)?),
+        false => None,
+    };
+
+    // Builds without a report are revalidated as a whole
+    let immutable = BuildReport::read(&options.dir.join(BUILD_REPORT_FILE))
+        .map(|report| report.immutable_files())
+        .unwrap_or_default();
+
+    let app = Router::new()
+        .fallback(serve_preview_handler)
+        .with_state(Arc::new(PreviewState { options, immutable }));
+    let app = match csp {
+        Some(csp) => app.layer(middleware::map_response_with_state(
+            csp,
+            add_security_headers,
+        )),
+        None => app,
+    };
+
+    axum::serve(tcp, app).await?;
+    Ok(())
+}
+
+/// Returns the policy suggested for the build in `dir` as a header value.
+fn build_csp(dir: &Path) -> PalladinResult<HeaderValue> {
+    let files = read_files(dir, dir)?;
+    let files: Vec<(String, &[u8])> = files
+        .iter()
+        .map(|(name, content)| (name.clone(), content.as_slice()))
+        .collect();
+
+    let csp = suggest_csp(&files);
+    info!("{}", Message::ReportingCsp { csp: &csp });
+    HeaderValue::from_str(&csp).map_err(|err| PalladinError::Build(err.into()))
+}
+
+/// Reads every file below `dir`, named relative to `root` with `/` as
+/// separator.
+fn read_files(root: &Path, dir: &Path) -> PalladinResult<Vec<(String, Vec<u8>)>> {
+    let mut files = Vec::new();
+    for entry in fs_err::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            files.extend(read_files(root, &path)?);
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            let name = relative.to_string_lossy().replace('\\', "/");
+            files.push((name, fs_err::read(&path)?));
+        }
+    }
+    Ok(files)
+}
+
+async fn add_security_headers(State(csp): State<HeaderValue>, mut response: Response) -> Response {
+    let headers = response.headers_mut();
+    headers.insert(CONTENT_SECURITY_POLICY_REPORT_ONLY, csp);
+    headers.insert(X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff"));
+    headers.insert(
+        REFERRER_POLICY,
+        HeaderValue::from_static("strict-origin-when-cross-origin"),
+    );
+    response
+}
+
+async fn serve_preview_handler(State(state): State<Arc<PreviewState>>, uri: Uri) -> Response {
+    serve_preview_impl(&state, &uri).into_response()
+}
+
+fn serve_preview_impl(state: &PreviewState, uri: &Uri) -> PalladinResult<Response> {
+    let options = &state.options;
+    let path = uri.path();
+    let runtime_config = options
+        .runtime_config
+        .as_ref()
+        .filter(|config| config.url_path == path);
+    if let Some(runtime_config) = runtime_config {
+        let body = fs_err::read(&runtime_config.file)?;
+        return Ok(file_response("config.json", REVALIDATE, body));
+    }
+
+    match options.url_policy.map(|policy| policy.route(path)) {
+        None | Some(Routing::Serve) => {}
+        Some(Routing::Redirect(canonical)) => {
+            let location = match uri.query() {
+                Some(query) => format!("{canonical}?{query}"),
+                None => canonical,
+            };
+            let location =
+                HeaderValue::from_str(&location).map_err(|err| PalladinError::Build(err.into()))?;
+            return Ok((REDIRECT_STATUS, [(LOCATION, location)]).into_response());
+        }
+        Some(Routing::NotFound) => return Err(PalladinError::FileNotFound(path.to_string())),
+    }
+
+    let relative = path.trim_start_matches('/');
+    let Some(file) = resolve_within(&options.dir, relative) else {
+        return Err(PalladinError::FileNotFound(relative.to_string()));
+    };
+
+    if file.is_file() {
+        let body = fs_err::read(&file)?;
+        let immutable = state.immutable.contains(relative);
+        return Ok(file_response(relative, cache_control(immutable), body));
+    }
+
+    if Path::new(relative).extension().is_some() {
+        return Err(PalladinError::FileNotFound(relative.to_string()));
+    }
+
+    let route = relative.trim_end_matches('/');
+    let page = [format!("{route}.html"), format!("{route}/index.html")]
+        .into_iter()
+        .filter(|_| !route.is_empty())
+        .filter_map(|page| resolve_within(&options.dir, &page))
+        .find(|page| page.is_file());
+    let body = fs_err::read(page.unwrap_or_else(|| options.dir.join("index.html")))?;
+    Ok(file_response("index.html", REVALIDATE, body))
+}
+
+/// Joins `relative` onto `dir`, rejecting paths that would leave it.
+fn resolve_within(dir: &Path, relative: &str) -> Option<PathBuf> {
+    let relative = Path::new(relative);
+    relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_)))
+        .then(|| dir.join(relative))
+}
+
+fn file_response(filename: &str, cache_control: &'static str, body: Vec<u8>) -> Response {
+    let content_type = HeaderValue::from_static(guess_content_type(filename));
+    let headers = [
+        (CONTENT_TYPE, content_type),
+        (CACHE_CONTROL, HeaderValue::from_static(cache_control)),
+    ];
+    (headers, body).into_response()
+}
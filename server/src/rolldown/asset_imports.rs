@@ -0,0 +1,263 @@
+use crate::rolldown::{BundleMode, guess_content_type};
+use crate::server::Context;
+use anyhow::anyhow;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use parking_lot::RwLock;
+use rolldown_common::{ModuleType, Output, OutputAsset};
+use rolldown_plugin::{
+    HookGenerateBundleArgs, HookLoadArgs, HookLoadOutput, HookLoadReturn, HookNoopReturn,
+    HookUsage, Plugin, PluginContext,
+};
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Default size in bytes below which imported assets are inlined.
+pub const DEFAULT_ASSET_INLINE_LIMIT: usize = 4096;
+
+/// Extensions of the files imported as assets.
+const ASSET_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "gif", "svg", "webp", "avif", "ico", "bmp", "woff", "woff2", "ttf",
+    "otf", "eot", "mp4", "webm", "ogg", "mp3", "wav", "flac", "aac", "pdf", "wasm",
+];
+
+/// Turns imports of static assets, e.g. `import logo from './logo.png'`,
+/// into their URL.
+///
+/// Assets below the configured inline limit become `data:` URLs. Larger ones
+/// are served from where they are by the dev server, and emitted into the
+/// build directory under the name the [`AssetPolicy`](crate::server::AssetPolicy)
+/// gives them in builds.
+///
+/// WebAssembly modules export an init function instead, instantiating the
+/// module with the given imports and resolving to its exports.
+#[derive(Debug)]
+pub struct AssetImports {
+    ctx: Arc<Context>,
+    mode: BundleMode,
+    /// Inline every asset, for scopes that can't fetch them
+    inline_all: bool,
+    /// Assets emitted by a build, by output filename
+    emitted: RwLock<BTreeMap<String, EmittedAsset>>,
+}
+
+#[derive(Debug)]
+pub(super) struct EmittedAsset {
+    pub name: String,
+    /// Path relative to the root, for the build manifest
+    pub original_file_name: Option<String>,
+    pub content: Vec<u8>,
+}
+
+impl AssetImports {
+    pub fn new(ctx: Arc<Context>, mode: BundleMode) -> Self {
+        Self {
+            ctx,
+            mode,
+            inline_all: false,
+            emitted: RwLock::default(),
+        }
+    }
+
+    /// Inlines every asset regardless of its size, e.g. for worklets, which
+    /// can't fetch anything.
+    pub fn inlined(mut self) -> Self {
+        self.inline_all = true;
+        self
+    }
+
+    /// Returns the module exporting the asset at `path`.
+    fn asset_module(&self, path: &Path) -> anyhow::Result<String> {
+        if self.inline_all && is_wasm(path) {
+            let content = self
+                .ctx
+                .vfs()
+                .read(path)
+                .map_err(|err| anyhow!("failed to load asset {}: {err}", path.display()))?;
+            return Ok(inline_wasm_module(&STANDARD.encode(&content)));
+        }
+        self.asset_url(path).map(|url| asset_module(path, &url))
+    }
+
+    /// Returns the URL the asset at `path` is referenced by.
+    fn asset_url(&self, path: &Path) -> anyhow::Result<String> {
+        let content = self
+            .ctx
+            .vfs()
+            .read(path)
+            .map_err(|err| anyhow!("failed to load asset {}: {err}", path.display()))?;
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let served = match self.mode {
+            BundleMode::Dev => self
+                .ctx
+                .url_path(path)
+                .filter(|_| !self.ctx.is_excluded(path)),
+            BundleMode::Build => None,
+        };
+
+        // Assets the dev server can't serve are inlined too
+        let unservable = self.mode == BundleMode::Dev && served.is_none();
+        if content.len() < self.ctx.config().asset_inline_limit || unservable || self.inline_all {
+            let mime = guess_content_type(&name);
+            return Ok(format!("data:{mime};base64,{}", STANDARD.encode(&content)));
+        }
+        if let Some(url_path) = served {
+            return Ok(self.ctx.base_url(&url_path));
+        }
+
+        let pattern = self.ctx.config().assets.pattern_for(&name);
+        let filename = asset_filename(pattern, path, &content);
+        let url = self.ctx.base_url(&format!("/{filename}"));
+        let original_file_name = self
+            .ctx
+            .url_path(path)
+            .map(|path| path.trim_start_matches('/').to_string());
+        self.emitted.write().insert(
+            filename,
+            EmittedAsset {
+                name,
+                original_file_name,
+                content,
+            },
+        );
+        Ok(url)
+    }
+}
+
+impl Plugin for AssetImports {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("palladin:assets")
+    }
+
+    fn load(
+        &self,
+        _ctx: &PluginContext,
+        args: &HookLoadArgs<'_>,
+    ) -> impl Future<Output = HookLoadReturn> + Send {
+        let path = Path::new(args.id.split('?').next().unwrap_or(args.id));
+        let result = match is_asset(path) {
+            true => self.asset_module(path).map(|code| {
+                Some(HookLoadOutput {
+                    code: code.into(),
+                    module_type: Some(ModuleType::Js),
+                    ..Default::default()
+                })
+            }),
+            false => Ok(None),
+        };
+        async move { result }
+    }
+
+    fn generate_bundle(
+        &self,
+        _ctx: &PluginContext,
+        args: &mut HookGenerateBundleArgs<'_>,
+    ) -> impl Future<Output = HookNoopReturn> + Send {
+        let emitted = self.emitted.read();
+        args.bundle.extend(emitted.iter().map(|(filename, asset)| {
+            Output::Asset(Arc::new(OutputAsset {
+                filename: filename.clone().into(),
+                source: asset.content.clone().into(),
+                names: vec![asset.name.clone()],
+                original_file_names: asset.original_file_name.iter().cloned().collect(),
+            }))
+        }));
+        async { Ok(()) }
+    }
+
+    fn register_hook_usage(&self) -> HookUsage {
+        HookUsage::Load | HookUsage::GenerateBundle
+    }
+}
+
+/// Checks if `path` is imported as an asset.
+pub(crate) fn is_asset(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+        .is_some_and(|ext| ASSET_EXTENSIONS.contains(&ext.as_str()))
+}
+
+/// Returns the module exporting the asset at `path` referenced by `url`.
+fn asset_module(path: &Path, url: &str) -> String {
+    let url = serde_json::Value::from(url);
+    if !is_wasm(path) {
+        return format!("export default {url};");
+    }
+
+    // `instantiateStreaming` needs the `application/wasm` content type, which
+    // not every host sends
+    format!(
+        "export default async function init(imports = {{}}) {{
+  const response = await fetch({url});
+  const {{ instance }} = response.headers.get('content-type')?.startsWith('application/wasm')
+    ? await WebAssembly.instantiateStreaming(response, imports)
+    : await WebAssembly.instantiate(await response.arrayBuffer(), imports);
+  return instance.exports;
+}}
+"
+    )
+}
+
+/// Returns the module instantiating the WebAssembly module encoded as
+/// `base64`, without `fetch` or `atob`, which worklet scopes lack.
+fn inline_wasm_module(base64: &str) -> String {
+    format!(
+        "const BASE64 = \"{base64}\";
+const ALPHABET = \"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/\";
+
+function decode(text) {{
+  const bytes = new Uint8Array((text.replace(/=+$/, '').length * 3) >> 2);
+  let bits = 0, value = 0, index = 0;
+  for (const char of text) {{
+    const digit = ALPHABET.indexOf(char);
+    if (digit < 0) continue;
+    value = (value << 6) | digit;
+    bits += 6;
+    if (bits >= 8) {{
+      bits -= 8;
+      bytes[index++] = (value >> bits) & 0xff;
+    }}
+  }}
+  return bytes;
+}}
+
+export default async function init(imports = {{}}) {{
+  const {{ instance }} = await WebAssembly.instantiate(decode(BASE64), imports);
+  return instance.exports;
+}}
+"
+    )
+}
+
+fn is_wasm(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("wasm"))
+}
+
+/// Fills in `pattern` with the `[name]`, `[hash]`, `[ext]` and `[extname]`
+/// of the asset at `path`.
+pub(super) fn asset_filename(pattern: &str, path: &Path, content: &[u8]) -> String {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy())
+        .unwrap_or_default();
+    let ext = path
+        .extension()
+        .map(|ext| ext.to_string_lossy())
+        .unwrap_or_default();
+    let hash = format!("{:x}", Sha256::digest(content));
+
+    pattern
+        .replace("[name]", &stem)
+        .replace("[hash]", &hash[..8])
+        .replace("[extname]", &format!(".{ext}"))
+        .replace("[ext]", &ext)
+}
@@ -0,0 +1,124 @@
+use crate::messages::Message;
+use crate::server::PalladinPlugin;
+use anyhow::anyhow;
+use log::warn;
+use palladin_shared::{PalladinError, PalladinResult};
+use std::ffi::OsStr;
+use std::path::{Component, Path, PathBuf};
+
+/// Flags relative imports whose casing differs from the file on disk.
+///
+/// They resolve on case-insensitive filesystems like the macOS and Windows
+/// defaults, but break once the project is built on Linux.
+#[derive(Debug)]
+pub struct CaseSensitivityCheck {
+    /// Fail the build instead of warning
+    pub strict: bool,
+}
+
+impl PalladinPlugin for CaseSensitivityCheck {
+    fn name(&self) -> &str {
+        "case-sensitivity-check"
+    }
+
+    fn resolve_id(
+        &self,
+        specifier: &str,
+        importer: Option<&str>,
+    ) -> PalladinResult<Option<String>> {
+        let Some(importer) = importer else {
+            return Ok(None);
+        };
+        if !specifier.starts_with("./") && !specifier.starts_with("../") {
+            return Ok(None);
+        }
+
+        let Some(dir) = Path::new(importer).parent() else {
+            return Ok(None);
+        };
+        let Some((expected, actual)) = find_case_mismatch(dir, specifier) else {
+            return Ok(None);
+        };
+
+        let message = Message::CaseMismatch {
+            importer,
+            specifier,
+            expected: &expected,
+            actual: &actual,
+        };
+        if self.strict {
+            return Err(PalladinError::Build(anyhow!("{message}")));
+        }
+
+        warn!("{message}");
+        Ok(None)
+    }
+}
+
+/// Walks `specifier` from `dir` and returns the first component whose casing
+/// differs from the entry on disk, along with the entry's actual name.
+///
+/// The last component may omit its extension.
+fn find_case_mismatch(dir: &Path, specifier: &str) -> Option<(String, String)> {
+    let specifier = specifier.split(['?', '#']).next().unwrap_or(specifier);
+    let components: Vec<_> = Path::new(specifier).components().collect();
+    let mut current = dir.to_path_buf();
+
+    for (index, component) in components.iter().enumerate() {
+        let name = match component {
+            Component::Normal(name) => *name,
+            Component::ParentDir => {
+                current.pop();
+                continue;
+            }
+            _ => continue,
+        };
+
+        let is_last = index == components.len() - 1;
+        match find_entry(&current, name, is_last) {
+            Entry::Exact(path) => current = path,
+            Entry::Mismatch(actual) => {
+                return Some((name.to_string_lossy().to_string(), actual));
+            }
+            Entry::Missing => return None,
+        }
+    }
+
+    None
+}
+
+enum Entry {
+    Exact(PathBuf),
+    Mismatch(String),
+    Missing,
+}
+
+fn find_entry(dir: &Path, name: &OsStr, allow_extension: bool) -> Entry {
+    let Ok(entries) = fs_err::read_dir(dir) else {
+        return Entry::Missing;
+    };
+
+    let name = name.to_string_lossy();
+    let lower = name.to_lowercase();
+    let mut mismatch = None;
+
+    for entry in entries.flatten() {
+        let entry_name = entry.file_name().to_string_lossy().to_string();
+        let stem = match entry_name.rsplit_once('.') {
+            Some((stem, _)) if allow_extension => stem,
+            _ => entry_name.as_str(),
+        };
+
+        if entry_name == name || stem == name {
+            return Entry::Exact(entry.path());
+        }
+        if entry_name.to_lowercase() == lower || stem.to_lowercase() == lower {
+            mismatch = Some(entry_name.clone());
+        }
+    }
+
+    match mismatch {
+        Some(actual) => Entry::Mismatch(actual),
+        None => Entry::Missing,
+    }
+}
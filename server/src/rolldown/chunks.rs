@@ -1,3 +1,4 @@
+use super::css::chunk_stylesheets;
 use log::debug;
 use parking_lot::RwLock;
 use rolldown_common::Output;
@@ -16,6 +17,8 @@ pub struct MainAsset {
     pub filename: String,
     pub content: String,
     pub content_type: String,
+    /// Stylesheets of the chunk and the chunks it imports, in load order
+    pub stylesheets: Vec<String>,
 }
 
 /// Manages chunk storage and retrieval
@@ -105,14 +108,20 @@ impl Default for ChunkManager {
     }
 }
 
+/// Entry chunks keyed by their entrypoint, and the remaining chunks keyed by
+/// file name.
+pub type ProcessedAssets = (HashMap<PathBuf, MainAsset>, HashMap<String, ChunkAsset>);
+
 pub struct ChunkProcessor;
 
 impl ChunkProcessor {
+    /// Splits the bundle output into the entry chunk of every entrypoint and
+    /// the remaining chunks.
     pub fn process_assets(
         assets: &[Output],
-        source_path: &PathBuf,
-    ) -> Result<(MainAsset, HashMap<String, ChunkAsset>), String> {
-        let mut main_output: Option<MainAsset> = None;
+        entrypoints: &[PathBuf],
+    ) -> Result<ProcessedAssets, String> {
+        let mut main_outputs = HashMap::new();
         let mut chunks = HashMap::new();
 
         for asset in assets {
@@ -123,13 +132,17 @@ impl ChunkProcessor {
 
             debug!("Processing asset: {}", filename);
 
-            if Self::is_main_output(filename, source_path) {
+            if let Some(entrypoint) = Self::entrypoint_of(asset, entrypoints) {
                 debug!("Identified main output: {}", filename);
-                main_output = Some(MainAsset {
-                    filename: filename.to_string(),
-                    content,
-                    content_type: content_type.to_string(),
-                });
+                main_outputs.insert(
+                    entrypoint.clone(),
+                    MainAsset {
+                        filename: filename.to_string(),
+                        content,
+                        content_type: content_type.to_string(),
+                        stylesheets: chunk_stylesheets(assets, filename),
+                    },
+                );
             } else {
                 debug!("Storing as chunk: {}", filename);
                 chunks.insert(
@@ -142,17 +155,36 @@ impl ChunkProcessor {
             }
         }
 
-        if main_output.is_none() {
-            main_output = Self::find_fallback_output(assets)?;
+        if main_outputs.is_empty()
+            && let Some(output) = Self::find_fallback_output(assets)?
+        {
+            main_outputs.insert(entrypoints[0].clone(), output);
         }
 
-        match main_output {
-            Some(output) => Ok((output, chunks)),
-            None => Err("No JavaScript output generated by rolldown".to_string()),
+        if main_outputs.is_empty() {
+            return Err("No JavaScript output generated by rolldown".to_string());
         }
+        Ok((main_outputs, chunks))
     }
 
-    fn is_main_output(filename: &str, source_path: &PathBuf) -> bool {
+    /// Returns the entrypoint `asset` is the entry chunk of.
+    fn entrypoint_of<'a>(asset: &Output, entrypoints: &'a [PathBuf]) -> Option<&'a PathBuf> {
+        if let Output::Chunk(chunk) = asset
+            && chunk.is_entry
+            && let Some(facade) = &chunk.facade_module_id
+        {
+            return entrypoints
+                .iter()
+                .find(|entrypoint| Path::new(facade.as_str()) == entrypoint.as_path());
+        }
+
+        // Without a facade module the chunk is matched by file name
+        entrypoints
+            .iter()
+            .find(|entrypoint| Self::is_main_output(asset.filename(), entrypoint))
+    }
+
+    fn is_main_output(filename: &str, source_path: &Path) -> bool {
         if !filename.ends_with(".js") {
             return false;
         }
@@ -176,6 +208,7 @@ impl ChunkProcessor {
                     filename: filename.to_string(),
                     content,
                     content_type: guess_content_type(filename).to_string(),
+                    stylesheets: chunk_stylesheets(assets, filename),
                 }));
             }
         }
@@ -183,7 +216,23 @@ impl ChunkProcessor {
     }
 }
 
-fn guess_content_type(filename: &str) -> &'static str {
+/// Appends `query` to every import of one of `filenames` in `code`, e.g.
+/// `"./chunk-a1b2.js"` becomes `"./chunk-a1b2.js?build=3"`.
+pub fn tag_chunk_imports(code: &str, filenames: &[&str], query: &str) -> String {
+    let mut code = code.to_string();
+    for filename in filenames {
+        let name = filename.rsplit('/').next().unwrap_or(filename);
+        for quote in ['"', '\'', '`'] {
+            code = code.replace(
+                &format!("/{name}{quote}"),
+                &format!("/{name}?{query}{quote}"),
+            );
+        }
+    }
+    code
+}
+
+pub(crate) fn guess_content_type(filename: &str) -> &'static str {
     match Path::new(filename)
         .extension()
         .and_then(|s| s.to_str())
@@ -196,6 +245,27 @@ fn guess_content_type(filename: &str) -> &'static str {
         Some("css") => "text/css",
         Some("html") => "text/html",
         Some("json") => "application/json",
+        Some("svg") => "image/svg+xml",
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("avif") => "image/avif",
+        Some("bmp") => "image/bmp",
+        Some("ico") => "image/x-icon",
+        Some("woff2") => "font/woff2",
+        Some("woff") => "font/woff",
+        Some("ttf") => "font/ttf",
+        Some("otf") => "font/otf",
+        Some("mp4") => "video/mp4",
+        Some("webm") => "video/webm",
+        Some("ogg") => "audio/ogg",
+        Some("mp3") => "audio/mpeg",
+        Some("wav") => "audio/wav",
+        Some("flac") => "audio/flac",
+        Some("pdf") => "application/pdf",
+        Some("wasm") => "application/wasm",
+        Some("txt") => "text/plain",
         _ => "application/octet-stream",
     }
 }
@@ -0,0 +1,323 @@
+use crate::messages::Message;
+use crate::rolldown::css_modules::{CssModule, is_css_module};
+use crate::server::{Context, PostCss, Tailwind, Vfs, process_stylesheet, uses_tailwind};
+use anyhow::anyhow;
+use log::warn;
+use palladin_shared::PalladinResult;
+use parking_lot::RwLock;
+use rolldown_common::{ModuleType, Output, OutputAsset};
+use rolldown_plugin::{
+    HookAugmentChunkHashReturn, HookGenerateBundleArgs, HookLoadArgs, HookLoadOutput,
+    HookLoadReturn, HookNoopReturn, HookUsage, Plugin, PluginContext, RollupRenderedChunk,
+};
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Bundles stylesheets imported from JavaScript into one CSS file per chunk.
+///
+/// Imported stylesheets are loaded as empty modules, so they are split along
+/// with the chunks they're imported by, advanced chunk groups included. Once
+/// the chunks are generated, the stylesheets of each chunk are concatenated
+/// into a `[name]-[hash].css` asset, see [`chunk_stylesheets`] for finding
+/// them. Stylesheets go through PostCSS and Tailwind first when configured,
+/// `@import` and `url()` references are otherwise left as written.
+///
+/// Tailwind stylesheets depend on the classes used by other files, so they
+/// are generated again for every bundle instead of once when loaded.
+///
+/// The classes of `*.module.css` files are scoped to the file and the module
+/// exports their scoped names, see [`CssModule`].
+///
+/// No page links the stylesheets of lazily loaded chunks, so those chunks
+/// link them once they run. Chunk hashes cover the styles of their modules,
+/// so a chunk is renamed when the stylesheets it links are.
+#[derive(Debug)]
+pub struct CssBundler {
+    vfs: Arc<dyn Vfs>,
+    postcss: Option<Arc<PostCss>>,
+    tailwind: Option<Arc<Tailwind>>,
+    root: PathBuf,
+    /// Pattern scoped class names of CSS modules are built from
+    modules_pattern: String,
+    /// Contents of the stylesheets loaded so far, by module id
+    styles: RwLock<HashMap<String, String>>,
+    /// Unprocessed contents of the Tailwind stylesheets, by module id
+    tailwind_sources: RwLock<HashMap<String, String>>,
+    /// Stylesheets generated by other plugins, e.g. the styles of Svelte
+    /// components, by module id
+    virtual_styles: RwLock<HashMap<String, String>>,
+}
+
+impl CssBundler {
+    pub fn new(ctx: &Context) -> Self {
+        Self {
+            vfs: ctx.vfs().clone(),
+            postcss: ctx.config().postcss.clone(),
+            tailwind: ctx.config().tailwind.clone(),
+            root: ctx.root().clone(),
+            modules_pattern: ctx.config().css_modules_pattern.clone(),
+            styles: RwLock::default(),
+            tailwind_sources: RwLock::default(),
+            virtual_styles: RwLock::default(),
+        }
+    }
+
+    /// Registers `css` as the stylesheet loaded for the module `id`, which
+    /// isn't read from disk.
+    pub fn add_virtual_stylesheet(&self, id: String, css: String) {
+        self.virtual_styles.write().insert(id, css);
+    }
+
+    /// Reads the stylesheet `id`, processed by the configured tools, and
+    /// returns it with the code of its module. Tailwind stylesheets are left
+    /// for when the bundle is generated.
+    async fn read_stylesheet(&self, id: &str, path: &Path) -> PalladinResult<(String, String)> {
+        let css = self.vfs.read_to_string(path)?;
+        if !is_css_module(path) {
+            if self.tailwind.is_some() && uses_tailwind(&css) {
+                self.tailwind_sources
+                    .write()
+                    .insert(id.to_string(), css.clone());
+                return Ok((css, "export {};".to_string()));
+            }
+            return Ok((self.process(path, &css).await?, "export {};".to_string()));
+        }
+
+        // Hashes are derived from the root-relative path, so builds on
+        // different machines name classes alike
+        let key = path.strip_prefix(&self.root).unwrap_or(path);
+        let module = CssModule::scope(
+            &self.process(path, &css).await?,
+            path,
+            &key.to_string_lossy().replace('\\', "/"),
+            &self.modules_pattern,
+        );
+        let code = module.to_module();
+        Ok((module.css, code))
+    }
+
+    /// Runs `css` through the configured tools, off the async workers so
+    /// the bundler goes on with other modules.
+    async fn process(&self, path: &Path, css: &str) -> PalladinResult<String> {
+        process_stylesheet(self.postcss.clone(), self.tailwind.clone(), path, css).await
+    }
+
+    /// Generates the Tailwind stylesheets again for the current content files.
+    async fn regenerate_tailwind(&self) {
+        let sources = self.tailwind_sources.read().clone();
+        for (id, css) in sources {
+            let path = stylesheet_path(&id).unwrap_or(&id);
+            match self.process(Path::new(path), &css).await {
+                Ok(css) => {
+                    self.styles.write().insert(id, css);
+                }
+                Err(err) => warn!("{}", Message::TailwindFailed { path, error: &err }),
+            }
+        }
+    }
+
+    /// Adds a stylesheet for every chunk importing stylesheets to the bundle,
+    /// and makes lazily loaded chunks link theirs.
+    fn add_stylesheets(&self, args: &mut HookGenerateBundleArgs<'_>) {
+        let styles = self.styles.read();
+        let mut stylesheets = Vec::new();
+
+        for output in args.bundle.iter() {
+            let Output::Chunk(chunk) = output else {
+                continue;
+            };
+
+            let css: String = chunk
+                .module_ids
+                .iter()
+                .filter_map(|id| styles.get(&id.to_string()))
+                .map(|css| format!("{}\n", css.trim_end()))
+                .collect();
+            if css.is_empty() {
+                continue;
+            }
+
+            let hash = format!("{:x}", Sha256::digest(css.as_bytes()));
+            let filename = format!("{}-{}.css", chunk.name, &hash[..8]);
+            stylesheets.push(Output::Asset(Arc::new(OutputAsset {
+                filename: filename.into(),
+                source: css.into(),
+                names: vec![stylesheet_name(&chunk.filename)],
+                original_file_names: Vec::new(),
+            })));
+        }
+
+        drop(styles);
+        args.bundle.extend(stylesheets);
+
+        let loaders: Vec<(usize, Vec<String>)> = args
+            .bundle
+            .iter()
+            .enumerate()
+            .filter_map(|(index, output)| match output {
+                Output::Chunk(chunk) if chunk.is_dynamic_entry => {
+                    let hrefs = chunk_stylesheets(args.bundle, &chunk.filename);
+                    (!hrefs.is_empty()).then_some((index, hrefs))
+                }
+                _ => None,
+            })
+            .collect();
+        for (index, hrefs) in loaders {
+            if let Output::Chunk(chunk) = &mut args.bundle[index] {
+                let chunk = Arc::make_mut(chunk);
+                let loader = stylesheet_loader(&chunk.filename, &hrefs);
+                chunk.code.push_str(&loader);
+            }
+        }
+    }
+}
+
+impl Plugin for CssBundler {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("palladin:css")
+    }
+
+    async fn load(&self, _ctx: &PluginContext, args: &HookLoadArgs<'_>) -> HookLoadReturn {
+        let virtual_css = self.virtual_styles.read().get(args.id).cloned();
+        let stylesheet = match virtual_css {
+            Some(css) => {
+                let path = args.id.split('?').next().unwrap_or(args.id);
+                let css = self.process(Path::new(path), &css).await;
+                css.map(|css| (css, "export {};".to_string()))
+            }
+            None => match stylesheet_path(args.id) {
+                Some(path) => self.read_stylesheet(args.id, Path::new(path)).await,
+                None => return Ok(None),
+            },
+        };
+        let (css, code) = stylesheet.map_err(|err| anyhow!("failed to load stylesheet: {err}"))?;
+        self.styles.write().insert(args.id.to_string(), css);
+        Ok(Some(HookLoadOutput {
+            code: code.into(),
+            module_type: Some(ModuleType::Js),
+            ..Default::default()
+        }))
+    }
+
+    async fn generate_bundle(
+        &self,
+        _ctx: &PluginContext,
+        args: &mut HookGenerateBundleArgs<'_>,
+    ) -> HookNoopReturn {
+        self.regenerate_tailwind().await;
+        self.add_stylesheets(args);
+        Ok(())
+    }
+
+    fn augment_chunk_hash(
+        &self,
+        _ctx: &PluginContext,
+        chunk: Arc<RollupRenderedChunk>,
+    ) -> impl Future<Output = HookAugmentChunkHashReturn> + Send {
+        let styles = self.styles.read();
+        let tailwind_sources = self.tailwind_sources.read();
+        let css: String = chunk
+            .module_ids
+            .iter()
+            .filter_map(|id| {
+                let id = id.to_string();
+                styles
+                    .get(&id)
+                    .or_else(|| tailwind_sources.get(&id))
+                    .cloned()
+            })
+            .collect();
+        let hash = (!css.is_empty()).then(|| format!("{:x}", Sha256::digest(css.as_bytes())));
+        async move { Ok(hash) }
+    }
+
+    fn register_hook_usage(&self) -> HookUsage {
+        HookUsage::Load | HookUsage::GenerateBundle | HookUsage::AugmentChunkHash
+    }
+}
+
+/// Returns the stylesheets of the chunk emitted as `filename` and of the
+/// chunks it statically imports, in load order. Stylesheets of the chunks it
+/// imports dynamically are linked by those chunks when they load.
+pub fn chunk_stylesheets(assets: &[Output], filename: &str) -> Vec<String> {
+    let chunks: HashMap<&str, _> = assets
+        .iter()
+        .filter_map(|output| match output {
+            Output::Chunk(chunk) => Some((chunk.filename.as_str(), chunk)),
+            Output::Asset(_) => None,
+        })
+        .collect();
+
+    // Imports are visited first so their styles come before the importer's
+    let mut order = Vec::new();
+    let mut seen = HashSet::new();
+    let mut queue = VecDeque::from([filename.to_string()]);
+    while let Some(filename) = queue.pop_front() {
+        if !seen.insert(filename.clone()) {
+            continue;
+        }
+        if let Some(chunk) = chunks.get(filename.as_str()) {
+            queue.extend(chunk.imports.iter().map(|import| import.to_string()));
+        }
+        order.push(filename);
+    }
+
+    order
+        .iter()
+        .rev()
+        .filter_map(|filename| {
+            let name = stylesheet_name(filename);
+            assets.iter().find_map(|output| match output {
+                Output::Asset(asset) if asset.names.contains(&name) => {
+                    Some(asset.filename.to_string())
+                }
+                _ => None,
+            })
+        })
+        .collect()
+}
+
+/// Returns the code appended to the chunk emitted as `filename` that links
+/// the stylesheets `hrefs` when the chunk runs, unless the page already
+/// links them.
+fn stylesheet_loader(filename: &str, hrefs: &[String]) -> String {
+    // Stylesheets are emitted at the root of the output directory
+    let prefix = "../".repeat(filename.matches('/').count());
+    let hrefs: Vec<String> = hrefs.iter().map(|href| format!("{prefix}{href}")).collect();
+    let hrefs = serde_json::to_string(&hrefs).unwrap_or_default();
+    format!(
+        r#"
+if (typeof document !== "undefined") {{
+  for (const href of {hrefs}) {{
+    const url = new URL(href, import.meta.url).href;
+    const links = document.querySelectorAll('link[rel="stylesheet"]');
+    if (![...links].some((link) => link.href === url)) {{
+      const link = document.createElement("link");
+      link.rel = "stylesheet";
+      link.href = url;
+      document.head.append(link);
+    }}
+  }}
+}}
+"#
+    )
+}
+
+/// Name the stylesheet of the chunk emitted as `filename` is registered
+/// under, e.g. `vendor-a1b2c3d4.css` for `vendor-a1b2c3d4.js`.
+fn stylesheet_name(filename: &str) -> String {
+    let stem = Path::new(filename)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy())
+        .unwrap_or_default();
+    format!("{stem}.css")
+}
+
+/// Returns the path of the module `id` if it is a stylesheet.
+fn stylesheet_path(id: &str) -> Option<&str> {
+    id.split('?').next().filter(|path| path.ends_with(".css"))
+}
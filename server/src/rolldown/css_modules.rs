@@ -0,0 +1,285 @@
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Default pattern scoped class names of CSS modules are built from.
+pub const DEFAULT_CSS_MODULES_PATTERN: &str = "[name]_[local]_[hash]";
+
+/// Words that cannot be exported as a binding, the classes named like them
+/// are only reachable through the default export.
+const RESERVED_WORDS: &[&str] = &[
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "import",
+    "in",
+    "instanceof",
+    "let",
+    "new",
+    "null",
+    "return",
+    "static",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+];
+
+/// A CSS module with its class names scoped to the file.
+#[derive(Debug, Clone)]
+pub(crate) struct CssModule {
+    pub css: String,
+    /// Scoped class name of every class, by its name in the source
+    pub classes: BTreeMap<String, String>,
+}
+
+impl CssModule {
+    /// Scopes the classes of the stylesheet `css`, named with `pattern`.
+    ///
+    /// `key` identifies the stylesheet, e.g. its root-relative path, and is
+    /// what the `[hash]` of the names is derived from. `[name]` is replaced
+    /// with the file name without `.module.css` and `[local]` with the class
+    /// name. Classes wrapped in `:global(...)` are left as written.
+    pub fn scope(css: &str, path: &Path, key: &str, pattern: &str) -> Self {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default();
+        let name = name.trim_end_matches(".css").trim_end_matches(".module");
+
+        let mut classes = BTreeMap::new();
+        let mut scoped = |local: &str| {
+            classes
+                .entry(local.to_string())
+                .or_insert_with(|| class_name(pattern, name, local, key))
+                .clone()
+        };
+
+        let mut out = String::with_capacity(css.len());
+        let mut segment = String::new();
+        let mut chars = css.char_indices().peekable();
+        while let Some((start, c)) = chars.next() {
+            match c {
+                '/' if css[start..].starts_with("/*") => {
+                    let end = css[start + 2..]
+                        .find("*/")
+                        .map_or(css.len(), |end| start + end + 4);
+                    segment.push_str(&css[start..end]);
+                    while chars.peek().is_some_and(|(index, _)| *index < end) {
+                        chars.next();
+                    }
+                }
+                '"' | '\'' => {
+                    let end = string_end(css, start);
+                    segment.push_str(&css[start..end]);
+                    while chars.peek().is_some_and(|(index, _)| *index < end) {
+                        chars.next();
+                    }
+                }
+                // The text before a block is a selector, unless it's an at-rule
+                '{' => {
+                    if segment.trim_start().starts_with('@') {
+                        out.push_str(&segment);
+                    } else {
+                        out.push_str(&scope_selector(&segment, &mut scoped));
+                    }
+                    out.push('{');
+                    segment.clear();
+                }
+                ';' | '}' => {
+                    out.push_str(&segment);
+                    out.push(c);
+                    segment.clear();
+                }
+                _ => segment.push(c),
+            }
+        }
+        out.push_str(&segment);
+
+        Self { css: out, classes }
+    }
+
+    /// Returns the JavaScript module exporting the scoped class names, as
+    /// the default export and as named exports where the class name allows.
+    pub fn to_module(&self) -> String {
+        let entries: Vec<String> = self
+            .classes
+            .iter()
+            .map(|(local, scoped)| format!("  {}: {}", json_string(local), json_string(scoped)))
+            .collect();
+
+        let mut code = format!("const classes = {{\n{}\n}};\n", entries.join(",\n"));
+        for (local, scoped) in &self.classes {
+            if is_identifier(local) && local != "classes" {
+                code.push_str(&format!(
+                    "export const {local} = {};\n",
+                    json_string(scoped)
+                ));
+            }
+        }
+        code.push_str("export default classes;\n");
+        code
+    }
+}
+
+/// Checks if the stylesheet at `path` is a CSS module.
+pub(crate) fn is_css_module(path: &Path) -> bool {
+    path.to_string_lossy().ends_with(".module.css")
+}
+
+/// Replaces the class names in `selector` with their scoped names.
+fn scope_selector(selector: &str, scoped: &mut impl FnMut(&str) -> String) -> String {
+    let mut out = String::with_capacity(selector.len());
+    let mut rest = selector;
+
+    while let Some(c) = rest.chars().next() {
+        if let Some(inner) = rest.strip_prefix(":global(") {
+            let end = closing_paren(inner);
+            out.push_str(&inner[..end]);
+            rest = inner.get(end + 1..).unwrap_or_default();
+            continue;
+        }
+        if let Some(inner) = rest.strip_prefix(":local(") {
+            let end = closing_paren(inner);
+            out.push_str(&scope_selector(&inner[..end], scoped));
+            rest = inner.get(end + 1..).unwrap_or_default();
+            continue;
+        }
+
+        match c {
+            '/' if rest.starts_with("/*") => {
+                let end = rest[2..].find("*/").map_or(rest.len(), |end| end + 4);
+                out.push_str(&rest[..end]);
+                rest = &rest[end..];
+            }
+            // Attribute values are matched as written
+            '[' => {
+                let end = rest.find(']').map_or(rest.len(), |end| end + 1);
+                out.push_str(&rest[..end]);
+                rest = &rest[end..];
+            }
+            '"' | '\'' => {
+                let end = string_end(rest, 0);
+                out.push_str(&rest[..end]);
+                rest = &rest[end..];
+            }
+            '.' if starts_identifier(&rest[1..]) => {
+                let len = rest[1..]
+                    .find(|c: char| !is_name_char(c))
+                    .unwrap_or(rest.len() - 1);
+                out.push('.');
+                out.push_str(&scoped(&rest[1..=len]));
+                rest = &rest[len + 1..];
+            }
+            _ => {
+                out.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+
+    out
+}
+
+/// Builds the scoped name of the class `local` from `pattern`.
+fn class_name(pattern: &str, name: &str, local: &str, key: &str) -> String {
+    let hash = format!("{:x}", Sha256::digest(format!("{key}:{local}").as_bytes()));
+    let class: String = pattern
+        .replace("[name]", name)
+        .replace("[local]", local)
+        .replace("[hash]", &hash[..8])
+        .chars()
+        .map(|c| if is_name_char(c) { c } else { '_' })
+        .collect();
+
+    if starts_identifier(&class) {
+        class
+    } else {
+        format!("_{class}")
+    }
+}
+
+/// Returns the index after the string starting at `start`, the end of `css`
+/// if it isn't closed.
+fn string_end(css: &str, start: usize) -> usize {
+    let quote = css[start..].chars().next().unwrap_or('"');
+    let mut escaped = false;
+    for (index, c) in css[start + 1..].char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            _ if c == quote => return start + 1 + index + 1,
+            _ => {}
+        }
+    }
+    css.len()
+}
+
+/// Returns the index of the parenthesis closing the group `s` starts in.
+fn closing_paren(s: &str) -> usize {
+    let mut depth = 0;
+    for (index, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return index,
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    s.len()
+}
+
+/// Checks if `s` starts with a CSS identifier, not a number like `.5em`.
+fn starts_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some('-') => chars
+            .next()
+            .is_some_and(|c| c == '-' || c == '_' || c.is_alphabetic() || !c.is_ascii()),
+        Some(c) => c == '_' || c.is_alphabetic() || !c.is_ascii(),
+        None => false,
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '_' || !c.is_ascii()
+}
+
+/// Checks if `name` can be exported as a binding.
+pub(super) fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c == '_' || c == '$' || c.is_ascii_alphabetic())
+        && chars.all(|c| c == '_' || c == '$' || c.is_ascii_alphanumeric())
+        && !RESERVED_WORDS.contains(&name)
+}
+
+fn json_string(value: &str) -> String {
+    serde_json::Value::from(value).to_string()
+}
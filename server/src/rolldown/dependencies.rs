@@ -0,0 +1,250 @@
+use crate::messages::Message;
+use crate::server::{
+    AutoInstall, Context, PackageManager, PalladinPlugin, install, read_manifest,
+    workspace_packages,
+};
+use anyhow::anyhow;
+use log::warn;
+use palladin_shared::{PalladinError, PalladinResult};
+use parking_lot::Mutex;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeSet, HashSet};
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+
+/// Dependency fields of `package.json` searched for a package.
+const DEPENDENCY_FIELDS: &[&str] = &[
+    "dependencies",
+    "devDependencies",
+    "peerDependencies",
+    "optionalDependencies",
+];
+
+/// Turns imports of packages that aren't installed into errors saying how to
+/// fix them, when the package is declared by this project or a workspace
+/// sibling, or was downloaded by npm before.
+///
+/// With auto-install enabled the package is also queued on
+/// [`MissingPackages`]. Asking and installing would hold up the bundler, so
+/// it's done by [`install_missing_packages`] once the build finished, and the
+/// build runs again.
+///
+/// Runs after every other plugin, so aliases they resolve aren't reported.
+#[derive(Debug)]
+pub struct MissingDependencyCheck {
+    root: PathBuf,
+    auto_install: AutoInstall,
+    /// Dependencies of the other workspace packages as `(package dir, names)`
+    siblings: OnceLock<Vec<(PathBuf, Vec<String>)>>,
+    missing: Arc<MissingPackages>,
+}
+
+impl MissingDependencyCheck {
+    pub fn new(root: PathBuf, auto_install: AutoInstall, missing: Arc<MissingPackages>) -> Self {
+        Self {
+            root,
+            auto_install,
+            siblings: OnceLock::new(),
+            missing,
+        }
+    }
+
+    fn suggestion(&self, package: &str) -> Option<String> {
+        let manager = PackageManager::detect(&self.root);
+        if declared_dependencies(&self.root)
+            .iter()
+            .any(|d| d == package)
+        {
+            return Some(format!(
+                "\"{package}\" is listed in package.json but not installed, \
+                 did you forget to run `{}`?",
+                manager.install_args(None).join(" ")
+            ));
+        }
+
+        let siblings = self.siblings.get_or_init(|| workspace_siblings(&self.root));
+        if let Some((dir, _)) = siblings
+            .iter()
+            .find(|(_, deps)| deps.iter().any(|d| d == package))
+        {
+            return Some(format!(
+                "\"{package}\" is a dependency of {} but not of this package, \
+                 did you forget to add it to package.json?",
+                dir.display()
+            ));
+        }
+
+        in_npm_cache(package).then(|| {
+            format!(
+                "\"{package}\" is not installed, did you forget to install it? \
+                 Run `{}`",
+                manager.install_args(Some(package)).join(" ")
+            )
+        })
+    }
+}
+
+impl PalladinPlugin for MissingDependencyCheck {
+    fn name(&self) -> &str {
+        "missing-dependency-check"
+    }
+
+    fn resolve_id(
+        &self,
+        specifier: &str,
+        importer: Option<&str>,
+    ) -> PalladinResult<Option<String>> {
+        let (Some(importer), Some(package)) = (importer, package_name(specifier)) else {
+            return Ok(None);
+        };
+
+        if is_installed(Path::new(importer), package) {
+            return Ok(None);
+        }
+
+        let Some(suggestion) = self.suggestion(package) else {
+            return Ok(None);
+        };
+        if self.auto_install != AutoInstall::Disabled {
+            self.missing.push(package);
+        }
+        Err(PalladinError::Build(anyhow!(
+            "{importer} imports \"{specifier}\": {suggestion}"
+        )))
+    }
+}
+
+/// Packages imports were found missing for while bundling, installed after
+/// the build instead of from inside the resolve hook.
+#[derive(Debug, Default)]
+pub(crate) struct MissingPackages {
+    /// Packages waiting to be installed
+    pending: Mutex<BTreeSet<String>>,
+    /// Packages an install was offered for, each is only offered once
+    offered: Mutex<HashSet<String>>,
+}
+
+impl MissingPackages {
+    /// Queues `package` for the next install, unless it was offered before.
+    fn push(&self, package: &str) {
+        if self.offered.lock().insert(package.to_string()) {
+            self.pending.lock().insert(package.to_string());
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.pending.lock().is_empty()
+    }
+
+    /// Installs the queued packages into `root`, asking first unless `policy`
+    /// is [`AutoInstall::Yes`]. Returns whether any of them is installed
+    /// afterwards.
+    fn install(&self, root: &Path, policy: AutoInstall) -> bool {
+        let packages = std::mem::take(&mut *self.pending.lock());
+        if packages.is_empty() {
+            return false;
+        }
+
+        // Packages declared in package.json come with the rest of the install
+        let declared = declared_dependencies(root);
+        let (declared, undeclared): (Vec<&String>, Vec<&String>) = packages
+            .iter()
+            .partition(|package| declared.contains(package));
+        let targets = (!declared.is_empty())
+            .then_some(None)
+            .into_iter()
+            .chain(undeclared.into_iter().map(|package| Some(package.as_str())));
+
+        for target in targets {
+            if let Err(e) = install(root, target, policy) {
+                warn!("{}", Message::InstallFailed { error: &e });
+            }
+        }
+        packages.iter().any(|package| is_installed(root, package))
+    }
+}
+
+/// Installs the packages the last build of `ctx` found missing, on a blocking
+/// thread since it may wait for an answer on the terminal. Returns whether
+/// any was installed, so the build has to run again.
+pub(crate) async fn install_missing_packages(ctx: &Context) -> bool {
+    let missing = ctx.missing_packages().clone();
+    let root = ctx.root().clone();
+    let policy = ctx.config().auto_install;
+    tokio::task::spawn_blocking(move || missing.install(&root, policy))
+        .await
+        .unwrap_or(false)
+}
+
+/// Returns the package a bare specifier imports from, e.g. `@scope/pkg` for
+/// `@scope/pkg/sub/path`.
+fn package_name(specifier: &str) -> Option<&str> {
+    let is_bare = !specifier.starts_with(['.', '/', '\0', '#']) && !specifier.contains(':');
+    if !is_bare {
+        return None;
+    }
+
+    let segments = if specifier.starts_with('@') { 2 } else { 1 };
+    let end = specifier
+        .match_indices('/')
+        .nth(segments - 1)
+        .map_or(specifier.len(), |(index, _)| index);
+    Some(&specifier[..end])
+}
+
+/// Checks if `package` is installed in a `node_modules` next to `path` or one
+/// of its ancestors.
+fn is_installed(path: &Path, package: &str) -> bool {
+    path.ancestors()
+        .any(|dir| dir.join("node_modules").join(package).exists())
+}
+
+fn declared_dependencies(dir: &Path) -> Vec<String> {
+    let Some(manifest) = read_manifest(dir) else {
+        return Vec::new();
+    };
+
+    DEPENDENCY_FIELDS
+        .iter()
+        .filter_map(|field| manifest.get(field)?.as_object())
+        .flat_map(|deps| deps.keys().cloned())
+        .collect()
+}
+
+/// Finds the packages of the workspace `root` belongs to, other than `root`
+/// itself, along with their dependencies.
+fn workspace_siblings(root: &Path) -> Vec<(PathBuf, Vec<String>)> {
+    workspace_packages(root)
+        .into_iter()
+        .map(|dir| {
+            let deps = declared_dependencies(&dir);
+            (dir, deps)
+        })
+        .collect()
+}
+
+/// Checks if npm has the registry metadata of `package` in its cache, i.e. it
+/// was installed on this machine before.
+fn in_npm_cache(package: &str) -> bool {
+    let cache = match env::var_os("npm_config_cache") {
+        Some(cache) => PathBuf::from(cache),
+        None => match env::var_os("HOME").or_else(|| env::var_os("USERPROFILE")) {
+            Some(home) => PathBuf::from(home).join(".npm"),
+            None => return false,
+        },
+    };
+
+    // npm's cacache stores index entries under the sha256 of their key
+    let url = format!("https://registry.npmjs.org/{}", package.replace('/', "%2f"));
+    let key = format!("make-fetch-happen:request-cache:{url}");
+    let hash = format!("{:x}", Sha256::digest(key.as_bytes()));
+
+    cache
+        .join("_cacache")
+        .join("index-v5")
+        .join(&hash[..2])
+        .join(&hash[2..4])
+        .join(&hash[4..])
+        .is_file()
+}
@@ -0,0 +1,362 @@
+use parking_lot::RwLock;
+use rolldown_common::{ModuleInfo, NormalModule, Output};
+use rolldown_plugin::{HookNoopReturn, HookUsage, Plugin, PluginContext};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Snapshot of the bundled module graph, rebuilt after every bundle output.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ModuleGraph {
+    pub chunks: Vec<ChunkNode>,
+    pub modules: Vec<ModuleNode>,
+    /// Problems that can make module execution order differ from source order
+    pub hazards: Vec<OrderHazard>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChunkNode {
+    pub filename: String,
+    pub name: String,
+    pub is_entry: bool,
+    pub is_dynamic_entry: bool,
+    /// Size of the emitted chunk code in bytes
+    pub size: usize,
+    pub modules: Vec<String>,
+    pub imports: Vec<String>,
+    pub dynamic_imports: Vec<String>,
+    /// Chunks that statically or dynamically import this chunk
+    pub importers: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModuleNode {
+    pub id: String,
+    /// Filename of the chunk the module was bundled into
+    pub chunk: String,
+    /// Position the module is executed at within its chunk
+    pub exec_order: usize,
+    /// Size of the source file on disk, `None` for virtual modules
+    pub size: Option<u64>,
+    /// Modules this module statically imports
+    pub imports: Vec<String>,
+    pub dynamic_imports: Vec<String>,
+    /// Modules that statically or dynamically import this module
+    pub importers: Vec<String>,
+}
+
+/// Imports of a parsed module, as resolved module ids.
+#[derive(Debug, Clone, Default)]
+struct ParsedImports {
+    imports: Vec<String>,
+    dynamic_imports: Vec<String>,
+}
+
+/// Imports of every module parsed by the bundler, by module id. The bundle
+/// output only tells which chunks import each other, so they're recorded
+/// while bundling by [`ImportRecorder`].
+///
+/// Incremental rebuilds only parse the changed modules again, so entries are
+/// kept across builds.
+#[derive(Debug, Default)]
+pub(crate) struct ModuleImports {
+    modules: RwLock<HashMap<String, ParsedImports>>,
+}
+
+impl ModuleImports {
+    fn record(&self, module: &ModuleInfo) {
+        let imports = ParsedImports {
+            imports: module
+                .imported_ids
+                .iter()
+                .map(|id| id.to_string())
+                .collect(),
+            dynamic_imports: module
+                .dynamically_imported_ids
+                .iter()
+                .map(|id| id.to_string())
+                .collect(),
+        };
+        self.modules.write().insert(module.id.to_string(), imports);
+    }
+}
+
+/// Records the imports of the modules the bundler parses in
+/// [`ModuleImports`].
+#[derive(Debug)]
+pub(crate) struct ImportRecorder {
+    imports: Arc<ModuleImports>,
+}
+
+impl ImportRecorder {
+    pub fn new(imports: Arc<ModuleImports>) -> Self {
+        Self { imports }
+    }
+}
+
+impl Plugin for ImportRecorder {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("palladin:imports")
+    }
+
+    async fn module_parsed(
+        &self,
+        _ctx: &PluginContext,
+        module_info: Arc<ModuleInfo>,
+        _normal_module: &NormalModule,
+    ) -> HookNoopReturn {
+        self.imports.record(&module_info);
+        Ok(())
+    }
+
+    fn register_hook_usage(&self) -> HookUsage {
+        HookUsage::ModuleParsed
+    }
+}
+
+/// Execution order hazard found in the bundle output.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum OrderHazard {
+    /// Chunks that statically import each other, so which one runs first
+    /// depends on the entry that loaded them.
+    ChunkCycle { chunks: Vec<String> },
+    /// A module emitted into several chunks runs once per chunk, repeating its
+    /// side effects.
+    DuplicatedModule { module: String, chunks: Vec<String> },
+}
+
+impl fmt::Display for OrderHazard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrderHazard::ChunkCycle { chunks } => {
+                write!(f, "circular chunk imports: {}", chunks.join(" -> "))
+            }
+            OrderHazard::DuplicatedModule { module, chunks } => {
+                write!(
+                    f,
+                    "{module} is executed by every one of {}",
+                    chunks.join(", ")
+                )
+            }
+        }
+    }
+}
+
+impl ModuleGraph {
+    pub fn from_assets(assets: &[Output]) -> Self {
+        let mut chunks = Vec::new();
+        let mut modules = Vec::new();
+
+        for asset in assets {
+            let Output::Chunk(chunk) = asset else {
+                continue;
+            };
+
+            let filename = chunk.filename.to_string();
+            let module_ids: Vec<String> =
+                chunk.module_ids.iter().map(|id| id.to_string()).collect();
+
+            // Rolldown emits modules in execution order
+            for (exec_order, id) in module_ids.iter().enumerate() {
+                modules.push(ModuleNode {
+                    id: id.clone(),
+                    chunk: filename.clone(),
+                    exec_order,
+                    size: Path::new(id).metadata().ok().map(|m| m.len()),
+                    imports: Vec::new(),
+                    dynamic_imports: Vec::new(),
+                    importers: Vec::new(),
+                });
+            }
+
+            chunks.push(ChunkNode {
+                filename,
+                name: chunk.name.to_string(),
+                is_entry: chunk.is_entry,
+                is_dynamic_entry: chunk.is_dynamic_entry,
+                size: chunk.code.len(),
+                modules: module_ids,
+                imports: chunk.imports.iter().map(|i| i.to_string()).collect(),
+                dynamic_imports: chunk
+                    .dynamic_imports
+                    .iter()
+                    .map(|i| i.to_string())
+                    .collect(),
+                importers: Vec::new(),
+            });
+        }
+
+        let mut importers: HashMap<String, Vec<String>> = HashMap::new();
+        for chunk in &chunks {
+            for imported in chunk.imports.iter().chain(&chunk.dynamic_imports) {
+                importers
+                    .entry(imported.clone())
+                    .or_default()
+                    .push(chunk.filename.clone());
+            }
+        }
+
+        for chunk in &mut chunks {
+            if let Some(list) = importers.remove(&chunk.filename) {
+                chunk.importers = list;
+            }
+        }
+
+        let mut hazards = chunk_cycles(&chunks);
+        hazards.extend(duplicated_modules(&modules));
+
+        Self {
+            chunks,
+            modules,
+            hazards,
+        }
+    }
+
+    /// Fills in the imports and importers of the modules from the imports
+    /// recorded while bundling.
+    pub(crate) fn with_imports(mut self, recorded: &ModuleImports) -> Self {
+        let recorded = recorded.modules.read();
+        let bundled: HashSet<String> = self.modules.iter().map(|m| m.id.clone()).collect();
+        let mut importers: HashMap<&str, Vec<String>> = HashMap::new();
+        for (id, parsed) in recorded.iter().filter(|(id, _)| bundled.contains(*id)) {
+            for imported in parsed.imports.iter().chain(&parsed.dynamic_imports) {
+                importers.entry(imported).or_default().push(id.clone());
+            }
+        }
+
+        for module in &mut self.modules {
+            if let Some(parsed) = recorded.get(&module.id) {
+                module.imports = parsed.imports.clone();
+                module.dynamic_imports = parsed.dynamic_imports.clone();
+            }
+            let mut list = importers
+                .get(module.id.as_str())
+                .cloned()
+                .unwrap_or_default();
+            list.sort();
+            module.importers = list;
+        }
+        self
+    }
+
+    /// Returns the modules of `chunk` in the order they are executed.
+    pub fn execution_order(&self, chunk: &str) -> Vec<&str> {
+        let mut modules: Vec<&ModuleNode> =
+            self.modules.iter().filter(|m| m.chunk == chunk).collect();
+        modules.sort_by_key(|m| m.exec_order);
+        modules.into_iter().map(|m| m.id.as_str()).collect()
+    }
+
+    /// Checks if the file at `path` was bundled as a module.
+    pub fn contains_module(&self, path: &Path) -> bool {
+        self.modules
+            .iter()
+            .any(|module| Path::new(&module.id) == path)
+    }
+}
+
+/// Finds groups of chunks whose static imports form a cycle (Tarjan's algorithm).
+fn chunk_cycles(chunks: &[ChunkNode]) -> Vec<OrderHazard> {
+    struct State<'a> {
+        edges: HashMap<&'a str, &'a [String]>,
+        index: HashMap<&'a str, usize>,
+        low: HashMap<&'a str, usize>,
+        stack: Vec<&'a str>,
+        on_stack: HashSet<&'a str>,
+        cycles: Vec<OrderHazard>,
+    }
+
+    fn visit<'a>(state: &mut State<'a>, chunk: &'a str) {
+        let index = state.index.len();
+        state.index.insert(chunk, index);
+        state.low.insert(chunk, index);
+        state.stack.push(chunk);
+        state.on_stack.insert(chunk);
+
+        let imports = state.edges.get(chunk).copied().unwrap_or_default();
+        for imported in imports {
+            let imported = imported.as_str();
+            if !state.edges.contains_key(imported) {
+                continue;
+            }
+
+            if !state.index.contains_key(imported) {
+                visit(state, imported);
+                let low = state.low[chunk].min(state.low[imported]);
+                state.low.insert(chunk, low);
+            } else if state.on_stack.contains(imported) {
+                let low = state.low[chunk].min(state.index[imported]);
+                state.low.insert(chunk, low);
+            }
+        }
+
+        if state.low[chunk] != state.index[chunk] {
+            return;
+        }
+
+        let mut component = Vec::new();
+        while let Some(member) = state.stack.pop() {
+            state.on_stack.remove(member);
+            component.push(member.to_string());
+            if member == chunk {
+                break;
+            }
+        }
+
+        let self_import = imports.iter().any(|i| i == chunk);
+        if component.len() > 1 || self_import {
+            component.reverse();
+            state
+                .cycles
+                .push(OrderHazard::ChunkCycle { chunks: component });
+        }
+    }
+
+    let mut state = State {
+        edges: chunks
+            .iter()
+            .map(|c| (c.filename.as_str(), c.imports.as_slice()))
+            .collect(),
+        index: HashMap::new(),
+        low: HashMap::new(),
+        stack: Vec::new(),
+        on_stack: HashSet::new(),
+        cycles: Vec::new(),
+    };
+
+    for chunk in chunks {
+        if !state.index.contains_key(chunk.filename.as_str()) {
+            visit(&mut state, &chunk.filename);
+        }
+    }
+
+    state.cycles
+}
+
+fn duplicated_modules(modules: &[ModuleNode]) -> Vec<OrderHazard> {
+    let mut chunks_by_module: HashMap<&str, Vec<String>> = HashMap::new();
+    for module in modules {
+        chunks_by_module
+            .entry(&module.id)
+            .or_default()
+            .push(module.chunk.clone());
+    }
+
+    let mut duplicated: Vec<_> = chunks_by_module
+        .into_iter()
+        .filter(|(_, chunks)| chunks.len() > 1)
+        .collect();
+    duplicated.sort_by_key(|(module, _)| *module);
+
+    duplicated
+        .into_iter()
+        .map(|(module, chunks)| OrderHazard::DuplicatedModule {
+            module: module.to_string(),
+            chunks,
+        })
+        .collect()
+}
@@ -0,0 +1,104 @@
+use rolldown_common::ModuleType;
+use rolldown_plugin::{
+    HookLoadArgs, HookLoadOutput, HookLoadReturn, HookResolveIdArgs, HookResolveIdOutput,
+    HookResolveIdReturn, HookTransformArgs, HookTransformOutput, HookTransformReturn, HookUsage,
+    Plugin, PluginContext, SharedTransformPluginContext,
+};
+use serde_json::Value;
+use std::borrow::Cow;
+use std::future::Future;
+
+/// Specifier modules import the hot context runtime with.
+const RUNTIME_SPECIFIER: &str = "palladin:hot";
+
+/// Id of the runtime module, not a file so nothing else resolves to it.
+const RUNTIME_ID: &str = "\0palladin:hot";
+
+/// Completes the `import.meta.hot` API of the dev bundle.
+const RUNTIME: &str = include_str!("hot_runtime.js");
+
+/// Gives every module using `import.meta.hot` the parts of the API rolldown's
+/// HMR runtime leaves out: `dispose(cb)`, `data` and `invalidate()`, plus
+/// `on(event, cb)`, `off(event, cb)` and `send(event, data)` for custom
+/// events exchanged with the server.
+///
+/// Such modules start by handing their hot object to a runtime module, which
+/// adds them. Their `import.meta.hot.accept` calls are left as they are, so
+/// rolldown still sees which modules accept updates.
+#[derive(Debug, Default)]
+pub struct HotContexts;
+
+impl Plugin for HotContexts {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("palladin:hot")
+    }
+
+    fn resolve_id(
+        &self,
+        _ctx: &PluginContext,
+        args: &HookResolveIdArgs<'_>,
+    ) -> impl Future<Output = HookResolveIdReturn> + Send {
+        let output = (args.specifier == RUNTIME_SPECIFIER).then(|| HookResolveIdOutput {
+            id: RUNTIME_ID.into(),
+            ..Default::default()
+        });
+        async move { Ok(output) }
+    }
+
+    fn load(
+        &self,
+        _ctx: &PluginContext,
+        args: &HookLoadArgs<'_>,
+    ) -> impl Future<Output = HookLoadReturn> + Send {
+        let output = (args.id == RUNTIME_ID).then(|| HookLoadOutput {
+            code: RUNTIME.into(),
+            module_type: Some(ModuleType::Js),
+            ..Default::default()
+        });
+        async move { Ok(output) }
+    }
+
+    fn transform(
+        &self,
+        _ctx: SharedTransformPluginContext,
+        args: &HookTransformArgs<'_>,
+    ) -> impl Future<Output = HookTransformReturn> + Send {
+        let output = (args.id != RUNTIME_ID && args.code.contains("import.meta.hot")).then(|| {
+            HookTransformOutput {
+                code: Some(with_hot_context(args.id, args.code)),
+                ..Default::default()
+            }
+        });
+        async move { Ok(output) }
+    }
+
+    fn register_hook_usage(&self) -> HookUsage {
+        HookUsage::ResolveId | HookUsage::Load | HookUsage::Transform
+    }
+}
+
+/// Returns `code` of the module `id` handing its hot object to the runtime
+/// first. The prologue is kept on the first line so the lines of the module
+/// don't move.
+fn with_hot_context(id: &str, code: &str) -> String {
+    format!(
+        "import {{ __palladin_hot__ }} from \"{RUNTIME_SPECIFIER}\"; \
+         __palladin_hot__(import.meta.hot, {});{code}",
+        Value::from(id)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prologue_keeps_lines() {
+        let code = with_hot_context("/src/app.js", "import.meta.hot.accept();\nexport {};\n");
+        assert!(code.starts_with(
+            "import { __palladin_hot__ } from \"palladin:hot\"; \
+             __palladin_hot__(import.meta.hot, \"/src/app.js\");"
+        ));
+        assert_eq!(code.lines().count(), 2);
+    }
+}
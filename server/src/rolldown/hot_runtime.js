@@ -0,0 +1,81 @@
+// Palladin's additions to the `import.meta.hot` object rolldown's HMR runtime
+// gives every module: `dispose`, `data` and `invalidate`, for the parts of the
+// API it leaves out, and `on`, `off` and `send` for custom events exchanged
+// with the server. Whatever rolldown provides itself is left alone.
+
+// Shared with the HMR client, which may run before or after the modules.
+const bridge = (globalThis.__palladin_hmr__ ??= { listeners: new Map(), send: null });
+
+// State of the last evaluated version of each module, by id.
+const modules = new Map();
+
+// Properties added to each hot object, so they're replaced for new versions
+// of its module rather than mistaken for rolldown's own.
+const added = new WeakMap();
+
+function provide(hot, name, value) {
+  const names = added.get(hot) ?? new Set();
+  if (name in hot && !names.has(name)) {
+    return;
+  }
+  hot[name] = value;
+  names.add(name);
+  added.set(hot, names);
+}
+
+// Called first thing by every module using `import.meta.hot`. A module
+// evaluated again is a new version of it: the old version's dispose
+// callbacks run with `data`, which is handed on to the new one, and its event
+// listeners are removed.
+export function __palladin_hot__(hot, id) {
+  if (!hot) {
+    return;
+  }
+
+  const previous = modules.get(id);
+  const data = previous?.data ?? {};
+  for (const callback of previous?.disposers ?? []) {
+    try {
+      callback(data);
+    } catch (err) {
+      console.error(`[palladin] error in dispose callback of ${id}`, err);
+    }
+  }
+
+  for (const [event, callback] of previous?.listeners ?? []) {
+    bridge.listeners.get(event)?.delete(callback);
+  }
+
+  const state = { data, disposers: [], listeners: [] };
+  modules.set(id, state);
+
+  provide(hot, "data", data);
+  provide(hot, "dispose", (callback) => {
+    state.disposers.push(callback);
+  });
+  // Custom events sent by the server, e.g. by `server.ws().send(event, data)`
+  provide(hot, "on", (event, callback) => {
+    if (!bridge.listeners.has(event)) {
+      bridge.listeners.set(event, new Set());
+    }
+    bridge.listeners.get(event).add(callback);
+    state.listeners.push([event, callback]);
+  });
+  provide(hot, "off", (event, callback) => {
+    bridge.listeners.get(event)?.delete(callback);
+  });
+  // Handled on the server by the handlers registered for `event`
+  provide(hot, "send", (event, data) => {
+    bridge.send?.({ type: "custom", event, data });
+  });
+  // Lets a module that accepted an update find it can't apply it, so the
+  // modules importing it are updated instead.
+  provide(hot, "invalidate", (message) => {
+    if (message) {
+      console.debug(`[palladin] ${id} invalidated: ${message}`);
+    }
+    if (!bridge.send?.({ type: "invalidate", path: id })) {
+      location.reload();
+    }
+  });
+}
@@ -0,0 +1,116 @@
+use crate::rolldown::css_modules::is_identifier;
+use crate::server::Vfs;
+use anyhow::anyhow;
+use rolldown_common::ModuleType;
+use rolldown_plugin::{
+    HookLoadArgs, HookLoadOutput, HookLoadReturn, HookUsage, Plugin, PluginContext,
+};
+use serde_json::Value;
+use std::borrow::Cow;
+use std::future::Future;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Name the parsed object is bound to in the generated module.
+const BINDING: &str = "json";
+
+/// Turns imports of `.json` files into a module exporting the parsed value,
+/// e.g. `import config from './config.json'`.
+///
+/// The value is the default export. The keys of a top-level object are named
+/// exports too where the key is a valid identifier, so
+/// `import { title } from './en.json'` works.
+#[derive(Debug)]
+pub struct JsonImports {
+    vfs: Arc<dyn Vfs>,
+}
+
+impl JsonImports {
+    pub fn new(vfs: Arc<dyn Vfs>) -> Self {
+        Self { vfs }
+    }
+
+    fn json_module(&self, path: &Path) -> anyhow::Result<String> {
+        let content = self
+            .vfs
+            .read(path)
+            .map_err(|err| anyhow!("failed to load {}: {err}", path.display()))?;
+        let value: Value = serde_json::from_slice(&content)
+            .map_err(|err| anyhow!("invalid JSON in {}: {err}", path.display()))?;
+        Ok(to_module(&value))
+    }
+}
+
+impl Plugin for JsonImports {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("palladin:json")
+    }
+
+    fn load(
+        &self,
+        _ctx: &PluginContext,
+        args: &HookLoadArgs<'_>,
+    ) -> impl Future<Output = HookLoadReturn> + Send {
+        let path = args.id.split('?').next().unwrap_or(args.id);
+        let result = match is_json(Path::new(path)) {
+            true => self.json_module(Path::new(path)).map(|code| {
+                Some(HookLoadOutput {
+                    code: code.into(),
+                    module_type: Some(ModuleType::Js),
+                    ..Default::default()
+                })
+            }),
+            false => Ok(None),
+        };
+        async move { result }
+    }
+
+    fn register_hook_usage(&self) -> HookUsage {
+        HookUsage::Load
+    }
+}
+
+/// Returns the JavaScript module exporting `value`.
+///
+/// The value is parsed at runtime rather than written as a literal, where a
+/// `__proto__` key would set the prototype of the object instead of being
+/// an own property.
+fn to_module(value: &Value) -> String {
+    let source = Value::from(value.to_string());
+    let mut code = format!("const {BINDING} = JSON.parse({source});\n");
+    if let Value::Object(object) = value {
+        for key in object.keys() {
+            if is_identifier(key) && key != BINDING {
+                code.push_str(&format!(
+                    "export const {key} = {BINDING}[{}];\n",
+                    Value::from(key.as_str())
+                ));
+            }
+        }
+    }
+    code.push_str(&format!("export default {BINDING};\n"));
+    code
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn proto_keys_stay_own_properties() {
+        let value: Value = serde_json::from_str(r#"{"__proto__":{"admin":true},"title":"a"}"#)
+            .expect("valid JSON");
+        assert_eq!(
+            to_module(&value),
+            "const json = JSON.parse(\"{\\\"__proto__\\\":{\\\"admin\\\":true},\\\"title\\\":\\\"a\\\"}\");\n\
+             export const __proto__ = json[\"__proto__\"];\n\
+             export const title = json[\"title\"];\n\
+             export default json;\n"
+        );
+    }
+}
@@ -0,0 +1,113 @@
+use crate::server::{Context, Mdx};
+use anyhow::anyhow;
+use pulldown_cmark::{Options, Parser, html};
+use rolldown_common::ModuleType;
+use rolldown_plugin::{
+    HookLoadArgs, HookLoadOutput, HookLoadReturn, HookUsage, Plugin, PluginContext,
+};
+use serde_json::Value;
+use std::borrow::Cow;
+use std::future::Future;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Turns imports of markdown files into modules.
+///
+/// `.md` files are rendered to HTML, exported as a string, e.g.
+/// `import html from './intro.md'`. `.mdx` files are compiled to a component
+/// by the project's `@mdx-js/mdx`, using the JSX runtime of the framework in
+/// use.
+#[derive(Debug)]
+pub struct MarkdownImports {
+    ctx: Arc<Context>,
+    mdx: Option<Arc<Mdx>>,
+    dev: bool,
+}
+
+impl MarkdownImports {
+    pub fn new(ctx: Arc<Context>, dev: bool) -> Self {
+        let mdx = ctx.config().mdx.clone();
+        Self { ctx, mdx, dev }
+    }
+
+    fn markdown_module(&self, path: &Path, mdx: bool) -> anyhow::Result<String> {
+        let source = self
+            .ctx
+            .vfs()
+            .read_to_string(path)
+            .map_err(|err| anyhow!("failed to load {}: {err}", path.display()))?;
+
+        if !mdx {
+            let html = Value::from(render_markdown(&source));
+            return Ok(format!(
+                "export const html = {html};\nexport default html;\n"
+            ));
+        }
+
+        let compiler = self.mdx.as_ref().ok_or_else(|| {
+            anyhow!(
+                "cannot import {}, install @mdx-js/mdx to compile MDX",
+                path.display()
+            )
+        })?;
+        let jsx_import_source = self.ctx.framework().jsx_import_source();
+        compiler
+            .compile(path, &source, self.dev, jsx_import_source)
+            .map_err(|err| anyhow!("failed to compile {}: {err}", path.display()))
+    }
+}
+
+impl Plugin for MarkdownImports {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("palladin:markdown")
+    }
+
+    fn load(
+        &self,
+        _ctx: &PluginContext,
+        args: &HookLoadArgs<'_>,
+    ) -> impl Future<Output = HookLoadReturn> + Send {
+        let path = Path::new(args.id.split('?').next().unwrap_or(args.id));
+        let extension = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_ascii_lowercase());
+
+        let result = match extension.as_deref() {
+            Some(ext @ ("md" | "mdx")) => {
+                let module_type = if ext == "mdx" {
+                    ModuleType::Jsx
+                } else {
+                    ModuleType::Js
+                };
+                self.markdown_module(path, ext == "mdx").map(|code| {
+                    Some(HookLoadOutput {
+                        code: code.into(),
+                        module_type: Some(module_type),
+                        ..Default::default()
+                    })
+                })
+            }
+            _ => Ok(None),
+        };
+        async move { result }
+    }
+
+    fn register_hook_usage(&self) -> HookUsage {
+        HookUsage::Load
+    }
+}
+
+/// Renders the markdown `source` to HTML, with GitHub's extensions and
+/// front matter left out.
+fn render_markdown(source: &str) -> String {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_HEADING_ATTRIBUTES
+        | Options::ENABLE_YAML_STYLE_METADATA_BLOCKS;
+
+    let mut out = String::with_capacity(source.len() * 3 / 2);
+    html::push_html(&mut out, Parser::new_ext(source, options));
+    out
+}
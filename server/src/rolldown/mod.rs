@@ -1,89 +1,623 @@
+mod asset_imports;
+mod casing;
 mod chunks;
+mod css;
+mod css_modules;
+mod dependencies;
 mod engine;
+mod graph;
+mod hot_context;
+mod json_imports;
+mod markdown;
+mod plugin;
+mod ssr;
+mod stdin;
+mod svelte;
+mod validate;
+mod vfs;
+mod worklets;
+mod workspace_sources;
 
-use crate::server::Context;
-use rolldown::{BundlerBuilder, BundlerOptions, ExperimentalOptions, TreeshakeOptions};
+use crate::build::{SsrFormat, ssr_build_dir};
+use crate::messages::Message;
+use crate::server::{
+    AssetPolicy, ChunkAddon, ChunkGroup, Context, Framework, Inject, InjectExport, Minify,
+    ModuleDependencies, PalladinPlugin, TreeshakeConfig,
+};
+use crate::transform::TransformOptions;
+use asset_imports::AssetImports;
+use casing::CaseSensitivityCheck;
+use css::CssBundler;
+use dependencies::MissingDependencyCheck;
+use either::Either;
+use hot_context::HotContexts;
+use json_imports::JsonImports;
+use log::warn;
+use markdown::MarkdownImports;
+use plugin::RolldownPlugin;
+use rolldown::{
+    BundlerBuilder, BundlerOptions, ExperimentalOptions, InnerOptions, InputItem,
+    ModuleSideEffects, TreeshakeOptions,
+};
 use rolldown_common::{
-    AdvancedChunksOptions, MatchGroup, MatchGroupName, MatchGroupTest, OutputFormat,
+    AddonOutputOption, AdvancedChunksOptions, AssetFilenamesOutputOption, BundlerTransformOptions,
+    HmrOptions, InjectImport, JsxOptions, MatchGroup, MatchGroupName, MatchGroupTest,
+    MinifyOptionsObject, Output, OutputFormat, Platform, RawMinifyOptions, SourceMapType,
 };
+use rolldown_plugin::SharedPluginable;
+use ssr::SsrExternals;
+use std::collections::HashSet;
+use std::path::Path;
 use std::sync::Arc;
+use stdin::StdinModule;
+use svelte::SvelteComponents;
+use vfs::VirtualFiles;
+use worklets::WorkletImports;
+use workspace_sources::WorkspaceSources;
+
+pub use asset_imports::DEFAULT_ASSET_INLINE_LIMIT;
+pub(crate) use asset_imports::is_asset;
+pub(crate) use chunks::guess_content_type;
+pub use chunks::{ChunkAsset, ChunkManager, ChunkProcessor, MainAsset, tag_chunk_imports};
+pub use css::chunk_stylesheets;
+pub use css_modules::DEFAULT_CSS_MODULES_PATTERN;
+pub(crate) use dependencies::{MissingPackages, install_missing_packages};
+pub use graph::ModuleGraph;
+pub(crate) use graph::{ImportRecorder, ModuleImports};
+pub(crate) use validate::validate_artifacts;
+pub(crate) use worklets::{bundle_worklet, is_worklet};
 
-pub use chunks::{ChunkManager, ChunkProcessor, MainAsset};
+/// Filename pattern of entry chunks when none is configured.
+pub const DEFAULT_ENTRY_FILENAMES: &str = "[name].js";
+
+/// Filename pattern of shared and lazily loaded chunks when none is
+/// configured.
+pub const DEFAULT_CHUNK_FILENAMES: &str = "[name]-[hash].js";
+
+/// What a bundle is made for, deciding how imported assets are referenced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BundleMode {
+    /// Served by the dev server, which serves assets from where they are
+    Dev,
+    /// Written to the build directory, assets are emitted next to the chunks
+    Build,
+}
 
-pub fn create_bundler(ctx: Arc<Context>) -> BundlerBuilder {
-    BundlerBuilder::default().with_options(BundlerOptions {
-        input: Some(vec![ctx.entrypoint().to_string_lossy().to_string().into()]),
+pub fn create_bundler(ctx: Arc<Context>, mode: BundleMode) -> BundlerBuilder {
+    let case_check = CaseSensitivityCheck {
+        strict: ctx.config().strict,
+    };
+    let dependency_check = MissingDependencyCheck::new(
+        ctx.root().clone(),
+        ctx.config().auto_install,
+        ctx.missing_packages().clone(),
+    );
+    let virtual_files = VirtualFiles::new(ctx.vfs().clone());
+    let mut plugins: Vec<SharedPluginable> = std::iter::once(Arc::new(case_check) as _)
+        .chain(ctx.config().plugins.iter().cloned())
+        .map(|plugin| {
+            let plugin =
+                RolldownPlugin::new(plugin).with_dependencies(ctx.module_dependencies().clone());
+            Arc::new(plugin) as SharedPluginable
+        })
+        .collect();
+    plugins.extend(workspace_sources(&ctx));
+    let css = Arc::new(CssBundler::new(&ctx));
+    plugins.push(css.clone());
+    if let Some(svelte) = &ctx.config().svelte {
+        plugins.push(Arc::new(SvelteComponents::new(
+            svelte.clone(),
+            ctx.vfs().clone(),
+            css,
+            mode == BundleMode::Dev,
+        )));
+    }
+    plugins.push(Arc::new(WorkletImports::new(ctx.clone(), mode)));
+    plugins.push(Arc::new(AssetImports::new(ctx.clone(), mode)));
+    plugins.push(Arc::new(JsonImports::new(ctx.vfs().clone())));
+    plugins.push(Arc::new(MarkdownImports::new(
+        ctx.clone(),
+        mode == BundleMode::Dev,
+    )));
+    plugins.push(Arc::new(RolldownPlugin::new(Arc::new(virtual_files))));
+    plugins.push(Arc::new(RolldownPlugin::new(Arc::new(dependency_check))));
+    plugins.push(Arc::new(ImportRecorder::new(ctx.module_imports().clone())));
+    if mode == BundleMode::Dev {
+        plugins.push(Arc::new(HotContexts));
+    }
+
+    let options = BundlerOptions {
+        input: Some(
+            ctx.entrypoints()
+                .iter()
+                .map(|entry| InputItem {
+                    name: entry.name.clone(),
+                    import: entry.path.to_string_lossy().to_string(),
+                })
+                .collect(),
+        ),
         cwd: Some(ctx.root().clone()),
         tsconfig: ctx.tsconfig_path().map(|p| p.to_string_lossy().to_string()),
 
-        entry_filenames: Some("[name].js".to_string().into()),
-        chunk_filenames: Some("[name]-[hash].js".to_string().into()),
+        entry_filenames: Some(ctx.config().entry_filenames.clone().into()),
+        chunk_filenames: Some(ctx.config().chunk_filenames.clone().into()),
+        asset_filenames: Some(asset_filenames(ctx.config().assets.clone())),
 
         dir: Some(ctx.build_dir().to_string_lossy().to_string()),
 
         format: Some(OutputFormat::Esm),
 
-        treeshake: TreeshakeOptions::Boolean(true),
+        treeshake: treeshake_options(&ctx.config().treeshake),
+        minify: Some(minify_options(ctx.config().minify)),
+        transform: transform_options(ctx.target(), ctx.framework()),
 
         experimental: Some(ExperimentalOptions {
             strict_execution_order: Some(true),
             incremental_build: Some(true),
+            // The dev engine only computes patches for bundles built with HMR
+            hmr: (mode == BundleMode::Dev).then(HmrOptions::default),
             ..Default::default()
         }),
 
         advanced_chunks: Some(AdvancedChunksOptions {
-            groups: Some(vec![
-                MatchGroup {
-                    name: MatchGroupName::Static("vendor".into()),
-                    test: Some(MatchGroupTest::Regex(r#"node_modules[\\/]"#.into())),
-                    min_size: Some((100 * 1024) as f64),
-                    priority: Some(0),
-                    ..Default::default()
-                },
-                MatchGroup {
-                    name: MatchGroupName::Static("react-vendor".to_string()),
-                    test: Some(MatchGroupTest::Function(Arc::new(|module_id| {
-                        let module_id = module_id.to_string();
-                        Box::pin(async move {
-                            Ok(Some(
-                                module_id.contains("node_modules")
-                                    && (module_id.contains("react")
-                                        || module_id.contains("react-dom")
-                                        || module_id.contains("scheduler")),
-                            ))
-                        })
-                    }))),
-                    priority: Some(20),
-                    min_size: None,
-                    max_size: None,
-                    min_share_count: None,
-                    min_module_size: None,
-                    max_module_size: None,
-                },
-                MatchGroup {
-                    name: MatchGroupName::Static("ui-vendor".to_string()),
-                    test: Some(MatchGroupTest::Function(Arc::new(|module_id| {
-                        let module_id = module_id.to_string();
-                        let test_against = ["@mui", "antd", "chakra-ui", "icons"];
-
-                        Box::pin(async move {
-                            Ok(Some(
-                                module_id.contains("node_modules")
-                                    && test_against.iter().any(|t| module_id.contains(t)),
-                            ))
-                        })
-                    }))),
-                    priority: Some(15),
-                    min_size: None,
-                    max_size: None,
-                    min_share_count: None,
-                    min_module_size: None,
-                    max_module_size: None,
-                },
-            ]),
+            groups: Some(chunk_groups(&ctx.config().chunk_groups, ctx.framework())),
             ..Default::default()
         }),
 
+        banner: addon(&ctx.config().banner, &ctx.config().chunk_banners),
+        footer: addon(&ctx.config().footer, &ctx.config().chunk_footers),
+        inject: inject_imports(&ctx.config().inject),
+
+        ..Default::default()
+    };
+
+    BundlerBuilder::default()
+        .with_options(options)
+        .with_plugins(plugins)
+}
+
+/// Returns the plugin resolving the packages of the workspace to their
+/// sources, if enabled.
+fn workspace_sources(ctx: &Context) -> Option<SharedPluginable> {
+    ctx.config().workspace_sources.then(|| {
+        let overrides = ctx.config().source_entries.clone();
+        let sources = WorkspaceSources::new(ctx.root().clone(), overrides);
+        Arc::new(RolldownPlugin::new(Arc::new(sources))) as SharedPluginable
+    })
+}
+
+/// Creates a bundler for modules that aren't on disk but loaded by
+/// `plugins`, with `entry` as the id of the entry module. The entry chunk is
+/// named `[name].js`, other chunks after `chunk_filenames`. The dependencies
+/// plugins declare are recorded in `dependencies`.
+pub(crate) fn create_virtual_bundler(
+    entry: String,
+    chunk_filenames: &str,
+    plugins: &[Arc<dyn PalladinPlugin>],
+    dependencies: &Arc<ModuleDependencies>,
+) -> BundlerBuilder {
+    let plugins: Vec<SharedPluginable> = plugins
+        .iter()
+        .cloned()
+        .map(|plugin| {
+            let plugin = RolldownPlugin::new(plugin).with_dependencies(dependencies.clone());
+            Arc::new(plugin) as SharedPluginable
+        })
+        .collect();
+
+    let options = BundlerOptions {
+        input: Some(vec![InputItem {
+            name: Some("index".to_string()),
+            import: entry,
+        }]),
+
+        entry_filenames: Some(DEFAULT_ENTRY_FILENAMES.to_string().into()),
+        chunk_filenames: Some(chunk_filenames.to_string().into()),
+
+        format: Some(OutputFormat::Esm),
+
+        ..Default::default()
+    };
+
+    BundlerBuilder::default()
+        .with_options(options)
+        .with_plugins(plugins)
+}
+
+/// Creates a bundler compiling `source` as a module of its own, leaving its
+/// imports external.
+pub(crate) fn create_transform_bundler(
+    source: String,
+    options: &TransformOptions,
+) -> BundlerBuilder {
+    let id = format!("stdin.{}", options.loader.name());
+    let module = StdinModule::new(id.clone(), source, options.loader.module_type());
+    let plugins: Vec<SharedPluginable> = std::iter::once(Arc::new(module) as SharedPluginable)
+        .chain(
+            options
+                .plugins
+                .iter()
+                .cloned()
+                .map(|plugin| Arc::new(RolldownPlugin::new(plugin)) as SharedPluginable),
+        )
+        .collect();
+
+    let options = BundlerOptions {
+        input: Some(vec![InputItem {
+            name: Some("stdin".to_string()),
+            import: id,
+        }]),
+
+        format: Some(OutputFormat::Esm),
+
+        // Unused code of a single module is still meant to be kept
+        treeshake: TreeshakeOptions::Boolean(false),
+        minify: Some(minify_options(options.minify)),
+        transform: transform_options(&options.target, Framework::Vanilla),
+        sourcemap: options.sourcemap.then_some(SourceMapType::Hidden),
+
+        ..Default::default()
+    };
+
+    BundlerBuilder::default()
+        .with_options(options)
+        .with_plugins(plugins)
+}
+
+/// Creates a bundler compiling `entry` into a single module for Node,
+/// leaving builtins and installed packages for the runtime to load.
+///
+/// Assets are referenced like in the client bundle of `mode`, so rendered
+/// markup matches it. Dev bundles come with a source map to fix up stack
+/// traces.
+pub(crate) fn create_ssr_bundler(
+    ctx: Arc<Context>,
+    entry: &Path,
+    mode: BundleMode,
+    format: SsrFormat,
+) -> BundlerBuilder {
+    let mut plugins: Vec<SharedPluginable> = ctx
+        .config()
+        .plugins
+        .iter()
+        .cloned()
+        .map(|plugin| {
+            let plugin =
+                RolldownPlugin::new(plugin).with_dependencies(ctx.module_dependencies().clone());
+            Arc::new(plugin) as SharedPluginable
+        })
+        .collect();
+    plugins.extend(workspace_sources(&ctx));
+    plugins.push(Arc::new(CssBundler::new(&ctx)));
+    plugins.push(Arc::new(AssetImports::new(ctx.clone(), mode)));
+    plugins.push(Arc::new(JsonImports::new(ctx.vfs().clone())));
+    plugins.push(Arc::new(MarkdownImports::new(
+        ctx.clone(),
+        mode == BundleMode::Dev,
+    )));
+    let virtual_files = VirtualFiles::new(ctx.vfs().clone());
+    plugins.push(Arc::new(RolldownPlugin::new(Arc::new(virtual_files))));
+    // Last, so plugins and aliases resolve their specifiers first
+    plugins.push(Arc::new(SsrExternals::new(ctx.root().clone())));
+
+    let options = BundlerOptions {
+        input: Some(vec![InputItem {
+            name: entry
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string()),
+            import: entry.to_string_lossy().to_string(),
+        }]),
+        cwd: Some(ctx.root().clone()),
+        tsconfig: ctx.tsconfig_path().map(|p| p.to_string_lossy().to_string()),
+
+        entry_filenames: Some(format!("[name].{}", format.extension()).into()),
+        asset_filenames: Some(asset_filenames(ctx.config().assets.clone())),
+        dir: Some(ssr_build_dir(ctx.build_dir()).to_string_lossy().to_string()),
+
+        format: Some(match format {
+            SsrFormat::Esm => OutputFormat::Esm,
+            SsrFormat::Cjs => OutputFormat::Cjs,
+        }),
+        platform: Some(Platform::Node),
+        inline_dynamic_imports: Some(true),
+        sourcemap: (mode == BundleMode::Dev).then_some(SourceMapType::Hidden),
+        inject: inject_imports(&ctx.config().inject),
+
+        ..Default::default()
+    };
+
+    BundlerBuilder::default()
+        .with_options(options)
+        .with_plugins(plugins)
+}
+
+/// Creates a bundler compiling the worklet `entry` into a single module
+/// for an `AudioWorkletGlobalScope` or `PaintWorkletGlobalScope`, with its
+/// dependencies and assets inlined.
+pub(crate) fn create_worklet_bundler(
+    ctx: Arc<Context>,
+    entry: &Path,
+    mode: BundleMode,
+) -> BundlerBuilder {
+    let mut plugins: Vec<SharedPluginable> = ctx
+        .config()
+        .plugins
+        .iter()
+        .cloned()
+        .map(|plugin| {
+            let plugin =
+                RolldownPlugin::new(plugin).with_dependencies(ctx.module_dependencies().clone());
+            Arc::new(plugin) as SharedPluginable
+        })
+        .collect();
+    plugins.extend(workspace_sources(&ctx));
+    plugins.push(Arc::new(AssetImports::new(ctx.clone(), mode).inlined()));
+    plugins.push(Arc::new(JsonImports::new(ctx.vfs().clone())));
+    let virtual_files = VirtualFiles::new(ctx.vfs().clone());
+    plugins.push(Arc::new(RolldownPlugin::new(Arc::new(virtual_files))));
+
+    let options = BundlerOptions {
+        input: Some(vec![InputItem {
+            name: entry
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string()),
+            import: entry.to_string_lossy().to_string(),
+        }]),
+        cwd: Some(ctx.root().clone()),
+        tsconfig: ctx.tsconfig_path().map(|p| p.to_string_lossy().to_string()),
+
+        format: Some(OutputFormat::Esm),
+        platform: Some(Platform::Browser),
+        // Worklets can't import anything at runtime
+        inline_dynamic_imports: Some(true),
+        minify: (mode == BundleMode::Build).then(|| minify_options(ctx.config().minify)),
+        transform: transform_options(ctx.target(), ctx.framework()),
+
+        ..Default::default()
+    };
+
+    BundlerBuilder::default()
+        .with_options(options)
+        .with_plugins(plugins)
+}
+
+/// Converts a configured banner or footer to rolldown's option, picking
+/// the code of the first of `overrides` matching each chunk.
+fn addon(code: &Option<String>, overrides: &[ChunkAddon]) -> Option<AddonOutputOption> {
+    if overrides.is_empty() {
+        return code
+            .clone()
+            .map(|code| AddonOutputOption::String(Some(code)));
+    }
+
+    let code = code.clone();
+    let overrides = overrides.to_vec();
+    Some(AddonOutputOption::Fn(Arc::new(move |chunk| {
+        let code = overrides
+            .iter()
+            .find(|addon| addon.matches(&chunk.name))
+            .map(|addon| addon.code.clone())
+            .or_else(|| code.clone());
+        Box::pin(async move { Ok(code) })
+    })))
+}
+
+/// Converts the configured automatic imports to rolldown's option.
+fn inject_imports(inject: &[Inject]) -> Option<Vec<InjectImport>> {
+    if inject.is_empty() {
+        return None;
+    }
+
+    let imports = inject.iter().map(|inject| match &inject.export {
+        InjectExport::Namespace => InjectImport::Namespace {
+            alias: inject.name.clone(),
+            from: inject.from.clone(),
+        },
+        InjectExport::Default => InjectImport::Named {
+            imported: "default".to_string(),
+            alias: Some(inject.name.clone()),
+            from: inject.from.clone(),
+        },
+        InjectExport::Named(export) => InjectImport::Named {
+            imported: export.clone(),
+            alias: (*export != inject.name).then(|| inject.name.clone()),
+            from: inject.from.clone(),
+        },
+    });
+    Some(imports.collect())
+}
+
+/// Returns the names of the files in `assets` that carry a content hash,
+/// going by the configured pattern each was named with, so they can be
+/// cached forever.
+pub(crate) fn content_hashed_files(ctx: &Context, assets: &[Output]) -> HashSet<String> {
+    let is_hashed = |pattern: &str| pattern.contains("[hash");
+    let chunks: HashSet<String> = assets
+        .iter()
+        .filter_map(|asset| match asset {
+            Output::Chunk(chunk) if chunk.is_entry => {
+                is_hashed(&ctx.config().entry_filenames).then(|| chunk.filename.to_string())
+            }
+            Output::Chunk(chunk) => {
+                is_hashed(&ctx.config().chunk_filenames).then(|| chunk.filename.to_string())
+            }
+            Output::Asset(_) => None,
+        })
+        .collect();
+
+    let assets = assets.iter().filter_map(|asset| match asset {
+        Output::Asset(asset) => {
+            let filename = asset.filename.to_string();
+            // Source maps are named after their chunk
+            let hashed = match filename.strip_suffix(".map") {
+                Some(chunk) if chunks.contains(chunk) => true,
+                _ => {
+                    let name = asset
+                        .names
+                        .first()
+                        .map(|n| n.to_string())
+                        .unwrap_or_default();
+                    is_hashed(ctx.config().assets.pattern_for(&name))
+                }
+            };
+            hashed.then_some(filename)
+        }
+        Output::Chunk(_) => None,
+    });
+    assets.chain(chunks.iter().cloned()).collect()
+}
+
+fn asset_filenames(policy: AssetPolicy) -> AssetFilenamesOutputOption {
+    AssetFilenamesOutputOption::Fn(Arc::new(move |asset| {
+        let name = asset
+            .names
+            .first()
+            .map(|n| n.to_string())
+            .unwrap_or_default();
+        let pattern = policy.pattern_for(&name).to_string();
+        Box::pin(async move { Ok(pattern) })
+    }))
+}
+
+fn minify_options(minify: Minify) -> RawMinifyOptions {
+    match minify {
+        Minify::Off => RawMinifyOptions::Bool(false),
+        Minify::On => RawMinifyOptions::Bool(true),
+        Minify::WhitespaceOnly => RawMinifyOptions::Object(MinifyOptionsObject {
+            mangle: false,
+            compress: false,
+            remove_whitespace: true,
+        }),
+    }
+}
+
+/// Returns the transform preset of `framework`, compiling JSX for its
+/// runtime, lowering syntax for `target`.
+fn transform_options(target: &[String], framework: Framework) -> Option<BundlerTransformOptions> {
+    let jsx = framework.jsx_import_source().map(|source| {
+        Either::Right(JsxOptions {
+            runtime: Some("automatic".to_string()),
+            import_source: Some(source.to_string()),
+            ..Default::default()
+        })
+    });
+    if target.is_empty() && jsx.is_none() {
+        return None;
+    }
+
+    Some(BundlerTransformOptions {
+        target: (!target.is_empty()).then(|| Either::Right(target.to_vec())),
+        jsx,
+        ..Default::default()
+    })
+}
+
+fn treeshake_options(config: &TreeshakeConfig) -> TreeshakeOptions {
+    if !config.enabled {
+        return TreeshakeOptions::Boolean(false);
+    }
+
+    let side_effect_free = config.side_effect_free_globs().unwrap_or_else(|err| {
+        warn!("{}", Message::InvalidSideEffectGlobs { error: &err });
+        Default::default()
+    });
+    let respect_package = config.respect_package_side_effects;
+
+    let module_side_effects = if side_effect_free.is_empty() && respect_package {
+        ModuleSideEffects::Boolean(true)
+    } else {
+        ModuleSideEffects::Function(Arc::new(move |id, _external| {
+            // `None` leaves the decision to the package.json `sideEffects` field
+            let side_effects = if side_effect_free.is_match(id) {
+                Some(false)
+            } else if respect_package {
+                None
+            } else {
+                Some(true)
+            };
+            Box::pin(async move { Ok(side_effects) })
+        }))
+    };
+
+    TreeshakeOptions::Option(InnerOptions {
+        module_side_effects,
+        annotations: Some(config.annotations),
+        manual_pure_functions: (!config.pure_functions.is_empty())
+            .then(|| config.pure_functions.iter().cloned().collect()),
         ..Default::default()
     })
 }
+
+/// Converts the configured chunk groups, falling back to the built-in vendor
+/// groups for `framework` when there are none.
+fn chunk_groups(groups: &[ChunkGroup], framework: Framework) -> Vec<MatchGroup> {
+    if groups.is_empty() {
+        return default_chunk_groups(framework);
+    }
+
+    groups
+        .iter()
+        .map(|group| MatchGroup {
+            name: MatchGroupName::Static(group.name.clone()),
+            test: Some(MatchGroupTest::Regex(group.test.as_str().into())),
+            min_size: group.min_size.map(|size| size as f64),
+            priority: Some(group.priority),
+            ..Default::default()
+        })
+        .collect()
+}
+
+fn default_chunk_groups(framework: Framework) -> Vec<MatchGroup> {
+    let mut groups = vec![MatchGroup {
+        name: MatchGroupName::Static("vendor".into()),
+        test: Some(MatchGroupTest::Regex(r#"node_modules[\\/]"#.into())),
+        min_size: Some((100 * 1024) as f64),
+        priority: Some(0),
+        ..Default::default()
+    }];
+
+    let packages = framework.vendor_packages();
+    if !packages.is_empty() {
+        groups.push(MatchGroup {
+            name: MatchGroupName::Static(format!("{framework}-vendor")),
+            test: Some(MatchGroupTest::Function(Arc::new(move |module_id| {
+                let module_id = module_id.to_string();
+                Box::pin(async move {
+                    Ok(Some(
+                        module_id.contains("node_modules")
+                            && packages.iter().any(|p| module_id.contains(p)),
+                    ))
+                })
+            }))),
+            priority: Some(20),
+            min_size: None,
+            max_size: None,
+            min_share_count: None,
+            min_module_size: None,
+            max_module_size: None,
+        });
+    }
+
+    groups.push(MatchGroup {
+        name: MatchGroupName::Static("ui-vendor".to_string()),
+        test: Some(MatchGroupTest::Function(Arc::new(|module_id| {
+            let module_id = module_id.to_string();
+            let test_against = ["@mui", "antd", "chakra-ui", "icons"];
+
+            Box::pin(async move {
+                Ok(Some(
+                    module_id.contains("node_modules")
+                        && test_against.iter().any(|t| module_id.contains(t)),
+                ))
+            })
+        }))),
+        priority: Some(15),
+        min_size: None,
+        max_size: None,
+        min_share_count: None,
+        min_module_size: None,
+        max_module_size: None,
+    });
+
+    groups
+}
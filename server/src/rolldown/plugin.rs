@@ -0,0 +1,126 @@
+use crate::server::{Dependency, ModuleDependencies, PalladinPlugin};
+use anyhow::anyhow;
+use palladin_shared::PalladinResult;
+use rolldown_plugin::{
+    HookLoadArgs, HookLoadOutput, HookLoadReturn, HookResolveIdArgs, HookResolveIdOutput,
+    HookResolveIdReturn, HookTransformArgs, HookTransformOutput, HookTransformReturn, HookUsage,
+    Plugin, PluginContext, SharedTransformPluginContext,
+};
+use std::borrow::Cow;
+use std::sync::Arc;
+
+/// Runs a [`PalladinPlugin`] as a rolldown plugin.
+#[derive(Debug)]
+pub struct RolldownPlugin {
+    plugin: Arc<dyn PalladinPlugin>,
+    name: String,
+    /// Where the dependencies the plugin declares for modules are recorded
+    dependencies: Option<Arc<ModuleDependencies>>,
+}
+
+impl RolldownPlugin {
+    pub fn new(plugin: Arc<dyn PalladinPlugin>) -> Self {
+        let name = format!("palladin:{}", plugin.name());
+        Self {
+            plugin,
+            name,
+            dependencies: None,
+        }
+    }
+
+    /// Records the dependencies the plugin declares for the modules it loads
+    /// or transforms in `dependencies`.
+    pub fn with_dependencies(mut self, dependencies: Arc<ModuleDependencies>) -> Self {
+        self.dependencies = Some(dependencies);
+        self
+    }
+
+    /// Calls `hook` with the plugin, on a blocking thread if its hooks block.
+    async fn call<T: Send + 'static>(
+        &self,
+        hook: impl FnOnce(&dyn PalladinPlugin) -> PalladinResult<T> + Send + 'static,
+    ) -> anyhow::Result<T> {
+        let result = if self.plugin.is_blocking() {
+            let plugin = Arc::clone(&self.plugin);
+            tokio::task::spawn_blocking(move || hook(plugin.as_ref()))
+                .await
+                .map_err(|err| anyhow!("{}: {err}", self.name))?
+        } else {
+            hook(self.plugin.as_ref())
+        };
+        result.map_err(|err| anyhow!("{}: {err}", self.name))
+    }
+
+    /// Records the dependencies of the module `id`, watching the files among
+    /// them through the bundler as well.
+    fn track_dependencies(&self, ctx: &PluginContext, id: &str) {
+        let Some(dependencies) = &self.dependencies else {
+            return;
+        };
+
+        let declared = self.plugin.module_dependencies(id);
+        for dependency in &declared {
+            if let Dependency::File(path) = dependency {
+                ctx.add_watch_file(&path.to_string_lossy());
+            }
+        }
+        dependencies.add(id, declared);
+    }
+}
+
+impl Plugin for RolldownPlugin {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Owned(self.name.clone())
+    }
+
+    async fn resolve_id(
+        &self,
+        _ctx: &PluginContext,
+        args: &HookResolveIdArgs<'_>,
+    ) -> HookResolveIdReturn {
+        let specifier = args.specifier.to_string();
+        let importer = args.importer.map(str::to_string);
+        let id = self
+            .call(move |plugin| plugin.resolve_id(&specifier, importer.as_deref()))
+            .await?;
+        Ok(id.map(|id| HookResolveIdOutput {
+            id: id.into(),
+            ..Default::default()
+        }))
+    }
+
+    async fn load(&self, ctx: &PluginContext, args: &HookLoadArgs<'_>) -> HookLoadReturn {
+        let id = args.id.to_string();
+        let Some(code) = self.call(move |plugin| plugin.load(&id)).await? else {
+            return Ok(None);
+        };
+        self.track_dependencies(ctx, args.id);
+        Ok(Some(HookLoadOutput {
+            code: code.into(),
+            ..Default::default()
+        }))
+    }
+
+    async fn transform(
+        &self,
+        ctx: SharedTransformPluginContext,
+        args: &HookTransformArgs<'_>,
+    ) -> HookTransformReturn {
+        let (id, code) = (args.id.to_string(), args.code.to_string());
+        let Some(code) = self
+            .call(move |plugin| plugin.transform(&id, &code))
+            .await?
+        else {
+            return Ok(None);
+        };
+        self.track_dependencies(&ctx, args.id);
+        Ok(Some(HookTransformOutput {
+            code: Some(code),
+            ..Default::default()
+        }))
+    }
+
+    fn register_hook_usage(&self) -> HookUsage {
+        HookUsage::ResolveId | HookUsage::Load | HookUsage::Transform
+    }
+}
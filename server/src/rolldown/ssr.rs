@@ -0,0 +1,64 @@
+use rolldown_plugin::{
+    HookResolveIdArgs, HookResolveIdOutput, HookResolveIdReturn, HookUsage, Plugin, PluginContext,
+};
+use std::borrow::Cow;
+use std::future::Future;
+use std::path::PathBuf;
+
+/// Leaves the packages installed in `node_modules` external in SSR bundles,
+/// so the JS runtime executing them loads its own copies.
+#[derive(Debug)]
+pub struct SsrExternals {
+    root: PathBuf,
+}
+
+impl SsrExternals {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// Checks if `specifier` imports a Node builtin or an installed package,
+    /// rather than e.g. a path alias.
+    fn is_external(&self, specifier: &str) -> bool {
+        if specifier.starts_with("node:") {
+            return true;
+        }
+        if specifier.starts_with(['.', '/', '\0']) || PathBuf::from(specifier).is_absolute() {
+            return false;
+        }
+
+        let mut segments = specifier.split('/');
+        let package = match segments.next() {
+            Some(scope) if scope.starts_with('@') => {
+                segments.next().map(|name| format!("{scope}/{name}"))
+            }
+            name => name.map(str::to_string),
+        };
+        package.is_some_and(|package| self.root.join("node_modules").join(package).is_dir())
+    }
+}
+
+impl Plugin for SsrExternals {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("palladin:ssr-externals")
+    }
+
+    fn resolve_id(
+        &self,
+        _ctx: &PluginContext,
+        args: &HookResolveIdArgs<'_>,
+    ) -> impl Future<Output = HookResolveIdReturn> + Send {
+        let output = self
+            .is_external(args.specifier)
+            .then(|| HookResolveIdOutput {
+                id: args.specifier.into(),
+                external: Some(true.into()),
+                ..Default::default()
+            });
+        async move { Ok(output) }
+    }
+
+    fn register_hook_usage(&self) -> HookUsage {
+        HookUsage::ResolveId
+    }
+}
@@ -0,0 +1,62 @@
+use rolldown_common::ModuleType;
+use rolldown_plugin::{
+    HookLoadArgs, HookLoadOutput, HookLoadReturn, HookResolveIdArgs, HookResolveIdOutput,
+    HookResolveIdReturn, HookUsage, Plugin, PluginContext,
+};
+use std::borrow::Cow;
+use std::future::Future;
+
+/// Provides a single module that isn't on disk, e.g. one read from stdin,
+/// leaving everything it imports external so it's compiled on its own.
+#[derive(Debug)]
+pub struct StdinModule {
+    id: String,
+    source: String,
+    module_type: ModuleType,
+}
+
+impl StdinModule {
+    pub fn new(id: String, source: String, module_type: ModuleType) -> Self {
+        Self {
+            id,
+            source,
+            module_type,
+        }
+    }
+}
+
+impl Plugin for StdinModule {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("palladin:stdin")
+    }
+
+    fn resolve_id(
+        &self,
+        _ctx: &PluginContext,
+        args: &HookResolveIdArgs<'_>,
+    ) -> impl Future<Output = HookResolveIdReturn> + Send {
+        let output = HookResolveIdOutput {
+            id: args.specifier.into(),
+            external: (args.specifier != self.id).then(|| true.into()),
+            ..Default::default()
+        };
+        async move { Ok(Some(output)) }
+    }
+
+    fn load(
+        &self,
+        _ctx: &PluginContext,
+        args: &HookLoadArgs<'_>,
+    ) -> impl Future<Output = HookLoadReturn> + Send {
+        let output = (args.id == self.id).then(|| HookLoadOutput {
+            code: self.source.clone().into(),
+            module_type: Some(self.module_type.clone()),
+            ..Default::default()
+        });
+        async move { Ok(output) }
+    }
+
+    fn register_hook_usage(&self) -> HookUsage {
+        HookUsage::ResolveId | HookUsage::Load
+    }
+}
@@ -0,0 +1,101 @@
+use crate::rolldown::css::CssBundler;
+use crate::server::{Svelte, Vfs};
+use anyhow::anyhow;
+use rolldown_common::ModuleType;
+use rolldown_plugin::{
+    HookLoadArgs, HookLoadOutput, HookLoadReturn, HookResolveIdArgs, HookResolveIdOutput,
+    HookResolveIdReturn, HookUsage, Plugin, PluginContext,
+};
+use std::borrow::Cow;
+use std::future::Future;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Query appended to a component's id to import its extracted styles.
+const STYLE_QUERY: &str = "?svelte&type=style&lang.css";
+
+/// Compiles imported `.svelte` components with the project's compiler.
+///
+/// The styles of a component are extracted into a stylesheet imported by
+/// the compiled module, which the [`CssBundler`] bundles like any other. In
+/// dev bundles Svelte 5 components accept the patches the dev engine sends
+/// for them, keeping their state across edits; with older versions the
+/// engine reloads the page instead.
+#[derive(Debug)]
+pub struct SvelteComponents {
+    svelte: Arc<Svelte>,
+    vfs: Arc<dyn Vfs>,
+    css: Arc<CssBundler>,
+    dev: bool,
+}
+
+impl SvelteComponents {
+    pub fn new(svelte: Arc<Svelte>, vfs: Arc<dyn Vfs>, css: Arc<CssBundler>, dev: bool) -> Self {
+        Self {
+            svelte,
+            vfs,
+            css,
+            dev,
+        }
+    }
+
+    /// Compiles the component `id`, returning the code of its module. The
+    /// compiler runs in node, so it's waited for on a blocking thread.
+    async fn compile(&self, id: &str) -> anyhow::Result<String> {
+        let path = Path::new(id);
+        let source = self
+            .vfs
+            .read_to_string(path)
+            .map_err(|err| anyhow!("failed to load {id}: {err}"))?;
+        let (svelte, file, dev) = (self.svelte.clone(), path.to_path_buf(), self.dev);
+        let component = tokio::task::spawn_blocking(move || svelte.compile(&file, &source, dev))
+            .await?
+            .map_err(|err| anyhow!("failed to compile {id}: {err}"))?;
+
+        let Some(css) = component.css else {
+            return Ok(component.js);
+        };
+        let style_id = format!("{id}{STYLE_QUERY}");
+        let import = format!("import {};\n", serde_json::Value::from(style_id.as_str()));
+        self.css.add_virtual_stylesheet(style_id, css);
+        Ok(import + &component.js)
+    }
+}
+
+impl Plugin for SvelteComponents {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("palladin:svelte")
+    }
+
+    fn resolve_id(
+        &self,
+        _ctx: &PluginContext,
+        args: &HookResolveIdArgs<'_>,
+    ) -> impl Future<Output = HookResolveIdReturn> + Send {
+        // Style imports are emitted with the component's resolved id
+        let result = args
+            .specifier
+            .ends_with(STYLE_QUERY)
+            .then(|| HookResolveIdOutput {
+                id: args.specifier.to_string().into(),
+                ..Default::default()
+            });
+        async move { Ok(result) }
+    }
+
+    async fn load(&self, _ctx: &PluginContext, args: &HookLoadArgs<'_>) -> HookLoadReturn {
+        if !args.id.ends_with(".svelte") {
+            return Ok(None);
+        }
+        let code = self.compile(args.id).await?;
+        Ok(Some(HookLoadOutput {
+            code: code.into(),
+            module_type: Some(ModuleType::Js),
+            ..Default::default()
+        }))
+    }
+
+    fn register_hook_usage(&self) -> HookUsage {
+        HookUsage::ResolveId | HookUsage::Load
+    }
+}
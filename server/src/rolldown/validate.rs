@@ -0,0 +1,85 @@
+use super::chunks::{ChunkAsset, MainAsset};
+use oxc::allocator::Allocator;
+use oxc::parser::Parser;
+use oxc::span::SourceType;
+use rolldown_common::Output;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::PathBuf;
+
+/// Problem with the output of a bundle that would only surface once a
+/// browser loads the page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArtifactProblem {
+    /// An entry chunk isn't valid JavaScript
+    Syntax { chunk: String, message: String },
+    /// A chunk imports a chunk the bundle doesn't contain
+    MissingChunk { importer: String, chunk: String },
+}
+
+impl fmt::Display for ArtifactProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArtifactProblem::Syntax { chunk, message } => {
+                write!(f, "entry chunk {chunk} doesn't parse: {message}")
+            }
+            ArtifactProblem::MissingChunk { importer, chunk } => {
+                write!(f, "{importer} imports {chunk}, which wasn't emitted")
+            }
+        }
+    }
+}
+
+/// Checks that the entry chunks parse and that every chunk they load, directly
+/// or through other chunks, is served.
+pub(crate) fn validate_artifacts(
+    assets: &[Output],
+    entry_assets: &HashMap<PathBuf, MainAsset>,
+    chunks: &HashMap<String, ChunkAsset>,
+) -> Vec<ArtifactProblem> {
+    let mut problems = Vec::new();
+    for asset in entry_assets.values() {
+        let allocator = Allocator::default();
+        let parsed = Parser::new(&allocator, &asset.content, SourceType::mjs()).parse();
+        if let Some(error) = parsed.errors.first() {
+            problems.push(ArtifactProblem::Syntax {
+                chunk: asset.filename.clone(),
+                message: error.to_string(),
+            });
+        }
+    }
+
+    let outputs: HashMap<&str, &Output> = assets
+        .iter()
+        .map(|asset| (asset.filename(), asset))
+        .collect();
+    let served = |filename: &str| {
+        chunks.contains_key(filename)
+            || entry_assets
+                .values()
+                .any(|asset| asset.filename == filename)
+    };
+
+    let mut queue: Vec<String> = entry_assets
+        .values()
+        .map(|asset| asset.filename.clone())
+        .collect();
+    let mut seen: HashSet<String> = queue.iter().cloned().collect();
+    while let Some(filename) = queue.pop() {
+        let Some(Output::Chunk(chunk)) = outputs.get(filename.as_str()) else {
+            continue;
+        };
+        for imported in chunk.imports.iter().chain(&chunk.dynamic_imports) {
+            let imported = imported.to_string();
+            if !served(&imported) {
+                problems.push(ArtifactProblem::MissingChunk {
+                    importer: filename.clone(),
+                    chunk: imported,
+                });
+            } else if seen.insert(imported.clone()) {
+                queue.push(imported);
+            }
+        }
+    }
+    problems
+}
@@ -0,0 +1,73 @@
+use crate::server::{PalladinPlugin, Vfs};
+use palladin_shared::PalladinResult;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Suffixes tried when a relative import leaves out the extension.
+const RESOLVE_SUFFIXES: &[&str] = &[
+    "",
+    ".tsx",
+    ".ts",
+    ".jsx",
+    ".js",
+    "/index.tsx",
+    "/index.ts",
+    "/index.jsx",
+    "/index.js",
+];
+
+/// Resolves and loads the modules held in memory by the configured [`Vfs`],
+/// e.g. unsaved editor buffers, which the bundler can't read from disk.
+/// Relative and absolute specifiers are resolved, e.g. entrypoints only held
+/// in memory.
+///
+/// Files on disk are left to the bundler.
+#[derive(Debug)]
+pub struct VirtualFiles {
+    vfs: Arc<dyn Vfs>,
+}
+
+impl VirtualFiles {
+    pub fn new(vfs: Arc<dyn Vfs>) -> Self {
+        Self { vfs }
+    }
+}
+
+impl PalladinPlugin for VirtualFiles {
+    fn name(&self) -> &str {
+        "virtual-files"
+    }
+
+    fn resolve_id(
+        &self,
+        specifier: &str,
+        importer: Option<&str>,
+    ) -> PalladinResult<Option<String>> {
+        let path = if Path::new(specifier).is_absolute() {
+            PathBuf::from(specifier)
+        } else if specifier.starts_with("./") || specifier.starts_with("../") {
+            let Some(dir) = importer.and_then(|importer| Path::new(importer).parent()) else {
+                return Ok(None);
+            };
+            dir.join(specifier)
+        } else {
+            return Ok(None);
+        };
+
+        Ok(RESOLVE_SUFFIXES.iter().find_map(|suffix| {
+            let candidate = format!("{}{suffix}", path.display());
+            let candidate = self.vfs.canonicalize(Path::new(&candidate)).ok()?;
+            self.vfs
+                .is_virtual(&candidate)
+                .then(|| candidate.to_string_lossy().to_string())
+        }))
+    }
+
+    fn load(&self, id: &str) -> PalladinResult<Option<String>> {
+        let path = Path::new(id);
+        if !self.vfs.is_virtual(path) {
+            return Ok(None);
+        }
+        self.vfs.read_to_string(path).map(Some)
+    }
+}
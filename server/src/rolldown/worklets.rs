@@ -0,0 +1,189 @@
+use crate::rolldown::asset_imports::{EmittedAsset, asset_filename};
+use crate::rolldown::{BundleMode, create_worklet_bundler};
+use crate::server::{Context, Dependency};
+use anyhow::anyhow;
+use palladin_shared::{PalladinError, PalladinResult};
+use parking_lot::RwLock;
+use rolldown_common::{ModuleType, Output, OutputAsset};
+use rolldown_plugin::{
+    HookGenerateBundleArgs, HookLoadArgs, HookLoadOutput, HookLoadReturn, HookNoopReturn,
+    HookUsage, Plugin, PluginContext,
+};
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Extensions of the scripts a worklet can be written in.
+const WORKLET_EXTENSIONS: &[&str] = &["js", "mjs", "ts"];
+
+/// Turns imports of worklet scripts, e.g.
+/// `import processor from './noise.worklet.ts'`, into the URL to pass to
+/// `audioWorklet.addModule` or `CSS.paintWorklet.addModule`.
+///
+/// Worklets run in a global scope of their own without `fetch`, HMR or
+/// dynamic imports, so each is bundled into a standalone module with its
+/// assets inlined. The dev server compiles them when requested, builds emit
+/// them under the name the [`AssetPolicy`](crate::server::AssetPolicy) gives
+/// them.
+#[derive(Debug)]
+pub struct WorkletImports {
+    ctx: Arc<Context>,
+    mode: BundleMode,
+    /// Worklets emitted by a build, by output filename
+    emitted: RwLock<BTreeMap<String, EmittedAsset>>,
+}
+
+impl WorkletImports {
+    pub fn new(ctx: Arc<Context>, mode: BundleMode) -> Self {
+        Self {
+            ctx,
+            mode,
+            emitted: RwLock::default(),
+        }
+    }
+
+    /// Returns the URL the worklet at `path` is loaded from.
+    async fn worklet_url(&self, ctx: &PluginContext, path: &Path) -> anyhow::Result<String> {
+        let BundledWorklet { code, modules } = bundle_worklet(self.ctx.clone(), path, self.mode)
+            .await
+            .map_err(|err| anyhow!("failed to bundle worklet {}: {err}", path.display()))?;
+
+        if self.mode == BundleMode::Dev {
+            let url_path = self
+                .ctx
+                .url_path(path)
+                .filter(|_| !self.ctx.is_excluded(path))
+                .ok_or_else(|| anyhow!("worklet {} is outside the root", path.display()))?;
+            // The importing module is loaded again when a module bundled
+            // into the worklet changes, not only the worklet itself
+            let id = path.to_string_lossy();
+            for module in &modules {
+                ctx.add_watch_file(&module.to_string_lossy());
+            }
+            self.ctx
+                .module_dependencies()
+                .add(&id, modules.into_iter().map(Dependency::File));
+
+            // Worklet scopes cache modules by URL, so edits need a new one
+            let hash = format!("{:x}", Sha256::digest(code.as_bytes()));
+            return Ok(format!("{}?v={}", self.ctx.base_url(&url_path), &hash[..8]));
+        }
+
+        let output = PathBuf::from(path).with_extension("js");
+        let name = output
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let pattern = self.ctx.config().assets.pattern_for(&name);
+        let filename = asset_filename(pattern, &output, code.as_bytes());
+        let url = self.ctx.base_url(&format!("/{filename}"));
+        let original_file_name = self
+            .ctx
+            .url_path(path)
+            .map(|path| path.trim_start_matches('/').to_string());
+        self.emitted.write().insert(
+            filename,
+            EmittedAsset {
+                name,
+                original_file_name,
+                content: code.into_bytes(),
+            },
+        );
+        Ok(url)
+    }
+}
+
+impl Plugin for WorkletImports {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("palladin:worklets")
+    }
+
+    async fn load(&self, ctx: &PluginContext, args: &HookLoadArgs<'_>) -> HookLoadReturn {
+        let path = PathBuf::from(args.id.split('?').next().unwrap_or(args.id));
+        if !is_worklet(&path) {
+            return Ok(None);
+        }
+        let url = serde_json::Value::from(self.worklet_url(ctx, &path).await?);
+        Ok(Some(HookLoadOutput {
+            code: format!("export default {url};").into(),
+            module_type: Some(ModuleType::Js),
+            ..Default::default()
+        }))
+    }
+
+    fn generate_bundle(
+        &self,
+        _ctx: &PluginContext,
+        args: &mut HookGenerateBundleArgs<'_>,
+    ) -> impl Future<Output = HookNoopReturn> + Send {
+        let emitted = self.emitted.read();
+        args.bundle.extend(emitted.iter().map(|(filename, asset)| {
+            Output::Asset(Arc::new(OutputAsset {
+                filename: filename.clone().into(),
+                source: asset.content.clone().into(),
+                names: vec![asset.name.clone()],
+                original_file_names: asset.original_file_name.iter().cloned().collect(),
+            }))
+        }));
+        async { Ok(()) }
+    }
+
+    fn register_hook_usage(&self) -> HookUsage {
+        HookUsage::Load | HookUsage::GenerateBundle
+    }
+}
+
+/// Checks if `path` is a worklet script, named like `noise.worklet.ts`.
+pub(crate) fn is_worklet(path: &Path) -> bool {
+    let is_script = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+        .is_some_and(|ext| WORKLET_EXTENSIONS.contains(&ext.as_str()));
+    is_script
+        && path
+            .file_stem()
+            .is_some_and(|stem| stem.to_string_lossy().ends_with(".worklet"))
+}
+
+/// A worklet bundled into a single standalone module.
+#[derive(Debug)]
+pub(crate) struct BundledWorklet {
+    pub code: String,
+    /// Files bundled into the worklet, the worklet itself included
+    pub modules: Vec<PathBuf>,
+}
+
+/// Bundles the worklet at `path` into a single standalone module.
+///
+/// # Errors
+///
+/// Returns an error if the worklet can't be bundled.
+pub(crate) async fn bundle_worklet(
+    ctx: Arc<Context>,
+    path: &Path,
+    mode: BundleMode,
+) -> PalladinResult<BundledWorklet> {
+    let mut bundler = create_worklet_bundler(ctx, path, mode).build()?;
+    let output = bundler.generate().await;
+    bundler.close().await?;
+
+    output?
+        .assets
+        .into_iter()
+        .find_map(|asset| match asset {
+            Output::Chunk(chunk) if chunk.is_entry => Some(BundledWorklet {
+                code: chunk.code.to_string(),
+                modules: chunk
+                    .module_ids
+                    .iter()
+                    .map(|id| PathBuf::from(id.to_string()))
+                    .filter(|module| module.is_absolute())
+                    .collect(),
+            }),
+            _ => None,
+        })
+        .ok_or_else(|| PalladinError::Build(anyhow!("the worklet produced no output")))
+}
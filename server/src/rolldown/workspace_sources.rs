@@ -0,0 +1,48 @@
+use crate::server::{PalladinPlugin, SourceAlias, SourceEntry, source_aliases};
+use palladin_shared::PalladinResult;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// Resolves imports of workspace packages to their source entry instead of
+/// their build output, so editing a package hot-updates the app without
+/// rebuilding the package first.
+///
+/// Only imports of the package itself are aliased, e.g. `@acme/ui` but not
+/// `@acme/ui/button`, which resolve through its `exports` as usual.
+#[derive(Debug)]
+pub struct WorkspaceSources {
+    root: PathBuf,
+    overrides: Vec<SourceEntry>,
+    aliases: OnceLock<Vec<SourceAlias>>,
+}
+
+impl WorkspaceSources {
+    pub fn new(root: PathBuf, overrides: Vec<SourceEntry>) -> Self {
+        Self {
+            root,
+            overrides,
+            aliases: OnceLock::new(),
+        }
+    }
+}
+
+impl PalladinPlugin for WorkspaceSources {
+    fn name(&self) -> &str {
+        "workspace-sources"
+    }
+
+    fn resolve_id(
+        &self,
+        specifier: &str,
+        _importer: Option<&str>,
+    ) -> PalladinResult<Option<String>> {
+        let aliases = self
+            .aliases
+            .get_or_init(|| source_aliases(&self.root, &self.overrides));
+
+        Ok(aliases
+            .iter()
+            .find(|alias| alias.package == specifier)
+            .map(|alias| alias.entry.to_string_lossy().to_string()))
+    }
+}
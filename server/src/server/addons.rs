@@ -0,0 +1,58 @@
+use globset::{Glob, GlobMatcher};
+use std::fmt;
+use std::str::FromStr;
+
+/// Banner or footer of the chunks whose name matches `pattern`, replacing
+/// the one configured for every chunk, e.g. a license header for `vendor`.
+#[derive(Debug, Clone)]
+pub struct ChunkAddon {
+    /// Glob matched against the chunk name, e.g. `vendor` or `pages-*`
+    pub pattern: String,
+    /// Code added to the chunk, usually a comment
+    pub code: String,
+    matcher: GlobMatcher,
+}
+
+impl ChunkAddon {
+    /// Creates an addon of `code` for chunks named like `pattern`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `pattern` isn't a valid glob.
+    pub fn new(pattern: impl Into<String>, code: impl Into<String>) -> Result<Self, String> {
+        let pattern = pattern.into();
+        let matcher = Glob::new(&pattern)
+            .map_err(|err| format!("invalid chunk pattern '{pattern}': {err}"))?
+            .compile_matcher();
+        Ok(Self {
+            pattern,
+            code: code.into(),
+            matcher,
+        })
+    }
+
+    /// Checks if the addon belongs to the chunk named `name`.
+    pub fn matches(&self, name: &str) -> bool {
+        self.matcher.is_match(name)
+    }
+}
+
+/// Parses `CHUNK=CODE`, e.g. `vendor=/*! Licenses: vendor.LICENSE.txt */`.
+impl FromStr for ChunkAddon {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((pattern, code)) if !pattern.trim().is_empty() => Self::new(pattern.trim(), code),
+            _ => Err(format!(
+                "invalid chunk addon '{s}', expected CHUNK=CODE such as vendor=/*! MIT */"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ChunkAddon {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.pattern, self.code)
+    }
+}
@@ -0,0 +1,90 @@
+use globset::{Glob, GlobMatcher};
+use std::fmt;
+use std::str::FromStr;
+
+/// How the dev server answers routes, extensionless paths no file exists
+/// for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AppType {
+    /// Pages are served for their routes, any other route falls back to
+    /// `index.html` so the client side router can handle it
+    #[default]
+    Spa,
+    /// Pages are served for their routes, any other route is a 404
+    Mpa,
+    /// Routes are never answered with HTML, e.g. when the app's own server
+    /// renders the pages
+    Custom,
+}
+
+palladin_shared::named_enum!(AppType, "app type" {
+    Spa => "spa",
+    Mpa => "mpa",
+    Custom => "custom",
+});
+
+/// Serves the file at `to` for routes matching `pattern`, whatever the
+/// app type, e.g. the shell of a client side routed section of a
+/// multi-page app.
+#[derive(Debug, Clone)]
+pub struct Rewrite {
+    /// Glob matched against the route without its leading slash, e.g.
+    /// `app/**`, only set through [`Rewrite::new`] so it stays in sync with
+    /// the compiled matcher
+    pattern: String,
+    /// Path of the served file, relative to the root, e.g. `app/index.html`
+    pub to: String,
+    matcher: GlobMatcher,
+}
+
+impl Rewrite {
+    /// Creates a rule serving `to` for routes matching `pattern`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `pattern` isn't a valid glob.
+    pub fn new(pattern: impl Into<String>, to: impl Into<String>) -> Result<Self, String> {
+        let pattern = pattern.into();
+        let matcher = Glob::new(pattern.trim_start_matches('/'))
+            .map_err(|err| format!("invalid rewrite pattern '{pattern}': {err}"))?
+            .compile_matcher();
+        Ok(Self {
+            pattern,
+            to: to.into(),
+            matcher,
+        })
+    }
+
+    /// Returns the glob routes are matched against.
+    #[inline(always)]
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Checks if the rule applies to `route`.
+    pub fn matches(&self, route: &str) -> bool {
+        self.matcher.is_match(route.trim_start_matches('/'))
+    }
+}
+
+/// Parses `PATTERN=PATH`, e.g. `app/**=app/index.html`.
+impl FromStr for Rewrite {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((pattern, to)) if !pattern.trim().is_empty() && !to.trim().is_empty() => {
+                Self::new(pattern.trim(), to.trim())
+            }
+            _ => Err(format!(
+                "invalid rewrite '{s}', expected PATTERN=PATH such as app/**=app/index.html"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Rewrite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.pattern, self.to)
+    }
+}
@@ -0,0 +1,74 @@
+use std::path::Path;
+
+/// Filename pattern for emitted assets that no rule matches.
+pub const DEFAULT_ASSET_PATTERN: &str = "assets/[name]-[hash][extname]";
+
+/// Places assets with one of `extensions` using `pattern`.
+#[derive(Debug, Clone)]
+pub struct AssetRule {
+    /// Lowercase extensions without the leading dot, e.g. `png`
+    pub extensions: Vec<String>,
+    /// Output pattern, supporting rolldown's `[name]`, `[hash]` and `[extname]`
+    pub pattern: String,
+}
+
+/// Decides where emitted assets land, both for dev URLs and build output.
+#[derive(Debug, Clone)]
+pub struct AssetPolicy {
+    /// Pattern used when no rule matches the asset
+    pub default_pattern: String,
+    /// Rules checked in order, the first one matching the extension wins
+    pub rules: Vec<AssetRule>,
+}
+
+impl AssetPolicy {
+    /// Creates a policy that places every asset using `default_pattern`.
+    pub fn new(default_pattern: impl Into<String>) -> Self {
+        Self {
+            default_pattern: default_pattern.into(),
+            rules: Vec::new(),
+        }
+    }
+
+    /// Returns a new `AssetPolicy` with an additional placement rule.
+    #[must_use]
+    pub fn with_rule(mut self, extensions: &[&str], pattern: impl Into<String>) -> Self {
+        self.rules.push(AssetRule {
+            extensions: extensions.iter().map(|e| e.to_ascii_lowercase()).collect(),
+            pattern: pattern.into(),
+        });
+        self
+    }
+
+    /// Returns the filename pattern for an asset named `name`.
+    pub fn pattern_for(&self, name: &str) -> &str {
+        let extension = Path::new(name)
+            .extension()
+            .and_then(|s| s.to_str())
+            .map(|s| s.to_ascii_lowercase());
+
+        extension
+            .and_then(|extension| {
+                self.rules
+                    .iter()
+                    .find(|rule| rule.extensions.contains(&extension))
+            })
+            .map(|rule| rule.pattern.as_str())
+            .unwrap_or(&self.default_pattern)
+    }
+}
+
+impl Default for AssetPolicy {
+    /// Images go to `img/`, fonts to `fonts/` and everything else to `assets/`.
+    fn default() -> Self {
+        Self::new(DEFAULT_ASSET_PATTERN)
+            .with_rule(
+                &["png", "jpg", "jpeg", "gif", "svg", "webp", "avif", "ico"],
+                "img/[name]-[hash][extname]",
+            )
+            .with_rule(
+                &["woff", "woff2", "ttf", "otf", "eot"],
+                "fonts/[name]-[hash][extname]",
+            )
+    }
+}
@@ -0,0 +1,107 @@
+use crate::server::errors::HttpResponse;
+use crate::server::restart::reject_remote;
+use crate::server::{OverlayFs, Server};
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, Response, StatusCode};
+use axum::response::IntoResponse;
+use log::debug;
+use palladin_shared::{PalladinError, PalladinResult};
+use std::path::{Component, Path as FsPath, PathBuf};
+use std::sync::Arc;
+
+/// Lists the root-relative URL paths of the files shadowed by an unsaved
+/// buffer.
+pub async fn list_buffers_handler(State(server): State<Arc<Server>>) -> impl IntoResponse {
+    Server::list_buffers_impl(server)
+}
+
+/// Shadows the file at the root-relative `path` with the request body until
+/// the buffer is discarded, e.g. on every keystroke of an editor extension.
+///
+/// Like restarting, only pages served by this server on a loopback address
+/// may change buffers, as they replace the code sent to every page.
+pub async fn write_buffer_handler(
+    State(server): State<Arc<Server>>,
+    headers: HeaderMap,
+    Path(path): Path<String>,
+    contents: String,
+) -> impl IntoResponse {
+    if let Some(rejected) = reject_remote(&server, &headers, "buffer update") {
+        return Ok(rejected);
+    }
+    Server::write_buffer_impl(server, path, contents)
+}
+
+/// Discards the buffer of `path`, e.g. once it was saved or closed, so the
+/// file is read from disk again.
+pub async fn discard_buffer_handler(
+    State(server): State<Arc<Server>>,
+    headers: HeaderMap,
+    Path(path): Path<String>,
+) -> impl IntoResponse {
+    if let Some(rejected) = reject_remote(&server, &headers, "buffer update") {
+        return Ok(rejected);
+    }
+    Server::discard_buffer_impl(server, path)
+}
+
+impl Server {
+    fn list_buffers_impl(server: Arc<Self>) -> PalladinResult<Response<String>> {
+        let paths: Vec<String> = server
+            .ctx
+            .buffers()
+            .map(|buffers| buffers.paths())
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|path| server.ctx.url_path(path))
+            .collect();
+        let body =
+            serde_json::to_string_pretty(&paths).map_err(|e| PalladinError::Build(e.into()))?;
+
+        Ok(HttpResponse::ok(body)
+            .content_type("application/json")
+            .cache_control("no-cache")
+            .finish())
+    }
+
+    fn write_buffer_impl(
+        server: Arc<Self>,
+        path: String,
+        contents: String,
+    ) -> PalladinResult<Response<String>> {
+        let (buffers, full_path) = server.buffer_path(&path)?;
+        debug!("buffer of {path} updated");
+        buffers.write(&full_path, contents);
+        Ok(HttpResponse::new(StatusCode::NO_CONTENT, "").finish())
+    }
+
+    fn discard_buffer_impl(server: Arc<Self>, path: String) -> PalladinResult<Response<String>> {
+        let (buffers, full_path) = server.buffer_path(&path)?;
+        if !buffers.remove(&full_path) {
+            return Err(PalladinError::FileNotFound(path));
+        }
+        debug!("buffer of {path} discarded");
+        Ok(HttpResponse::new(StatusCode::NO_CONTENT, "").finish())
+    }
+
+    /// Returns the buffers along with the full path of the root-relative
+    /// `path`, which must neither leave the root nor be excluded.
+    fn buffer_path(&self, path: &str) -> PalladinResult<(&Arc<OverlayFs>, PathBuf)> {
+        let not_found = || PalladinError::FileNotFound(path.to_string());
+        let buffers = self.ctx.buffers().ok_or_else(not_found)?;
+
+        let relative = FsPath::new(path.trim_start_matches('/'));
+        if !relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+        {
+            return Err(not_found());
+        }
+
+        let full_path = self.ctx.root().join(relative);
+        if self.ctx.is_excluded(&full_path) {
+            return Err(not_found());
+        }
+        Ok((buffers, full_path))
+    }
+}
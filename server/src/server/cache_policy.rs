@@ -0,0 +1,139 @@
+use globset::{Glob, GlobMatcher};
+use std::fmt;
+use std::str::FromStr;
+
+/// Sends `value` as the `Cache-Control` header of files whose path matches
+/// `pattern`.
+#[derive(Debug, Clone)]
+pub struct CacheRule {
+    /// Glob matched against the path without its leading slash, e.g.
+    /// `**/*.html` or `assets/**`, only set through [`CacheRule::new`] so it
+    /// stays in sync with the compiled matcher
+    pattern: String,
+    /// Header value, e.g. `public, max-age=3600`
+    pub value: String,
+    matcher: GlobMatcher,
+}
+
+impl CacheRule {
+    /// Creates a rule sending `value` for paths matching `pattern`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `pattern` isn't a valid glob.
+    pub fn new(pattern: impl Into<String>, value: impl Into<String>) -> Result<Self, String> {
+        let pattern = pattern.into();
+        let matcher = Glob::new(&pattern)
+            .map_err(|err| format!("invalid cache rule pattern '{pattern}': {err}"))?
+            .compile_matcher();
+        Ok(Self {
+            pattern,
+            value: value.into(),
+            matcher,
+        })
+    }
+
+    /// Returns the glob paths are matched against.
+    #[inline(always)]
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+}
+
+/// Parses `PATTERN=VALUE`, e.g. `assets/**=public, max-age=3600`.
+impl FromStr for CacheRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((pattern, value)) if !pattern.trim().is_empty() => {
+                Self::new(pattern.trim(), value.trim())
+            }
+            _ => Err(format!(
+                "invalid cache rule '{s}', expected PATTERN=VALUE such as **/*.html=no-cache"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for CacheRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.pattern, self.value)
+    }
+}
+
+/// Decides the `Cache-Control` header of the files the dev server serves.
+///
+/// Files no rule matches keep the header they get by default: hashed chunks
+/// are immutable and assets are revalidated.
+#[derive(Debug, Clone, Default)]
+pub struct CachePolicy {
+    /// Rules checked in order, the first one matching the path wins
+    pub rules: Vec<CacheRule>,
+}
+
+impl CachePolicy {
+    /// Returns a new `CachePolicy` with an additional rule.
+    #[must_use]
+    pub fn with_rule(mut self, rule: CacheRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Returns the header value of the file at `path`, `default` when no
+    /// rule matches it.
+    pub(crate) fn cache_control<'a>(
+        &'a self,
+        path: &str,
+        default: Option<&'a str>,
+    ) -> Option<&'a str> {
+        let path = path.trim_start_matches('/');
+        self.rules
+            .iter()
+            .find(|rule| rule.matcher.is_match(path))
+            .map(|rule| rule.value.as_str())
+            .or(default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rules_are_parsed_from_pattern_and_value() {
+        let rule: CacheRule = "assets/** = public, max-age=3600".parse().unwrap();
+        assert_eq!(rule.pattern(), "assets/**");
+        assert_eq!(rule.value, "public, max-age=3600");
+        assert_eq!(rule.to_string(), "assets/**=public, max-age=3600");
+
+        assert!("no-cache".parse::<CacheRule>().is_err());
+        assert!("=no-cache".parse::<CacheRule>().is_err());
+        assert!("assets/[=no-cache".parse::<CacheRule>().is_err());
+    }
+
+    #[test]
+    fn first_matching_rule_wins() {
+        let policy = CachePolicy::default()
+            .with_rule(CacheRule::new("**/*.html", "no-cache").unwrap())
+            .with_rule(CacheRule::new("**", "public, max-age=60").unwrap());
+
+        assert_eq!(policy.cache_control("/index.html", None), Some("no-cache"));
+        assert_eq!(
+            policy.cache_control("/assets/logo.png", None),
+            Some("public, max-age=60")
+        );
+    }
+
+    #[test]
+    fn unmatched_paths_keep_the_default() {
+        let policy =
+            CachePolicy::default().with_rule(CacheRule::new("assets/**", "no-store").unwrap());
+
+        assert_eq!(
+            policy.cache_control("/main.js", Some("no-cache")),
+            Some("no-cache")
+        );
+        assert_eq!(policy.cache_control("/main.js", None), None);
+    }
+}
@@ -0,0 +1,247 @@
+use axum::http::StatusCode;
+
+/// Name of the redirect rules written next to the build output.
+pub const REDIRECTS_FILE: &str = "_redirects";
+
+/// Status non-canonical URLs are redirected with, by the dev and preview
+/// servers and in the `_redirects` file alike.
+pub const REDIRECT_STATUS: StatusCode = StatusCode::PERMANENT_REDIRECT;
+
+/// Page answering the URLs rejected with [`NonCanonical::NotFound`] in the
+/// `_redirects` file.
+const NOT_FOUND_PAGE: &str = "404.html";
+
+/// Whether the canonical URL of a page ends with a slash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrailingSlash {
+    /// `/about/`
+    Always,
+    /// `/about`
+    Never,
+}
+
+palladin_shared::named_enum!(TrailingSlash, "trailing slash mode" {
+    Always => "always",
+    Never => "never",
+});
+
+/// How requests for a page under a non-canonical URL are answered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NonCanonical {
+    /// Redirect to the canonical URL
+    #[default]
+    Redirect,
+    /// Serve the page under every URL
+    Serve,
+    /// Answer with a 404
+    NotFound,
+}
+
+palladin_shared::named_enum!(NonCanonical, "non-canonical URL action" {
+    Redirect => "redirect",
+    Serve => "serve",
+    NotFound => "404",
+});
+
+/// Canonical form of page URLs, e.g. whether `/about`, `/about/` and
+/// `/about.html` all serve `about.html`.
+///
+/// The dev server, the preview server and the `_redirects` file of a build
+/// apply the same policy, so routing doesn't change between them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UrlPolicy {
+    pub trailing_slash: TrailingSlash,
+    pub non_canonical: NonCanonical,
+}
+
+/// How a request for a page URL is answered under a [`UrlPolicy`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Routing {
+    Serve,
+    /// Redirect to the canonical URL
+    Redirect(String),
+    NotFound,
+}
+
+impl UrlPolicy {
+    /// Creates a policy redirecting to URLs with or without a trailing slash.
+    pub fn new(trailing_slash: TrailingSlash) -> Self {
+        Self {
+            trailing_slash,
+            non_canonical: NonCanonical::default(),
+        }
+    }
+
+    /// Decides how a request for the URL `path` is answered. Paths of files
+    /// other than HTML pages are always served.
+    pub(crate) fn route(&self, path: &str) -> Routing {
+        let Some(route) = page_route(path) else {
+            return Routing::Serve;
+        };
+
+        let canonical = self.canonical(route);
+        if canonical == path {
+            return Routing::Serve;
+        }
+        match self.non_canonical {
+            NonCanonical::Redirect => Routing::Redirect(canonical),
+            NonCanonical::Serve => Routing::Serve,
+            NonCanonical::NotFound => Routing::NotFound,
+        }
+    }
+
+    /// Returns the canonical URL of `route`, the page path without `.html`,
+    /// `index.html` or slashes around it.
+    fn canonical(&self, route: &str) -> String {
+        match (route.is_empty(), self.trailing_slash) {
+            (true, _) => "/".to_string(),
+            (false, TrailingSlash::Always) => format!("/{route}/"),
+            (false, TrailingSlash::Never) => format!("/{route}"),
+        }
+    }
+
+    /// Returns the rules applying the policy to the built `pages`, given by
+    /// path relative to the build directory, in the `_redirects` format of
+    /// Netlify and Cloudflare Pages.
+    ///
+    /// Canonical URLs are rewritten to the page they serve, the other URLs of
+    /// a page are redirected, rewritten or answered with `404.html`. Without
+    /// a `404.html` page, rejected URLs are left to the host's own 404.
+    pub fn redirect_rules(&self, pages: &[String]) -> String {
+        let has_not_found_page = pages.iter().any(|page| page == NOT_FOUND_PAGE);
+        let mut rules = String::new();
+        for page in pages {
+            let Some(route) = page_route(page) else {
+                continue;
+            };
+            let file = format!("/{page}");
+            let canonical = self.canonical(route);
+            if canonical != file {
+                rules.push_str(&format!("{canonical} {file} 200\n"));
+            }
+
+            let urls = match route.is_empty() {
+                true => vec![format!("/{page}")],
+                false => vec![
+                    format!("/{route}"),
+                    format!("/{route}/"),
+                    format!("/{route}.html"),
+                    format!("/{route}/index.html"),
+                ],
+            };
+            for url in urls.into_iter().filter(|url| *url != canonical) {
+                let rule = match self.non_canonical {
+                    NonCanonical::Redirect => {
+                        format!("{url} {canonical} {}!", REDIRECT_STATUS.as_u16())
+                    }
+                    NonCanonical::Serve if url == file => continue,
+                    NonCanonical::Serve => format!("{url} {file} 200"),
+                    NonCanonical::NotFound if !has_not_found_page => continue,
+                    NonCanonical::NotFound => format!("{url} /{NOT_FOUND_PAGE} 404!"),
+                };
+                rules.push_str(&rule);
+                rules.push('\n');
+            }
+        }
+        rules
+    }
+}
+
+/// Returns the route of the page URL `path`, `None` when it names a file
+/// that isn't an HTML page.
+fn page_route(path: &str) -> Option<&str> {
+    let path = path.trim_start_matches('/');
+    let route = match path.strip_suffix(".html") {
+        Some(stem) => stem
+            .strip_suffix("index")
+            .filter(|dir| dir.is_empty() || dir.ends_with('/'))
+            .unwrap_or(stem),
+        None => {
+            let name = path.rsplit('/').next().unwrap_or(path);
+            if name.contains('.') {
+                return None;
+            }
+            path
+        }
+    };
+    Some(route.trim_end_matches('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn page_routes_drop_html_and_index() {
+        assert_eq!(page_route("/"), Some(""));
+        assert_eq!(page_route("/index.html"), Some(""));
+        assert_eq!(page_route("/about.html"), Some("about"));
+        assert_eq!(page_route("/about/"), Some("about"));
+        assert_eq!(page_route("/docs/index.html"), Some("docs"));
+        assert_eq!(page_route("/assets/logo.svg"), None);
+    }
+
+    #[test]
+    fn non_canonical_urls_are_redirected() {
+        let policy = UrlPolicy::new(TrailingSlash::Never);
+        assert_eq!(policy.route("/about"), Routing::Serve);
+        assert_eq!(
+            policy.route("/about/"),
+            Routing::Redirect("/about".to_string())
+        );
+        assert_eq!(
+            policy.route("/about.html"),
+            Routing::Redirect("/about".to_string())
+        );
+        assert_eq!(policy.route("/main.js"), Routing::Serve);
+
+        let policy = UrlPolicy::new(TrailingSlash::Always);
+        assert_eq!(policy.route("/about/"), Routing::Serve);
+        assert_eq!(
+            policy.route("/about"),
+            Routing::Redirect("/about/".to_string())
+        );
+    }
+
+    #[test]
+    fn non_canonical_urls_can_be_served_or_rejected() {
+        let mut policy = UrlPolicy::new(TrailingSlash::Never);
+        policy.non_canonical = NonCanonical::Serve;
+        assert_eq!(policy.route("/about/"), Routing::Serve);
+
+        policy.non_canonical = NonCanonical::NotFound;
+        assert_eq!(policy.route("/about/"), Routing::NotFound);
+    }
+
+    #[test]
+    fn redirect_rules_rewrite_canonical_urls_and_redirect_the_rest() {
+        let policy = UrlPolicy::new(TrailingSlash::Never);
+        let rules = policy.redirect_rules(&["about.html".to_string()]);
+        assert_eq!(
+            rules,
+            "/about /about.html 200\n\
+             /about/ /about 308!\n\
+             /about.html /about 308!\n\
+             /about/index.html /about 308!\n"
+        );
+    }
+
+    #[test]
+    fn rejected_urls_are_only_pointed_at_an_existing_404_page() {
+        let mut policy = UrlPolicy::new(TrailingSlash::Never);
+        policy.non_canonical = NonCanonical::NotFound;
+        let rules = policy.redirect_rules(&["about.html".to_string()]);
+        assert_eq!(rules, "/about /about.html 200\n");
+
+        let rules = policy.redirect_rules(&["about.html".to_string(), "404.html".to_string()]);
+        assert!(rules.contains("/about/ /404.html 404!\n"));
+    }
+
+    #[test]
+    fn modes_are_parsed_by_name() {
+        assert_eq!("Always".parse(), Ok(TrailingSlash::Always));
+        assert_eq!("404".parse(), Ok(NonCanonical::NotFound));
+        assert!("sometimes".parse::<TrailingSlash>().is_err());
+        assert_eq!(NonCanonical::Serve.to_string(), "serve");
+    }
+}
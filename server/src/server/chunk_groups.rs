@@ -0,0 +1,104 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// Splits the modules whose id matches `test` into a chunk of their own.
+///
+/// Without any configured groups, vendor code is split into a `vendor` chunk
+/// and a chunk for the framework's packages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkGroup {
+    /// Name of the emitted chunk, e.g. `charts`
+    pub name: String,
+    /// Regex matched against module ids, e.g. `node_modules[\\/]echarts`
+    pub test: String,
+    /// Size in bytes the group has to reach to be split out
+    pub min_size: Option<u64>,
+    /// Groups with a higher priority claim shared modules first
+    pub priority: u32,
+}
+
+impl ChunkGroup {
+    /// Creates a group splitting out the modules matching `test`.
+    pub fn new(name: impl Into<String>, test: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            test: test.into(),
+            min_size: None,
+            priority: 0,
+        }
+    }
+}
+
+/// Parses `NAME=REGEX`, optionally with a priority and minimum size, e.g.
+/// `charts:20:50000=node_modules/echarts`.
+impl FromStr for ChunkGroup {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid =
+            || format!("invalid chunk group '{s}', expected NAME[:PRIORITY[:MIN_SIZE]]=REGEX");
+        let (head, test) = s.split_once('=').ok_or_else(invalid)?;
+        let mut parts = head.split(':');
+
+        let name = parts.next().unwrap_or_default().trim();
+        if name.is_empty() || test.is_empty() {
+            return Err(invalid());
+        }
+        let mut group = Self::new(name, test);
+        if let Some(priority) = parts.next() {
+            group.priority = priority.trim().parse().map_err(|_| invalid())?;
+        }
+        if let Some(min_size) = parts.next() {
+            group.min_size = Some(min_size.trim().parse().map_err(|_| invalid())?);
+        }
+        match parts.next() {
+            Some(_) => Err(invalid()),
+            None => Ok(group),
+        }
+    }
+}
+
+impl fmt::Display for ChunkGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.name, self.priority)?;
+        if let Some(min_size) = self.min_size {
+            write!(f, ":{min_size}")?;
+        }
+        write!(f, "={}", self.test)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_are_parsed_with_optional_priority_and_size() {
+        let group: ChunkGroup = "charts=node_modules/echarts".parse().unwrap();
+        assert_eq!(group, ChunkGroup::new("charts", "node_modules/echarts"));
+
+        let group: ChunkGroup = "charts:20:50000=node_modules/echarts".parse().unwrap();
+        assert_eq!(group.priority, 20);
+        assert_eq!(group.min_size, Some(50000));
+    }
+
+    #[test]
+    fn invalid_groups_are_rejected() {
+        for s in [
+            "charts",
+            "=node_modules",
+            "charts=",
+            "charts:high=node_modules",
+            "charts:1:2:3=node_modules",
+        ] {
+            assert!(s.parse::<ChunkGroup>().is_err(), "{s} was accepted");
+        }
+    }
+
+    #[test]
+    fn groups_are_displayed_as_parsed() {
+        let group: ChunkGroup = "charts:20:50000=node_modules/echarts".parse().unwrap();
+        assert_eq!(group.to_string(), "charts:20:50000=node_modules/echarts");
+        assert_eq!(ChunkGroup::new("ui", "ui").to_string(), "ui:0=ui");
+    }
+}
@@ -1,4 +1,15 @@
+use super::{
+    AppType, AssetPolicy, AutoInstall, CachePolicy, ChunkAddon, ChunkGroup,
+    DEFAULT_ASSET_INLINE_LIMIT, DEFAULT_BUILD_HISTORY, DEFAULT_CHUNK_FILENAMES,
+    DEFAULT_CSS_MODULES_PATTERN, DEFAULT_ENTRY_FILENAMES, Entrypoint, ErrorPages, Framework,
+    HarOptions, HmrPolicy, HtmlMeta, HttpOptions, Inject, Mdx, Minify, Mocks, NoIndex,
+    PalladinPlugin, PostCss, RealFs, ResourceHint, Rewrite, SourceEntry, Svelte, Tailwind,
+    TreeshakeConfig, UrlPolicy, Vfs, WatchOptions,
+};
+use axum::http::HeaderMap;
+use std::net::IpAddr;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 /// Configuration for the server, including host and port settings.
 #[derive(Debug, Clone)]
@@ -9,10 +20,123 @@ pub struct ServerConfig {
     pub port: u16,
     /// The root directory from which files will be served.
     pub root: PathBuf,
+    /// The URL path the app is served under, `/` unless mounted under a
+    /// prefix, e.g. `/branch-a/`.
+    pub base: String,
     /// The output directory for bundled files (relative to root).
     pub build_dir: PathBuf,
-    /// The entrypoint file to the bundle (e.g., "src/index.tsx")
-    pub entrypoint: PathBuf,
+    /// The entrypoints of the bundle (e.g., "src/index.tsx"), the first one
+    /// being the primary entry served by `index.html`.
+    pub entrypoints: Vec<Entrypoint>,
+    /// Additional `host:port` addresses served alongside `host` and `port`.
+    pub listen: Vec<String>,
+    /// The framework preset to use, detected from package.json when `None`.
+    pub framework: Option<Framework>,
+    /// Where emitted assets are placed, relative to the build directory.
+    pub assets: AssetPolicy,
+    /// Size in bytes below which imported assets are inlined as `data:` URLs.
+    pub asset_inline_limit: usize,
+    /// `Cache-Control` headers of served files by path.
+    pub cache_policy: CachePolicy,
+    /// How routes without a file are answered.
+    pub app_type: AppType,
+    /// Files served for matching routes, checked before the app type.
+    pub rewrites: Vec<Rewrite>,
+    /// Lists the files of directories without an `index.html` instead of
+    /// treating them as routes.
+    pub directory_listing: bool,
+    /// HTML pages answering failed page loads instead of plain text errors.
+    pub error_pages: ErrorPages,
+    /// Headers added to every response, e.g. `Cross-Origin-Opener-Policy`
+    /// and `Cross-Origin-Embedder-Policy` to enable `SharedArrayBuffer`.
+    pub headers: HeaderMap,
+    /// Records all requests into a HAR file when set.
+    pub har: Option<HarOptions>,
+    /// Settings of outbound HTTP requests, e.g. deploy uploads.
+    pub http: HttpOptions,
+    /// Controls which file changes are picked up by the watcher.
+    pub watch: WatchOptions,
+    /// How pages are updated when files the bundler doesn't handle change.
+    pub hmr: HmrPolicy,
+    /// Tree shaking options passed to the bundler.
+    pub treeshake: TreeshakeConfig,
+    /// Rules splitting modules into chunks of their own, the built-in vendor
+    /// groups when empty.
+    pub chunk_groups: Vec<ChunkGroup>,
+    /// Filename pattern of entry chunks, supporting rolldown's `[name]` and
+    /// `[hash]`. Asset filenames are decided by [`AssetPolicy`].
+    pub entry_filenames: String,
+    /// Filename pattern of shared and lazily loaded chunks.
+    pub chunk_filenames: String,
+    /// Plugins run by the bundler and the dev server, in registration order.
+    pub plugins: Vec<Arc<dyn PalladinPlugin>>,
+    /// Turns warnings about code that breaks on other platforms into errors.
+    pub strict: bool,
+    /// Installs packages reported missing with the project's package manager.
+    pub auto_install: AutoInstall,
+    /// How much the bundle output is minified.
+    pub minify: Minify,
+    /// Syntax lowering targets (e.g., "es2017", "chrome80"), read from the
+    /// project's browserslist when empty.
+    pub target: Vec<String>,
+    /// Code prepended to every emitted JavaScript chunk, e.g. a license
+    /// comment.
+    pub banner: Option<String>,
+    /// Code appended to every emitted JavaScript chunk.
+    pub footer: Option<String>,
+    /// Banners replacing `banner` for the chunks they match, first match wins.
+    pub chunk_banners: Vec<ChunkAddon>,
+    /// Footers replacing `footer` for the chunks they match, first match wins.
+    pub chunk_footers: Vec<ChunkAddon>,
+    /// Imports added to modules using an identifier without declaring it.
+    pub inject: Vec<Inject>,
+    /// External origins every HTML page preconnects to.
+    pub resource_hints: Vec<ResourceHint>,
+    /// Language, title suffix and `<meta>` tags set on every HTML page.
+    pub html_meta: HtmlMeta,
+    /// Canonical form of page URLs, every form is served when `None`.
+    pub url_policy: Option<UrlPolicy>,
+    /// Server entry bundled for SSR and served at
+    /// [`SSR_MODULE_PATH`](super::SSR_MODULE_PATH), relative to the root.
+    pub ssr_entry: Option<PathBuf>,
+    /// Canned API responses the dev server answers with when set.
+    pub mocks: Option<Arc<Mocks>>,
+    /// The filesystem project files are read from.
+    pub vfs: Arc<dyn Vfs>,
+    /// Runs stylesheets through the project's PostCSS config when set.
+    pub postcss: Option<Arc<PostCss>>,
+    /// Generates the CSS of stylesheets using Tailwind directives when set.
+    pub tailwind: Option<Arc<Tailwind>>,
+    /// Compiles imported `.svelte` components when set.
+    pub svelte: Option<Arc<Svelte>>,
+    /// Compiles imported `.mdx` documents when set.
+    pub mdx: Option<Arc<Mdx>>,
+    /// Pattern the scoped class names of `*.module.css` files are built
+    /// from, with `[name]`, `[local]` and `[hash]` placeholders.
+    pub css_modules_pattern: String,
+    /// Number of builds the dev server keeps to roll back to.
+    pub build_history: usize,
+    /// Accepts unsaved editor buffers at `/__buffers` and serves them over
+    /// the files on disk.
+    pub hot_typing: bool,
+    /// Answers the probes browsers make on their own, e.g. for a missing
+    /// `/favicon.ico`, quietly instead of with a 404.
+    pub probe_routes: bool,
+    /// When `/robots.txt` denies crawlers and responses carry
+    /// `X-Robots-Tag: noindex`.
+    pub noindex: NoIndex,
+    /// Resolves imports of workspace packages to their sources instead of
+    /// their build output, watching the sources for changes.
+    pub workspace_sources: bool,
+    /// Source entries of workspace packages, overriding the `source` field
+    /// of their `package.json` and the conventional `src/index.*`.
+    pub source_entries: Vec<SourceEntry>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ServerConfig {
@@ -23,8 +147,54 @@ impl ServerConfig {
             host: "127.0.0.1".to_string(),
             port: 8080,
             root: PathBuf::from("."),
+            base: "/".to_string(),
             build_dir: PathBuf::from("dist"),
-            entrypoint: PathBuf::from("src/index.tsx"),
+            entrypoints: vec![Entrypoint::from(PathBuf::from("src/index.tsx"))],
+            listen: Vec::new(),
+            framework: None,
+            assets: AssetPolicy::default(),
+            asset_inline_limit: DEFAULT_ASSET_INLINE_LIMIT,
+            cache_policy: CachePolicy::default(),
+            app_type: AppType::Spa,
+            rewrites: Vec::new(),
+            directory_listing: false,
+            error_pages: ErrorPages::default(),
+            headers: HeaderMap::new(),
+            har: None,
+            http: HttpOptions::default(),
+            watch: WatchOptions::default(),
+            hmr: HmrPolicy::default(),
+            treeshake: TreeshakeConfig::default(),
+            chunk_groups: Vec::new(),
+            entry_filenames: DEFAULT_ENTRY_FILENAMES.to_string(),
+            chunk_filenames: DEFAULT_CHUNK_FILENAMES.to_string(),
+            plugins: Vec::new(),
+            strict: false,
+            auto_install: AutoInstall::default(),
+            minify: Minify::default(),
+            target: Vec::new(),
+            banner: None,
+            footer: None,
+            chunk_banners: Vec::new(),
+            chunk_footers: Vec::new(),
+            inject: Vec::new(),
+            resource_hints: Vec::new(),
+            html_meta: HtmlMeta::default(),
+            url_policy: None,
+            ssr_entry: None,
+            mocks: None,
+            vfs: Arc::new(RealFs),
+            postcss: None,
+            tailwind: None,
+            svelte: None,
+            mdx: None,
+            css_modules_pattern: DEFAULT_CSS_MODULES_PATTERN.to_string(),
+            build_history: DEFAULT_BUILD_HISTORY,
+            hot_typing: false,
+            probe_routes: true,
+            noindex: NoIndex::Auto,
+            workspace_sources: false,
+            source_entries: Vec::new(),
         }
     }
 
@@ -52,10 +222,10 @@ impl ServerConfig {
         &self.build_dir
     }
 
-    /// Returns a reference to the entrypoint.
+    /// Returns the configured entrypoints.
     #[inline(always)]
-    pub fn entrypoint(&self) -> &PathBuf {
-        &self.entrypoint
+    pub fn entrypoints(&self) -> &[Entrypoint] {
+        &self.entrypoints
     }
 
     /// Returns a new `ServerConfig` with the specified port.
@@ -82,6 +252,14 @@ impl ServerConfig {
         self
     }
 
+    /// Returns a new `ServerConfig` with the specified base path.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_base(mut self, base: String) -> Self {
+        self.base = base;
+        self
+    }
+
     /// Returns a new `ServerConfig` with the specified build directory.
     #[must_use]
     #[inline(always)]
@@ -90,16 +268,435 @@ impl ServerConfig {
         self
     }
 
-    /// Returns a new `ServerConfig` with the specified entrypoint.
+    /// Returns a new `ServerConfig` with the specified entrypoint as the only one.
     #[must_use]
     #[inline(always)]
     pub fn with_entrypoint(mut self, entrypoint: PathBuf) -> Self {
-        self.entrypoint = entrypoint;
+        self.entrypoints = vec![Entrypoint::from(entrypoint)];
+        self
+    }
+
+    /// Returns a new `ServerConfig` with the specified entrypoints.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_entrypoints(mut self, entrypoints: Vec<Entrypoint>) -> Self {
+        self.entrypoints = entrypoints;
+        self
+    }
+
+    /// Returns a new `ServerConfig` with the specified additional listen addresses.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_listen(mut self, listen: Vec<String>) -> Self {
+        self.listen = listen;
+        self
+    }
+
+    /// Returns a new `ServerConfig` with the specified framework preset.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_framework(mut self, framework: Option<Framework>) -> Self {
+        self.framework = framework;
+        self
+    }
+
+    /// Returns a new `ServerConfig` with the specified asset placement policy.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_assets(mut self, assets: AssetPolicy) -> Self {
+        self.assets = assets;
+        self
+    }
+
+    /// Returns a new `ServerConfig` with the specified cache headers policy.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_cache_policy(mut self, cache_policy: CachePolicy) -> Self {
+        self.cache_policy = cache_policy;
+        self
+    }
+
+    /// Returns a new `ServerConfig` with the specified app type.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_app_type(mut self, app_type: AppType) -> Self {
+        self.app_type = app_type;
+        self
+    }
+
+    /// Returns a new `ServerConfig` with the specified route rewrites.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_rewrites(mut self, rewrites: Vec<Rewrite>) -> Self {
+        self.rewrites = rewrites;
+        self
+    }
+
+    /// Returns a new `ServerConfig` with directory listings enabled or
+    /// disabled.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_directory_listing(mut self, directory_listing: bool) -> Self {
+        self.directory_listing = directory_listing;
+        self
+    }
+
+    /// Returns a new `ServerConfig` with the specified error pages.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_error_pages(mut self, error_pages: ErrorPages) -> Self {
+        self.error_pages = error_pages;
+        self
+    }
+
+    /// Returns a new `ServerConfig` with the specified headers added to
+    /// every response.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_headers(mut self, headers: HeaderMap) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    /// Returns a new `ServerConfig` inlining imported assets below the
+    /// specified size in bytes, `0` to never inline them.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_asset_inline_limit(mut self, asset_inline_limit: usize) -> Self {
+        self.asset_inline_limit = asset_inline_limit;
+        self
+    }
+
+    /// Returns a new `ServerConfig` with the specified HAR recording options.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_har(mut self, har: Option<HarOptions>) -> Self {
+        self.har = har;
+        self
+    }
+
+    /// Returns a new `ServerConfig` with the specified outbound HTTP settings.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_http(mut self, http: HttpOptions) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// Returns a new `ServerConfig` with the specified watcher options.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_watch(mut self, watch: WatchOptions) -> Self {
+        self.watch = watch;
+        self
+    }
+
+    /// Returns a new `ServerConfig` with the specified HMR policy.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_hmr_policy(mut self, hmr: HmrPolicy) -> Self {
+        self.hmr = hmr;
+        self
+    }
+
+    /// Returns a new `ServerConfig` with the specified tree shaking options.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_treeshake(mut self, treeshake: TreeshakeConfig) -> Self {
+        self.treeshake = treeshake;
+        self
+    }
+
+    /// Returns a new `ServerConfig` with the specified chunk groups.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_chunk_groups(mut self, chunk_groups: Vec<ChunkGroup>) -> Self {
+        self.chunk_groups = chunk_groups;
+        self
+    }
+
+    /// Returns a new `ServerConfig` with the specified filename pattern for
+    /// entry chunks.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_entry_filenames(mut self, pattern: String) -> Self {
+        self.entry_filenames = pattern;
+        self
+    }
+
+    /// Returns a new `ServerConfig` with the specified filename pattern for
+    /// shared and lazily loaded chunks.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_chunk_filenames(mut self, pattern: String) -> Self {
+        self.chunk_filenames = pattern;
+        self
+    }
+
+    /// Returns a new `ServerConfig` placing every emitted asset using
+    /// `pattern`, replacing the per-extension rules of the asset policy.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_asset_filenames(mut self, pattern: String) -> Self {
+        self.assets = AssetPolicy::new(pattern);
+        self
+    }
+
+    /// Returns a new `ServerConfig` with the specified plugin added.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_plugin(mut self, plugin: impl PalladinPlugin) -> Self {
+        self.plugins.push(Arc::new(plugin));
+        self
+    }
+
+    /// Returns a new `ServerConfig` with strict checks enabled or disabled.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Returns a new `ServerConfig` with the specified auto-install policy.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_auto_install(mut self, auto_install: AutoInstall) -> Self {
+        self.auto_install = auto_install;
+        self
+    }
+
+    /// Returns a new `ServerConfig` with the specified minification.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_minify(mut self, minify: Minify) -> Self {
+        self.minify = minify;
+        self
+    }
+
+    /// Returns a new `ServerConfig` with the specified code prepended to
+    /// emitted chunks.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_banner(mut self, banner: Option<String>) -> Self {
+        self.banner = banner;
+        self
+    }
+
+    /// Returns a new `ServerConfig` with the specified code appended to
+    /// emitted chunks.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_footer(mut self, footer: Option<String>) -> Self {
+        self.footer = footer;
+        self
+    }
+
+    /// Returns a new `ServerConfig` with the specified banners of the chunks
+    /// they match.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_chunk_banners(mut self, chunk_banners: Vec<ChunkAddon>) -> Self {
+        self.chunk_banners = chunk_banners;
+        self
+    }
+
+    /// Returns a new `ServerConfig` with the specified footers of the chunks
+    /// they match.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_chunk_footers(mut self, chunk_footers: Vec<ChunkAddon>) -> Self {
+        self.chunk_footers = chunk_footers;
+        self
+    }
+
+    /// Returns a new `ServerConfig` with the specified automatic imports.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_inject(mut self, inject: Vec<Inject>) -> Self {
+        self.inject = inject;
+        self
+    }
+
+    /// Returns a new `ServerConfig` with the specified external origins to
+    /// preconnect to.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_resource_hints(mut self, resource_hints: Vec<ResourceHint>) -> Self {
+        self.resource_hints = resource_hints;
+        self
+    }
+
+    /// Returns a new `ServerConfig` with the specified metadata of HTML pages.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_html_meta(mut self, html_meta: HtmlMeta) -> Self {
+        self.html_meta = html_meta;
+        self
+    }
+
+    /// Returns a new `ServerConfig` with the specified canonical form of page
+    /// URLs.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_url_policy(mut self, url_policy: Option<UrlPolicy>) -> Self {
+        self.url_policy = url_policy;
+        self
+    }
+
+    /// Returns a new `ServerConfig` with the specified server entry to bundle
+    /// for SSR.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_ssr_entry(mut self, ssr_entry: Option<PathBuf>) -> Self {
+        self.ssr_entry = ssr_entry;
+        self
+    }
+
+    /// Returns a new `ServerConfig` with the specified API mocks.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_mocks(mut self, mocks: Mocks) -> Self {
+        self.mocks = Some(Arc::new(mocks));
+        self
+    }
+
+    /// Returns a new `ServerConfig` with the specified transpile targets.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_target(mut self, target: Vec<String>) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Returns a new `ServerConfig` with the specified filesystem.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_vfs(mut self, vfs: Arc<dyn Vfs>) -> Self {
+        self.vfs = vfs;
+        self
+    }
+
+    /// Returns a new `ServerConfig` with the specified PostCSS pipeline.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_postcss(mut self, postcss: PostCss) -> Self {
+        self.postcss = Some(Arc::new(postcss));
+        self
+    }
+
+    /// Returns a new `ServerConfig` with the specified Tailwind generator.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_tailwind(mut self, tailwind: Tailwind) -> Self {
+        self.tailwind = Some(Arc::new(tailwind));
+        self
+    }
+
+    /// Returns a new `ServerConfig` with the specified Svelte compiler.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_svelte(mut self, svelte: Svelte) -> Self {
+        self.svelte = Some(Arc::new(svelte));
+        self
+    }
+
+    /// Returns a new `ServerConfig` with the specified MDX compiler.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_mdx(mut self, mdx: Mdx) -> Self {
+        self.mdx = Some(Arc::new(mdx));
+        self
+    }
+
+    /// Returns a new `ServerConfig` with the specified CSS modules class name
+    /// pattern.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_css_modules_pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.css_modules_pattern = pattern.into();
+        self
+    }
+
+    /// Returns a new `ServerConfig` keeping the specified number of builds.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_build_history(mut self, build_history: usize) -> Self {
+        self.build_history = build_history;
+        self
+    }
+
+    /// Returns a new `ServerConfig` with unsaved editor buffers accepted or not.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_hot_typing(mut self, hot_typing: bool) -> Self {
+        self.hot_typing = hot_typing;
+        self
+    }
+
+    /// Returns a new `ServerConfig` with browser probes answered quietly or not.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_probe_routes(mut self, probe_routes: bool) -> Self {
+        self.probe_routes = probe_routes;
+        self
+    }
+
+    /// Returns a new `ServerConfig` with the specified policy for asking
+    /// crawlers not to index the server.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_noindex(mut self, noindex: NoIndex) -> Self {
+        self.noindex = noindex;
+        self
+    }
+
+    /// Returns a new `ServerConfig` with workspace packages resolved to their
+    /// sources enabled or disabled.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_workspace_sources(mut self, workspace_sources: bool) -> Self {
+        self.workspace_sources = workspace_sources;
+        self
+    }
+
+    /// Returns a new `ServerConfig` with the specified source entries of
+    /// workspace packages.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_source_entries(mut self, source_entries: Vec<SourceEntry>) -> Self {
+        self.source_entries = source_entries;
         self
     }
 
     /// Returns the full address in the format `host:port`.
+    /// IPv6 hosts are bracketed, e.g. `[::1]:8080`.
     pub fn address(&self) -> String {
-        format!("{}:{}", self.host, self.port)
+        format_address(&self.host, self.port)
+    }
+
+    /// Returns every address the server binds to, starting with the primary one.
+    pub fn addresses(&self) -> Vec<String> {
+        let mut addresses = vec![self.address()];
+        for address in &self.listen {
+            if !addresses.contains(address) {
+                addresses.push(address.clone());
+            }
+        }
+        addresses
+    }
+}
+
+/// Formats a host and port as a socket address, bracketing IPv6 literals.
+/// Hosts that are already bracketed are accepted as-is.
+fn format_address(host: &str, port: u16) -> String {
+    let unbracketed = host
+        .strip_prefix('[')
+        .and_then(|h| h.strip_suffix(']'))
+        .unwrap_or(host);
+
+    match unbracketed.parse::<IpAddr>() {
+        Ok(IpAddr::V6(ip)) => format!("[{ip}]:{port}"),
+        _ => format!("{unbracketed}:{port}"),
     }
 }
@@ -1,9 +1,14 @@
 use std::path::{Path, PathBuf};
 
-use super::ServerConfig;
-use fs_err::create_dir_all;
+use super::{
+    Entrypoint, Framework, ModuleDependencies, OverlayFs, ServerConfig, Vfs, browserslist_targets,
+};
+use crate::messages::Message;
+use crate::rolldown::{MissingPackages, ModuleImports};
+use log::info;
 use palladin_shared::PalladinError::FileNotFound;
-use palladin_shared::{canonicalize_with_strip, PalladinResult};
+use palladin_shared::PalladinResult;
+use std::sync::Arc;
 
 /// Context holds all the application-wide data including configuration,
 /// canonicalized paths, and runtime state.
@@ -17,6 +22,20 @@ pub struct Context {
     build_dir: PathBuf,
     /// The path to the tsconfig.json file, if it exists
     tsconfig_path: Option<PathBuf>,
+    /// The framework preset in use, either configured or detected
+    framework: Framework,
+    /// The transpile targets, either configured or read from browserslist
+    target: Vec<String>,
+    /// Unsaved editor buffers shadowing the project files, when hot typing
+    buffers: Option<Arc<OverlayFs>>,
+    /// The base path, starting and ending with `/`
+    base: String,
+    /// Extra dependencies plugins declared for modules
+    module_dependencies: Arc<ModuleDependencies>,
+    /// Packages found missing while bundling, installed between builds
+    missing_packages: Arc<MissingPackages>,
+    /// Imports of the modules parsed by the dev engine
+    module_imports: Arc<ModuleImports>,
 }
 
 impl Context {
@@ -27,30 +46,75 @@ impl Context {
     ///
     /// Returns an error if the root path cannot be canonicalized.
     pub fn new(mut config: ServerConfig) -> PalladinResult<Self> {
-        let root = canonicalize_with_strip(&config.root)
+        let buffers = config.hot_typing.then(|| {
+            let buffers = Arc::new(OverlayFs::new(config.vfs.clone()));
+            config.vfs = buffers.clone();
+            buffers
+        });
+        let vfs = config.vfs.clone();
+        let root = vfs
+            .canonicalize(&config.root)
             .map_err(|_| FileNotFound(config.root.to_string_lossy().to_string()))?;
 
         let build_dir_path = root.join(&config.build_dir);
-        if !build_dir_path.exists() {
-            create_dir_all(&build_dir_path)
+        if !vfs.exists(&build_dir_path) {
+            vfs.create_dir_all(&build_dir_path)
                 .map_err(|e| FileNotFound(format!("Failed to create build dir: {}", e)))?;
         }
-        let build_dir = canonicalize_with_strip(&build_dir_path)
+        let build_dir = vfs
+            .canonicalize(&build_dir_path)
             .map_err(|_| FileNotFound(build_dir_path.to_string_lossy().to_string()))?;
 
         let tsconfig_path = {
             let path = root.join("tsconfig.json");
-            if path.exists() { Some(path) } else { None }
+            if vfs.exists(&path) { Some(path) } else { None }
         };
 
-        let entrypoint_path = canonicalize_with_strip(&config.entrypoint)?;
-        config.entrypoint = entrypoint_path;
+        if config.entrypoints.is_empty() {
+            return Err(FileNotFound("no entrypoint configured".to_string()));
+        }
+        for entrypoint in &mut config.entrypoints {
+            entrypoint.path = vfs.canonicalize(&entrypoint.path)?;
+        }
+
+        let framework = match config.framework {
+            Some(framework) => framework,
+            None => {
+                let framework = Framework::detect(&root);
+                if framework != Framework::Vanilla {
+                    info!("{}", Message::DetectedFramework { framework });
+                }
+                framework
+            }
+        };
+
+        let target = if config.target.is_empty() {
+            let target = browserslist_targets(&root);
+            if !target.is_empty() {
+                info!("{}", Message::BrowserslistTargets { targets: &target });
+            }
+            target
+        } else {
+            config.target.clone()
+        };
+
+        let base = match config.base.trim_matches('/') {
+            "" => "/".to_string(),
+            base => format!("/{base}/"),
+        };
 
         Ok(Self {
             config,
             root,
             build_dir,
             tsconfig_path,
+            framework,
+            target,
+            buffers,
+            base,
+            module_dependencies: Arc::default(),
+            missing_packages: Arc::default(),
+            module_imports: Arc::default(),
         })
     }
 
@@ -84,10 +148,21 @@ impl Context {
         self.config.port()
     }
 
-    /// Returns the entrypoint path.
+    /// Returns the path of the primary entrypoint.
     #[inline(always)]
     pub fn entrypoint(&self) -> &PathBuf {
-        &self.config.entrypoint
+        &self.config.entrypoints[0].path
+    }
+
+    /// Returns every entrypoint, with canonicalized paths.
+    #[inline(always)]
+    pub fn entrypoints(&self) -> &[Entrypoint] {
+        &self.config.entrypoints
+    }
+
+    /// Checks if `path` is one of the entrypoints.
+    pub fn is_entrypoint(&self, path: &Path) -> bool {
+        self.entrypoints().iter().any(|entry| entry.path == path)
     }
 
     /// Returns the full address in the format `host:port`.
@@ -96,6 +171,56 @@ impl Context {
         self.config.address()
     }
 
+    /// Returns every address the server binds to.
+    #[inline(always)]
+    pub fn addresses(&self) -> Vec<String> {
+        self.config.addresses()
+    }
+
+    /// Returns the framework preset in use.
+    #[inline(always)]
+    pub fn framework(&self) -> Framework {
+        self.framework
+    }
+
+    /// Returns the transpile targets, empty when the bundler's default is used.
+    #[inline(always)]
+    pub fn target(&self) -> &[String] {
+        &self.target
+    }
+
+    /// Returns the filesystem project files are read from.
+    #[inline(always)]
+    pub fn vfs(&self) -> &Arc<dyn Vfs> {
+        &self.config.vfs
+    }
+
+    /// Returns the unsaved editor buffers if hot typing is enabled.
+    #[inline(always)]
+    pub fn buffers(&self) -> Option<&Arc<OverlayFs>> {
+        self.buffers.as_ref()
+    }
+
+    /// Returns the extra dependencies plugins declared for modules, e.g. to
+    /// invalidate the modules generated from a changed env variable.
+    #[inline(always)]
+    pub fn module_dependencies(&self) -> &Arc<ModuleDependencies> {
+        &self.module_dependencies
+    }
+
+    /// Returns the packages imports were found missing for, waiting to be
+    /// installed once the build finished.
+    #[inline(always)]
+    pub(crate) fn missing_packages(&self) -> &Arc<MissingPackages> {
+        &self.missing_packages
+    }
+
+    /// Returns the imports of the modules parsed while bundling.
+    #[inline(always)]
+    pub(crate) fn module_imports(&self) -> &Arc<ModuleImports> {
+        &self.module_imports
+    }
+
     /// Returns a reference to the tsconfig.json path if it exists.
     #[inline(always)]
     pub fn tsconfig_path(&self) -> Option<&PathBuf> {
@@ -109,12 +234,45 @@ impl Context {
     /// Returns an error if the path cannot be canonicalized.
     pub fn resolve_path<P: AsRef<Path>>(&self, path: P) -> PalladinResult<PathBuf> {
         let full_path = self.root.join(path);
-        canonicalize_with_strip(&full_path)
+        self.vfs()
+            .canonicalize(&full_path)
             .map_err(|_| FileNotFound(full_path.to_string_lossy().to_string()))
     }
 
+    /// Returns the root-relative URL path (e.g. `/src/main.tsx`) for a file
+    /// inside the root directory.
+    pub fn url_path(&self, path: &Path) -> Option<String> {
+        let relative = path.strip_prefix(&self.root).ok()?;
+        Some(format!(
+            "/{}",
+            relative.to_string_lossy().replace('\\', "/")
+        ))
+    }
+
+    /// Returns the URL path the app is served under, e.g. `/` or `/app/`.
+    #[inline(always)]
+    pub fn base(&self) -> &str {
+        &self.base
+    }
+
+    /// Returns the root-relative URL path `path` (e.g. `/src/main.tsx`)
+    /// prefixed with the base path.
+    pub fn base_url(&self, path: &str) -> String {
+        format!("{}{path}", self.base.trim_end_matches('/'))
+    }
+
     /// Checks if a path is within the root directory (prevents directory traversal).
     pub fn is_within_root(&self, path: &Path) -> bool {
         path.starts_with(&self.root)
     }
+
+    /// Checks if a path is inside one of the subtrees excluded from watching
+    /// and serving.
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        self.config
+            .watch
+            .excluded
+            .iter()
+            .any(|excluded| path.starts_with(self.root.join(excluded)))
+    }
 }
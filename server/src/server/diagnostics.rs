@@ -0,0 +1,71 @@
+use super::PalladinPlugin;
+use rolldown_error::BuildDiagnostic;
+use std::fmt;
+use std::sync::Arc;
+
+/// How serious a [`Diagnostic`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl Severity {
+    /// Returns the name of the severity as used by Rollup's `onLog`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Severity::Warning => "warn",
+            Severity::Error => "error",
+        }
+    }
+}
+
+/// A warning or error reported by the bundler, passed to
+/// [`PalladinPlugin::on_diagnostic`] before it's printed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// Kind of problem, e.g. `CIRCULAR_DEPENDENCY` or `UNRESOLVED_IMPORT`
+    pub code: String,
+    /// Module the diagnostic is about, if any
+    pub id: Option<String>,
+    /// The rendered diagnostic
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub(crate) fn from_rolldown(diagnostic: &BuildDiagnostic, severity: Severity) -> Self {
+        Self {
+            severity,
+            code: diagnostic.kind().to_string(),
+            id: diagnostic.id(),
+            message: diagnostic.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Runs every plugin's `on_diagnostic` hook over the `severity` diagnostics
+/// reported by the bundler, returning the ones left to print.
+pub(crate) fn filter_diagnostics<'a>(
+    plugins: &[Arc<dyn PalladinPlugin>],
+    diagnostics: impl IntoIterator<Item = &'a BuildDiagnostic>,
+    severity: Severity,
+) -> Vec<Diagnostic> {
+    diagnostics
+        .into_iter()
+        .filter_map(|diagnostic| {
+            let diagnostic = Diagnostic::from_rolldown(diagnostic, severity);
+            plugins.iter().try_fold(diagnostic, |diagnostic, plugin| {
+                plugin.on_diagnostic(diagnostic)
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,100 @@
+use crate::build::format_size;
+use crate::server::Context;
+use crate::server::html_meta::escape;
+use palladin_shared::{PalladinResult, UtcDateTime};
+use std::path::Path;
+use std::time::SystemTime;
+
+/// Entry of a listed directory.
+struct ListingEntry {
+    name: String,
+    is_dir: bool,
+    size: Option<u64>,
+    modified: Option<SystemTime>,
+}
+
+/// Renders an HTML page listing the files of `dir`, served at the URL path
+/// `route`, e.g. `assets/icons`. Directories come first, then files, each
+/// sorted by name, and excluded paths are left out.
+pub(crate) fn render_listing(ctx: &Context, dir: &Path, route: &str) -> PalladinResult<String> {
+    let vfs = ctx.vfs();
+    let mut entries: Vec<ListingEntry> = vfs
+        .read_dir(dir)?
+        .into_iter()
+        .filter(|path| !ctx.is_excluded(path))
+        .filter_map(|path| {
+            let name = path.file_name()?.to_string_lossy().into_owned();
+            let is_dir = vfs.is_dir(&path);
+            // Files only in memory have no modification time
+            let modified = fs_err::metadata(&path)
+                .ok()
+                .and_then(|metadata| metadata.modified().ok());
+            Some(ListingEntry {
+                name,
+                is_dir,
+                size: (!is_dir).then(|| vfs.file_size(&path).ok()).flatten(),
+                modified,
+            })
+        })
+        .collect();
+    entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+
+    let route = route.trim_matches('/');
+    let title = escape(&format!("/{route}"));
+    let mut rows = String::new();
+    if !route.is_empty() {
+        let parent = route.rsplit_once('/').map_or("", |(parent, _)| parent);
+        let href = ctx.base_url(&format!("/{parent}"));
+        rows.push_str(&format!(
+            "<tr><td><a href=\"{}\">../</a></td><td></td><td></td></tr>\n",
+            escape(&href)
+        ));
+    }
+    for entry in &entries {
+        let suffix = if entry.is_dir { "/" } else { "" };
+        let path = match route {
+            "" => format!("/{}", entry.name),
+            route => format!("/{route}/{}", entry.name),
+        };
+        let size = entry.size.map(format_size).unwrap_or_default();
+        let modified = entry
+            .modified
+            .map(|time| {
+                let time = UtcDateTime::from_system_time(time);
+                format!(
+                    "{:04}-{:02}-{:02} {:02}:{:02}",
+                    time.year, time.month, time.day, time.hour, time.minute
+                )
+            })
+            .unwrap_or_default();
+        let href = escape(&ctx.base_url(&path));
+        let name = escape(&entry.name);
+        rows.push_str(&format!(
+            "<tr><td><a href=\"{href}{suffix}\">{name}{suffix}</a></td>"
+        ));
+        rows.push_str(&format!("<td>{size}</td><td>{modified}</td></tr>\n"));
+    }
+
+    Ok(format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Index of {title}</title>
+<style>
+body {{ font-family: system-ui, sans-serif; margin: 2rem; }}
+table {{ border-collapse: collapse; }}
+td {{ padding: 0.2rem 1.5rem 0.2rem 0; }}
+td:nth-child(2) {{ text-align: right; }}
+</style>
+</head>
+<body>
+<h1>Index of {title}</h1>
+<table>
+<tr><th align="left">Name</th><th align="right">Size</th><th align="left">Modified (UTC)</th></tr>
+{rows}</table>
+</body>
+</html>
+"#
+    ))
+}
@@ -0,0 +1,49 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// A module the bundle starts from, named after its file unless given a name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entrypoint {
+    /// Name of the emitted entry chunk, e.g. `admin` for `admin.js`
+    pub name: Option<String>,
+    pub path: PathBuf,
+}
+
+impl From<PathBuf> for Entrypoint {
+    fn from(path: PathBuf) -> Self {
+        Self { name: None, path }
+    }
+}
+
+/// Parses `path` or `name=path`.
+impl FromStr for Entrypoint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, path) = match s.split_once('=') {
+            Some((name, path)) => (Some(name.trim().to_string()), path.trim()),
+            None => (None, s.trim()),
+        };
+
+        if path.is_empty() || name.as_deref() == Some("") {
+            return Err(format!(
+                "invalid entrypoint '{s}', expected PATH or NAME=PATH"
+            ));
+        }
+
+        Ok(Self {
+            name,
+            path: PathBuf::from(path),
+        })
+    }
+}
+
+impl fmt::Display for Entrypoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "{name}={}", self.path.display()),
+            None => write!(f, "{}", self.path.display()),
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use crate::messages::Message;
+use crate::server::Server;
+use crate::server::errors::{HttpResponse, REVALIDATE};
+use crate::server::hmr::inject_hmr_client;
+use crate::server::html_meta::escape;
+use axum::body::to_bytes;
+use axum::extract::{Request, State};
+use axum::http::StatusCode;
+use axum::http::header::ACCEPT;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use log::warn;
+use palladin_shared::ErrorResponse;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Largest error body rendered into a page, longer ones are sent as they are.
+const MAX_MESSAGE_BYTES: usize = 64 * 1024;
+
+/// HTML pages answering failed page loads instead of the plain text error.
+///
+/// Pages may contain `{{status}}` and `{{message}}`, replaced with the status
+/// code and the error message.
+#[derive(Debug, Clone, Default)]
+pub struct ErrorPages {
+    /// Page of missing files and routes relative to the root, e.g. `404.html`
+    pub not_found: Option<PathBuf>,
+    /// Page of server errors relative to the root, e.g. `500.html`
+    pub server_error: Option<PathBuf>,
+}
+
+impl ErrorPages {
+    pub fn is_empty(&self) -> bool {
+        self.not_found.is_none() && self.server_error.is_none()
+    }
+
+    /// Returns the page answering a response with `status`.
+    fn page(&self, status: StatusCode) -> Option<&Path> {
+        match status {
+            StatusCode::NOT_FOUND => self.not_found.as_deref(),
+            status if status.is_server_error() => self.server_error.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+/// Fills in the placeholders of an error page.
+fn render_error_page(template: &str, status: StatusCode, message: &str) -> String {
+    template
+        .replace("{{status}}", status.as_str())
+        .replace("{{message}}", &escape(message))
+}
+
+/// Replaces the plain text errors answering page loads with the configured
+/// error pages. Only requests accepting HTML are answered with a page, so
+/// failed module and asset requests keep their plain text error.
+pub async fn error_pages(
+    State(server): State<Arc<Server>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let accepts_html = request
+        .headers()
+        .get(ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/html"));
+    let response = next.run(request).await;
+
+    let status = response.status();
+    let Some(page) = server.config().error_pages.page(status) else {
+        return response;
+    };
+    // Only failures are replaced, other responses with an error status were
+    // rendered on purpose, e.g. by a mock or a server hook
+    if !accepts_html || response.extensions().get::<ErrorResponse>().is_none() {
+        return response;
+    }
+
+    let template = match server
+        .ctx
+        .resolve_path(page)
+        .and_then(|path| server.ctx.vfs().read_to_string(&path))
+    {
+        Ok(template) => template,
+        Err(err) => {
+            warn!("{}", Message::ErrorPageUnreadable { page, error: &err });
+            return response;
+        }
+    };
+    let message = match to_bytes(response.into_body(), MAX_MESSAGE_BYTES).await {
+        Ok(body) => String::from_utf8_lossy(&body).into_owned(),
+        Err(_) => String::new(),
+    };
+
+    // The page reloads once the error is fixed, like any served page
+    let html = render_error_page(&template, status, &message);
+    let html = inject_hmr_client(&html, server.build_id());
+    HttpResponse::new(status, html)
+        .content_type("text/html; charset=utf-8")
+        .cache_control(REVALIDATE)
+        .finish()
+        .into_response()
+}
@@ -0,0 +1,70 @@
+use crate::messages::Message;
+use axum::http::header::{CACHE_CONTROL, CONTENT_TYPE};
+use axum::http::{HeaderName, HeaderValue, Response, StatusCode};
+use log::warn;
+
+/// Cache policy for content-hashed chunks.
+pub const IMMUTABLE: &str = "public, max-age=31536000, immutable";
+
+/// Cache policy for files whose name stays the same across builds.
+pub const REVALIDATE: &str = "no-cache";
+
+/// Returns the cache policy for a build output file. Files whose name
+/// carries a content hash, as recorded in the build report, never change
+/// and can be cached forever.
+pub fn cache_control(immutable: bool) -> &'static str {
+    if immutable { IMMUTABLE } else { REVALIDATE }
+}
+
+/// Infallible alternative to `Response::builder()`.
+///
+/// Header values that aren't valid are dropped with a warning instead of
+/// failing the whole response.
+#[derive(Debug)]
+pub struct HttpResponse {
+    response: Response<String>,
+}
+
+impl HttpResponse {
+    /// Creates a `200 OK` response with `body`.
+    pub fn ok(body: impl Into<String>) -> Self {
+        Self::new(StatusCode::OK, body)
+    }
+
+    pub fn new(status: StatusCode, body: impl Into<String>) -> Self {
+        let mut response = Response::new(body.into());
+        *response.status_mut() = status;
+        Self { response }
+    }
+
+    /// Returns a new `HttpResponse` with the specified header set.
+    #[must_use]
+    pub fn header(mut self, name: HeaderName, value: &str) -> Self {
+        match HeaderValue::from_str(value) {
+            Ok(value) => {
+                self.response.headers_mut().insert(name, value);
+            }
+            Err(_) => warn!("{}", Message::InvalidHeader { name: &name, value }),
+        }
+        self
+    }
+
+    /// Returns a new `HttpResponse` with the specified content type.
+    #[must_use]
+    #[inline(always)]
+    pub fn content_type(self, value: &str) -> Self {
+        self.header(CONTENT_TYPE, value)
+    }
+
+    /// Returns a new `HttpResponse` with the specified cache policy.
+    #[must_use]
+    #[inline(always)]
+    pub fn cache_control(self, value: &str) -> Self {
+        self.header(CACHE_CONTROL, value)
+    }
+
+    #[inline(always)]
+    pub fn finish(self) -> Response<String> {
+        self.response
+    }
+}
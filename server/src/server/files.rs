@@ -1,64 +1,154 @@
-use crate::file::{File, FileContent, calculate_content_hash, detect_file_type};
-use crate::server::Server;
-use axum::extract::{Path, State};
-use axum::http::Response;
+use crate::file::{File, FileContent, FileType, calculate_content_hash, detect_file_type};
+use crate::html::{
+    inject_into_head, module_script_sources, rebase_urls, rewrite_module_script_src,
+    stylesheet_links,
+};
+use crate::messages::Message;
+use crate::rolldown::{BundleMode, bundle_worklet, guess_content_type, is_asset, is_worklet};
+use crate::server::directory_listing::render_listing;
+use crate::server::errors::{HttpResponse, IMMUTABLE, REVALIDATE};
+use crate::server::hmr::{BUILD_ID_HEADER, BUILD_QUERY_PARAM, inject_hmr_client};
+use crate::server::plugin::transform_index_html;
+use crate::server::{
+    AppType, REDIRECT_STATUS, Routing, Server, inject_html_meta, inject_resource_hints,
+    process_stylesheet,
+};
+use axum::extract::{OriginalUri, Path, Query, State};
+use axum::http::header::{CACHE_CONTROL, CONTENT_TYPE, LOCATION};
+use axum::http::{HeaderName, HeaderValue, Response, StatusCode, Uri};
 use axum::response::IntoResponse;
-use log::debug;
+use log::{debug, warn};
 use palladin_shared::{PalladinError, PalladinResult};
+use serde::Deserialize;
 use std::path::PathBuf;
 use std::sync::Arc;
 
+/// Query of module and chunk requests.
+#[derive(Debug, Deserialize)]
+pub struct BuildQuery {
+    /// Build the requesting page was served for, see [`BUILD_QUERY_PARAM`]
+    build: Option<u64>,
+}
+
 pub async fn serve_file_handler(
     State(server): State<Arc<Server>>,
     Path(file): Path<String>,
+    Query(query): Query<BuildQuery>,
+    OriginalUri(original_uri): OriginalUri,
+    uri: Uri,
 ) -> impl IntoResponse {
-    Server::serve_file_impl(server, file)
-        .await
-        .unwrap_or_else(|err| err.response())
+    if let Some(response) = server.stale_build_response(query.build) {
+        return response.into_response();
+    }
+    if let Some(response) = server.url_policy_response(&original_uri, &uri) {
+        return response.into_response();
+    }
+
+    // Worklets are bundled on their own and served without the HMR client
+    if is_worklet(std::path::Path::new(&file)) {
+        return server.serve_worklet_impl(&file).await.into_response();
+    }
+    // Assets are binary, so they skip the source file cache
+    if is_asset(std::path::Path::new(&file)) {
+        return server.serve_asset_impl(&file).into_response();
+    }
+    Server::serve_file_impl(server, file).await.into_response()
 }
 
 pub async fn serve_index_handler(State(server): State<Arc<Server>>) -> impl IntoResponse {
-    Server::serve_index_impl(server)
-        .await
-        .unwrap_or_else(|err| err.response())
+    Server::serve_index_impl(server).await
 }
 
 pub async fn serve_chunk_handler(
     State(server): State<Arc<Server>>,
     Path(chunk_name): Path<String>,
+    Query(query): Query<BuildQuery>,
 ) -> impl IntoResponse {
-    Server::serve_chunk_impl(server, chunk_name).unwrap_or_else(|err| err.response())
+    match server.stale_build_response(query.build) {
+        Some(response) => Ok(response),
+        None => Server::serve_chunk_impl(server, chunk_name),
+    }
 }
 
 impl Server {
+    /// Answers requests made for an older build with a reload directive
+    /// instead of chunks that don't match the rest of the page.
+    fn stale_build_response(&self, build: Option<u64>) -> Option<Response<String>> {
+        let current = self.build_id();
+        if build.is_none_or(|build| build == current) {
+            return None;
+        }
+
+        debug!("request for build {build:?} while serving build {current}, asking to reload");
+        Some(
+            HttpResponse::new(StatusCode::CONFLICT, Message::StaleBuild.to_string())
+                .header(
+                    HeaderName::from_static(BUILD_ID_HEADER),
+                    &current.to_string(),
+                )
+                .cache_control("no-store")
+                .finish(),
+        )
+    }
+
+    /// Answers a request for a page under a URL the configured
+    /// [`UrlPolicy`](crate::server::UrlPolicy) doesn't serve as it is.
+    ///
+    /// `uri` is the one the server was mounted to see, redirects keep the
+    /// prefix of `original_uri` in front of it.
+    fn url_policy_response(&self, original_uri: &Uri, uri: &Uri) -> Option<Response<String>> {
+        let policy = self.config().url_policy?;
+        match policy.route(uri.path()) {
+            Routing::Serve => None,
+            Routing::Redirect(canonical) => {
+                let prefix = original_uri
+                    .path()
+                    .strip_suffix(uri.path())
+                    .unwrap_or_default();
+                let location = match original_uri.query() {
+                    Some(query) => format!("{prefix}{canonical}?{query}"),
+                    None => format!("{prefix}{canonical}"),
+                };
+                debug!("redirecting {} to {location}", uri.path());
+                Some(
+                    HttpResponse::new(REDIRECT_STATUS, "")
+                        .header(LOCATION, &location)
+                        .finish(),
+                )
+            }
+            Routing::NotFound => {
+                Some(PalladinError::FileNotFound(uri.path().to_string()).response())
+            }
+        }
+    }
+
     async fn serve_file_impl(server: Arc<Self>, file: String) -> PalladinResult<Response<String>> {
         let request_path = file.trim_start_matches('/');
 
         if let Some(chunk) = server.chunk_manager().get_chunk(request_path) {
-            return Ok(Response::builder()
-                .header("content-type", chunk.content_type.as_str())
-                .header("cache-control", "public, max-age=31536000, immutable")
-                .body(chunk.content)
-                .unwrap());
+            let response = HttpResponse::ok(chunk.content).content_type(&chunk.content_type);
+            return Ok(server
+                .with_cache_control(response, request_path, Some(IMMUTABLE))
+                .finish());
         }
 
-        if let Some(main_asset) = server.entry_asset() {
-            let build_dir = server
-                .config()
-                .build_dir()
-                .to_string_lossy()
-                .replace('\\', "/");
-            let build_dir_trimmed = build_dir.trim_matches('/');
-            let matches_filename = request_path == main_asset.filename;
-            let matches_build_dir = !build_dir_trimmed.is_empty()
-                && request_path == format!("{}/{}", build_dir_trimmed, main_asset.filename);
-
-            if matches_filename || matches_build_dir {
-                return Ok(Response::builder()
-                    .header("content-type", main_asset.content_type.as_str())
-                    .body(main_asset.content)
-                    .unwrap());
-            }
+        let build_dir = server
+            .config()
+            .build_dir()
+            .to_string_lossy()
+            .replace('\\', "/");
+        let build_dir_trimmed = build_dir.trim_matches('/');
+        let filename = request_path
+            .strip_prefix(&format!("{build_dir_trimmed}/"))
+            .filter(|_| !build_dir_trimmed.is_empty())
+            .unwrap_or(request_path);
+
+        if let Some(main_asset) = server.entry_asset(filename) {
+            let response =
+                HttpResponse::ok(main_asset.content).content_type(&main_asset.content_type);
+            return Ok(server
+                .with_cache_control(response, request_path, None)
+                .finish());
         }
 
         let full_path = server
@@ -67,15 +157,39 @@ impl Server {
             .map_err(|_| PalladinError::FileNotFound(file.clone()));
 
         let full_path = match full_path {
-            Ok(path) if path.is_file() && server.ctx.is_within_root(&path) => path,
+            Ok(path)
+                if server.ctx.vfs().is_file(&path)
+                    && server.ctx.is_within_root(&path)
+                    && !server.ctx.is_excluded(&path) =>
+            {
+                path
+            }
+            Ok(path)
+                if server.config().directory_listing
+                    && server.ctx.vfs().is_dir(&path)
+                    && server.ctx.is_within_root(&path)
+                    && !server.ctx.is_excluded(&path)
+                    && server.page_path(request_path).is_none() =>
+            {
+                let html = render_listing(&server.ctx, &path, request_path)?;
+                return Ok(HttpResponse::ok(html)
+                    .content_type("text/html; charset=utf-8")
+                    .cache_control(REVALIDATE)
+                    .finish());
+            }
             _ if file.contains('.') => {
                 // treat as file request that failed
                 return Err(PalladinError::FileNotFound(file.clone()));
             }
-            _ => {
-                // No extension - assume that it's a route
-                return Self::serve_index_impl(server).await;
-            }
+            // No extension - assume that it's a route
+            _ => match server.route_path(request_path) {
+                Some(path) => path,
+                // Client side routers handle the routes of single-page apps
+                None if server.config().app_type == AppType::Spa => {
+                    return Self::serve_index_impl(server).await;
+                }
+                None => return Err(PalladinError::FileNotFound(file.clone())),
+            },
         };
 
         debug!(
@@ -86,29 +200,156 @@ impl Server {
                 .unwrap_or("unknown".into())
         );
 
-        let file_struct = Self::get_or_load_file(&server, &full_path)?;
-        Self::build_file_response(&file_struct)
+        Ok(Self::load_file_response(&server, full_path).await)
+    }
+
+    /// Loads the file at `path` and builds its response, once for all the
+    /// requests made for it while it's loading.
+    async fn load_file_response(server: &Arc<Self>, path: PathBuf) -> Response<String> {
+        let response = server
+            .loads
+            .run(path.clone(), || async {
+                let file = match Self::get_or_load_file(server, &path) {
+                    Ok(file) => file,
+                    Err(err) => return err.response(),
+                };
+                server
+                    .build_file_response(&file)
+                    .await
+                    .unwrap_or_else(|err| err.response())
+            })
+            .await;
+
+        let mut clone = Response::new(response.body().clone());
+        *clone.status_mut() = response.status();
+        *clone.headers_mut() = response.headers().clone();
+        clone
+    }
+
+    /// Serves the static asset `file` as it is on disk, e.g. an image
+    /// imported by a module.
+    pub(crate) fn serve_asset_impl(&self, file: &str) -> PalladinResult<axum::response::Response> {
+        let not_found = || PalladinError::FileNotFound(file.to_string());
+        let path = self.ctx.resolve_path(file).map_err(|_| not_found())?;
+        if !self.ctx.vfs().is_file(&path)
+            || !self.ctx.is_within_root(&path)
+            || self.ctx.is_excluded(&path)
+        {
+            return Err(not_found());
+        }
+
+        let content = self.ctx.vfs().read(&path)?;
+        let cache_control = self
+            .config()
+            .cache_policy
+            .cache_control(file, None)
+            .and_then(|value| HeaderValue::from_str(value).ok())
+            .unwrap_or(HeaderValue::from_static(REVALIDATE));
+        let headers = [
+            (
+                CONTENT_TYPE,
+                HeaderValue::from_static(guess_content_type(file)),
+            ),
+            (CACHE_CONTROL, cache_control),
+        ];
+        Ok((headers, content).into_response())
+    }
+
+    /// Serves the worklet `file` bundled into a standalone module, which
+    /// pages load with `audioWorklet.addModule` or `CSS.paintWorklet.addModule`.
+    async fn serve_worklet_impl(&self, file: &str) -> PalladinResult<Response<String>> {
+        let not_found = || PalladinError::FileNotFound(file.to_string());
+        let path = self.ctx.resolve_path(file).map_err(|_| not_found())?;
+        if !self.ctx.vfs().is_file(&path)
+            || !self.ctx.is_within_root(&path)
+            || self.ctx.is_excluded(&path)
+        {
+            return Err(not_found());
+        }
+
+        debug!("bundling worklet {}", path.display());
+        let worklet = bundle_worklet(self.ctx.clone(), &path, BundleMode::Dev).await?;
+        let response = HttpResponse::ok(worklet.code).content_type("application/javascript");
+        Ok(self
+            .with_cache_control(response, file, Some(REVALIDATE))
+            .finish())
     }
 
     async fn serve_index_impl(server: Arc<Self>) -> PalladinResult<Response<String>> {
         let index_path = server
             .ctx
             .resolve_path("index.html")
-            .map_err(|_| PalladinError::FileNotFound("index.html".to_string()))?;
+            .ok()
+            .filter(|path| server.ctx.vfs().is_file(path));
+        // A root without an index page is listed like any other directory
+        let index_path = match index_path {
+            Some(path) => path,
+            None if server.config().directory_listing => {
+                let html = render_listing(&server.ctx, server.ctx.root(), "")?;
+                return Ok(HttpResponse::ok(html)
+                    .content_type("text/html; charset=utf-8")
+                    .cache_control(REVALIDATE)
+                    .finish());
+            }
+            None => return Err(PalladinError::FileNotFound("index.html".to_string())),
+        };
 
         debug!("Serving index.html");
 
-        let file_struct = Self::get_or_load_file(&server, &index_path)?;
-        Self::build_file_response(&file_struct)
+        Ok(Self::load_file_response(&server, index_path).await)
+    }
+
+    /// Returns the file serving `route`, the target of the first rewrite
+    /// matching it or, unless the app type is custom, its page.
+    fn route_path(&self, route: &str) -> Option<PathBuf> {
+        let config = self.config();
+        if let Some(rewrite) = config
+            .rewrites
+            .iter()
+            .find(|rewrite| rewrite.matches(route))
+        {
+            let path = self.ctx.resolve_path(&rewrite.to).ok().filter(|path| {
+                self.ctx.vfs().is_file(path)
+                    && self.ctx.is_within_root(path)
+                    && !self.ctx.is_excluded(path)
+            });
+            if path.is_none() {
+                warn!("{}", Message::MissingRewriteTarget { rewrite });
+            }
+            return path;
+        }
+
+        match config.app_type {
+            // Routes map onto the pages of multi-page apps when they exist
+            AppType::Spa | AppType::Mpa => self.page_path(route),
+            AppType::Custom => None,
+        }
+    }
+
+    /// Returns the HTML page serving `route`, either `<route>.html` or
+    /// `<route>/index.html`.
+    fn page_path(&self, route: &str) -> Option<PathBuf> {
+        let route = route.trim_end_matches('/');
+        if route.is_empty() {
+            return None;
+        }
+
+        [format!("{route}.html"), format!("{route}/index.html")]
+            .into_iter()
+            .filter_map(|candidate| self.ctx.resolve_path(candidate).ok())
+            .find(|path| {
+                self.ctx.vfs().is_file(path)
+                    && self.ctx.is_within_root(path)
+                    && !self.ctx.is_excluded(path)
+            })
     }
 
     fn serve_chunk_impl(server: Arc<Self>, chunk_name: String) -> PalladinResult<Response<String>> {
         if let Some(chunk) = server.chunk_manager().get_chunk(&chunk_name) {
-            return Ok(Response::builder()
-                .header("content-type", chunk.content_type.as_str())
-                .header("cache-control", "public, max-age=31536000, immutable")
-                .body(chunk.content)
-                .unwrap());
+            let response = HttpResponse::ok(chunk.content).content_type(&chunk.content_type);
+            return Ok(server
+                .with_cache_control(response, &chunk_name, Some(IMMUTABLE))
+                .finish());
         }
 
         Err(PalladinError::FileNotFound(format!(
@@ -117,16 +358,88 @@ impl Server {
         )))
     }
 
-    fn build_file_response(file: &File) -> PalladinResult<Response<String>> {
-        Ok(Response::builder()
-            .header("content-type", file.content_type())
-            .body(file.content.transformed.clone())
-            .unwrap())
+    async fn build_file_response(&self, file: &File) -> PalladinResult<Response<String>> {
+        let response = match file.ty {
+            FileType::Html => {
+                let build_id = self.build_id();
+                let html = transform_index_html(&self.config().plugins, &file.content.transformed);
+                let html = inject_resource_hints(&html, &self.config().resource_hints);
+                let html = inject_html_meta(&html, &self.config().html_meta);
+                let html = self.link_entry_stylesheets(&html, build_id);
+                let html = self.tag_entry_script(&html, build_id);
+                let html = inject_hmr_client(&html, build_id);
+                HttpResponse::ok(rebase_urls(&html, self.ctx.base())).header(
+                    HeaderName::from_static(BUILD_ID_HEADER),
+                    &build_id.to_string(),
+                )
+            }
+            FileType::Css
+                if self.config().postcss.is_some() || self.config().tailwind.is_some() =>
+            {
+                let css = process_stylesheet(
+                    self.config().postcss.clone(),
+                    self.config().tailwind.clone(),
+                    &file.path,
+                    &file.content.original,
+                )
+                .await?;
+                HttpResponse::ok(css)
+            }
+            _ => HttpResponse::ok(file.content.transformed.clone()),
+        };
+
+        let url_path = self.ctx.url_path(&file.path).unwrap_or_default();
+        let response = response.content_type(file.content_type());
+        Ok(self.with_cache_control(response, &url_path, None).finish())
+    }
+
+    /// Sets the `Cache-Control` header the configured
+    /// [`CachePolicy`](crate::server::CachePolicy) gives `path`, `default`
+    /// when no rule matches it.
+    fn with_cache_control(
+        &self,
+        response: HttpResponse,
+        path: &str,
+        default: Option<&str>,
+    ) -> HttpResponse {
+        match self.config().cache_policy.cache_control(path, default) {
+            Some(value) => response.cache_control(value),
+            None => response,
+        }
+    }
+
+    /// Tags the entrypoint script of `html` with the build being served.
+    fn tag_entry_script(&self, html: &str, build_id: u64) -> String {
+        rewrite_module_script_src(html, |src| {
+            let path = self.ctx.resolve_path(src.trim_start_matches('/')).ok()?;
+            self.ctx
+                .is_entrypoint(&path)
+                .then(|| format!("{src}?{BUILD_QUERY_PARAM}={build_id}"))
+        })
+    }
+
+    /// Links the stylesheets bundled with the entrypoints `html` loads.
+    fn link_entry_stylesheets(&self, html: &str, build_id: u64) -> String {
+        let stylesheets: Vec<String> = module_script_sources(html)
+            .iter()
+            .filter_map(|src| self.ctx.resolve_path(src.trim_start_matches('/')).ok())
+            .filter_map(|path| self.entry_stylesheets(&path))
+            .flatten()
+            .map(|css| format!("/__chunks/{css}?{BUILD_QUERY_PARAM}={build_id}"))
+            .collect();
+
+        if stylesheets.is_empty() {
+            return html.to_string();
+        }
+        inject_into_head(html, &stylesheet_links(stylesheets))
     }
 
     pub(crate) fn get_or_load_file(server: &Arc<Self>, path: &PathBuf) -> PalladinResult<File> {
         let mut files = server.files.write();
-        let content = fs_err::read_to_string(path)
+        let content = server
+            .ctx
+            .vfs()
+            .read_to_string(path)
             .map_err(|_| PalladinError::FileNotFound(path.display().to_string()))?;
         let hash = calculate_content_hash(&content);
         let ty = detect_file_type(path);
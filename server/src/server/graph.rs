@@ -0,0 +1,54 @@
+use crate::server::Server;
+use crate::server::errors::HttpResponse;
+use axum::extract::State;
+use axum::http::Response;
+use axum::response::IntoResponse;
+use palladin_shared::{PalladinError, PalladinResult};
+use serde_json::json;
+use std::sync::Arc;
+
+pub async fn serve_graph_handler(State(server): State<Arc<Server>>) -> impl IntoResponse {
+    Server::serve_graph_impl(server)
+}
+
+pub async fn serve_execution_order_handler(State(server): State<Arc<Server>>) -> impl IntoResponse {
+    Server::serve_execution_order_impl(server)
+}
+
+impl Server {
+    fn serve_graph_impl(server: Arc<Self>) -> PalladinResult<Response<String>> {
+        let graph = server.module_graph();
+        let body =
+            serde_json::to_string_pretty(&graph).map_err(|e| PalladinError::Build(e.into()))?;
+
+        Ok(HttpResponse::ok(body)
+            .content_type("application/json")
+            .cache_control("no-cache")
+            .finish())
+    }
+
+    /// Reports the modules of every chunk in execution order along with the
+    /// ordering hazards found in the bundle.
+    fn serve_execution_order_impl(server: Arc<Self>) -> PalladinResult<Response<String>> {
+        let graph = server.module_graph();
+        let chunks: Vec<_> = graph
+            .chunks
+            .iter()
+            .map(|chunk| {
+                json!({
+                    "filename": chunk.filename,
+                    "modules": graph.execution_order(&chunk.filename),
+                })
+            })
+            .collect();
+
+        let report = json!({ "chunks": chunks, "hazards": graph.hazards });
+        let body =
+            serde_json::to_string_pretty(&report).map_err(|e| PalladinError::Build(e.into()))?;
+
+        Ok(HttpResponse::ok(body)
+            .content_type("application/json")
+            .cache_control("no-cache")
+            .finish())
+    }
+}
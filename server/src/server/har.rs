@@ -0,0 +1,202 @@
+use crate::messages::Message;
+use axum::body::{Body, Bytes, to_bytes};
+use axum::extract::{Request, State};
+use axum::http::HeaderMap;
+use axum::middleware::Next;
+use axum::response::Response;
+use log::{debug, warn};
+use palladin_shared::UtcDateTime;
+use parking_lot::Mutex;
+use serde_json::{Value, json};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Headers whose values are never written to the HAR file.
+const REDACTED_HEADERS: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+    "x-api-key",
+    "x-auth-token",
+];
+
+/// Bodies larger than this are not captured.
+const MAX_CAPTURED_BODY: usize = 1024 * 1024;
+
+/// How often recorded entries are flushed to disk.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Options for recording dev-server traffic into a HAR file.
+#[derive(Debug, Clone)]
+pub struct HarOptions {
+    /// File the HAR log is written to
+    pub path: PathBuf,
+    /// Whether request and response bodies are included
+    pub capture_bodies: bool,
+}
+
+/// Records request/response metadata and periodically writes it as HAR 1.2.
+pub struct HarRecorder {
+    options: HarOptions,
+    entries: Mutex<Vec<Value>>,
+    dirty: AtomicBool,
+}
+
+impl HarRecorder {
+    pub fn new(options: HarOptions) -> Self {
+        Self {
+            options,
+            entries: Mutex::new(Vec::new()),
+            dirty: AtomicBool::new(false),
+        }
+    }
+
+    /// Writes the log to disk whenever new entries were recorded.
+    pub async fn flush_periodically(self: Arc<Self>) {
+        let mut ticker = tokio::time::interval(FLUSH_INTERVAL);
+        loop {
+            ticker.tick().await;
+            if self.dirty.swap(false, Ordering::SeqCst)
+                && let Err(err) = self.write()
+            {
+                warn!("{}", Message::HarWriteFailed { error: &err });
+            }
+        }
+    }
+
+    fn write(&self) -> std::io::Result<()> {
+        let log = json!({
+            "log": {
+                "version": "1.2",
+                "creator": { "name": "palladin", "version": env!("CARGO_PKG_VERSION") },
+                "entries": *self.entries.lock(),
+            }
+        });
+
+        fs_err::write(&self.options.path, serde_json::to_vec_pretty(&log)?)?;
+        debug!("wrote HAR log to {}", self.options.path.display());
+        Ok(())
+    }
+
+    fn record(&self, entry: Value) {
+        self.entries.lock().push(entry);
+        self.dirty.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Middleware recording every request passing through the router.
+pub async fn record_har(
+    State(recorder): State<Arc<HarRecorder>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let started = SystemTime::now();
+    let timer = Instant::now();
+    let capture = recorder.options.capture_bodies;
+
+    let (parts, body) = request.into_parts();
+    let (request_body, body) = capture_body(body, capture).await;
+
+    let url = parts.uri.to_string();
+    let query: Vec<Value> = parts
+        .uri
+        .query()
+        .unwrap_or("")
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            json!({ "name": name, "value": value })
+        })
+        .collect();
+
+    let mut request_json = json!({
+        "method": parts.method.as_str(),
+        "url": url,
+        "httpVersion": format!("{:?}", parts.version),
+        "headers": headers_json(&parts.headers),
+        "queryString": query,
+        "cookies": [],
+        "headersSize": -1,
+        "bodySize": request_body.as_ref().map_or(-1, |b| b.len() as i64),
+    });
+    if let Some(text) = request_body.as_ref().map(|b| String::from_utf8_lossy(b)) {
+        request_json["postData"] = json!({
+            "mimeType": header_value(&parts.headers, "content-type"),
+            "text": text,
+        });
+    }
+
+    let response = next.run(Request::from_parts(parts, body)).await;
+    let elapsed = timer.elapsed().as_secs_f64() * 1000.0;
+
+    let (parts, body) = response.into_parts();
+    let (response_body, body) = capture_body(body, capture).await;
+
+    let mut content = json!({
+        "size": response_body.as_ref().map_or(-1, |b| b.len() as i64),
+        "mimeType": header_value(&parts.headers, "content-type"),
+    });
+    if let Some(text) = response_body.as_ref().map(|b| String::from_utf8_lossy(b)) {
+        content["text"] = json!(text);
+    }
+
+    recorder.record(json!({
+        "startedDateTime": UtcDateTime::from_system_time(started).iso8601(),
+        "time": elapsed,
+        "request": request_json,
+        "response": {
+            "status": parts.status.as_u16(),
+            "statusText": parts.status.canonical_reason().unwrap_or(""),
+            "httpVersion": format!("{:?}", parts.version),
+            "headers": headers_json(&parts.headers),
+            "cookies": [],
+            "content": content,
+            "redirectURL": header_value(&parts.headers, "location"),
+            "headersSize": -1,
+            "bodySize": -1,
+        },
+        "cache": {},
+        "timings": { "send": 0, "wait": elapsed, "receive": 0 },
+    }));
+
+    Response::from_parts(parts, body)
+}
+
+/// Buffers `body` when capturing is enabled, returning the captured bytes and
+/// a body to forward in its place.
+async fn capture_body(body: Body, capture: bool) -> (Option<Bytes>, Body) {
+    if !capture {
+        return (None, body);
+    }
+
+    match to_bytes(body, MAX_CAPTURED_BODY).await {
+        Ok(bytes) => (Some(bytes.clone()), Body::from(bytes)),
+        Err(_) => (None, Body::empty()),
+    }
+}
+
+fn headers_json(headers: &HeaderMap) -> Vec<Value> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if REDACTED_HEADERS.contains(&name.as_str()) {
+                "[redacted]".into()
+            } else {
+                String::from_utf8_lossy(value.as_bytes()).into_owned()
+            };
+            json!({ "name": name.as_str(), "value": value })
+        })
+        .collect()
+}
+
+fn header_value(headers: &HeaderMap, name: &str) -> String {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("")
+        .to_string()
+}
@@ -0,0 +1,58 @@
+use axum::extract::{Request, State};
+use axum::http::{HeaderMap, HeaderName, HeaderValue};
+use axum::middleware::Next;
+use axum::response::Response;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Header added to every response of the dev server, e.g.
+/// `Cross-Origin-Opener-Policy: same-origin`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResponseHeader {
+    pub name: HeaderName,
+    pub value: HeaderValue,
+}
+
+/// Parses `NAME: VALUE`, e.g. `Cross-Origin-Embedder-Policy: require-corp`.
+impl FromStr for ResponseHeader {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid =
+            || format!("invalid header '{s}', expected NAME: VALUE such as X-Frame-Options: DENY");
+        let (name, value) = s.split_once(':').ok_or_else(invalid)?;
+        Ok(Self {
+            name: HeaderName::from_str(name.trim()).map_err(|_| invalid())?,
+            value: HeaderValue::from_str(value.trim()).map_err(|_| invalid())?,
+        })
+    }
+}
+
+impl fmt::Display for ResponseHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {}",
+            self.name,
+            self.value.to_str().unwrap_or_default()
+        )
+    }
+}
+
+/// Adds the configured headers to every response, replacing the values the
+/// handlers set for the same names.
+pub async fn add_headers(
+    State(headers): State<Arc<HeaderMap>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let mut response = next.run(request).await;
+    for name in headers.keys() {
+        response.headers_mut().remove(name);
+    }
+    for (name, value) in headers.iter() {
+        response.headers_mut().append(name, value.clone());
+    }
+    response
+}
@@ -0,0 +1,179 @@
+use crate::messages::Message;
+use crate::rolldown::{ChunkAsset, MainAsset, ModuleGraph};
+use crate::server::Server;
+use crate::server::errors::HttpResponse;
+use crate::server::hmr::HmrMessage;
+use crate::server::restart::reject_remote;
+use axum::extract::State;
+use axum::http::{HeaderMap, Response};
+use axum::response::IntoResponse;
+use log::{info, warn};
+use palladin_shared::{PalladinError, PalladinResult};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Default number of builds kept to roll back to.
+pub const DEFAULT_BUILD_HISTORY: usize = 10;
+
+/// Output of a single build, everything needed to serve it again.
+#[derive(Debug, Clone)]
+pub(crate) struct ServedBuild {
+    pub id: u64,
+    pub built_at: SystemTime,
+    pub chunks: HashMap<String, ChunkAsset>,
+    /// Entry chunk of every entrypoint
+    pub entry_assets: HashMap<PathBuf, MainAsset>,
+    pub graph: ModuleGraph,
+}
+
+/// The latest builds, oldest first, and which one is being served.
+#[derive(Debug)]
+pub(crate) struct BuildHistory {
+    builds: VecDeque<ServedBuild>,
+    capacity: usize,
+    /// Index of the served build, the latest one when `None`
+    position: Option<usize>,
+    latest_id: u64,
+}
+
+/// A kept build as reported by `/__history`.
+#[derive(Debug, Serialize)]
+struct BuildEntry {
+    id: u64,
+    /// Milliseconds since the Unix epoch
+    built_at: u64,
+    chunks: usize,
+    served: bool,
+}
+
+impl BuildHistory {
+    /// Creates a history keeping the last `capacity` builds, at least the
+    /// served one.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            builds: VecDeque::new(),
+            capacity: capacity.max(1),
+            position: None,
+            latest_id: 0,
+        }
+    }
+
+    /// Returns the id the next build gets.
+    pub fn next_id(&self) -> u64 {
+        self.latest_id + 1
+    }
+
+    /// Adds a new build, which is served from now on.
+    pub fn push(&mut self, build: ServedBuild) {
+        self.latest_id = self.latest_id.max(build.id);
+        self.builds.push_back(build);
+        while self.builds.len() > self.capacity {
+            self.builds.pop_front();
+        }
+        self.position = None;
+    }
+
+    /// Moves to the build before the served one, returning it.
+    pub fn back(&mut self) -> Option<&ServedBuild> {
+        let current = self.position.unwrap_or(self.builds.len().checked_sub(1)?);
+        let previous = current.checked_sub(1)?;
+        self.position = Some(previous);
+        self.builds.get(previous)
+    }
+
+    /// Moves to the build after the served one, returning it.
+    pub fn forward(&mut self) -> Option<&ServedBuild> {
+        let next = self.position? + 1;
+        self.position = (next + 1 < self.builds.len()).then_some(next);
+        self.builds.get(next)
+    }
+
+    /// Checks if an older build is being served instead of the latest one.
+    pub fn is_rolled_back(&self) -> bool {
+        self.position.is_some()
+    }
+
+    fn entries(&self) -> Vec<BuildEntry> {
+        let served = self.position.unwrap_or(self.builds.len().saturating_sub(1));
+        self.builds
+            .iter()
+            .enumerate()
+            .map(|(index, build)| BuildEntry {
+                id: build.id,
+                built_at: build
+                    .built_at
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_millis() as u64)
+                    .unwrap_or_default(),
+                chunks: build.chunks.len(),
+                served: index == served,
+            })
+            .collect()
+    }
+}
+
+pub async fn serve_history_handler(State(server): State<Arc<Server>>) -> impl IntoResponse {
+    Server::serve_history_impl(&server)
+}
+
+/// Serves the build before the one being served, e.g. to find which change
+/// broke the app.
+pub async fn history_back_handler(
+    State(server): State<Arc<Server>>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Some(rejected) = reject_remote(&server, &headers, "rollback") {
+        return Ok(rejected);
+    }
+
+    let build = server.history.write().back().cloned();
+    Server::roll_to(server, build, "no older build to roll back to")
+}
+
+/// Serves the build after the one being served, up to the latest one.
+pub async fn history_forward_handler(
+    State(server): State<Arc<Server>>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Some(rejected) = reject_remote(&server, &headers, "rollback") {
+        return Ok(rejected);
+    }
+
+    let build = server.history.write().forward().cloned();
+    Server::roll_to(server, build, "already serving the latest build")
+}
+
+impl Server {
+    fn serve_history_impl(&self) -> PalladinResult<Response<String>> {
+        let body = serde_json::to_string_pretty(&self.history.read().entries())
+            .map_err(|e| PalladinError::Build(e.into()))?;
+
+        Ok(HttpResponse::ok(body)
+            .content_type("application/json")
+            .cache_control("no-cache")
+            .finish())
+    }
+
+    /// Serves `build` and reloads the connected pages, failing with `missing`
+    /// when there is no build to move to.
+    fn roll_to(
+        server: Arc<Self>,
+        build: Option<ServedBuild>,
+        missing: &str,
+    ) -> PalladinResult<Response<String>> {
+        let build = build.ok_or_else(|| PalladinError::FileNotFound(missing.to_string()))?;
+        server.serve_build(&build)?;
+
+        if server.history.read().is_rolled_back() {
+            warn!("{}", Message::ServingOldBuild { id: build.id });
+        } else {
+            info!("{}", Message::ServingLatestBuild { id: build.id });
+        }
+        let _ = server.hmr_tx.send(HmrMessage::FullReload);
+
+        server.serve_history_impl()
+    }
+}
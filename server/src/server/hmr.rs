@@ -0,0 +1,304 @@
+use crate::html::inject_into_head;
+use palladin_shared::{PalladinError, PalladinResult};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use tokio::sync::broadcast;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+
+/// Client runtime injected into served HTML pages.
+pub const HMR_CLIENT: &str = include_str!("hmr_client.js");
+
+/// Path the HMR client script is served from.
+pub const HMR_CLIENT_PATH: &str = "/__hmr_client.js";
+
+/// Version of palladin serving the HMR client, part of its URL so pages
+/// cached by another version don't keep a stale client.
+pub const PALLADIN_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Query parameter carrying [`PALLADIN_VERSION`] in the HMR client URL.
+pub const VERSION_QUERY_PARAM: &str = "v";
+
+/// Query parameter tagging chunk and module requests with the build they
+/// were served for.
+pub const BUILD_QUERY_PARAM: &str = "build";
+
+/// Response header carrying the id of the build being served.
+pub const BUILD_ID_HEADER: &str = "x-palladin-build";
+
+/// Version of the message protocol spoken over `/__hmr`.
+pub const HMR_PROTOCOL_VERSION: u32 = 1;
+
+/// Optional message types this server can send or receive.
+pub const HMR_CAPABILITIES: &[&str] = &[
+    "prune",
+    "patch",
+    "css-update",
+    "asset-update",
+    "custom",
+    "client-messages",
+];
+
+/// Messages pushed to connected browsers over the `/__hmr` websocket.
+#[derive(Debug, Clone, Serialize)]
+#[serde(
+    tag = "type",
+    rename_all = "kebab-case",
+    rename_all_fields = "camelCase"
+)]
+#[non_exhaustive]
+pub enum HmrMessage {
+    /// Sent once after the socket is opened.
+    Connected {
+        server_id: String,
+        client_id: String,
+        /// Version of palladin, see [`PALLADIN_VERSION`]
+        version: String,
+        protocol: u32,
+        capabilities: Vec<String>,
+    },
+    /// Asks the client to reload the whole page.
+    FullReload,
+    /// Modules that were deleted and should be disposed, as root-relative URLs.
+    Prune { paths: Vec<String> },
+    /// Code patch produced by the dev engine for a single client.
+    Patch { filename: String, code: String },
+    /// Stylesheets that were generated again, as root-relative URLs, to be
+    /// fetched again without reloading the page.
+    CssUpdate { paths: Vec<String> },
+    /// Files referenced by the page that changed, e.g. images, as
+    /// root-relative URLs, to be fetched again without reloading the page.
+    AssetUpdate { paths: Vec<String> },
+    /// Application defined event, delivered to listeners registered with
+    /// `import.meta.hot.on(event, cb)`.
+    Custom { event: String, data: Value },
+}
+
+impl HmrMessage {
+    /// Capability a client has to announce before it's sent this message,
+    /// `None` for messages every client understands.
+    pub fn required_capability(&self) -> Option<&'static str> {
+        match self {
+            HmrMessage::Connected { .. } | HmrMessage::FullReload => None,
+            HmrMessage::Prune { .. } => Some("prune"),
+            HmrMessage::Patch { .. } => Some("patch"),
+            HmrMessage::CssUpdate { .. } => Some("css-update"),
+            HmrMessage::AssetUpdate { .. } => Some("asset-update"),
+            HmrMessage::Custom { .. } => Some("custom"),
+        }
+    }
+
+    /// Adapts the message to a client with the given capabilities.
+    ///
+    /// Patches, stylesheet and asset updates fall back to a full reload,
+    /// other unsupported messages are dropped.
+    pub fn negotiate(self, capabilities: &[String]) -> Option<Self> {
+        match self.required_capability() {
+            Some(capability) if !capabilities.iter().any(|c| c == capability) => match self {
+                HmrMessage::Patch { .. }
+                | HmrMessage::CssUpdate { .. }
+                | HmrMessage::AssetUpdate { .. } => Some(HmrMessage::FullReload),
+                _ => None,
+            },
+            _ => Some(self),
+        }
+    }
+}
+
+/// Cloneable handle for pushing custom events to every connected HMR client.
+///
+/// Modules receive them through `import.meta.hot.on(event, cb)`.
+#[derive(Debug, Clone)]
+pub struct HmrChannel {
+    tx: broadcast::Sender<HmrMessage>,
+}
+
+impl HmrChannel {
+    pub fn new(tx: broadcast::Sender<HmrMessage>) -> Self {
+        Self { tx }
+    }
+
+    /// Broadcasts `event` with `data` serialized as JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `data` cannot be serialized.
+    pub fn send(&self, event: impl Into<String>, data: impl Serialize) -> PalladinResult {
+        let data = serde_json::to_value(data).map_err(|e| PalladinError::Build(e.into()))?;
+
+        // No receivers just means no browser is connected
+        let _ = self.tx.send(HmrMessage::Custom {
+            event: event.into(),
+            data,
+        });
+        Ok(())
+    }
+}
+
+/// Messages sent by browsers over the `/__hmr` websocket.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(
+    tag = "type",
+    rename_all = "kebab-case",
+    rename_all_fields = "camelCase"
+)]
+#[non_exhaustive]
+pub enum ClientMessage {
+    /// A patch for `path` was applied without reloading the page.
+    HotAccepted { path: String },
+    /// The user closed the error overlay.
+    ErrorOverlayDismissed,
+    /// The module `path` called `import.meta.hot.invalidate()`, its
+    /// importers are to be updated instead.
+    Invalidate { path: String },
+    /// Application defined event sent with `import.meta.hot.send(event, data)`.
+    Custom { event: String, data: Value },
+}
+
+impl ClientMessage {
+    /// Name handlers are registered under, the custom event name for
+    /// [`ClientMessage::Custom`] and the message type otherwise.
+    pub fn event(&self) -> &str {
+        match self {
+            ClientMessage::HotAccepted { .. } => "hot-accepted",
+            ClientMessage::ErrorOverlayDismissed => "error-overlay-dismissed",
+            ClientMessage::Invalidate { .. } => "invalidate",
+            ClientMessage::Custom { event, .. } => event,
+        }
+    }
+}
+
+/// Callback invoked with the id of the sending client and its message.
+pub type ClientMessageHandler = Arc<dyn Fn(&str, &ClientMessage) + Send + Sync>;
+
+/// Handlers for messages sent by clients, keyed by event name.
+#[derive(Default)]
+pub struct ClientHandlers {
+    handlers: RwLock<HashMap<String, Vec<ClientMessageHandler>>>,
+}
+
+impl ClientHandlers {
+    pub fn add(&self, event: impl Into<String>, handler: ClientMessageHandler) {
+        self.handlers
+            .write()
+            .entry(event.into())
+            .or_default()
+            .push(handler);
+    }
+
+    /// Runs every handler registered for the message's event.
+    ///
+    /// Returns `false` if there are none.
+    pub fn dispatch(&self, client_id: &str, message: &ClientMessage) -> bool {
+        // Clone the handlers so they can register further handlers themselves
+        let handlers = self
+            .handlers
+            .read()
+            .get(message.event())
+            .cloned()
+            .unwrap_or_default();
+
+        for handler in &handlers {
+            handler(client_id, message);
+        }
+        !handlers.is_empty()
+    }
+}
+
+/// Registry of connected HMR clients, used to deliver client-specific messages.
+#[derive(Default)]
+pub struct HmrClients {
+    clients: RwLock<HashMap<String, UnboundedSender<HmrMessage>>>,
+    next_id: AtomicU64,
+}
+
+impl HmrClients {
+    /// Registers a new client and returns its id and the receiving end of its
+    /// message channel.
+    pub fn register(&self) -> (String, UnboundedReceiver<HmrMessage>) {
+        let id = format!("client-{}", self.next_id.fetch_add(1, Ordering::SeqCst));
+        let (tx, rx) = unbounded_channel();
+        self.clients.write().insert(id.clone(), tx);
+        (id, rx)
+    }
+
+    pub fn unregister(&self, id: &str) {
+        self.clients.write().remove(id);
+    }
+
+    /// Sends a message to a single client, returning `false` if it's not connected.
+    pub fn send_to(&self, id: &str, message: HmrMessage) -> bool {
+        self.clients
+            .read()
+            .get(id)
+            .is_some_and(|tx| tx.send(message).is_ok())
+    }
+
+    /// Returns the ids of all connected clients.
+    pub fn ids(&self) -> Vec<String> {
+        self.clients.read().keys().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.clients.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clients.read().is_empty()
+    }
+}
+
+/// Clients to reload once the next build is served.
+///
+/// The dev engine asks for reloads as soon as it sees a change, before the
+/// rebuild is done, so reloading right away would load the old output.
+#[derive(Debug, Default)]
+pub struct PendingReloads {
+    clients: Mutex<HashSet<String>>,
+    all: AtomicBool,
+}
+
+/// Who to reload once a build is served.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Reload {
+    All,
+    Clients(Vec<String>),
+}
+
+impl PendingReloads {
+    /// Reloads the client `client_id` once the next build is served.
+    pub fn add(&self, client_id: String) {
+        self.clients.lock().insert(client_id);
+    }
+
+    /// Reloads every client once the next build is served.
+    pub fn add_all(&self) {
+        self.all.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns who to reload and forgets about them, `None` if nobody.
+    pub fn take(&self) -> Option<Reload> {
+        let clients = std::mem::take(&mut *self.clients.lock());
+        if self.all.swap(false, Ordering::SeqCst) {
+            Some(Reload::All)
+        } else if clients.is_empty() {
+            None
+        } else {
+            Some(Reload::Clients(clients.into_iter().collect()))
+        }
+    }
+}
+
+/// Inserts the HMR client script tag into an HTML document, tagged with the
+/// version of palladin and the build the document is served for.
+pub fn inject_hmr_client(html: &str, build_id: u64) -> String {
+    let src = format!(
+        "{HMR_CLIENT_PATH}?{VERSION_QUERY_PARAM}={PALLADIN_VERSION}&{BUILD_QUERY_PARAM}={build_id}"
+    );
+    let tag = format!(r#"<script type="module" src="{src}"></script>"#);
+
+    inject_into_head(html, &tag)
+}
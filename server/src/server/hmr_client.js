@@ -0,0 +1,404 @@
+// Palladin HMR client, injected into every served HTML page.
+
+// Must match `HMR_PROTOCOL_VERSION` on the server.
+const PROTOCOL_VERSION = 1;
+// Optional message types this client understands.
+const CAPABILITIES = [
+  "prune",
+  "patch",
+  "css-update",
+  "asset-update",
+  "custom",
+  "client-messages",
+];
+
+const BASE_RECONNECT_DELAY = 500;
+const MAX_RECONNECT_DELAY = 10_000;
+
+// Alt+Shift+H toggles the debugging panel, which stays open across reloads.
+const PANEL_KEY = "KeyH";
+const PANEL_STORAGE_KEY = "palladin:hmr-panel";
+// Number of applied updates listed in the panel.
+const RECENT_UPDATES = 10;
+
+// Connect back to the origin this script was loaded from, so every
+// listen address the page was opened on works. The socket lives next to
+// this script, which is under the server's base path.
+const origin = new URL(import.meta.url);
+const protocol = origin.protocol === "https:" ? "wss:" : "ws:";
+const socketPath = new URL("__hmr", origin).pathname;
+const statusUrl = new URL("__palladin/hmr", origin).href;
+// Build the page was served for, so the server can tell it's stale.
+const build = origin.searchParams.get("build");
+// Version of palladin that served the page, so a page cached before an
+// upgrade is reloaded instead of running this client against it.
+const version = origin.searchParams.get("v");
+
+let serverId = null;
+let serverCapabilities = [];
+let attempts = 0;
+let socket = null;
+
+// What the debugging panel shows, kept up to date while it's closed.
+const debug = {
+  status: "connecting",
+  clientId: null,
+  // Latest updates applied, newest first
+  updates: [],
+  // Patches being evaluated
+  pending: new Set(),
+  // Last answer of the server to a stats request
+  server: null,
+};
+let panel = null;
+
+// Lets the `import.meta.hot` runtime bundled with the app reach the server.
+// Its `on(event, cb)` listeners are kept here by event name, so custom events
+// are delivered to them.
+const bridge = (globalThis.__palladin_hmr__ ??= { listeners: new Map(), send: null });
+bridge.send = send;
+
+function connect() {
+  const query = new URLSearchParams({
+    protocol: PROTOCOL_VERSION,
+    capabilities: CAPABILITIES.join(","),
+  });
+  if (serverId) {
+    query.set("serverId", serverId);
+  }
+  if (build) {
+    query.set("build", build);
+  }
+  if (version) {
+    query.set("version", version);
+  }
+  socket = new WebSocket(`${protocol}//${origin.host}${socketPath}?${query}`);
+
+  socket.addEventListener("open", () => {
+    attempts = 0;
+    setStatus("connected");
+  });
+
+  socket.addEventListener("message", (event) => {
+    handleMessage(JSON.parse(event.data));
+  });
+
+  socket.addEventListener("close", scheduleReconnect);
+}
+
+// Messages sent while disconnected, or to a server that doesn't accept
+// them, are dropped. Returns whether the message was sent.
+function send(message) {
+  if (
+    socket?.readyState === WebSocket.OPEN &&
+    serverCapabilities.includes("client-messages")
+  ) {
+    socket.send(JSON.stringify(message));
+    return true;
+  }
+  return false;
+}
+
+function scheduleReconnect() {
+  const delay = Math.min(
+    BASE_RECONNECT_DELAY * 2 ** attempts,
+    MAX_RECONNECT_DELAY,
+  );
+  attempts += 1;
+  setStatus(`disconnected, retrying in ${delay}ms`);
+
+  console.debug(`[palladin] connection lost, reconnecting in ${delay}ms`);
+  setTimeout(connect, delay);
+}
+
+function handleMessage(message) {
+  switch (message.type) {
+    case "connected":
+      serverId = message.serverId;
+      serverCapabilities = message.capabilities ?? [];
+      debug.clientId = message.clientId;
+      renderPanel();
+      if (message.protocol !== PROTOCOL_VERSION) {
+        console.warn(
+          `[palladin] server speaks HMR protocol ${message.protocol}, client speaks ${PROTOCOL_VERSION}`,
+        );
+      }
+      console.debug("[palladin] connected");
+      break;
+    case "full-reload":
+      location.reload();
+      break;
+    case "prune":
+      prune(message.paths);
+      recordUpdate(message.type, message.paths);
+      break;
+    case "patch":
+      applyPatch(message);
+      break;
+    case "css-update":
+      updateStylesheets(message.paths);
+      recordUpdate(message.type, message.paths);
+      break;
+    case "asset-update":
+      updateAssets(message.paths);
+      recordUpdate(message.type, message.paths);
+      break;
+    case "custom":
+      emit(message.event, message.data);
+      break;
+    default:
+      console.debug(`[palladin] ignoring unknown message "${message.type}"`);
+  }
+}
+
+function emit(event, data) {
+  for (const callback of bridge.listeners.get(event) ?? []) {
+    try {
+      callback(data);
+    } catch (err) {
+      console.error(`[palladin] error in "${event}" listener`, err);
+    }
+  }
+}
+
+// Drops stylesheets and scripts of deleted files and lets the app clean up
+// anything else through a `palladin:prune` event.
+function prune(paths) {
+  for (const element of document.querySelectorAll("link[href], script[src]")) {
+    const url = new URL(element.href || element.src, location.href);
+    if (url.origin === location.origin && paths.includes(url.pathname)) {
+      element.remove();
+    }
+  }
+
+  window.dispatchEvent(new CustomEvent("palladin:prune", { detail: { paths } }));
+}
+
+// Fetches regenerated stylesheets again, swapping each link once its
+// replacement loaded so the page never renders unstyled.
+function updateStylesheets(paths) {
+  const links = document.querySelectorAll('link[rel="stylesheet"][href]');
+  for (const link of links) {
+    const url = new URL(link.href, location.href);
+    if (url.origin !== location.origin || !paths.includes(url.pathname)) {
+      continue;
+    }
+
+    url.searchParams.set("t", Date.now());
+    const replacement = link.cloneNode();
+    replacement.href = url.href;
+    replacement.addEventListener("load", () => link.remove());
+    replacement.addEventListener("error", () => replacement.remove());
+    link.after(replacement);
+  }
+  console.debug(`[palladin] updated ${paths.join(", ")}`);
+}
+
+// Fetches changed files again by pointing the elements and styles
+// referencing them, e.g. images, icons and backgrounds, at a fresh URL.
+function updateAssets(paths) {
+  const version = Date.now();
+  const fresh = (value, base = location.href) => {
+    const url = new URL(value, base);
+    if (url.origin !== location.origin || !paths.includes(url.pathname)) {
+      return value;
+    }
+    url.searchParams.set("t", version);
+    return url.href;
+  };
+  const update = (element, attribute, value) => {
+    if (value !== element.getAttribute(attribute)) {
+      element.setAttribute(attribute, value);
+    }
+  };
+
+  const elements = document.querySelectorAll("[src], [srcset], link[href]");
+  for (const element of elements) {
+    if (element.hasAttribute("src")) {
+      update(element, "src", fresh(element.getAttribute("src")));
+    }
+    if (element.hasAttribute("srcset")) {
+      const srcset = element
+        .getAttribute("srcset")
+        .split(",")
+        .map((candidate) => {
+          const [url, ...descriptor] = candidate.trim().split(/\s+/);
+          return [fresh(url), ...descriptor].join(" ");
+        })
+        .join(", ");
+      update(element, "srcset", srcset);
+    }
+    if (element.localName === "link" && element.rel !== "stylesheet") {
+      update(element, "href", fresh(element.getAttribute("href")));
+    }
+  }
+
+  for (const element of document.querySelectorAll("[style]")) {
+    updateStyleUrls(element.style, (url) => fresh(url));
+  }
+  for (const sheet of document.styleSheets) {
+    const base = sheet.href ?? location.href;
+    updateRuleUrls(sheet, (url) => fresh(url, base));
+  }
+  console.debug(`[palladin] updated ${paths.join(", ")}`);
+}
+
+// Rewrites the `url()` references of the rules in `sheet`, including the
+// ones nested in media queries and the like.
+function updateRuleUrls(sheet, fresh) {
+  let rules;
+  try {
+    rules = sheet.cssRules;
+  } catch {
+    // Rules of cross-origin stylesheets can't be read
+    return;
+  }
+
+  for (const rule of rules) {
+    if (rule.style) {
+      updateStyleUrls(rule.style, fresh);
+    }
+    if (rule.cssRules) {
+      updateRuleUrls(rule, fresh);
+    }
+  }
+}
+
+function updateStyleUrls(style, fresh) {
+  for (const property of Array.from(style)) {
+    const value = style.getPropertyValue(property);
+    if (!value.includes("url(")) {
+      continue;
+    }
+
+    const updated = value.replace(
+      /url\((['"]?)([^'")]+)\1\)/g,
+      (_, quote, url) => `url(${quote}${fresh(url)}${quote})`,
+    );
+    if (updated !== value) {
+      style.setProperty(property, updated, style.getPropertyPriority(property));
+    }
+  }
+}
+
+// Evaluates a patch the dev engine produced for this client.
+async function applyPatch({ filename, code }) {
+  const url = URL.createObjectURL(
+    new Blob([`${code}\n//# sourceURL=${filename}`], { type: "text/javascript" }),
+  );
+  debug.pending.add(filename);
+  renderPanel();
+
+  try {
+    await import(url);
+    console.debug(`[palladin] applied update for ${filename}`);
+    send({ type: "hot-accepted", path: filename });
+    recordUpdate("patch", [filename]);
+  } catch (err) {
+    console.error(`[palladin] failed to apply update for ${filename}`, err);
+    location.reload();
+  } finally {
+    URL.revokeObjectURL(url);
+    debug.pending.delete(filename);
+    renderPanel();
+  }
+}
+
+function setStatus(status) {
+  debug.status = status;
+  renderPanel();
+}
+
+function recordUpdate(type, paths) {
+  debug.updates.unshift({ type, paths, at: new Date() });
+  debug.updates.length = Math.min(debug.updates.length, RECENT_UPDATES);
+  renderPanel();
+}
+
+// Asks the server for its current build and connected clients.
+async function refreshServerStats() {
+  try {
+    const response = await fetch(statusUrl, { cache: "no-store" });
+    debug.server = response.ok ? await response.json() : null;
+  } catch {
+    debug.server = null;
+  }
+  renderPanel();
+}
+
+function togglePanel() {
+  if (panel) {
+    panel.remove();
+    panel = null;
+    sessionStorage.removeItem(PANEL_STORAGE_KEY);
+    return;
+  }
+
+  panel = document.createElement("palladin-hmr-panel");
+  panel.style.cssText = [
+    "position: fixed",
+    "right: 8px",
+    "bottom: 8px",
+    "z-index: 2147483647",
+    "max-width: 420px",
+    "max-height: 50vh",
+    "overflow: auto",
+    "padding: 8px 10px",
+    "border-radius: 6px",
+    "background: rgba(20, 20, 20, 0.92)",
+    "color: #eee",
+    "font: 12px/1.5 ui-monospace, monospace",
+    "white-space: pre-wrap",
+  ].join(";");
+  panel.title = "Click to refresh, Alt+Shift+H to close";
+  panel.addEventListener("click", refreshServerStats);
+  document.body.append(panel);
+  sessionStorage.setItem(PANEL_STORAGE_KEY, "open");
+  renderPanel();
+  refreshServerStats();
+}
+
+// Writes the debugging state into the panel, as text so nothing sent by the
+// server is interpreted as markup.
+function renderPanel() {
+  if (!panel) {
+    return;
+  }
+
+  const server = debug.server;
+  const stale = server && build && String(server.buildId) !== build;
+  const lines = [
+    "palladin HMR",
+    `status:   ${debug.status}`,
+    `client:   ${debug.clientId ?? "-"}`,
+    `build:    ${build ?? "-"}${stale ? ` (server is on ${server.buildId}, reload)` : ""}`,
+    `server:   ${server ? `${server.serverId}, ${server.clients} client(s)` : "unknown"}`,
+    `pending:  ${debug.pending.size ? [...debug.pending].join(", ") : "none"}`,
+    "updates:",
+    ...debug.updates.map(
+      ({ type, paths, at }) =>
+        `  ${at.toLocaleTimeString()} ${type} ${paths.join(", ")}`,
+    ),
+  ];
+  if (!debug.updates.length) {
+    lines.push("  none yet");
+  }
+  panel.textContent = lines.join("\n");
+}
+
+window.addEventListener("keydown", (event) => {
+  if (event.altKey && event.shiftKey && event.code === PANEL_KEY) {
+    event.preventDefault();
+    togglePanel();
+  }
+});
+
+if (sessionStorage.getItem(PANEL_STORAGE_KEY)) {
+  if (document.body) {
+    togglePanel();
+  } else {
+    document.addEventListener("DOMContentLoaded", togglePanel, { once: true });
+  }
+}
+
+connect();
@@ -0,0 +1,158 @@
+use globset::{Glob, GlobMatcher};
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// What the dev server does when a file that isn't bundled changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HmrAction {
+    /// Refetches the elements of the page referencing the file, e.g. images
+    Refresh,
+    /// Sends a custom event with the URL of the file, for the app to handle
+    Event(String),
+    /// Reloads the pages that loaded the file
+    Reload,
+    /// Leaves the pages as they are
+    Ignore,
+}
+
+impl fmt::Display for HmrAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HmrAction::Refresh => f.write_str("refresh"),
+            HmrAction::Event(event) => write!(f, "event:{event}"),
+            HmrAction::Reload => f.write_str("reload"),
+            HmrAction::Ignore => f.write_str("ignore"),
+        }
+    }
+}
+
+impl FromStr for HmrAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "refresh" => Ok(HmrAction::Refresh),
+            "reload" => Ok(HmrAction::Reload),
+            "ignore" => Ok(HmrAction::Ignore),
+            s => match s.strip_prefix("event:") {
+                Some(event) if !event.is_empty() => Ok(HmrAction::Event(event.to_string())),
+                _ => Err(format!(
+                    "unknown HMR action `{s}`, expected one of: refresh, reload, ignore, \
+                     event:NAME"
+                )),
+            },
+        }
+    }
+}
+
+/// Applies `action` to the changed files matching `glob`.
+#[derive(Debug, Clone)]
+pub struct HmrRule {
+    /// Glob matched against root-relative paths, e.g. `**/*.md`, only set
+    /// through [`HmrRule::new`] so it stays in sync with the compiled matcher
+    glob: String,
+    pub action: HmrAction,
+    matcher: GlobMatcher,
+}
+
+impl HmrRule {
+    /// Creates a rule applying `action` to the files matching `glob`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `glob` is invalid.
+    pub fn new(glob: &str, action: HmrAction) -> Result<Self, String> {
+        let matcher = Glob::new(glob)
+            .map_err(|err| format!("invalid glob `{glob}`: {err}"))?
+            .compile_matcher();
+        Ok(Self {
+            glob: glob.to_string(),
+            action,
+            matcher,
+        })
+    }
+
+    /// Returns the glob changed files are matched against.
+    #[inline(always)]
+    pub fn glob(&self) -> &str {
+        &self.glob
+    }
+}
+
+/// Parses `GLOB=ACTION`, e.g. `**/*.md=event:content-update`.
+impl FromStr for HmrRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (glob, action) = s
+            .rsplit_once('=')
+            .ok_or_else(|| format!("invalid HMR rule `{s}`, expected GLOB=ACTION"))?;
+        HmrRule::new(glob.trim(), action.parse()?)
+    }
+}
+
+/// Decides how pages are updated when files the bundler doesn't handle
+/// change, e.g. images or markdown read at runtime.
+#[derive(Debug, Clone)]
+pub struct HmrPolicy {
+    /// Action for changed files that no rule matches
+    pub default_action: HmrAction,
+    /// Rules checked in order, the first one matching the path wins
+    pub rules: Vec<HmrRule>,
+}
+
+impl HmrPolicy {
+    /// Creates a policy applying `default_action` to every changed file.
+    pub fn new(default_action: HmrAction) -> Self {
+        Self {
+            default_action,
+            rules: Vec::new(),
+        }
+    }
+
+    /// Returns a new `HmrPolicy` with an additional rule, checked after the
+    /// existing ones.
+    #[must_use]
+    pub fn with_rule(mut self, rule: HmrRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Returns a new `HmrPolicy` with the given rules checked before the
+    /// existing ones.
+    #[must_use]
+    pub fn with_overrides(mut self, rules: impl IntoIterator<Item = HmrRule>) -> Self {
+        let defaults = std::mem::take(&mut self.rules);
+        self.rules = rules.into_iter().chain(defaults).collect();
+        self
+    }
+
+    /// Returns the action for the changed file at the root-relative `path`.
+    pub fn action_for(&self, path: &Path) -> &HmrAction {
+        self.rules
+            .iter()
+            .find(|rule| rule.matcher.is_match(path))
+            .map(|rule| &rule.action)
+            .unwrap_or(&self.default_action)
+    }
+}
+
+impl Default for HmrPolicy {
+    /// Images are refreshed in place, markdown changes are sent as a
+    /// `palladin:markdown` event and everything else reloads the page.
+    fn default() -> Self {
+        let rule = |glob: &str, action| {
+            HmrRule::new(glob, action).expect("default HMR rule globs are valid")
+        };
+        Self::new(HmrAction::Reload)
+            .with_rule(rule(
+                "**/*.{png,jpg,jpeg,gif,svg,webp,avif,ico}",
+                HmrAction::Refresh,
+            ))
+            .with_rule(rule(
+                "**/*.{md,mdx}",
+                HmrAction::Event("palladin:markdown".to_string()),
+            ))
+    }
+}
@@ -0,0 +1,44 @@
+use axum::extract::{Request, State};
+use axum::http::{Method, Uri};
+use axum::middleware::Next;
+use axum::response::Response;
+use std::sync::Arc;
+
+/// Hooks run around every request handled by the dev server, e.g. to add
+/// authentication, rewrite headers or mock API responses.
+///
+/// Register them with [`Server::add_hooks`](super::Server::add_hooks) before
+/// the router is built. Each registration is applied as its own layer, and
+/// hooks registered first see requests first and responses last.
+pub trait ServerHooks: Send + Sync + 'static {
+    /// Called before the request is routed. Returning a response skips the
+    /// remaining hooks and the dev server's handlers.
+    fn on_request(&self, request: &mut Request) -> Option<Response> {
+        let _ = request;
+        None
+    }
+
+    /// Called with the response for the request to `method` `uri`, including
+    /// responses returned by `on_request`.
+    fn on_response(&self, method: &Method, uri: &Uri, response: &mut Response) {
+        let _ = (method, uri, response);
+    }
+}
+
+/// Middleware running a single registration of [`ServerHooks`].
+pub async fn run_hooks(
+    State(hooks): State<Arc<dyn ServerHooks>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let method = request.method().clone();
+    let uri = request.uri().clone();
+
+    let mut response = match hooks.on_request(&mut request) {
+        Some(response) => response,
+        None => next.run(request).await,
+    };
+
+    hooks.on_response(&method, &uri, &mut response);
+    response
+}
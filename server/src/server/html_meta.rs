@@ -0,0 +1,212 @@
+use crate::html::{element_contents, inject_into_head, opening_tags, tag_attribute};
+use std::fmt;
+use std::str::FromStr;
+
+/// Document metadata set on every served and built HTML page, so values
+/// that differ per environment don't need an HTML file for each.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HtmlMeta {
+    /// Language of the documents, set as `<html lang>`
+    pub lang: Option<String>,
+    /// Text appended to the `<title>` of every page, e.g. ` (staging)`
+    pub title_suffix: Option<String>,
+    /// `<meta>` tags replacing those with the same name in the page
+    pub tags: Vec<MetaTag>,
+}
+
+impl HtmlMeta {
+    /// Returns whether the pages are left as they are.
+    pub fn is_empty(&self) -> bool {
+        self.lang.is_none() && self.title_suffix.is_none() && self.tags.is_empty()
+    }
+}
+
+/// A `<meta>` tag, e.g. `<meta name="theme-color" content="#0f172a">`.
+///
+/// Open Graph names like `og:title` are written as `property` attributes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetaTag {
+    pub name: String,
+    pub content: String,
+}
+
+impl MetaTag {
+    /// Creates a tag named `name` with `content`.
+    pub fn new(name: impl Into<String>, content: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            content: content.into(),
+        }
+    }
+
+    /// Returns the attribute the tag is named with.
+    fn attribute(&self) -> &'static str {
+        if self.name.starts_with("og:") {
+            "property"
+        } else {
+            "name"
+        }
+    }
+
+    fn tag(&self) -> String {
+        format!(
+            "<meta {}=\"{}\" content=\"{}\">",
+            self.attribute(),
+            escape(&self.name),
+            escape(&self.content)
+        )
+    }
+}
+
+/// Parses `NAME=CONTENT`, e.g. `description=Palladin docs`.
+impl FromStr for MetaTag {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((name, content)) if !name.trim().is_empty() => {
+                Ok(Self::new(name.trim(), content.trim()))
+            }
+            _ => Err(format!(
+                "invalid meta tag '{s}', expected NAME=CONTENT such as theme-color=#0f172a"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for MetaTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.name, self.content)
+    }
+}
+
+/// Applies `meta` to the document: sets `<html lang>`, suffixes the
+/// `<title>` and replaces or adds the `<meta>` tags.
+pub(crate) fn inject_html_meta(html: &str, meta: &HtmlMeta) -> String {
+    if meta.is_empty() {
+        return html.to_string();
+    }
+
+    let mut html = html.to_string();
+    if let Some(lang) = &meta.lang {
+        html = set_lang(&html, lang);
+    }
+    if let Some(suffix) = &meta.title_suffix {
+        html = suffix_title(&html, suffix);
+    }
+
+    let mut added = String::new();
+    for tag in &meta.tags {
+        let existing = opening_tags(&html, "meta")
+            .into_iter()
+            .find(|existing| tag_attribute(existing, tag.attribute()) == Some(tag.name.as_str()))
+            .map(str::to_string);
+        match existing {
+            Some(existing) => html = html.replacen(&existing, &tag.tag(), 1),
+            None => added.push_str(&format!("{}\n", tag.tag())),
+        }
+    }
+    if added.is_empty() {
+        html
+    } else {
+        inject_into_head(&html, &added)
+    }
+}
+
+/// Sets the `lang` attribute of the `<html>` tag, replacing the page's own.
+fn set_lang(html: &str, lang: &str) -> String {
+    let Some(tag) = opening_tags(html, "html")
+        .into_iter()
+        .find(|tag| tag.len() == "<html>".len() || tag.starts_with("<html "))
+    else {
+        return html.to_string();
+    };
+
+    let lang_attribute = format!(" lang=\"{}\"", escape(lang));
+    let updated = match tag_attribute(tag, "lang") {
+        Some(current) => {
+            // Drops the attribute with its quotes, wherever it is in the tag
+            let start = tag.find(" lang").unwrap_or(0);
+            let value = tag[start..].find(current).unwrap_or(0) + start;
+            let mut end = value + current.len();
+            if tag[end..].starts_with(['"', '\'']) {
+                end += 1;
+            }
+            format!("{}{lang_attribute}{}", &tag[..start], &tag[end..])
+        }
+        None => format!("<html{lang_attribute}{}", &tag["<html".len()..]),
+    };
+    html.replacen(tag, &updated, 1)
+}
+
+/// Appends `suffix` to the contents of the document's `<title>`.
+fn suffix_title(html: &str, suffix: &str) -> String {
+    let Some((tag, title)) = element_contents(html, "title").into_iter().next() else {
+        return html.to_string();
+    };
+    let element = format!("{tag}{title}</title>");
+    let suffixed = format!("{tag}{title}{}</title>", escape(suffix));
+    html.replacen(&element, &suffixed, 1)
+}
+
+/// Escapes `value` for use in an attribute or text.
+pub(crate) fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lang_is_added_or_replaced() {
+        assert_eq!(set_lang("<html><body>", "de"), "<html lang=\"de\"><body>");
+        assert_eq!(
+            set_lang("<html lang=\"en\" dir=\"ltr\">", "de"),
+            "<html lang=\"de\" dir=\"ltr\">"
+        );
+        assert_eq!(
+            set_lang("<html class='dark' lang='en'>", "de"),
+            "<html class='dark' lang=\"de\">"
+        );
+        assert_eq!(set_lang("<body>", "de"), "<body>");
+    }
+
+    #[test]
+    fn title_is_suffixed_and_escaped() {
+        assert_eq!(
+            suffix_title("<head><title>Docs</title></head>", " <staging>"),
+            "<head><title>Docs &lt;staging&gt;</title></head>"
+        );
+        assert_eq!(suffix_title("<head></head>", " (dev)"), "<head></head>");
+    }
+
+    #[test]
+    fn meta_tags_are_parsed_from_name_and_content() {
+        let tag: MetaTag = " description = Palladin docs ".parse().unwrap();
+        assert_eq!(tag, MetaTag::new("description", "Palladin docs"));
+        assert_eq!(tag.to_string(), "description=Palladin docs");
+
+        let tag: MetaTag = "theme-color=#0f172a".parse().unwrap();
+        assert_eq!(tag.content, "#0f172a");
+
+        assert!("description".parse::<MetaTag>().is_err());
+        assert!("=Palladin docs".parse::<MetaTag>().is_err());
+    }
+
+    #[test]
+    fn open_graph_tags_use_the_property_attribute() {
+        assert_eq!(
+            MetaTag::new("og:title", "Docs").tag(),
+            "<meta property=\"og:title\" content=\"Docs\">"
+        );
+        assert_eq!(
+            MetaTag::new("robots", "noindex").tag(),
+            "<meta name=\"robots\" content=\"noindex\">"
+        );
+    }
+}
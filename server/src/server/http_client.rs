@@ -0,0 +1,80 @@
+use anyhow::anyhow;
+use palladin_shared::{PalladinError, PalladinResult};
+use reqwest::{Certificate, Client, Proxy};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Seconds a request may take when no timeout is configured.
+pub const DEFAULT_HTTP_TIMEOUT_SECS: u64 = 30;
+
+/// Seconds connecting may take when no timeout is configured.
+pub const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 10;
+
+/// Settings of the HTTP client shared by everything making outbound
+/// requests, e.g. deploys.
+///
+/// Proxies are read from `HTTPS_PROXY`, `HTTP_PROXY`, `ALL_PROXY` and
+/// `NO_PROXY` unless one is configured.
+#[derive(Debug, Clone)]
+pub struct HttpOptions {
+    /// Time a request may take, including reading the response
+    pub timeout: Duration,
+    pub connect_timeout: Duration,
+    /// Proxy every request goes through, overriding the environment, e.g.
+    /// `http://proxy.internal:3128`
+    pub proxy: Option<String>,
+    /// PEM files of additional certificate authorities to trust, e.g. of a
+    /// corporate TLS-intercepting proxy
+    pub ca_certs: Vec<PathBuf>,
+    /// Accept invalid TLS certificates. Only meant for debugging
+    pub accept_invalid_certs: bool,
+}
+
+impl Default for HttpOptions {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(DEFAULT_HTTP_TIMEOUT_SECS),
+            connect_timeout: Duration::from_secs(DEFAULT_CONNECT_TIMEOUT_SECS),
+            proxy: None,
+            ca_certs: Vec::new(),
+            accept_invalid_certs: false,
+        }
+    }
+}
+
+impl HttpOptions {
+    /// Builds a client with these settings. Clones of it share one
+    /// connection pool.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the proxy URL is invalid or a certificate cannot
+    /// be read.
+    pub fn client(&self) -> PalladinResult<Client> {
+        let mut builder = Client::builder()
+            .user_agent(concat!("palladin/", env!("CARGO_PKG_VERSION")))
+            .timeout(self.timeout)
+            .connect_timeout(self.connect_timeout)
+            .danger_accept_invalid_certs(self.accept_invalid_certs);
+
+        if let Some(proxy) = &self.proxy {
+            let proxy = Proxy::all(proxy).map_err(|err| {
+                PalladinError::Build(anyhow!("invalid proxy URL '{proxy}': {err}"))
+            })?;
+            builder = builder.proxy(proxy);
+        }
+        for path in &self.ca_certs {
+            let pem = fs_err::read(path)?;
+            let certs = Certificate::from_pem_bundle(&pem).map_err(|err| {
+                PalladinError::Build(anyhow!("invalid certificate {}: {err}", path.display()))
+            })?;
+            for cert in certs {
+                builder = builder.add_root_certificate(cert);
+            }
+        }
+
+        builder
+            .build()
+            .map_err(|err| PalladinError::Build(err.into()))
+    }
+}
@@ -0,0 +1,71 @@
+use crate::html::module_script_sources;
+use std::path::{Path, PathBuf};
+
+/// Entrypoints tried, in order, when `index.html` doesn't reference one.
+const CONVENTIONAL_ENTRYPOINTS: &[&str] = &[
+    "src/main.tsx",
+    "src/main.ts",
+    "src/main.jsx",
+    "src/main.js",
+    "src/index.tsx",
+    "src/index.ts",
+    "src/index.jsx",
+    "src/index.js",
+];
+
+/// Finds the nearest directory containing a `package.json`, starting at `start`
+/// and walking up its ancestors.
+pub fn find_project_root(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .find(|dir| dir.join("package.json").is_file())
+        .map(Path::to_path_buf)
+}
+
+/// Infers the bundle entrypoint for a project rooted at `root`.
+///
+/// The first local module script referenced by `index.html` wins, otherwise
+/// the first existing file from [`CONVENTIONAL_ENTRYPOINTS`] is used.
+pub fn infer_entrypoint(root: &Path) -> Option<PathBuf> {
+    infer_entrypoints(root).into_iter().next()
+}
+
+/// Infers the bundle entrypoints for a project rooted at `root`, the way
+/// Vite does.
+///
+/// Every local module script referenced by `index.html` becomes an
+/// entrypoint, in document order, and the tags are pointed at the emitted
+/// chunks when the page is served or built. Without any, the first existing
+/// file from [`CONVENTIONAL_ENTRYPOINTS`] is used.
+pub fn infer_entrypoints(root: &Path) -> Vec<PathBuf> {
+    let mut entrypoints = html_entrypoints(root);
+    if entrypoints.is_empty() {
+        entrypoints.extend(
+            CONVENTIONAL_ENTRYPOINTS
+                .iter()
+                .map(|candidate| root.join(candidate))
+                .find(|path| path.is_file()),
+        );
+    }
+    entrypoints
+}
+
+/// Returns the existing local files loaded by the module scripts of the
+/// `index.html` in `root`, without duplicates.
+fn html_entrypoints(root: &Path) -> Vec<PathBuf> {
+    let Ok(html) = fs_err::read_to_string(root.join("index.html")) else {
+        return Vec::new();
+    };
+
+    let mut entrypoints: Vec<PathBuf> = Vec::new();
+    for src in module_script_sources(&html) {
+        if src.contains("://") || src.starts_with("//") {
+            continue;
+        }
+        let path = root.join(src.trim_start_matches('/'));
+        if path.is_file() && !entrypoints.contains(&path) {
+            entrypoints.push(path);
+        }
+    }
+    entrypoints
+}
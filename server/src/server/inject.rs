@@ -0,0 +1,65 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// An import added to every module using `name` without declaring it,
+/// e.g. `React` for the classic JSX runtime or a polyfilled global.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inject {
+    /// Identifier the import is bound to
+    pub name: String,
+    /// Module specifier imported from, e.g. `react`
+    pub from: String,
+    pub export: InjectExport,
+}
+
+/// What of the module an [`Inject`] binds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InjectExport {
+    /// `import name from 'from'`
+    Default,
+    /// `import { export as name } from 'from'`
+    Named(String),
+    /// `import * as name from 'from'`
+    Namespace,
+}
+
+/// Parses `NAME=MODULE`, `NAME=MODULE#EXPORT` or `NAME=MODULE#*`, e.g.
+/// `React=react` or `Promise=es6-promise#Promise`.
+impl FromStr for Inject {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "invalid inject '{s}', expected NAME=MODULE, NAME=MODULE#EXPORT or NAME=MODULE#*"
+            )
+        };
+        let (name, module) = s.split_once('=').ok_or_else(invalid)?;
+        let (from, export) = match module.rsplit_once('#') {
+            Some((from, "*")) => (from, InjectExport::Namespace),
+            Some((from, "default")) => (from, InjectExport::Default),
+            Some((from, export)) => (from, InjectExport::Named(export.trim().to_string())),
+            None => (module, InjectExport::Default),
+        };
+        let (name, from) = (name.trim(), from.trim());
+        if name.is_empty() || from.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Self {
+            name: name.to_string(),
+            from: from.to_string(),
+            export,
+        })
+    }
+}
+
+impl fmt::Display for Inject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.name, self.from)?;
+        match &self.export {
+            InjectExport::Default => Ok(()),
+            InjectExport::Named(export) => write!(f, "#{export}"),
+            InjectExport::Namespace => f.write_str("#*"),
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use crate::messages::Message;
+use crate::server::terminal::read_line;
+use anyhow::anyhow;
+use log::{info, warn};
+use palladin_shared::{PalladinError, PalladinResult};
+use std::fmt;
+use std::io::{self, IsTerminal, Write};
+use std::path::Path;
+use std::process::Command;
+
+/// Whether packages reported missing are installed automatically.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AutoInstall {
+    /// Only report missing packages
+    #[default]
+    Disabled,
+    /// Ask on the terminal before installing
+    Confirm,
+    /// Install without asking
+    Yes,
+}
+
+/// Package manager a project is managed with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackageManager {
+    Npm,
+    Pnpm,
+    Yarn,
+    Bun,
+}
+
+impl PackageManager {
+    /// Detects the package manager of `root` from the `packageManager` field
+    /// of package.json or the lockfile of `root` or one of its ancestors,
+    /// defaulting to npm.
+    pub fn detect(root: &Path) -> Self {
+        let declared = fs_err::read_to_string(root.join("package.json"))
+            .ok()
+            .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+            .and_then(|manifest| {
+                let field = manifest.get("packageManager")?.as_str()?;
+                Self::from_name(field.split('@').next()?)
+            });
+        if let Some(manager) = declared {
+            return manager;
+        }
+
+        const LOCKFILES: &[(&str, PackageManager)] = &[
+            ("pnpm-lock.yaml", PackageManager::Pnpm),
+            ("yarn.lock", PackageManager::Yarn),
+            ("bun.lockb", PackageManager::Bun),
+            ("bun.lock", PackageManager::Bun),
+            ("package-lock.json", PackageManager::Npm),
+        ];
+        root.ancestors()
+            .find_map(|dir| {
+                LOCKFILES
+                    .iter()
+                    .find(|(lockfile, _)| dir.join(lockfile).is_file())
+                    .map(|(_, manager)| *manager)
+            })
+            .unwrap_or(PackageManager::Npm)
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "npm" => Some(Self::Npm),
+            "pnpm" => Some(Self::Pnpm),
+            "yarn" => Some(Self::Yarn),
+            "bun" => Some(Self::Bun),
+            _ => None,
+        }
+    }
+
+    /// Returns the arguments installing `package`, or every dependency of
+    /// package.json when `None`.
+    pub fn install_args(self, package: Option<&str>) -> Vec<String> {
+        let verb = match (self, package) {
+            (Self::Npm, _) | (_, None) => "install",
+            _ => "add",
+        };
+        let mut args = vec![self.to_string(), verb.to_string()];
+        args.extend(package.map(str::to_string));
+        args
+    }
+}
+
+impl fmt::Display for PackageManager {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Npm => "npm",
+            Self::Pnpm => "pnpm",
+            Self::Yarn => "yarn",
+            Self::Bun => "bun",
+        })
+    }
+}
+
+/// Installs `package` into `root`, or every dependency of package.json when
+/// `None`, asking first unless `policy` is [`AutoInstall::Yes`].
+///
+/// Returns whether the install ran and succeeded.
+///
+/// # Errors
+///
+/// Returns an error if the package manager cannot be started.
+pub fn install(root: &Path, package: Option<&str>, policy: AutoInstall) -> PalladinResult<bool> {
+    let manager = PackageManager::detect(root);
+    let args = manager.install_args(package);
+    let command = args.join(" ");
+
+    match policy {
+        AutoInstall::Disabled => return Ok(false),
+        AutoInstall::Confirm if !confirm(&format!("Run `{command}`?"))? => return Ok(false),
+        _ => {}
+    }
+
+    info!("{}", Message::RunningCommand { command: &command });
+    let status = Command::new(&args[0])
+        .args(&args[1..])
+        .current_dir(root)
+        .status()
+        .map_err(|e| PalladinError::Build(anyhow!("failed to run `{command}`: {e}")))?;
+
+    if !status.success() {
+        warn!(
+            "{}",
+            Message::CommandFailed {
+                command: &command,
+                status: &status
+            }
+        );
+    }
+    Ok(status.success())
+}
+
+/// Asks a yes/no question on the terminal, answering no when stdin isn't one.
+fn confirm(question: &str) -> PalladinResult<bool> {
+    if !io::stdin().is_terminal() {
+        warn!("{}", Message::PromptSkipped { question });
+        return Ok(false);
+    }
+
+    let mut stderr = io::stderr();
+    write!(stderr, "{question} [y/N] ")?;
+    stderr.flush()?;
+
+    let answer = read_line()?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
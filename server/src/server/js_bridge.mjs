@@ -0,0 +1,103 @@
+// Palladin feature running Vite/Rollup plugins in the node host.
+
+import { createRequire } from "node:module";
+import { resolve } from "node:path";
+import { pathToFileURL } from "node:url";
+
+// Minimal plugin context, enough for plugins that only report problems
+const context = {
+  warn(message) {
+    console.error(`[palladin] ${message?.message ?? message}`);
+  },
+  error(message) {
+    throw message instanceof Error ? message : new Error(message);
+  },
+};
+
+// Hooks may be functions or `{ handler }` objects
+function hook(plugin, name) {
+  const value = plugin[name];
+  return typeof value === "function" ? value : value?.handler;
+}
+
+export default async function setup({ root, specifiers }) {
+  // Packages are resolved from the project, not from palladin
+  const require = createRequire(resolve(root, "package.json"));
+  const plugins = [];
+
+  for (const specifier of specifiers) {
+    // Paths are resolved against the project root, anything else as a package
+    const path =
+      specifier.startsWith(".") || specifier.startsWith("/")
+        ? resolve(root, specifier)
+        : require.resolve(specifier);
+    const module = await import(pathToFileURL(path).href);
+    let exported = module.default ?? module;
+    if (typeof exported === "function") {
+      exported = await exported();
+    }
+    plugins.push(...[exported].flat().filter(Boolean));
+  }
+
+  return {
+    async resolveId({ specifier, importer }) {
+      for (const plugin of plugins) {
+        const result = await hook(plugin, "resolveId")?.call(
+          context,
+          specifier,
+          importer ?? undefined,
+          {},
+        );
+        if (result != null && result !== false) {
+          return typeof result === "string" ? result : result.id;
+        }
+      }
+      return null;
+    },
+    async load({ id }) {
+      for (const plugin of plugins) {
+        const result = await hook(plugin, "load")?.call(context, id);
+        if (result != null) {
+          return typeof result === "string" ? result : result.code;
+        }
+      }
+      return null;
+    },
+    async transform({ id, code }) {
+      let changed = false;
+      for (const plugin of plugins) {
+        const result = await hook(plugin, "transform")?.call(context, code, id);
+        if (result != null) {
+          code = typeof result === "string" ? result : (result.code ?? code);
+          changed = true;
+        }
+      }
+      return changed ? code : null;
+    },
+    async transformIndexHtml({ html }) {
+      let changed = false;
+      for (const plugin of plugins) {
+        const result = await hook(plugin, "transformIndexHtml")?.call(
+          context,
+          html,
+          {},
+        );
+        if (typeof result === "string") {
+          html = result;
+          changed = true;
+        }
+      }
+      return changed ? html : null;
+    },
+    // Returns false when a plugin filtered the log out
+    async onLog({ level, log }) {
+      for (const plugin of plugins) {
+        const result = await hook(plugin, "onLog")?.call(context, level, log);
+        if (result === false) {
+          return false;
+        }
+      }
+      return log;
+    },
+  };
+}
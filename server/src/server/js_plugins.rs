@@ -0,0 +1,123 @@
+use super::node::NodeBridge;
+use super::{Diagnostic, PalladinPlugin};
+use crate::messages::Message;
+use log::warn;
+use palladin_shared::PalladinResult;
+use serde_json::{Value, json};
+use std::path::Path;
+
+/// Feature running the plugins in node.
+const BRIDGE: &str = include_str!("js_bridge.mjs");
+
+/// Runs Vite and Rollup plugins written in JavaScript in node,
+/// forwarding the `resolveId`, `load`, `transform`, `transformIndexHtml` and
+/// `onLog` hooks to it.
+///
+/// Plugins only get a minimal `this` context, so those relying on the rest of
+/// the Rollup plugin API won't work.
+#[derive(Debug)]
+pub struct JsPlugins {
+    specifiers: Vec<String>,
+    node: NodeBridge,
+}
+
+impl JsPlugins {
+    /// Loads the plugin modules in node, given as paths relative to `root`
+    /// or as package names.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if node cannot be started or a plugin cannot be
+    /// loaded.
+    pub fn spawn(root: &Path, specifiers: Vec<String>) -> PalladinResult<Self> {
+        let options = json!({ "root": root.to_string_lossy(), "specifiers": specifiers });
+        let node = NodeBridge::spawn("JavaScript plugins", BRIDGE, options)?;
+        Ok(Self { specifiers, node })
+    }
+
+    /// Calls `hook` of the plugins and returns its string result, `None`
+    /// if no plugin handled it.
+    fn call(&self, hook: &str, args: Value) -> PalladinResult<Option<String>> {
+        let result = self.node.call(hook, args)?;
+        Ok(result.as_str().map(str::to_string))
+    }
+}
+
+impl PalladinPlugin for JsPlugins {
+    fn name(&self) -> &str {
+        "js-plugins"
+    }
+
+    fn is_blocking(&self) -> bool {
+        true
+    }
+
+    fn resolve_id(
+        &self,
+        specifier: &str,
+        importer: Option<&str>,
+    ) -> PalladinResult<Option<String>> {
+        self.call(
+            "resolveId",
+            json!({ "specifier": specifier, "importer": importer }),
+        )
+    }
+
+    fn load(&self, id: &str) -> PalladinResult<Option<String>> {
+        self.call("load", json!({ "id": id }))
+    }
+
+    fn transform(&self, id: &str, code: &str) -> PalladinResult<Option<String>> {
+        self.call("transform", json!({ "id": id, "code": code }))
+    }
+
+    fn transform_index_html(&self, html: &str) -> Option<String> {
+        self.call("transformIndexHtml", json!({ "html": html }))
+            .unwrap_or_else(|err| {
+                warn!(
+                    "{}",
+                    Message::PluginsFailed {
+                        error: &err,
+                        specifiers: &self.specifiers
+                    }
+                );
+                None
+            })
+    }
+
+    fn on_diagnostic(&self, mut diagnostic: Diagnostic) -> Option<Diagnostic> {
+        let log = json!({
+            "code": diagnostic.code,
+            "message": diagnostic.message,
+            "id": diagnostic.id,
+        });
+        let result = self.node.call(
+            "onLog",
+            json!({ "level": diagnostic.severity.name(), "log": log }),
+        );
+
+        match result {
+            Ok(Value::Bool(false)) => None,
+            Ok(log) => {
+                // Plugins may have rewritten the log in place
+                if let Some(message) = log.get("message").and_then(Value::as_str) {
+                    diagnostic.message = message.to_string();
+                }
+                if let Some(code) = log.get("code").and_then(Value::as_str) {
+                    diagnostic.code = code.to_string();
+                }
+                Some(diagnostic)
+            }
+            Err(err) => {
+                warn!(
+                    "{}",
+                    Message::PluginsFailed {
+                        error: &err,
+                        specifiers: &self.specifiers
+                    }
+                );
+                Some(diagnostic)
+            }
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use palladin_shared::{PalladinError, PalladinResult};
+use socket2::{Domain, Protocol, Socket, Type};
+use std::net::SocketAddr;
+use tokio::net::{TcpListener, lookup_host};
+
+/// Resolves `address` and binds a listener to it.
+///
+/// Binding the IPv6 unspecified address (`[::]`) clears `IPV6_V6ONLY`, so the
+/// listener accepts IPv4 connections as well on every platform.
+///
+/// # Errors
+///
+/// Returns an error if the address cannot be resolved or bound.
+pub async fn bind(address: &str) -> PalladinResult<TcpListener> {
+    let addr = lookup_host(address)
+        .await
+        .map_err(|e| PalladinError::InvalidAddress(format!("{address}: {e}")))?
+        .next()
+        .ok_or_else(|| PalladinError::InvalidAddress(address.to_string()))?;
+
+    bind_socket(addr)
+}
+
+fn bind_socket(addr: SocketAddr) -> PalladinResult<TcpListener> {
+    let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))?;
+
+    if addr.is_ipv6() && addr.ip().is_unspecified() {
+        socket.set_only_v6(false)?;
+    }
+
+    #[cfg(not(windows))]
+    socket.set_reuse_address(true)?;
+
+    socket.set_nonblocking(true)?;
+    socket.bind(&addr.into())?;
+    socket.listen(1024)?;
+
+    Ok(TcpListener::from_std(socket.into())?)
+}
@@ -0,0 +1,67 @@
+use super::node::NodeBridge;
+use anyhow::anyhow;
+use palladin_shared::{PalladinError, PalladinResult};
+use serde_json::json;
+use std::path::Path;
+
+/// Feature running the MDX compiler in node.
+const WORKER: &str = include_str!("mdx_worker.mjs");
+
+/// Compiles `.mdx` documents to components in node.
+///
+/// `@mdx-js/mdx` is resolved from the project's `node_modules`.
+#[derive(Debug)]
+pub struct Mdx {
+    node: NodeBridge,
+}
+
+impl Mdx {
+    /// Loads the MDX compiler of the project in `root` in node.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if node cannot be started or `@mdx-js/mdx` isn't
+    /// installed.
+    pub fn spawn(root: &Path) -> PalladinResult<Self> {
+        let node = NodeBridge::spawn("MDX", WORKER, json!({ "root": root.to_string_lossy() }))?;
+        Ok(Self { node })
+    }
+
+    /// Compiles the document `source`, read from `path`, into a module
+    /// exporting its component, with JSX imported from `jsx_import_source`
+    /// or React when `None`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the document doesn't compile or node exited.
+    pub fn compile(
+        &self,
+        path: &Path,
+        source: &str,
+        dev: bool,
+        jsx_import_source: Option<&str>,
+    ) -> PalladinResult<String> {
+        let result = self.node.call(
+            "compile",
+            json!({
+                "filename": path.to_string_lossy(),
+                "source": source,
+                "dev": dev,
+                "jsxImportSource": jsx_import_source,
+            }),
+        )?;
+
+        result.as_str().map(str::to_string).ok_or_else(|| {
+            PalladinError::Build(anyhow!("MDX returned no code for {}", path.display()))
+        })
+    }
+}
+
+/// Checks if `@mdx-js/mdx` is installed in the project in `root`.
+pub fn find_mdx(root: &Path) -> bool {
+    root.join("node_modules")
+        .join("@mdx-js")
+        .join("mdx")
+        .join("package.json")
+        .is_file()
+}
@@ -0,0 +1,26 @@
+// Palladin feature compiling MDX documents in the node host.
+
+import { createRequire } from "node:module";
+import { resolve } from "node:path";
+import { pathToFileURL } from "node:url";
+
+export default async function setup({ root }) {
+  // The compiler comes from the project, not from palladin
+  const require = createRequire(resolve(root, "package.json"));
+  const { compile } = await import(
+    pathToFileURL(require.resolve("@mdx-js/mdx")).href
+  );
+
+  return {
+    async compile({ filename, source, dev, jsxImportSource }) {
+      const file = await compile(
+        { path: filename, value: source },
+        { development: dev, jsxImportSource: jsxImportSource ?? undefined },
+      );
+      for (const message of file.messages) {
+        console.error(`[mdx] ${filename}: ${message.reason}`);
+      }
+      return String(file);
+    },
+  };
+}
@@ -0,0 +1,17 @@
+/// How much the bundle output is minified.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Minify {
+    /// Output is left as generated
+    #[default]
+    Off,
+    /// Identifiers are mangled, code is compressed and whitespace is removed
+    On,
+    /// Only whitespace is removed, keeping names readable in stack traces
+    WhitespaceOnly,
+}
+
+palladin_shared::named_enum!(Minify, "minify mode" {
+    Off => "false",
+    On => "true",
+    WhitespaceOnly => "whitespace-only",
+});
@@ -0,0 +1,511 @@
+use super::node::NodeBridge;
+use crate::messages::Message;
+use crate::server::Server;
+use crate::server::errors::HttpResponse;
+use crate::server::probes::DEVTOOLS_JSON_PATH;
+use anyhow::anyhow;
+use axum::body::to_bytes;
+use axum::extract::{Query, Request, State};
+use axum::http::{HeaderName, Method, Response, StatusCode};
+use axum::middleware::Next;
+use axum::response::IntoResponse;
+use log::{debug, info, warn};
+use palladin_shared::{PalladinError, PalladinResult};
+use parking_lot::{Mutex, RwLock};
+use percent_encoding::percent_decode_str;
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+
+/// Directory of the project mocks are read from.
+pub const DEFAULT_MOCKS_DIR: &str = "mocks";
+
+/// Feature running JavaScript mocks in node.
+const WORKER: &str = include_str!("mocks_worker.mjs");
+
+/// Methods a mock file can be limited to, e.g. `users.post.json`.
+const METHODS: [&str; 7] = ["get", "post", "put", "patch", "delete", "head", "options"];
+
+/// Largest request body passed to a mock, axum's default limit.
+const MAX_BODY_SIZE: usize = 2 * 1024 * 1024;
+
+/// Canned API responses served by the dev server, so frontends can be
+/// developed without their backend.
+///
+/// Every file below the directory answers the URL path it's at:
+///
+/// - `api/users.json` answers `/api/users` with its contents
+/// - `api/users/index.json` does the same
+/// - `api/users.post.json` only answers `POST` requests
+/// - `api/users/[id].json` answers `/api/users/1`, `/api/users/2`, ...
+/// - `api/users.js` default exports a value or a function called with the
+///   request, `{ method, path, params, query, headers, body }`. Objects
+///   with a numeric `status` are sent as the response with their `headers`
+///   and `body`, anything else as JSON.
+///
+/// Files are read on every request, added and removed files are picked up
+/// as they change.
+/// Files of the same route naming their parameters differently, e.g.
+/// `users/[id].json` and `users/[slug].post.json`, each get the parameters
+/// by their own names.
+#[derive(Debug)]
+pub struct Mocks {
+    dir: PathBuf,
+    /// Routes of the mock files, read when the router is built and again
+    /// when files below the directory change, matched on every request
+    routes: RwLock<Vec<MockRoute>>,
+    /// Runs JavaScript mocks in node, set up with the first one
+    node: Mutex<Option<NodeBridge>>,
+}
+
+/// A route answered by mock files.
+#[derive(Debug, Clone)]
+pub(crate) struct MockRoute {
+    /// Route in axum's syntax, e.g. `/api/users/{id}`, named after the
+    /// parameters of its first file
+    pub path: String,
+    pub files: Vec<MockFile>,
+}
+
+/// A mock file answering a route.
+#[derive(Debug, Clone)]
+pub(crate) struct MockFile {
+    /// Method the file answers, `None` answering every method
+    method: Option<Method>,
+    /// Names the file gives the route's parameters, in order
+    params: Vec<String>,
+    path: PathBuf,
+}
+
+#[derive(Debug, Deserialize)]
+struct MockResponse {
+    status: u16,
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+    body: String,
+}
+
+impl Mocks {
+    /// Creates mocks read from `dir`.
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            routes: RwLock::new(Vec::new()),
+            node: Mutex::new(None),
+        }
+    }
+
+    /// Returns the directory mocks are read from.
+    #[inline(always)]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Reads the routes of the mock files, sorted by path.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory cannot be read.
+    pub(crate) fn read_routes(&self) -> PalladinResult<Vec<MockRoute>> {
+        let mut files = mock_files(&self.dir)?;
+        files.sort();
+
+        // Routes only differing in the names of their parameters are the
+        // same route, keyed by their path without the names
+        let mut routes: BTreeMap<String, MockRoute> = BTreeMap::new();
+        for file in files {
+            let Some((path, method)) = self.route_of(&file) else {
+                continue;
+            };
+            let params = route_params(&path);
+            let shape = params.iter().fold(path.clone(), |shape, param| {
+                shape.replace(&format!("{{{param}}}"), "{}")
+            });
+            routes
+                .entry(shape)
+                .or_insert_with(|| MockRoute {
+                    path,
+                    files: Vec::new(),
+                })
+                .files
+                .push(MockFile {
+                    method,
+                    params,
+                    path: file,
+                });
+        }
+
+        Ok(routes.into_values().collect())
+    }
+
+    /// Replaces the routes requests are answered with, returning the
+    /// previous ones.
+    pub(crate) fn replace_routes(&self, routes: Vec<MockRoute>) -> Vec<MockRoute> {
+        std::mem::replace(&mut *self.routes.write(), routes)
+    }
+
+    /// Returns the route answering the URL `path` along with the values of
+    /// its parameters by name. Like axum, routes without a parameter at a
+    /// segment win over the ones with one.
+    fn find(&self, path: &str) -> Option<(MockRoute, HashMap<String, String>)> {
+        self.routes
+            .read()
+            .iter()
+            .filter_map(|route| Some((route, match_route(&route.path, path)?)))
+            .min_by_key(|(route, _)| {
+                route
+                    .path
+                    .split('/')
+                    .map(|segment| segment.starts_with('{'))
+                    .collect::<Vec<_>>()
+            })
+            .map(|(route, params)| (route.clone(), params))
+    }
+
+    /// Returns the route and method `file` answers, `None` for files that
+    /// aren't mocks.
+    fn route_of(&self, file: &Path) -> Option<(String, Option<Method>)> {
+        let extension = file.extension()?.to_str()?;
+        if !matches!(extension, "json" | "js" | "mjs") {
+            return None;
+        }
+
+        let relative = file.strip_prefix(&self.dir).ok()?.with_extension("");
+        let mut segments: Vec<String> = relative
+            .iter()
+            .map(|segment| segment.to_string_lossy().to_string())
+            .collect();
+        let name = segments.pop()?;
+        let (name, method) = match name.rsplit_once('.') {
+            Some((name, method)) if METHODS.contains(&method) => (
+                name.to_string(),
+                Method::from_bytes(method.to_uppercase().as_bytes()).ok(),
+            ),
+            _ => (name, None),
+        };
+        if name != "index" {
+            segments.push(name);
+        }
+        if segments.is_empty() {
+            return Some(("/".to_string(), method));
+        }
+
+        let path: String = segments
+            .iter()
+            .map(
+                |segment| match segment.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+                    Some(param) => format!("/{{{param}}}"),
+                    None => format!("/{segment}"),
+                },
+            )
+            .collect();
+        Some((path, method))
+    }
+
+    /// Answers `request` with the mock `file`.
+    async fn respond(
+        &self,
+        file: &Path,
+        request: serde_json::Value,
+    ) -> PalladinResult<Response<String>> {
+        let is_json = file.extension().is_some_and(|ext| ext == "json");
+        if is_json {
+            return Ok(HttpResponse::ok(fs_err::read_to_string(file)?)
+                .content_type("application/json")
+                .cache_control("no-store")
+                .finish());
+        }
+
+        let version = fs_err::metadata(file)?
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default();
+        // Cloned out of the lock, so slow mocks don't hold up the others
+        let node = {
+            let mut node = self.node.lock();
+            match &*node {
+                Some(node) => node.clone(),
+                None => node
+                    .insert(NodeBridge::spawn("API mocks", WORKER, json!({}))?)
+                    .clone(),
+            }
+        };
+        let args =
+            json!({ "file": file.to_string_lossy(), "version": version, "request": request });
+        // Node answers through a blocking channel
+        let result = tokio::task::spawn_blocking(move || node.call("respond", args))
+            .await
+            .map_err(|err| PalladinError::Build(err.into()))??;
+
+        let response: MockResponse = serde_json::from_value(result).map_err(|err| {
+            PalladinError::Build(anyhow!("invalid response from {}: {err}", file.display()))
+        })?;
+        let status = StatusCode::from_u16(response.status).map_err(|err| {
+            PalladinError::Build(anyhow!("invalid status from {}: {err}", file.display()))
+        })?;
+        let mut builder = HttpResponse::new(status, response.body).cache_control("no-store");
+        for (name, value) in &response.headers {
+            match HeaderName::from_bytes(name.as_bytes()) {
+                Ok(name) => builder = builder.header(name, value),
+                Err(_) => debug!("dropping invalid header {name:?} from {}", file.display()),
+            }
+        }
+        Ok(builder.finish())
+    }
+}
+
+/// Returns the mocks directory of the project in `root`, if any.
+pub fn find_mocks(root: &Path) -> Option<PathBuf> {
+    Some(root.join(DEFAULT_MOCKS_DIR)).filter(|dir| dir.is_dir())
+}
+
+/// Returns the names of the parameters of the axum route `path`, in order.
+fn route_params(path: &str) -> Vec<String> {
+    path.split('/')
+        .filter_map(|segment| segment.strip_prefix('{')?.strip_suffix('}'))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Returns the parameters of the route `route` by name, with their values
+/// taken from the URL `path`, or `None` if the route doesn't match it.
+fn match_route(route: &str, path: &str) -> Option<HashMap<String, String>> {
+    let mut params = HashMap::new();
+    let mut segments = path.split('/');
+    for expected in route.split('/') {
+        let segment = percent_decode_str(segments.next()?).decode_utf8_lossy();
+        match expected.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+            Some(param) if !segment.is_empty() => {
+                params.insert(param.to_string(), segment.into_owned());
+            }
+            None if expected == segment => {}
+            _ => return None,
+        }
+    }
+    segments.next().is_none().then_some(params)
+}
+
+/// Returns the files below `dir`.
+fn mock_files(dir: &Path) -> PalladinResult<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs_err::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            files.extend(mock_files(&path)?);
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+/// Answers requests matching a route of the mocks, passing everything else
+/// on.
+///
+/// Routes are looked up in the live route table, so files added while the
+/// server runs are answered without rebuilding the router.
+pub async fn mocks_layer(
+    State(server): State<Arc<Server>>,
+    request: Request,
+    next: Next,
+) -> axum::response::Response {
+    let Some(mocks) = server.config().mocks.clone() else {
+        return next.run(request).await;
+    };
+    let path = request.uri().path();
+    let found = (!server.answers_route(path))
+        .then(|| mocks.find(path))
+        .flatten();
+    let Some((route, params)) = found else {
+        return next.run(request).await;
+    };
+
+    let (parts, body) = request.into_parts();
+    let body = match to_bytes(body, MAX_BODY_SIZE).await {
+        Ok(body) => body,
+        Err(err) => {
+            return HttpResponse::new(StatusCode::PAYLOAD_TOO_LARGE, err.to_string())
+                .finish()
+                .into_response();
+        }
+    };
+    let query: HashMap<String, String> = Query::try_from_uri(&parts.uri)
+        .map(|Query(query)| query)
+        .unwrap_or_default();
+    let headers: BTreeMap<&str, String> = parts
+        .headers
+        .iter()
+        .map(|(name, value)| {
+            let value = String::from_utf8_lossy(value.as_bytes()).to_string();
+            (name.as_str(), value)
+        })
+        .collect();
+    let request = json!({
+        "method": parts.method.as_str(),
+        "path": parts.uri.path(),
+        "params": params,
+        "query": query,
+        "headers": headers,
+        "body": String::from_utf8_lossy(&body),
+    });
+    server
+        .serve_mock_impl(&mocks, &route, &parts.method, request)
+        .await
+        .into_response()
+}
+
+impl Server {
+    /// Reads the routes of the mocks again, e.g. once files below their
+    /// directory changed. Routes the dev server answers itself are left out.
+    pub(crate) fn load_mocks(&self) {
+        let Some(mocks) = &self.config().mocks else {
+            return;
+        };
+        let routes = match mocks.read_routes() {
+            Ok(routes) => routes,
+            Err(err) => {
+                warn!(
+                    "{}",
+                    Message::MocksUnreadable {
+                        dir: mocks.dir(),
+                        error: &err
+                    }
+                );
+                return;
+            }
+        };
+
+        let routes: Vec<MockRoute> = routes
+            .into_iter()
+            .filter(|route| {
+                let shadowed = self.answers_route(&route.path);
+                if shadowed {
+                    warn!("{}", Message::MockShadowed { route: &route.path });
+                }
+                !shadowed
+            })
+            .collect();
+        let previous = mocks.replace_routes(routes.clone());
+        for route in &routes {
+            if !previous.iter().any(|previous| previous.path == route.path) {
+                info!("{}", Message::Mocking { route: &route.path });
+            }
+        }
+    }
+
+    /// Checks if the dev server answers the URL or route `path` itself, so
+    /// no mock may.
+    fn answers_route(&self, path: &str) -> bool {
+        path == "/"
+            || path.starts_with("/__")
+            || (self.config().probe_routes
+                && (path == "/favicon.ico" || path == DEVTOOLS_JSON_PATH))
+    }
+
+    async fn serve_mock_impl(
+        &self,
+        mocks: &Mocks,
+        route: &MockRoute,
+        method: &Method,
+        mut request: serde_json::Value,
+    ) -> PalladinResult<Response<String>> {
+        let file = route
+            .files
+            .iter()
+            .find(|file| file.method.as_ref() == Some(method))
+            .or_else(|| route.files.iter().find(|file| file.method.is_none()));
+        match file {
+            Some(file) => {
+                debug!(
+                    "answering {method} {} with {}",
+                    route.path,
+                    file.path.display()
+                );
+                // Parameters go by the names the file gives them
+                let params = request["params"].take();
+                request["params"] = route_params(&route.path)
+                    .iter()
+                    .zip(&file.params)
+                    .map(|(name, renamed)| (renamed.clone(), params[name].clone()))
+                    .collect::<serde_json::Map<_, _>>()
+                    .into();
+                mocks.respond(&file.path, request).await
+            }
+            None => Ok(HttpResponse::new(
+                StatusCode::METHOD_NOT_ALLOWED,
+                format!("no mock answers {method} {}", route.path),
+            )
+            .finish()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route_of(file: &str) -> Option<(String, Option<Method>)> {
+        Mocks::new(PathBuf::from("/mocks")).route_of(&Path::new("/mocks").join(file))
+    }
+
+    #[test]
+    fn files_answer_the_path_they_are_at() {
+        assert_eq!(
+            route_of("api/users.json"),
+            Some(("/api/users".to_string(), None))
+        );
+        assert_eq!(
+            route_of("api/users/index.js"),
+            Some(("/api/users".to_string(), None))
+        );
+        assert_eq!(route_of("index.mjs"), Some(("/".to_string(), None)));
+    }
+
+    #[test]
+    fn methods_are_taken_from_the_name() {
+        assert_eq!(
+            route_of("api/users.post.json"),
+            Some(("/api/users".to_string(), Some(Method::POST)))
+        );
+        // Only known methods count, anything else is part of the name
+        assert_eq!(
+            route_of("api/users.v2.json"),
+            Some(("/api/users.v2".to_string(), None))
+        );
+    }
+
+    #[test]
+    fn brackets_are_parameters() {
+        assert_eq!(
+            route_of("api/users/[id]/posts/[post].json"),
+            Some(("/api/users/{id}/posts/{post}".to_string(), None))
+        );
+    }
+
+    #[test]
+    fn urls_match_routes_by_segment() {
+        let params = match_route("/api/users/{id}", "/api/users/a%20b").unwrap();
+        assert_eq!(params["id"], "a b");
+        assert_eq!(
+            match_route("/api/users", "/api/users"),
+            Some(HashMap::new())
+        );
+        assert_eq!(match_route("/api/users/{id}", "/api/users/"), None);
+        assert_eq!(match_route("/api/users/{id}", "/api/users/1/posts"), None);
+        assert_eq!(match_route("/api/users", "/api/posts"), None);
+    }
+
+    #[test]
+    fn other_files_are_not_mocks() {
+        assert_eq!(route_of("api/users.txt"), None);
+        assert_eq!(route_of("README"), None);
+        assert_eq!(
+            Mocks::new(PathBuf::from("/mocks")).route_of(Path::new("/api/users.json")),
+            None
+        );
+    }
+}
@@ -0,0 +1,108 @@
+// Palladin feature running the JavaScript API mocks of a project in the node
+// host.
+
+import { pathToFileURL } from "node:url";
+import { Worker } from "node:worker_threads";
+
+// Objects with a numeric status are responses, anything else is sent as the
+// JSON body of a 200 response.
+function toResponse(result) {
+  if (result && typeof result === "object" && typeof result.status === "number") {
+    const { status, headers = {}, body = "" } = result;
+    return {
+      status,
+      headers,
+      body: typeof body === "string" ? body : JSON.stringify(body),
+    };
+  }
+  return {
+    status: 200,
+    headers: { "content-type": "application/json" },
+    body: JSON.stringify(result ?? null),
+  };
+}
+
+// Runs a mock module in its own thread. Edited mocks get a new thread and the
+// old one is stopped, which frees the modules it imported; importing every
+// version into this thread would keep all of them alive.
+const MOCK_THREAD = `
+const { parentPort, workerData } = require("node:worker_threads");
+const mock = import(workerData.url).then((module) => module.default);
+parentPort.on("message", async ({ id, request }) => {
+  try {
+    const value = await mock;
+    const result = typeof value === "function" ? await value(request) : value;
+    parentPort.postMessage({ id, result });
+  } catch (err) {
+    parentPort.postMessage({ id, error: String(err?.stack ?? err) });
+  }
+});
+`;
+
+class MockThread {
+  constructor(file, version) {
+    this.version = version;
+    this.retired = false;
+    this.calls = new Map();
+    this.nextCall = 0;
+    this.worker = new Worker(MOCK_THREAD, {
+      eval: true,
+      workerData: { url: pathToFileURL(file).href },
+    });
+    this.worker.unref();
+    this.worker.on("message", ({ id, result, error }) => {
+      const call = this.calls.get(id);
+      this.calls.delete(id);
+      if (this.retired && this.calls.size === 0) {
+        this.worker.terminate();
+      }
+      if (error === undefined) {
+        call?.resolve(result);
+      } else {
+        call?.reject(new Error(error));
+      }
+    });
+    this.worker.on("error", (err) => this.failAll(err));
+    this.worker.on("exit", () => this.failAll(new Error(`mock ${file} stopped`)));
+  }
+
+  call(request) {
+    this.nextCall += 1;
+    const id = this.nextCall;
+    return new Promise((resolve, reject) => {
+      this.calls.set(id, { resolve, reject });
+      this.worker.postMessage({ id, request });
+    });
+  }
+
+  // Stops the thread once the calls it's answering finished.
+  retire() {
+    this.retired = true;
+    if (this.calls.size === 0) {
+      this.worker.terminate();
+    }
+  }
+
+  failAll(err) {
+    for (const call of this.calls.values()) {
+      call.reject(err);
+    }
+    this.calls.clear();
+  }
+}
+
+export default async function setup() {
+  const threads = new Map();
+  return {
+    async respond({ file, version, request }) {
+      // The version changes with the file, so edits apply without a restart
+      let thread = threads.get(file);
+      if (thread?.version !== version) {
+        thread?.retire();
+        thread = new MockThread(file, version);
+        threads.set(file, thread);
+      }
+      return toResponse(await thread.call(request));
+    },
+  };
+}
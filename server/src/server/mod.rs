@@ -1,41 +1,239 @@
+mod addons;
+mod app_type;
+mod assets;
+mod buffers;
+mod cache_policy;
+mod canonical_urls;
+mod chunk_groups;
 mod config;
 mod context;
+mod diagnostics;
+mod directory_listing;
+mod entrypoint;
+mod error_pages;
+pub(crate) mod errors;
 pub mod files;
+mod graph;
+mod har;
+mod headers;
+mod history;
+pub mod hmr;
+mod hmr_policy;
+mod hooks;
+pub(crate) mod html_meta;
+mod http_client;
+mod infer;
+mod inject;
+mod install;
+mod js_plugins;
+pub(crate) mod listener;
+mod mdx;
+mod minify;
+mod mocks;
+mod module_deps;
+mod node;
+mod noindex;
+pub(crate) mod plugin;
+mod postcss;
+mod preset;
+mod probes;
+mod resource_hints;
+mod restart;
+mod scan;
+mod single_flight;
+mod source_map;
+mod ssr;
+mod svelte;
+mod tailwind;
+mod target;
+mod tenants;
+mod terminal;
+#[cfg(feature = "test-util")]
+mod testing;
+mod toolchain;
+mod treeshake;
+mod vfs;
+mod watch_stats;
+mod watcher;
+mod workspace;
+mod ws;
 
 use crate::file::File;
-use crate::rolldown::{ChunkManager, ChunkProcessor, MainAsset, create_bundler};
+use crate::messages::Message;
+use crate::rolldown::{
+    BundleMode, ChunkManager, ChunkProcessor, MainAsset, ModuleGraph, create_bundler,
+    tag_chunk_imports, validate_artifacts,
+};
+pub use crate::rolldown::{
+    DEFAULT_ASSET_INLINE_LIMIT, DEFAULT_CHUNK_FILENAMES, DEFAULT_CSS_MODULES_PATTERN,
+    DEFAULT_ENTRY_FILENAMES,
+};
+use crate::server::buffers::{discard_buffer_handler, list_buffers_handler, write_buffer_handler};
 pub use crate::server::config::ServerConfig;
+use crate::server::error_pages::error_pages;
 use crate::server::files::{serve_chunk_handler, serve_file_handler, serve_index_handler};
+use crate::server::graph::{serve_execution_order_handler, serve_graph_handler};
+use crate::server::har::{HarRecorder, record_har};
+use crate::server::headers::add_headers;
+use crate::server::history::{
+    BuildHistory, ServedBuild, history_back_handler, history_forward_handler, serve_history_handler,
+};
+use crate::server::hmr::{
+    BUILD_QUERY_PARAM, ClientHandlers, ClientMessage, HMR_CLIENT_PATH, HmrChannel, HmrClients,
+    HmrMessage, PendingReloads, Reload,
+};
+use crate::server::hooks::run_hooks;
+use crate::server::mocks::mocks_layer;
+use crate::server::noindex::{is_exposed, noindex};
+use crate::server::probes::{DEVTOOLS_JSON_PATH, devtools_json_handler, favicon_handler};
+use crate::server::restart::restart_handler;
+use crate::server::single_flight::SingleFlight;
+use crate::server::ssr::{BundledSsrModule, fix_ssr_stacktrace_handler, serve_ssr_module_handler};
+use crate::server::watch_stats::{WatchStats, serve_watcher_handler};
+use crate::server::ws::{hmr_ws_handler, serve_hmr_client_handler, serve_hmr_status_handler};
+pub use addons::ChunkAddon;
 use anyhow::anyhow;
+pub use app_type::{AppType, Rewrite};
+pub use assets::{AssetPolicy, AssetRule, DEFAULT_ASSET_PATTERN};
 use axum::Router;
-use axum::routing::get;
+use axum::http::Response;
+use axum::middleware;
+use axum::routing::{get, post, put};
+pub use cache_policy::{CachePolicy, CacheRule};
+pub(crate) use canonical_urls::Routing;
+pub use canonical_urls::{NonCanonical, REDIRECT_STATUS, REDIRECTS_FILE, TrailingSlash, UrlPolicy};
+pub use chunk_groups::ChunkGroup;
 pub use context::*;
-use log::{error, warn};
-use palladin_shared::PalladinResult;
-use parking_lot::RwLock;
-use rolldown::dev::{DevOptions, RebuildStrategy};
+pub(crate) use diagnostics::filter_diagnostics;
+pub use diagnostics::{Diagnostic, Severity};
+pub use entrypoint::Entrypoint;
+pub use error_pages::ErrorPages;
+use futures::future::try_join_all;
+pub use har::HarOptions;
+pub use headers::ResponseHeader;
+pub use history::DEFAULT_BUILD_HISTORY;
+pub use hmr_policy::{HmrAction, HmrPolicy, HmrRule};
+pub use hooks::ServerHooks;
+pub(crate) use html_meta::inject_html_meta;
+pub use html_meta::{HtmlMeta, MetaTag};
+pub use http_client::{DEFAULT_CONNECT_TIMEOUT_SECS, DEFAULT_HTTP_TIMEOUT_SECS, HttpOptions};
+pub use infer::{find_project_root, infer_entrypoint, infer_entrypoints};
+pub use inject::{Inject, InjectExport};
+pub use install::{AutoInstall, PackageManager, install};
+pub use js_plugins::JsPlugins;
+use log::{debug, error, info, warn};
+pub use mdx::{Mdx, find_mdx};
+pub use minify::Minify;
+pub use mocks::{DEFAULT_MOCKS_DIR, Mocks, find_mocks};
+pub use module_deps::{Dependency, ModuleDependencies};
+pub use noindex::NoIndex;
+use palladin_shared::{PalladinError, PalladinResult};
+use parking_lot::{Mutex, RwLock};
+pub use plugin::PalladinPlugin;
+pub use postcss::{PostCss, find_postcss_config};
+pub use preset::Framework;
+pub use resource_hints::ResourceHint;
+pub(crate) use resource_hints::inject_resource_hints;
+use rolldown::dev::{ClientSession, DevOptions, RebuildStrategy};
 use rolldown::{BundleOutput, DevEngine};
+use rolldown_common::{ClientHmrUpdate, HmrUpdate};
+pub use ssr::{SSR_MODULE_PATH, SSR_STACKTRACE_PATH, SsrModule};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::future::IntoFuture;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tokio::net::TcpListener;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+pub use svelte::{CompiledComponent, Svelte, find_svelte};
+pub use tailwind::{Tailwind, find_tailwind};
+pub(crate) use tailwind::{is_content_file, process_stylesheet, uses_tailwind};
+pub use target::browserslist_targets;
+pub use tenants::{Tenant, git_worktrees, serve_tenants};
+pub use terminal::on_terminal_line;
+#[cfg(feature = "test-util")]
+pub use testing::HmrProbe;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::sync::{Notify, broadcast};
+pub use toolchain::{MIN_NODE_VERSION, ToolRequirement, ToolchainReport, check_toolchain};
+pub use treeshake::TreeshakeConfig;
+pub use vfs::{MemoryFs, OverlayFs, RealFs, Vfs};
+pub use watcher::{
+    DEFAULT_DEBOUNCE_MS, DEFAULT_MAX_DIR_ENTRIES, DEFAULT_POLL_INTERVAL_MS, WatchOptions,
+};
+pub use workspace::SourceEntry;
+pub(crate) use workspace::{SourceAlias, read_manifest, source_aliases, workspace_packages};
 
 pub struct Server {
     pub ctx: Arc<Context>,
     pub files: RwLock<HashMap<PathBuf, File>>,
+    /// Responses of the files being loaded, shared by concurrent requests
+    loads: SingleFlight<PathBuf, Response<String>>,
     chunks: ChunkManager,
-    entry_asset: RwLock<Option<MainAsset>>,
+    /// Entry chunk of every entrypoint
+    entry_assets: RwLock<HashMap<PathBuf, MainAsset>>,
+    graph: RwLock<ModuleGraph>,
+    /// Id of the latest bundle output, carried by the chunk requests it makes
+    build_id: AtomicU64,
+    /// Broadcast channel for messages pushed to HMR clients
+    pub hmr_tx: broadcast::Sender<HmrMessage>,
+    /// Unique id of this server process, used by clients to detect restarts
+    server_id: String,
+    /// Currently connected HMR clients
+    hmr_clients: HmrClients,
+    /// Handlers for messages sent by HMR clients
+    client_handlers: ClientHandlers,
+    /// Clients the dev engine asked to reload, reloaded once the rebuild is
+    /// served
+    pending_reloads: PendingReloads,
+    /// The running dev engine, set once the initial bundle is being built and
+    /// replaced on restarts
+    engine: RwLock<Option<Arc<DevEngine>>>,
+    /// Whether a restart is replacing the dev engine
+    restarting: AtomicBool,
+    /// Request hooks applied as layers when the router is built
+    hooks: RwLock<Vec<Arc<dyn ServerHooks>>>,
+    /// The latest builds, to roll the served output back and forth
+    history: RwLock<BuildHistory>,
+    /// What the file watcher watches and the events it received
+    watch_stats: WatchStats,
+    /// Modules bundled for SSR by the path they were bundled from
+    ssr_modules: Mutex<HashMap<PathBuf, BundledSsrModule>>,
+    /// Error [`Server::serve`] stops with, e.g. a broken first build
+    fatal: Mutex<Option<PalladinError>>,
+    fatal_notify: Notify,
 }
 
 impl Server {
     pub fn new(config: ServerConfig) -> PalladinResult<Self> {
         let ctx = Arc::new(Context::new(config)?);
+        let history = BuildHistory::new(ctx.config().build_history);
+        let (hmr_tx, _) = broadcast::channel(64);
+        let server_id = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| format!("{:x}", d.as_nanos()))
+            .unwrap_or_default();
 
         Ok(Self {
             ctx: ctx.clone(),
             files: RwLock::new(HashMap::new()),
+            loads: SingleFlight::new(),
             chunks: ChunkManager::new(),
-            entry_asset: RwLock::new(None),
+            entry_assets: RwLock::new(HashMap::new()),
+            graph: RwLock::new(ModuleGraph::default()),
+            build_id: AtomicU64::new(0),
+            hmr_tx,
+            server_id,
+            hmr_clients: HmrClients::default(),
+            client_handlers: ClientHandlers::default(),
+            pending_reloads: PendingReloads::default(),
+            engine: RwLock::new(None),
+            restarting: AtomicBool::new(false),
+            hooks: RwLock::new(Vec::new()),
+            history: RwLock::new(history),
+            watch_stats: WatchStats::default(),
+            ssr_modules: Mutex::new(HashMap::new()),
+            fatal: Mutex::new(None),
+            fatal_notify: Notify::new(),
         })
     }
 
@@ -50,42 +248,85 @@ impl Server {
     }
 
     async fn spawn_engine(self: Arc<Self>) -> PalladinResult {
-        let options = create_bundler(self.ctx.clone());
+        let options = create_bundler(self.ctx.clone(), BundleMode::Dev);
         let server_for_output = Arc::clone(&self);
+        let server_for_hmr = Arc::clone(&self);
+        let runtime = tokio::runtime::Handle::current();
 
         let dev_engine = DevEngine::new(
             options,
             DevOptions {
                 rebuild_strategy: Some(RebuildStrategy::Always),
+                use_polling: Some(self.config().watch.poll_interval_ms.is_some()),
+                poll_interval: self.config().watch.poll_interval_ms,
+                // Coalesces bursts of events, e.g. a branch switch, into one
+                // rebuild like palladin's own watcher does
+                use_debounce: Some(true),
+                debounce_duration: Some(self.config().watch.debounce_ms),
                 on_output: Some(Arc::new(move |result| {
                     let server = Arc::clone(&server_for_output);
                     match result {
                         Ok(bundle_output) => {
-                            for warning in &bundle_output.warnings {
-                                warn!("rolldown warning: {warning:#?}");
+                            let warnings = filter_diagnostics(
+                                &server.config().plugins,
+                                &bundle_output.warnings,
+                                Severity::Warning,
+                            );
+                            for warning in warnings {
+                                warn!("{}", Message::BundleWarning { warning: &warning });
                             }
 
-                            if let Err(err) = server.handle_bundle_output(bundle_output) {
-                                error!("failed to process rolldown output: {err:#}");
+                            // Pages opened while the first build was running have
+                            // nothing that could be patched
+                            if server.build_id() == 0 {
+                                server.pending_reloads.add_all();
+                            }
+                            match server.handle_bundle_output(bundle_output) {
+                                Ok(()) => server.apply_pending_reloads(),
+                                Err(err) => {
+                                    error!("{}", Message::BundleOutputFailed { error: &err })
+                                }
                             }
                         }
-                        Err(err) => {
-                            error!("rolldown build error: {err:#?}");
+                        Err(errors) => {
+                            let errors = filter_diagnostics(
+                                &server.config().plugins,
+                                errors.iter(),
+                                Severity::Error,
+                            );
+                            for err in errors {
+                                error!("{}", Message::BundleError { error: &err });
+                            }
+                            // Asked for outside of the bundler, which would
+                            // wait for the answer otherwise
+                            if !server.ctx.missing_packages().is_empty() {
+                                server.install_and_restart(&runtime);
+                            }
                         }
                     }
                 })),
-                on_hmr_updates: Some(Arc::new(|result| match result {
+                on_hmr_updates: Some(Arc::new(move |result| match result {
                     Ok((updates, changed_files)) => {
-                        println!("HMR updates: {updates:#?} due to {changed_files:#?}");
+                        debug!("HMR updates due to {changed_files:?}");
+                        server_for_hmr.deliver_hmr_updates(updates);
                     }
                     Err(e) => {
-                        eprintln!("HMR error: {e:#?}");
+                        error!("{}", Message::HmrError { error: &e });
                     }
                 })),
                 ..Default::default()
             },
         )?;
 
+        let dev_engine = Arc::new(dev_engine);
+        for client_id in self.hmr_clients.ids() {
+            dev_engine
+                .clients
+                .insert(client_id, ClientSession::default());
+        }
+        *self.engine.write() = Some(Arc::clone(&dev_engine));
+        self.restarting.store(false, Ordering::SeqCst);
+
         dev_engine.run().await?;
 
         dev_engine
@@ -95,34 +336,239 @@ impl Server {
     }
 
     pub async fn serve(self: Arc<Self>) -> PalladinResult {
-        let tcp = TcpListener::bind(self.ctx.address()).await?;
-        let app = Router::new()
+        let mut listeners = Vec::new();
+        for address in self.ctx.addresses() {
+            listeners.push(listener::bind(&address).await?);
+        }
+
+        let app = Arc::clone(&self).into_router();
+        let servers = listeners
+            .into_iter()
+            .map(|tcp| axum::serve(tcp, app.clone()).into_future());
+
+        tokio::select! {
+            result = try_join_all(servers) => {
+                result?;
+                Ok(())
+            }
+            err = self.fatal_error() => Err(err),
+        }
+    }
+
+    /// Stops [`Server::serve`] with `err`, for failures leaving nothing to
+    /// serve. Routers from [`Server::into_router`] keep running, the error is
+    /// logged either way.
+    fn stop(&self, err: PalladinError) {
+        self.fatal.lock().get_or_insert(err);
+        self.fatal_notify.notify_one();
+    }
+
+    /// Waits for the error passed to [`Server::stop`].
+    async fn fatal_error(&self) -> PalladinError {
+        loop {
+            if let Some(err) = self.fatal.lock().take() {
+                return err;
+            }
+            self.fatal_notify.notified().await;
+        }
+    }
+
+    /// Returns the dev server's routes without binding a listener, so they can
+    /// be mounted in an existing axum application, e.g. as its fallback.
+    ///
+    /// The routes may only be nested under the base path of the config, see
+    /// [`ServerConfig::with_base`]: pages get their URLs rebased onto it and
+    /// the HMR client connects next to where it was loaded from, so nested
+    /// under any other prefix they point at the wrong routes. The bundler
+    /// and file watcher are started on the current tokio runtime.
+    pub fn into_router(self: Arc<Self>) -> Router {
+        for plugin in &self.config().plugins {
+            plugin.configure_server(&self);
+        }
+
+        let mut app = Router::new()
             .route("/", get(serve_index_handler))
             .route("/__chunks/{*chunk}", get(serve_chunk_handler))
-            .route("/{*file}", get(serve_file_handler))
-            .with_state(self.clone());
+            .route("/__graph", get(serve_graph_handler))
+            .route("/__graph/order", get(serve_execution_order_handler))
+            .route("/__history", get(serve_history_handler))
+            .route("/__history/back", post(history_back_handler))
+            .route("/__history/forward", post(history_forward_handler))
+            .route("/__hmr", get(hmr_ws_handler))
+            .route("/__palladin/hmr", get(serve_hmr_status_handler))
+            .route("/__palladin/restart", post(restart_handler))
+            .route("/__palladin/watcher", get(serve_watcher_handler))
+            .route(HMR_CLIENT_PATH, get(serve_hmr_client_handler))
+            .route("/{*file}", get(serve_file_handler));
+        if self.config().probe_routes {
+            app = app
+                .route("/favicon.ico", get(favicon_handler))
+                .route(DEVTOOLS_JSON_PATH, get(devtools_json_handler));
+        }
+        self.load_mocks();
+        if self.config().ssr_entry.is_some() {
+            info!(
+                "{}",
+                Message::ServingSsrEntry {
+                    path: SSR_MODULE_PATH
+                }
+            );
+            app = app
+                .route(SSR_MODULE_PATH, get(serve_ssr_module_handler))
+                .route(SSR_STACKTRACE_PATH, post(fix_ssr_stacktrace_handler));
+        }
+        if self.ctx.buffers().is_some() {
+            info!("{}", Message::AcceptingBuffers);
+            app = app.route("/__buffers", get(list_buffers_handler)).route(
+                "/__buffers/{*path}",
+                put(write_buffer_handler).delete(discard_buffer_handler),
+            );
+        }
+        let app = app.with_state(self.clone());
 
-        tokio::spawn(self.spawn_engine());
+        // Mocks are matched before the routes, on top of serving files
+        let app = if self.config().mocks.is_some() {
+            app.layer(middleware::from_fn_with_state(self.clone(), mocks_layer))
+        } else {
+            app
+        };
 
-        axum::serve(tcp, app).await.map_err(Into::into)
+        let app = if self.config().error_pages.is_empty() {
+            app
+        } else {
+            app.layer(middleware::from_fn_with_state(self.clone(), error_pages))
+        };
+
+        let app = match self.config().noindex {
+            NoIndex::Never => app,
+            mode => {
+                let always = mode == NoIndex::Always || is_exposed(&self.ctx.addresses());
+                if always {
+                    info!("{}", Message::NoIndex);
+                }
+                app.layer(middleware::from_fn_with_state(always, noindex))
+            }
+        };
+
+        let headers = &self.config().headers;
+        let app = if headers.is_empty() {
+            app
+        } else {
+            app.layer(middleware::from_fn_with_state(
+                Arc::new(headers.clone()),
+                add_headers,
+            ))
+        };
+
+        // Layers wrap everything added before them, so the first hooks go last
+        let hooks = self.hooks.read().clone();
+        let app = hooks.into_iter().rev().fold(app, |app, hooks| {
+            app.layer(middleware::from_fn_with_state(hooks, run_hooks))
+        });
+
+        let app = match self.config().har.clone() {
+            Some(options) => {
+                info!(
+                    "{}",
+                    Message::RecordingHar {
+                        path: &options.path
+                    }
+                );
+                let recorder = Arc::new(HarRecorder::new(options));
+                tokio::spawn(recorder.clone().flush_periodically());
+                app.layer(middleware::from_fn_with_state(recorder, record_har))
+            }
+            None => app,
+        };
+
+        tokio::spawn(self.clone().spawn_engine());
+
+        tokio::spawn(async move {
+            if let Err(err) = self.watch_files().await {
+                error!("{}", Message::WatcherStopped { error: &err });
+            }
+        });
+
+        app
     }
 
     fn handle_bundle_output(self: &Arc<Self>, bundle_output: BundleOutput) -> PalladinResult {
-        let entrypoint_path = self.ctx.entrypoint().clone();
+        let entrypoints: Vec<PathBuf> = self
+            .ctx
+            .entrypoints()
+            .iter()
+            .map(|entry| entry.path.clone())
+            .collect();
 
-        let (main_asset, chunks) =
-            ChunkProcessor::process_assets(&bundle_output.assets, &entrypoint_path)
+        let (mut main_assets, mut chunks) =
+            ChunkProcessor::process_assets(&bundle_output.assets, &entrypoints)
                 .map_err(|err| anyhow!(err))?;
 
-        self.chunks.clear();
-        self.chunks.store_chunks(chunks);
+        // Nothing is served before the first build, so a broken one would
+        // only show up as a blank page in the browser
+        if self.history.read().next_id() == 1 {
+            let problems = validate_artifacts(&bundle_output.assets, &main_assets, &chunks);
+            if !problems.is_empty() {
+                for problem in &problems {
+                    error!("{}", Message::BrokenOutput { problem });
+                }
+                // Nothing could ever be served, so the server stops instead
+                // of answering every request with a blank page
+                let message = Message::FirstBuildBroken {
+                    problems: problems.len(),
+                }
+                .to_string();
+                self.stop(PalladinError::Build(anyhow!("{message}")));
+                return Err(anyhow!(message).into());
+            }
+        }
 
-        {
-            let mut entry_asset = self.entry_asset.write();
-            *entry_asset = Some(main_asset.clone());
+        // Tag chunk imports with the build id, so a page loaded before a
+        // rebuild can't mix its chunks with the new ones
+        let build_id = self.history.read().next_id();
+        let query = format!("{BUILD_QUERY_PARAM}={build_id}");
+        let filenames: Vec<String> = chunks.keys().cloned().collect();
+        let filenames: Vec<&str> = filenames.iter().map(String::as_str).collect();
+        for main_asset in main_assets.values_mut() {
+            main_asset.content = tag_chunk_imports(&main_asset.content, &filenames, &query);
         }
+        let scripts = chunks
+            .values_mut()
+            .filter(|chunk| chunk.content_type == "application/javascript");
+        for chunk in scripts {
+            chunk.content = tag_chunk_imports(&chunk.content, &filenames, &query);
+        }
+
+        let graph =
+            ModuleGraph::from_assets(&bundle_output.assets).with_imports(self.ctx.module_imports());
+        for hazard in &graph.hazards {
+            warn!("{}", Message::OrderHazard { hazard });
+        }
+
+        let build = ServedBuild {
+            id: build_id,
+            built_at: SystemTime::now(),
+            chunks,
+            entry_assets: main_assets,
+            graph,
+        };
+        self.serve_build(&build)?;
+        self.history.write().push(build);
+        Ok(())
+    }
+
+    /// Replaces the served output with the one of `build`.
+    fn serve_build(self: &Arc<Self>, build: &ServedBuild) -> PalladinResult {
+        self.chunks.clear();
+        self.chunks.store_chunks(build.chunks.clone());
+        self.build_id.store(build.id, Ordering::SeqCst);
+        *self.graph.write() = build.graph.clone();
 
-        self.apply_main_asset(&entrypoint_path, &main_asset)
+        for (entrypoint_path, main_asset) in &build.entry_assets {
+            self.apply_main_asset(entrypoint_path, main_asset)?;
+        }
+        *self.entry_assets.write() = build.entry_assets.clone();
+        Ok(())
     }
 
     fn apply_main_asset(
@@ -140,11 +586,146 @@ impl Server {
         Ok(())
     }
 
-    pub(crate) fn entry_asset(&self) -> Option<MainAsset> {
-        self.entry_asset.read().clone()
+    /// Returns the entry chunk emitted as `filename`.
+    pub(crate) fn entry_asset(&self, filename: &str) -> Option<MainAsset> {
+        self.entry_assets
+            .read()
+            .values()
+            .find(|asset| asset.filename == filename)
+            .cloned()
+    }
+
+    /// Returns the stylesheets bundled with `entrypoint`, `None` if it isn't
+    /// an entrypoint or wasn't bundled yet.
+    pub(crate) fn entry_stylesheets(&self, entrypoint: &Path) -> Option<Vec<String>> {
+        self.entry_assets
+            .read()
+            .get(entrypoint)
+            .map(|asset| asset.stylesheets.clone())
     }
 
     pub(crate) fn chunk_manager(&self) -> &ChunkManager {
         &self.chunks
     }
+
+    pub(crate) fn module_graph(&self) -> ModuleGraph {
+        self.graph.read().clone()
+    }
+
+    /// Returns the number of browsers currently connected to `/__hmr`.
+    #[inline(always)]
+    pub fn hmr_client_count(&self) -> usize {
+        self.hmr_clients.len()
+    }
+
+    /// Returns a handle for sending custom events to HMR clients, e.g.
+    /// `server.ws().send("i18n:update", catalog)`.
+    #[inline(always)]
+    pub fn ws(&self) -> HmrChannel {
+        HmrChannel::new(self.hmr_tx.clone())
+    }
+
+    /// Registers `handler` for messages clients send with the given event name.
+    ///
+    /// Built-in messages use their type as event name, e.g. `hot-accepted`.
+    pub fn on_client_message(
+        &self,
+        event: impl Into<String>,
+        handler: impl Fn(&str, &ClientMessage) + Send + Sync + 'static,
+    ) {
+        self.client_handlers.add(event, Arc::new(handler));
+    }
+
+    /// Registers `hooks` to run around every request.
+    ///
+    /// Only hooks registered before [`Server::serve`] or [`Server::into_router`]
+    /// is called are applied.
+    pub fn add_hooks(&self, hooks: impl ServerHooks) {
+        self.hooks.write().push(Arc::new(hooks));
+    }
+
+    pub(crate) fn handle_client_message(self: &Arc<Self>, client_id: &str, message: ClientMessage) {
+        if let ClientMessage::Invalidate { path } = &message
+            && self.module_graph().contains_module(Path::new(path))
+        {
+            let server = Arc::clone(self);
+            let id = path.clone();
+            tokio::spawn(async move { server.invalidate_modules(vec![id]).await });
+        }
+        if !self.client_handlers.dispatch(client_id, &message)
+            && !matches!(message, ClientMessage::Invalidate { .. })
+        {
+            debug!("unhandled message from HMR client {client_id}: {message:?}");
+        }
+    }
+
+    /// Registers an HMR client with the server and the dev engine.
+    pub(crate) fn register_hmr_client(&self) -> (String, UnboundedReceiver<HmrMessage>) {
+        let (id, rx) = self.hmr_clients.register();
+        if let Some(engine) = self.engine() {
+            engine.clients.insert(id.clone(), ClientSession::default());
+        }
+        (id, rx)
+    }
+
+    pub(crate) fn unregister_hmr_client(&self, id: &str) {
+        self.hmr_clients.unregister(id);
+        if let Some(engine) = self.engine() {
+            engine.clients.remove(id);
+        }
+    }
+
+    /// Sends each client the update the dev engine computed for it.
+    fn deliver_hmr_updates(&self, updates: Vec<ClientHmrUpdate>) {
+        for ClientHmrUpdate { client_id, update } in updates {
+            let message = match update {
+                HmrUpdate::Patch(patch) => HmrMessage::Patch {
+                    filename: patch.filename.to_string(),
+                    code: patch.code.to_string(),
+                },
+                HmrUpdate::FullReload { reason } => {
+                    debug!("reloading client {client_id} once rebuilt: {reason}");
+                    self.pending_reloads.add(client_id);
+                    continue;
+                }
+                HmrUpdate::Noop => continue,
+            };
+
+            if !self.hmr_clients.send_to(&client_id, message) {
+                debug!("dropping HMR update for disconnected client {client_id}");
+            }
+        }
+    }
+
+    /// Reloads the clients waiting for the build that was just served.
+    fn apply_pending_reloads(&self) {
+        match self.pending_reloads.take() {
+            // No receivers just means no browser is connected
+            Some(Reload::All) => {
+                let _ = self.hmr_tx.send(HmrMessage::FullReload);
+            }
+            Some(Reload::Clients(ids)) => {
+                for id in ids {
+                    self.hmr_clients.send_to(&id, HmrMessage::FullReload);
+                }
+            }
+            None => {}
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn engine(&self) -> Option<Arc<DevEngine>> {
+        self.engine.read().clone()
+    }
+
+    /// Returns the id of the served bundle output, `0` before the first one.
+    #[inline(always)]
+    pub(crate) fn build_id(&self) -> u64 {
+        self.build_id.load(Ordering::SeqCst)
+    }
+
+    #[inline(always)]
+    pub(crate) fn server_id(&self) -> &str {
+        &self.server_id
+    }
 }
@@ -0,0 +1,92 @@
+use parking_lot::RwLock;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::PathBuf;
+use tokio::sync::broadcast;
+
+/// Something the output of a module depends on besides its own source, e.g.
+/// the schema a codegen plugin generates the module from.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Dependency {
+    /// An absolute file path, changes to it are picked up by the file watcher
+    File(PathBuf),
+    /// An environment variable
+    Env(String),
+    /// A configuration key of a plugin, e.g. `i18n.locales`
+    Config(String),
+}
+
+impl fmt::Display for Dependency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Dependency::File(path) => write!(f, "file {}", path.display()),
+            Dependency::Env(name) => write!(f, "env {name}"),
+            Dependency::Config(key) => write!(f, "config {key}"),
+        }
+    }
+}
+
+/// Tracks the extra dependencies plugins declared for modules, so changing
+/// one of them rebuilds exactly the modules depending on it.
+#[derive(Debug)]
+pub struct ModuleDependencies {
+    dependents: RwLock<HashMap<Dependency, HashSet<String>>>,
+    invalidated: broadcast::Sender<Invalidation>,
+}
+
+/// What [`ModuleDependencies::invalidate`] was asked to invalidate.
+#[derive(Debug, Clone)]
+pub(crate) enum Invalidation {
+    Dependency(Dependency),
+    Module(String),
+}
+
+impl Default for ModuleDependencies {
+    fn default() -> Self {
+        let (invalidated, _) = broadcast::channel(64);
+        Self {
+            dependents: RwLock::new(HashMap::new()),
+            invalidated,
+        }
+    }
+}
+
+impl ModuleDependencies {
+    /// Records that the module `id` depends on `dependencies`.
+    pub fn add(&self, id: &str, dependencies: impl IntoIterator<Item = Dependency>) {
+        let mut dependents = self.dependents.write();
+        for dependency in dependencies {
+            dependents
+                .entry(dependency)
+                .or_default()
+                .insert(id.to_string());
+        }
+    }
+
+    /// Returns the ids of the modules depending on `dependency`.
+    pub fn dependents(&self, dependency: &Dependency) -> Vec<String> {
+        self.dependents
+            .read()
+            .get(dependency)
+            .map(|ids| ids.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Rebuilds the modules depending on `dependency`, e.g. after an
+    /// environment variable or config key changed.
+    pub fn invalidate(&self, dependency: Dependency) {
+        let _ = self.invalidated.send(Invalidation::Dependency(dependency));
+    }
+
+    /// Rebuilds the module `id`, e.g. a virtual module whose generated code
+    /// changed.
+    pub fn invalidate_module(&self, id: impl Into<String>) {
+        let _ = self.invalidated.send(Invalidation::Module(id.into()));
+    }
+
+    /// Returns a receiver for the invalidations requested from now on.
+    pub(crate) fn subscribe(&self) -> broadcast::Receiver<Invalidation> {
+        self.invalidated.subscribe()
+    }
+}
@@ -0,0 +1,236 @@
+use crate::messages::Message;
+use anyhow::anyhow;
+use log::warn;
+use palladin_shared::{PalladinError, PalladinResult};
+use parking_lot::Mutex;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Weak, mpsc};
+
+/// Script run by node, hosting the features registered with it.
+const HOST: &str = include_str!("node_host.mjs");
+
+/// The node process shared by every [`NodeBridge`], started with the first
+/// one and stopped once the last one is dropped.
+static HOST_PROCESS: Mutex<Weak<NodeHost>> = Mutex::new(Weak::new());
+
+/// A feature running in the shared node process, e.g. PostCSS or the API
+/// mocks, answering calls to the handlers of its script.
+///
+/// Calls don't wait for each other: node answers them as they finish, so
+/// the bridge is used from any number of threads at once.
+#[derive(Debug, Clone)]
+pub(crate) struct NodeBridge {
+    /// What the feature runs, e.g. `JavaScript plugins`, for error messages
+    name: &'static str,
+    host: Arc<NodeHost>,
+    feature: u64,
+}
+
+impl NodeBridge {
+    /// Sets up the feature `script`, an ES module default exporting
+    /// `setup(options)` that returns its handlers by name, in the shared
+    /// node process.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if node cannot be started or the setup fails, e.g.
+    /// because a package the feature imports isn't installed.
+    pub(crate) fn spawn(name: &'static str, script: &str, options: Value) -> PalladinResult<Self> {
+        let host = NodeHost::shared()?;
+        let feature = host
+            .call(
+                0,
+                "register",
+                json!({ "source": script, "options": options }),
+            )
+            .map_err(|err| {
+                PalladinError::Build(anyhow!(
+                    "{}",
+                    Message::NodeSetupFailed { name, error: &err }
+                ))
+            })?;
+        let feature = feature
+            .as_u64()
+            .ok_or_else(|| PalladinError::Build(anyhow!("{}", Message::NoFeatureId { name })))?;
+
+        Ok(Self {
+            name,
+            host,
+            feature,
+        })
+    }
+
+    /// Calls the handler `hook` of the feature and returns its result.
+    pub(crate) fn call(&self, hook: &str, args: Value) -> PalladinResult<Value> {
+        self.host
+            .call(self.feature, hook, args)
+            .map_err(|err| match err {
+                CallError::Exited => PalladinError::Build(anyhow!(
+                    "{}",
+                    Message::NodeExited {
+                        name: Some(self.name)
+                    }
+                )),
+                CallError::Failed(error) => PalladinError::Build(anyhow!(
+                    "{}",
+                    Message::HookFailed {
+                        hook,
+                        error: &error
+                    }
+                )),
+                CallError::Io(err) => err.into(),
+            })
+    }
+}
+
+#[derive(Debug)]
+enum CallError {
+    Exited,
+    Failed(String),
+    Io(std::io::Error),
+}
+
+impl std::fmt::Display for CallError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CallError::Exited => Message::NodeExited { name: None }.fmt(f),
+            CallError::Failed(error) => f.write_str(error),
+            CallError::Io(err) => err.fmt(f),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct BridgeResponse {
+    id: u64,
+    #[serde(default)]
+    result: Value,
+    error: Option<String>,
+}
+
+/// Calls waiting for their response, by request id.
+#[derive(Debug, Default)]
+struct Pending {
+    /// `None` once node exited, no response comes anymore
+    calls: Mutex<Option<HashMap<u64, mpsc::Sender<BridgeResponse>>>>,
+}
+
+/// The node process, one JSON line per request and response: `{ id,
+/// feature, hook, args }` in, `{ id, result }` or `{ id, error }` out.
+#[derive(Debug)]
+struct NodeHost {
+    child: Mutex<Child>,
+    stdin: Mutex<ChildStdin>,
+    pending: Arc<Pending>,
+    next_id: AtomicU64,
+}
+
+impl NodeHost {
+    /// Returns the running node process, starting it if there is none.
+    fn shared() -> PalladinResult<Arc<Self>> {
+        let mut shared = HOST_PROCESS.lock();
+        if let Some(host) = shared.upgrade()
+            && host.pending.calls.lock().is_some()
+        {
+            return Ok(host);
+        }
+
+        let host = Arc::new(Self::spawn()?);
+        *shared = Arc::downgrade(&host);
+        Ok(host)
+    }
+
+    fn spawn() -> PalladinResult<Self> {
+        let mut child = Command::new("node")
+            .args(["--input-type=module", "--eval", HOST])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|err| {
+                PalladinError::Build(anyhow!("{}", Message::NodeStartFailed { error: &err }))
+            })?;
+
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let pending = Arc::new(Pending {
+            calls: Mutex::new(Some(HashMap::new())),
+        });
+        let reader = Arc::clone(&pending);
+        std::thread::spawn(move || reader.read_responses(stdout));
+
+        Ok(Self {
+            child: Mutex::new(child),
+            stdin: Mutex::new(stdin),
+            pending,
+            next_id: AtomicU64::new(0),
+        })
+    }
+
+    /// Calls `hook` of `feature` and waits for its result.
+    fn call(&self, feature: u64, hook: &str, args: Value) -> Result<Value, CallError> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let (tx, rx) = mpsc::channel();
+        match &mut *self.pending.calls.lock() {
+            Some(calls) => calls.insert(id, tx),
+            None => return Err(CallError::Exited),
+        };
+
+        let request = json!({ "id": id, "feature": feature, "hook": hook, "args": args });
+        let written = {
+            let mut stdin = self.stdin.lock();
+            writeln!(stdin, "{request}").and_then(|()| stdin.flush())
+        };
+        if let Err(err) = written {
+            if let Some(calls) = &mut *self.pending.calls.lock() {
+                calls.remove(&id);
+            }
+            return Err(CallError::Io(err));
+        }
+
+        // The sender is dropped without a response when node exits
+        let response = rx.recv().map_err(|_| CallError::Exited)?;
+        match response.error {
+            Some(error) => Err(CallError::Failed(error)),
+            None => Ok(response.result),
+        }
+    }
+}
+
+impl Pending {
+    /// Hands the responses node writes to the calls waiting for them, until
+    /// node exits.
+    fn read_responses(&self, stdout: ChildStdout) {
+        for line in BufReader::new(stdout).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            let response: BridgeResponse = match serde_json::from_str(&line) {
+                Ok(response) => response,
+                Err(err) => {
+                    warn!("{}", Message::InvalidNodeResponse { error: &err });
+                    continue;
+                }
+            };
+            let waiting = match &mut *self.calls.lock() {
+                Some(calls) => calls.remove(&response.id),
+                None => None,
+            };
+            if let Some(tx) = waiting {
+                let _ = tx.send(response);
+            }
+        }
+        // Wakes up the calls still waiting
+        self.calls.lock().take();
+    }
+}
+
+impl Drop for NodeHost {
+    fn drop(&mut self) {
+        let _ = self.child.lock().kill();
+    }
+}
@@ -0,0 +1,51 @@
+// Palladin's node process, running the JavaScript of every feature that
+// needs node, e.g. PostCSS, the Svelte compiler or the API mocks.
+//
+// Features are ES modules default exporting `setup(options)`, which returns
+// their handlers by name. One JSON request per line on stdin is answered by
+// one JSON line on stdout, `{ id, result }` or `{ id, error }`:
+//
+// - `{ id, hook: "register", args: { source, options } }` sets up the
+//   feature with the module `source`, its result is the feature's id
+// - `{ id, feature, hook, args }` calls the handler `hook` of a feature
+//
+// Requests are answered as they finish, so slow calls don't hold up others.
+
+import { createInterface } from "node:readline";
+
+// Features logging to stdout would corrupt the protocol
+const write = process.stdout.write.bind(process.stdout);
+console.log = console.info = console.debug = console.error;
+
+const features = new Map();
+let nextFeature = 0;
+
+async function register({ source, options }) {
+  const encoded = Buffer.from(source).toString("base64");
+  const { default: setup } = await import(`data:text/javascript;base64,${encoded}`);
+  const handlers = await setup(options);
+  nextFeature += 1;
+  features.set(nextFeature, handlers);
+  return nextFeature;
+}
+
+function call({ feature, hook, args }) {
+  if (hook === "register") {
+    return register(args);
+  }
+  const handler = features.get(feature)?.[hook];
+  if (typeof handler !== "function") {
+    throw new Error(`feature ${feature} has no handler ${hook}`);
+  }
+  return handler(args);
+}
+
+createInterface({ input: process.stdin }).on("line", async (line) => {
+  const request = JSON.parse(line);
+  try {
+    const result = await call(request);
+    write(JSON.stringify({ id: request.id, result }) + "\n");
+  } catch (err) {
+    write(JSON.stringify({ id: request.id, error: String(err?.stack ?? err) }) + "\n");
+  }
+});
@@ -0,0 +1,79 @@
+use crate::server::errors::{HttpResponse, REVALIDATE};
+use axum::extract::{Request, State};
+use axum::http::header::FORWARDED;
+use axum::http::{HeaderMap, HeaderName, HeaderValue};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use std::net::IpAddr;
+
+/// Header telling crawlers not to index a response.
+const X_ROBOTS_TAG: HeaderName = HeaderName::from_static("x-robots-tag");
+
+/// Headers added by proxies and tunnels in front of the server.
+const FORWARDED_HEADERS: [&str; 2] = ["x-forwarded-for", "x-forwarded-host"];
+
+/// `robots.txt` served while pages aren't indexed.
+const DENY_ALL_ROBOTS: &str = "User-agent: *\nDisallow: /\n";
+
+/// Whether the dev server asks crawlers to stay away.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NoIndex {
+    /// Only when bound to a non-loopback address or reached through a proxy
+    /// or tunnel
+    #[default]
+    Auto,
+    /// On every response
+    Always,
+    /// Never, serving the project's own `robots.txt`
+    Never,
+}
+
+palladin_shared::named_enum!(NoIndex, "noindex mode" {
+    Auto => "auto",
+    Always => "always",
+    Never => "never",
+});
+
+/// Checks if any of `addresses` can be reached from other machines.
+pub(crate) fn is_exposed(addresses: &[String]) -> bool {
+    addresses.iter().any(|address| {
+        let host = address
+            .rsplit_once(':')
+            .map_or(address.as_str(), |(host, _)| host);
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        match host.parse::<IpAddr>() {
+            Ok(ip) => !ip.is_loopback(),
+            Err(_) => host != "localhost",
+        }
+    })
+}
+
+/// Checks if a request was relayed by a proxy or tunnel.
+fn is_forwarded(headers: &HeaderMap) -> bool {
+    headers.contains_key(FORWARDED)
+        || FORWARDED_HEADERS
+            .iter()
+            .any(|name| headers.contains_key(*name))
+}
+
+/// Answers `/robots.txt` with a deny-all policy and tags responses with
+/// `X-Robots-Tag: noindex`, for every request when `always` and otherwise
+/// only for requests relayed by a proxy or tunnel.
+pub async fn noindex(State(always): State<bool>, request: Request, next: Next) -> Response {
+    if !always && !is_forwarded(request.headers()) {
+        return next.run(request).await;
+    }
+
+    let mut response = match request.uri().path() {
+        "/robots.txt" => HttpResponse::ok(DENY_ALL_ROBOTS.to_string())
+            .content_type("text/plain; charset=utf-8")
+            .cache_control(REVALIDATE)
+            .finish()
+            .into_response(),
+        _ => next.run(request).await,
+    };
+    response
+        .headers_mut()
+        .insert(X_ROBOTS_TAG, HeaderValue::from_static("noindex, nofollow"));
+    response
+}
@@ -0,0 +1,93 @@
+use super::{Dependency, Diagnostic, Server};
+use palladin_shared::PalladinResult;
+use std::fmt::Debug;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Extends the bundler and the dev server, e.g. to add support for a framework.
+///
+/// The bundler hooks mirror rolldown's `resolveId`, `load` and `transform`.
+/// Returning `None` from one of them passes the module on to the next plugin,
+/// and eventually to rolldown itself. Register plugins with
+/// [`ServerConfig::with_plugin`](super::ServerConfig::with_plugin).
+pub trait PalladinPlugin: Debug + Send + Sync + 'static {
+    /// Name shown in logs and bundler errors.
+    fn name(&self) -> &str;
+
+    /// Whether the bundler hooks block, e.g. waiting for another process.
+    /// They're then run on a blocking thread so the bundler goes on with
+    /// other modules meanwhile.
+    fn is_blocking(&self) -> bool {
+        false
+    }
+
+    /// Resolves `specifier` imported from `importer` to a module id.
+    fn resolve_id(
+        &self,
+        specifier: &str,
+        importer: Option<&str>,
+    ) -> PalladinResult<Option<String>> {
+        let _ = (specifier, importer);
+        Ok(None)
+    }
+
+    /// Returns the source code of the module `id`.
+    fn load(&self, id: &str) -> PalladinResult<Option<String>> {
+        let _ = id;
+        Ok(None)
+    }
+
+    /// Returns the transformed code of the module `id`.
+    fn transform(&self, id: &str, code: &str) -> PalladinResult<Option<String>> {
+        let _ = (id, code);
+        Ok(None)
+    }
+
+    /// Returns what the module `id` depends on besides its source, called
+    /// after this plugin loaded or transformed it. Changing a dependency
+    /// rebuilds the module; dependencies that aren't files are invalidated
+    /// through [`ModuleDependencies`](super::ModuleDependencies), e.g. when a
+    /// codegen plugin's input changed.
+    fn module_dependencies(&self, id: &str) -> Vec<Dependency> {
+        let _ = id;
+        Vec::new()
+    }
+
+    /// Called once before the dev server starts handling requests, e.g. to
+    /// register [`ServerHooks`](super::ServerHooks) or HMR message handlers.
+    fn configure_server(&self, server: &Server) {
+        let _ = server;
+    }
+
+    /// Called when a watched file changes. Returning `true` marks the change
+    /// as handled, e.g. after sending a custom HMR event, so the dev server
+    /// doesn't reload the page for it.
+    ///
+    /// Bundled modules are updated by the bundler either way.
+    fn handle_hot_update(&self, path: &Path, server: &Server) -> bool {
+        let _ = (path, server);
+        false
+    }
+
+    /// Returns the transformed HTML of a served page or of the built
+    /// `index.html`.
+    fn transform_index_html(&self, html: &str) -> Option<String> {
+        let _ = html;
+        None
+    }
+
+    /// Called for every warning the bundler reports, and for the errors of
+    /// dev server rebuilds, before they're printed. Returning `None`
+    /// suppresses the diagnostic, e.g. a known noisy warning from a
+    /// dependency; the returned diagnostic is passed on to the next plugin.
+    fn on_diagnostic(&self, diagnostic: Diagnostic) -> Option<Diagnostic> {
+        Some(diagnostic)
+    }
+}
+
+/// Runs every plugin's `transform_index_html` hook over `html` in order.
+pub fn transform_index_html(plugins: &[Arc<dyn PalladinPlugin>], html: &str) -> String {
+    plugins.iter().fold(html.to_string(), |html, plugin| {
+        plugin.transform_index_html(&html).unwrap_or(html)
+    })
+}
@@ -0,0 +1,97 @@
+use super::node::NodeBridge;
+use anyhow::anyhow;
+use palladin_shared::{PalladinError, PalladinResult};
+use parking_lot::Mutex;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Feature running the PostCSS pipeline in node.
+const WORKER: &str = include_str!("postcss_worker.mjs");
+
+/// Config files PostCSS is configured with, in lookup order.
+const POSTCSS_CONFIG_FILES: &[&str] = &[
+    "postcss.config.js",
+    "postcss.config.mjs",
+    "postcss.config.cjs",
+];
+
+/// Runs every stylesheet through the project's PostCSS config in node, so
+/// plugins like autoprefixer or Tailwind work.
+///
+/// `postcss` and the plugins named in the config are resolved from the
+/// project's `node_modules`.
+#[derive(Debug)]
+pub struct PostCss {
+    config: PathBuf,
+    node: NodeBridge,
+    /// Processed stylesheets, by hash of their path and content
+    cache: Mutex<HashMap<String, String>>,
+}
+
+impl PostCss {
+    /// Loads the PostCSS `config` of the project in `root` in node.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if node cannot be started or the config cannot be
+    /// loaded.
+    pub fn spawn(root: &Path, config: PathBuf) -> PalladinResult<Self> {
+        let options = json!({ "root": root.to_string_lossy(), "config": config.to_string_lossy() });
+        let node = NodeBridge::spawn("PostCSS", WORKER, options)?;
+        Ok(Self {
+            config,
+            node,
+            cache: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Returns the path of the config file in use.
+    #[inline(always)]
+    pub fn config(&self) -> &Path {
+        &self.config
+    }
+
+    /// Returns `css`, read from `path`, as processed by the config's plugins.
+    /// Results are cached by path and content until [`Self::clear_cache`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a plugin fails or node exited.
+    pub fn process(&self, path: &Path, css: &str) -> PalladinResult<String> {
+        let mut hasher = Sha256::new();
+        hasher.update(path.to_string_lossy().as_bytes());
+        hasher.update([0]);
+        hasher.update(css.as_bytes());
+        let key = format!("{:x}", hasher.finalize());
+        if let Some(css) = self.cache.lock().get(&key) {
+            return Ok(css.clone());
+        }
+
+        let result = self.node.call(
+            "process",
+            json!({ "from": path.to_string_lossy(), "css": css }),
+        )?;
+        let processed = result.as_str().map(str::to_string).ok_or_else(|| {
+            PalladinError::Build(anyhow!("PostCSS returned no CSS for {}", path.display()))
+        })?;
+        self.cache.lock().insert(key, processed.clone());
+        Ok(processed)
+    }
+
+    /// Forgets the processed stylesheets. Plugins read other files than the
+    /// stylesheet itself, e.g. through `@import` or Tailwind's content
+    /// files, so the cache is cleared when those change.
+    pub fn clear_cache(&self) {
+        self.cache.lock().clear();
+    }
+}
+
+/// Returns the PostCSS config file of the project in `root`, if any.
+pub fn find_postcss_config(root: &Path) -> Option<PathBuf> {
+    POSTCSS_CONFIG_FILES
+        .iter()
+        .map(|name| root.join(name))
+        .find(|path| path.is_file())
+}
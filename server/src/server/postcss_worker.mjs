@@ -0,0 +1,56 @@
+// Palladin feature running the project's PostCSS pipeline in the node host.
+
+import { createRequire } from "node:module";
+import { resolve } from "node:path";
+import { pathToFileURL } from "node:url";
+
+export default async function setup({ root, config: configFile }) {
+  const configPath = resolve(root, configFile);
+  // PostCSS and its plugins come from the project, not from palladin
+  const require = createRequire(configPath);
+
+  async function load(specifier) {
+    const module = await import(pathToFileURL(require.resolve(specifier)).href);
+    return module.default ?? module;
+  }
+
+  let config = await import(pathToFileURL(configPath).href);
+  config = config.default ?? config;
+  if (typeof config === "function") {
+    config = await config({ env: process.env.NODE_ENV ?? "development" });
+  }
+
+  // Plugins are either listed, or keyed by package name with their options
+  async function loadPlugins(plugins = []) {
+    if (Array.isArray(plugins)) {
+      return plugins.filter(Boolean);
+    }
+    const loaded = [];
+    for (const [name, options] of Object.entries(plugins)) {
+      if (options === false) continue;
+      const plugin = await load(name);
+      loaded.push(options === true || options == null ? plugin() : plugin(options));
+    }
+    return loaded;
+  }
+
+  const postcss = await load("postcss");
+  const processor = postcss(await loadPlugins(config.plugins));
+  const { plugins: _, ...options } = config;
+  // Custom syntaxes can be given by package name too
+  for (const key of ["parser", "syntax", "stringifier"]) {
+    if (typeof options[key] === "string") {
+      options[key] = await load(options[key]);
+    }
+  }
+
+  return {
+    async process({ from, css }) {
+      const result = await processor.process(css, { ...options, from, map: false });
+      for (const warning of result.warnings()) {
+        console.error(`[postcss] ${from}: ${warning.toString()}`);
+      }
+      return result.css;
+    },
+  };
+}
@@ -0,0 +1,80 @@
+use serde_json::Value;
+use std::path::Path;
+
+/// UI framework a project is built with, used to pick framework-specific defaults.
+///
+/// The framework's preset compiles JSX for its runtime and splits the runtime
+/// into a vendor chunk. Svelte components are compiled for hot updates by
+/// [`Svelte`](super::Svelte) in dev bundles; the other frameworks' modules are
+/// patched by the dev engine like any other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Framework {
+    React,
+    Vue,
+    Svelte,
+    Solid,
+    /// No known framework
+    Vanilla,
+}
+
+impl Framework {
+    /// Detects the framework from the dependencies declared in `root/package.json`.
+    pub fn detect(root: &Path) -> Framework {
+        let Some(manifest) = fs_err::read_to_string(root.join("package.json"))
+            .ok()
+            .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+        else {
+            return Framework::Vanilla;
+        };
+
+        let has_dependency = |name: &str| {
+            ["dependencies", "devDependencies", "peerDependencies"]
+                .iter()
+                .any(|key| manifest.get(key).and_then(|deps| deps.get(name)).is_some())
+        };
+
+        // Checked in order: solid and svelte projects may pull in packages
+        // that other frameworks also use.
+        [
+            ("solid-js", Framework::Solid),
+            ("svelte", Framework::Svelte),
+            ("vue", Framework::Vue),
+            ("react", Framework::React),
+        ]
+        .into_iter()
+        .find(|(dependency, _)| has_dependency(dependency))
+        .map(|(_, framework)| framework)
+        .unwrap_or(Framework::Vanilla)
+    }
+
+    /// Package compiled JSX imports its runtime from, `None` when the
+    /// framework doesn't use JSX.
+    pub fn jsx_import_source(&self) -> Option<&'static str> {
+        match self {
+            Framework::React => Some("react"),
+            Framework::Vue => Some("vue"),
+            Framework::Solid => Some("solid-js/h"),
+            Framework::Svelte | Framework::Vanilla => None,
+        }
+    }
+
+    /// Runtime packages split into the framework vendor chunk.
+    pub fn vendor_packages(&self) -> &'static [&'static str] {
+        match self {
+            Framework::React => &["react", "react-dom", "scheduler"],
+            Framework::Vue => &["vue", "@vue"],
+            Framework::Svelte => &["svelte"],
+            Framework::Solid => &["solid-js"],
+            Framework::Vanilla => &[],
+        }
+    }
+}
+
+palladin_shared::named_enum!(Framework, "framework" {
+    React => "react",
+    Vue => "vue",
+    Svelte => "svelte",
+    Solid => "solid",
+    Vanilla => "vanilla",
+});
@@ -0,0 +1,62 @@
+use crate::server::Server;
+use crate::server::errors::{HttpResponse, REVALIDATE};
+use crate::server::noindex::is_exposed;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use palladin_shared::PalladinError;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+
+/// Path Chrome's devtools probe for a workspace folder to map sources to.
+pub const DEVTOOLS_JSON_PATH: &str = "/.well-known/appspecific/com.chrome.devtools.json";
+
+/// Serves the project's favicon, or an empty response when it has none, so
+/// browsers asking for one on every page load don't get a 404.
+pub async fn favicon_handler(State(server): State<Arc<Server>>) -> impl IntoResponse {
+    let exists = server
+        .ctx
+        .resolve_path("favicon.ico")
+        .is_ok_and(|path| server.ctx.vfs().is_file(&path));
+    match exists {
+        true => server.serve_asset_impl("favicon.ico").into_response(),
+        false => HttpResponse::new(StatusCode::NO_CONTENT, "")
+            .cache_control(REVALIDATE)
+            .finish()
+            .into_response(),
+    }
+}
+
+/// Tells Chrome's devtools where the project lives, so it can offer to edit
+/// the files on disk from the Sources panel.
+///
+/// The project's path isn't disclosed while the server is exposed to the
+/// network, the probe gets a 404 then.
+pub async fn devtools_json_handler(State(server): State<Arc<Server>>) -> impl IntoResponse {
+    if is_exposed(&server.ctx.addresses()) {
+        return PalladinError::FileNotFound(DEVTOOLS_JSON_PATH.to_string()).response();
+    }
+
+    let root = server.ctx.root().to_string_lossy().to_string();
+    let body = json!({ "workspace": { "root": root, "uuid": workspace_uuid(&root) } });
+
+    HttpResponse::ok(body.to_string())
+        .content_type("application/json")
+        .cache_control(REVALIDATE)
+        .finish()
+}
+
+/// Derives a UUID from `root`, so devtools recognizes the workspace across
+/// restarts.
+fn workspace_uuid(root: &str) -> String {
+    let hash = format!("{:x}", Sha256::digest(root.as_bytes()));
+    format!(
+        "{}-{}-4{}-8{}-{}",
+        &hash[..8],
+        &hash[8..12],
+        &hash[13..16],
+        &hash[17..20],
+        &hash[20..32]
+    )
+}
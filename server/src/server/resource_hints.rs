@@ -0,0 +1,100 @@
+use crate::html::opening_tags;
+use std::fmt;
+use std::str::FromStr;
+
+/// An external origin pages connect to, announced with a `<link>` tag at the
+/// start of every served and built HTML page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceHint {
+    /// Scheme and host, e.g. `https://fonts.gstatic.com`
+    pub origin: String,
+    /// Only resolve the origin's DNS instead of opening a connection
+    pub dns_only: bool,
+    /// Connect in CORS mode, needed for fonts and `fetch` requests
+    pub crossorigin: bool,
+}
+
+impl ResourceHint {
+    /// Creates a hint to preconnect to `origin`.
+    pub fn preconnect(origin: impl Into<String>) -> Self {
+        Self {
+            origin: origin.into(),
+            dns_only: false,
+            crossorigin: false,
+        }
+    }
+
+    /// Returns the `<link>` tags of the hint. Preconnects fall back to a DNS
+    /// prefetch in browsers that don't support them.
+    fn tags(&self) -> String {
+        let dns_prefetch = format!("<link rel=\"dns-prefetch\" href=\"{}\">\n", self.origin);
+        if self.dns_only {
+            return dns_prefetch;
+        }
+
+        let crossorigin = if self.crossorigin { " crossorigin" } else { "" };
+        format!(
+            "<link rel=\"preconnect\" href=\"{}\"{crossorigin}>\n{dns_prefetch}",
+            self.origin
+        )
+    }
+}
+
+/// Parses `ORIGIN`, `dns-prefetch=ORIGIN` or `crossorigin=ORIGIN`, e.g.
+/// `crossorigin=https://fonts.gstatic.com`.
+impl FromStr for ResourceHint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, origin) = match s.split_once('=') {
+            Some((kind, origin)) => (Some(kind.trim()), origin.trim()),
+            None => (None, s.trim()),
+        };
+        if !origin.contains("://") {
+            return Err(format!(
+                "invalid resource hint '{s}', expected an origin such as https://cdn.example.com"
+            ));
+        }
+
+        let mut hint = Self::preconnect(origin.trim_end_matches('/'));
+        match kind {
+            None | Some("preconnect") => {}
+            Some("dns-prefetch") => hint.dns_only = true,
+            Some("crossorigin") => hint.crossorigin = true,
+            Some(kind) => {
+                return Err(format!(
+                    "unknown resource hint '{kind}', expected preconnect, dns-prefetch or \
+                     crossorigin"
+                ));
+            }
+        }
+        Ok(hint)
+    }
+}
+
+impl fmt::Display for ResourceHint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.dns_only, self.crossorigin) {
+            (true, _) => write!(f, "dns-prefetch={}", self.origin),
+            (false, true) => write!(f, "crossorigin={}", self.origin),
+            (false, false) => f.write_str(&self.origin),
+        }
+    }
+}
+
+/// Inserts the tags of `hints` at the start of the document's `<head>`, so
+/// connections are opened before anything else is requested.
+pub(crate) fn inject_resource_hints(html: &str, hints: &[ResourceHint]) -> String {
+    if hints.is_empty() {
+        return html.to_string();
+    }
+
+    let tags: String = hints.iter().map(ResourceHint::tags).collect();
+    let head = opening_tags(html, "head")
+        .into_iter()
+        .find(|tag| tag.len() == "<head>".len() || tag.starts_with("<head "));
+    match head.and_then(|tag| html.find(tag).map(|index| index + tag.len())) {
+        Some(index) => format!("{}\n{tags}{}", &html[..index], &html[index..]),
+        None => format!("{tags}{html}"),
+    }
+}
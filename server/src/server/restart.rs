@@ -0,0 +1,132 @@
+use crate::messages::Message;
+use crate::rolldown::install_missing_packages;
+use crate::server::Server;
+use crate::server::errors::HttpResponse;
+use crate::server::noindex::is_exposed;
+use axum::extract::State;
+use axum::http::header::{HOST, ORIGIN};
+use axum::http::{HeaderMap, Response, StatusCode};
+use axum::response::IntoResponse;
+use log::{error, info};
+use palladin_shared::PalladinResult;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use tokio::runtime::Handle;
+
+/// Restarts the dev engine, see [`Server::restart`].
+///
+/// Only pages served by this server on a loopback address may restart it,
+/// anyone else can press `r` in the terminal.
+pub async fn restart_handler(
+    State(server): State<Arc<Server>>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Some(rejected) = reject_remote(&server, &headers, "restart") {
+        return rejected;
+    }
+
+    match server.restart().await {
+        Ok(()) => HttpResponse::new(StatusCode::ACCEPTED, Message::Restarting.to_string()).finish(),
+        Err(err) => err.response(),
+    }
+}
+
+/// Returns the response rejecting a request that changes what the server
+/// does, named `action`, unless it was sent by a page served by this server
+/// on a loopback address.
+pub(crate) fn reject_remote(
+    server: &Server,
+    headers: &HeaderMap,
+    action: &str,
+) -> Option<Response<String>> {
+    if is_exposed(&server.ctx.addresses()) {
+        return Some(
+            HttpResponse::new(
+                StatusCode::FORBIDDEN,
+                Message::ServerExposed { action }.to_string(),
+            )
+            .finish(),
+        );
+    }
+    if is_cross_origin(headers) {
+        return Some(
+            HttpResponse::new(
+                StatusCode::FORBIDDEN,
+                Message::CrossOriginRejected { action }.to_string(),
+            )
+            .finish(),
+        );
+    }
+    None
+}
+
+/// Checks if a request was sent by a page of another origin. Browsers send
+/// `Origin` with every POST, requests without it come from other tools.
+fn is_cross_origin(headers: &HeaderMap) -> bool {
+    let Some(origin) = headers.get(ORIGIN) else {
+        return false;
+    };
+    let host = headers.get(HOST).and_then(|host| host.to_str().ok());
+    let origin_host = origin
+        .to_str()
+        .ok()
+        .and_then(|origin| origin.split_once("://"))
+        .map(|(_, host)| host);
+    host.is_none() || origin_host != host
+}
+
+impl Server {
+    /// Bundles the project again from scratch without stopping the server,
+    /// for when incremental rebuilds got into a bad state.
+    ///
+    /// The dev engine is replaced and the cached source files and SSR
+    /// modules are dropped. Connected pages are reloaded once the new bundle
+    /// is served. Restarts asked for while one is running are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the running dev engine cannot be closed.
+    pub async fn restart(self: &Arc<Self>) -> PalladinResult {
+        // Cleared once the new engine is running
+        if self.restarting.swap(true, Ordering::SeqCst) {
+            info!("{}", Message::RestartRunning);
+            return Ok(());
+        }
+
+        info!("{}", Message::Restarting);
+        let engine = self.engine.write().take();
+        if let Some(engine) = engine
+            && let Err(err) = engine.close().await
+        {
+            self.restarting.store(false, Ordering::SeqCst);
+            return Err(err.into());
+        }
+
+        self.files.write().clear();
+        self.ssr_modules.lock().clear();
+        // The new engine starts over, nothing served before can be patched
+        self.pending_reloads.add_all();
+
+        let server = Arc::clone(self);
+        tokio::spawn(async move {
+            if let Err(err) = Arc::clone(&server).spawn_engine().await {
+                server.restarting.store(false, Ordering::SeqCst);
+                error!("{}", Message::EngineStopped { error: &err });
+            }
+        });
+        Ok(())
+    }
+
+    /// Installs the packages the last build found missing on `runtime`,
+    /// then restarts so their imports are resolved again.
+    pub(crate) fn install_and_restart(self: &Arc<Self>, runtime: &Handle) {
+        let server = Arc::clone(self);
+        runtime.spawn(async move {
+            if install_missing_packages(&server.ctx).await
+                && let Err(err) = server.restart().await
+            {
+                error!("{}", Message::RestartFailed { error: &err });
+            }
+        });
+    }
+}
@@ -0,0 +1,148 @@
+use crate::messages::Message;
+use log::{debug, info, warn};
+use notify::RecursiveMode;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// How often progress is reported while scanning.
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Directories to watch, found by walking the project before watching it.
+#[derive(Debug, Default)]
+pub struct WatchPlan {
+    /// Directories to watch along with whether their subdirectories are included
+    pub watches: Vec<(PathBuf, RecursiveMode)>,
+    /// Directories skipped for having more entries than allowed
+    pub oversized: Vec<PathBuf>,
+    pub directories: usize,
+    pub files: usize,
+}
+
+/// Walks `root` and plans the fewest watches covering it without the
+/// directories `is_ignored` rejects or that hold more than `max_entries`
+/// entries.
+///
+/// Subtrees without anything to leave out are watched recursively, their
+/// ancestors non-recursively. Symlinks aren't followed.
+pub fn scan(root: &Path, max_entries: usize, is_ignored: impl Fn(&Path) -> bool) -> WatchPlan {
+    let mut scanner = Scanner {
+        plan: WatchPlan::default(),
+        max_entries,
+        is_ignored,
+        started: Instant::now(),
+        last_report: Instant::now(),
+    };
+
+    if scanner.scan_dir(root) {
+        scanner
+            .plan
+            .watches
+            .push((root.to_path_buf(), RecursiveMode::Recursive));
+    }
+
+    let plan = scanner.plan;
+    let elapsed = scanner.started.elapsed();
+    if elapsed >= PROGRESS_INTERVAL {
+        info!(
+            "{}",
+            Message::Scanned {
+                directories: plan.directories,
+                files: plan.files,
+                seconds: elapsed.as_secs_f64()
+            }
+        );
+    } else {
+        debug!(
+            "scanned {} directories and {} files in {}ms",
+            plan.directories,
+            plan.files,
+            elapsed.as_millis()
+        );
+    }
+    plan
+}
+
+struct Scanner<F> {
+    plan: WatchPlan,
+    max_entries: usize,
+    is_ignored: F,
+    started: Instant,
+    last_report: Instant,
+}
+
+impl<F: Fn(&Path) -> bool> Scanner<F> {
+    /// Scans `dir` and returns `true` if it can be watched recursively as a
+    /// whole. Otherwise the watches covering its contents are added to the
+    /// plan.
+    fn scan_dir(&mut self, dir: &Path) -> bool {
+        let entries: Vec<_> = match fs_err::read_dir(dir) {
+            Ok(entries) => entries.flatten().collect(),
+            Err(err) => {
+                warn!("{}", Message::CannotScan { dir, error: &err });
+                return false;
+            }
+        };
+
+        if entries.len() > self.max_entries {
+            warn!(
+                "{}",
+                Message::OversizedDirectory {
+                    dir,
+                    entries: entries.len(),
+                    limit: self.max_entries
+                }
+            );
+            self.plan.oversized.push(dir.to_path_buf());
+            return false;
+        }
+
+        self.plan.directories += 1;
+        self.report_progress();
+
+        let mut clean = Vec::new();
+        let mut complete = true;
+        for entry in entries {
+            let path = entry.path();
+            let is_dir = entry.file_type().is_ok_and(|ty| ty.is_dir());
+            if !is_dir {
+                self.plan.files += 1;
+                continue;
+            }
+
+            if (self.is_ignored)(&path) {
+                complete = false;
+            } else if self.scan_dir(&path) {
+                clean.push(path);
+            } else {
+                complete = false;
+            }
+        }
+
+        if !complete {
+            self.plan
+                .watches
+                .push((dir.to_path_buf(), RecursiveMode::NonRecursive));
+            self.plan.watches.extend(
+                clean
+                    .into_iter()
+                    .map(|path| (path, RecursiveMode::Recursive)),
+            );
+        }
+        complete
+    }
+
+    fn report_progress(&mut self) {
+        if self.last_report.elapsed() < PROGRESS_INTERVAL {
+            return;
+        }
+
+        self.last_report = Instant::now();
+        info!(
+            "{}",
+            Message::Scanning {
+                directories: self.plan.directories,
+                files: self.plan.files
+            }
+        );
+    }
+}
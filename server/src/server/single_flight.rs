@@ -0,0 +1,53 @@
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+
+/// Coalesces concurrent runs of the same work, e.g. loading a file many
+/// requests ask for at once.
+///
+/// Only one run per key is in flight, callers arriving while it is share its
+/// result. Once it finishes the next call for the key runs the work again.
+#[derive(Debug)]
+pub(crate) struct SingleFlight<K, V> {
+    in_flight: Mutex<HashMap<K, Arc<OnceCell<Arc<V>>>>>,
+}
+
+impl<K: Eq + Hash + Clone, V> SingleFlight<K, V> {
+    pub fn new() -> Self {
+        Self {
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Runs `work` for `key`, unless a run for it is in flight already, and
+    /// returns the result of the run.
+    pub async fn run<F>(&self, key: K, work: impl FnOnce() -> F) -> Arc<V>
+    where
+        F: Future<Output = V>,
+    {
+        let cell = self
+            .in_flight
+            .lock()
+            .entry(key.clone())
+            .or_default()
+            .clone();
+        let value = cell
+            .get_or_init(|| async { Arc::new(work().await) })
+            .await
+            .clone();
+
+        // The first caller to finish clears the run, unless a newer one
+        // replaced it already
+        let mut in_flight = self.in_flight.lock();
+        if in_flight
+            .get(&key)
+            .is_some_and(|current| Arc::ptr_eq(current, &cell))
+        {
+            in_flight.remove(&key);
+        }
+        value
+    }
+}
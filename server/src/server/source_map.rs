@@ -0,0 +1,107 @@
+use serde::Deserialize;
+
+/// Digits of the base64 VLQ encoding of source map mappings.
+const BASE64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// A position in an original source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct OriginalPosition<'a> {
+    pub source: &'a str,
+    /// 1-based line
+    pub line: u32,
+    /// 1-based column
+    pub column: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawSourceMap {
+    sources: Vec<String>,
+    mappings: String,
+}
+
+/// A source map with its mappings decoded, to look up where generated code
+/// came from.
+#[derive(Debug)]
+pub(crate) struct DecodedMap {
+    sources: Vec<String>,
+    /// Segments of every generated line as `(column, source, line, column)`,
+    /// sorted by generated column
+    lines: Vec<Vec<(u32, u32, u32, u32)>>,
+}
+
+impl DecodedMap {
+    /// Parses a source map in JSON, `None` if it's malformed.
+    pub fn parse(json: &str) -> Option<Self> {
+        let raw: RawSourceMap = serde_json::from_str(json).ok()?;
+
+        // Source, line and column are relative to the previous segment
+        let (mut source, mut line, mut column) = (0i64, 0i64, 0i64);
+        let mut lines = Vec::new();
+        for encoded_line in raw.mappings.split(';') {
+            let mut generated = 0i64;
+            let mut segments = Vec::new();
+            for encoded in encoded_line.split(',').filter(|s| !s.is_empty()) {
+                let fields = decode_vlq(encoded)?;
+                generated += fields.first()?;
+                if let [_, source_delta, line_delta, column_delta, ..] = fields[..] {
+                    source += source_delta;
+                    line += line_delta;
+                    column += column_delta;
+                    segments.push((
+                        u32::try_from(generated).ok()?,
+                        u32::try_from(source).ok()?,
+                        u32::try_from(line).ok()?,
+                        u32::try_from(column).ok()?,
+                    ));
+                }
+            }
+            segments.sort_by_key(|segment| segment.0);
+            lines.push(segments);
+        }
+
+        Some(Self {
+            sources: raw.sources,
+            lines,
+        })
+    }
+
+    /// Returns where the code at the 1-based `line` and `column` of the
+    /// generated file came from.
+    pub fn lookup(&self, line: u32, column: u32) -> Option<OriginalPosition<'_>> {
+        let segments = self.lines.get(line.checked_sub(1)? as usize)?;
+        let column = column.saturating_sub(1);
+        let index = segments.partition_point(|segment| segment.0 <= column);
+        let (_, source, line, column) = segments.get(index.checked_sub(1)?)?;
+
+        Some(OriginalPosition {
+            source: self.sources.get(*source as usize)?,
+            line: line + 1,
+            column: column + 1,
+        })
+    }
+}
+
+/// Decodes the base64 VLQ numbers of a mapping segment.
+fn decode_vlq(encoded: &str) -> Option<Vec<i64>> {
+    let mut values = Vec::new();
+    let (mut value, mut shift) = (0i64, 0u32);
+    for byte in encoded.bytes() {
+        let digit = BASE64.iter().position(|&b| b == byte)? as i64;
+        value = value.checked_add((digit & 0b11111).checked_shl(shift)?)?;
+        if digit & 0b100000 != 0 {
+            shift += 5;
+            continue;
+        }
+
+        // The lowest bit holds the sign
+        let magnitude = value >> 1;
+        values.push(if value & 1 == 1 {
+            -magnitude
+        } else {
+            magnitude
+        });
+        value = 0;
+        shift = 0;
+    }
+    Some(values)
+}
@@ -0,0 +1,230 @@
+use crate::build::{SsrFormat, ssr_build_dir};
+use crate::rolldown::{BundleMode, create_ssr_bundler};
+use crate::server::Server;
+use crate::server::errors::HttpResponse;
+use crate::server::source_map::DecodedMap;
+use axum::extract::State;
+use axum::http::Response;
+use axum::response::IntoResponse;
+use palladin_shared::{PalladinError, PalladinResult};
+use rolldown_common::Output;
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+/// URL path the module bundled from the configured SSR entry is served at.
+pub const SSR_MODULE_PATH: &str = "/__palladin/ssr";
+
+/// URL path stack traces of errors thrown by the SSR module are posted to,
+/// to get them back pointing at the original sources.
+pub const SSR_STACKTRACE_PATH: &str = "/__palladin/ssr/stacktrace";
+
+/// Scheme of the URLs SSR modules name themselves with.
+const SSR_URL_SCHEME: &str = "palladin-ssr://";
+
+/// A module bundled for execution in a server side JS runtime, see
+/// [`Server::ssr_load_module`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct SsrModule {
+    /// URL the code names itself with `//# sourceURL`, which stack traces of
+    /// errors it throws refer to, e.g. `palladin-ssr:///src/entry-server.tsx`
+    pub url: String,
+    /// The module as ESM, importing installed packages as they are
+    pub code: String,
+    /// Source map of `code` as JSON
+    pub map: Option<String>,
+    /// Directory the sources of the map are relative to
+    sources_dir: PathBuf,
+}
+
+/// An SSR module with what it was bundled from.
+#[derive(Debug)]
+pub(crate) struct BundledSsrModule {
+    /// Build of the project the module was bundled in
+    build_id: u64,
+    /// Files the bundle was made from, including server only modules the
+    /// dev engine doesn't know about
+    watch_files: HashSet<PathBuf>,
+    module: SsrModule,
+}
+
+impl SsrModule {
+    /// Rewrites the frames of `stack` that point into the module, e.g.
+    /// `palladin-ssr:///src/entry-server.tsx:120:9`, to the original source
+    /// file, line and column.
+    pub fn fix_stacktrace(&self, stack: &str) -> String {
+        let Some(map) = self.map.as_deref().and_then(DecodedMap::parse) else {
+            return stack.to_string();
+        };
+
+        let mut fixed = String::with_capacity(stack.len());
+        let mut rest = stack;
+        while let Some(index) = rest.find(&self.url) {
+            fixed.push_str(&rest[..index]);
+            rest = &rest[index + self.url.len()..];
+
+            let original = parse_position(rest)
+                .and_then(|(line, column, len)| Some((map.lookup(line, column)?, len)));
+            match original {
+                Some((position, len)) => {
+                    let source = normalize(&self.sources_dir.join(position.source));
+                    fixed.push_str(&format!(
+                        "{}:{}:{}",
+                        source.display(),
+                        position.line,
+                        position.column
+                    ));
+                    rest = &rest[len..];
+                }
+                None => fixed.push_str(&self.url),
+            }
+        }
+        fixed.push_str(rest);
+        fixed
+    }
+}
+
+impl Server {
+    /// Bundles the module at `path`, relative to the root, for execution in
+    /// a server side JS runtime like Node, e.g. by an SSR framework
+    /// rendering pages.
+    ///
+    /// Everything but installed packages and Node builtins is bundled into
+    /// the module. It's bundled again once the project was rebuilt or one of
+    /// the files it was bundled from changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the module doesn't exist or can't be bundled.
+    pub async fn ssr_load_module(&self, path: impl AsRef<Path>) -> PalladinResult<SsrModule> {
+        let path = self.ctx.resolve_path(path)?;
+        let build_id = self.build_id();
+        if let Some(bundled) = self.ssr_modules.lock().get(&path)
+            && bundled.build_id == build_id
+        {
+            return Ok(bundled.module.clone());
+        }
+
+        let mut bundler =
+            create_ssr_bundler(self.ctx.clone(), &path, BundleMode::Dev, SsrFormat::Esm).build()?;
+        let output = bundler.generate().await;
+        let watch_files: HashSet<PathBuf> = bundler
+            .get_watch_files()
+            .iter()
+            .map(|file| PathBuf::from(file.as_str()))
+            .collect();
+        bundler.close().await?;
+
+        let chunk = output?
+            .assets
+            .into_iter()
+            .find_map(|asset| match asset {
+                Output::Chunk(chunk) if chunk.is_entry => Some(chunk),
+                _ => None,
+            })
+            .ok_or_else(|| {
+                PalladinError::Build(anyhow::anyhow!("the module produced no output"))
+            })?;
+
+        let url_path = self
+            .ctx
+            .url_path(&path)
+            .unwrap_or_else(|| path.to_string_lossy().replace('\\', "/"));
+        let url = format!("{SSR_URL_SCHEME}{url_path}");
+        let module = SsrModule {
+            code: format!("{}\n//# sourceURL={url}\n", chunk.code),
+            map: chunk.map.as_ref().map(|map| map.to_json_string()),
+            url,
+            sources_dir: ssr_build_dir(self.ctx.build_dir()),
+        };
+
+        self.ssr_modules.lock().insert(
+            path,
+            BundledSsrModule {
+                build_id,
+                watch_files,
+                module: module.clone(),
+            },
+        );
+        Ok(module)
+    }
+
+    /// Drops the SSR modules bundled from any of `paths`, so they're bundled
+    /// again when loaded next.
+    pub(crate) fn forget_ssr_modules<'a>(&self, paths: impl IntoIterator<Item = &'a PathBuf>) {
+        let mut modules = self.ssr_modules.lock();
+        if modules.is_empty() {
+            return;
+        }
+        for path in paths {
+            modules.retain(|_, bundled| !bundled.watch_files.contains(path));
+        }
+    }
+
+    /// Loads the configured SSR entry.
+    async fn ssr_entry_module(&self) -> PalladinResult<SsrModule> {
+        let entry =
+            self.config().ssr_entry.clone().ok_or_else(|| {
+                PalladinError::FileNotFound("no SSR entry is configured".to_string())
+            })?;
+        self.ssr_load_module(entry).await
+    }
+
+    async fn serve_ssr_module_impl(&self) -> PalladinResult<Response<String>> {
+        let module = self.ssr_entry_module().await?;
+        Ok(HttpResponse::ok(module.code)
+            .content_type("application/javascript")
+            .cache_control("no-cache")
+            .finish())
+    }
+
+    async fn fix_ssr_stacktrace_impl(&self, stack: &str) -> PalladinResult<Response<String>> {
+        let module = self.ssr_entry_module().await?;
+        Ok(HttpResponse::ok(module.fix_stacktrace(stack))
+            .content_type("text/plain; charset=utf-8")
+            .cache_control("no-store")
+            .finish())
+    }
+}
+
+pub async fn serve_ssr_module_handler(State(server): State<Arc<Server>>) -> impl IntoResponse {
+    server.serve_ssr_module_impl().await
+}
+
+pub async fn fix_ssr_stacktrace_handler(
+    State(server): State<Arc<Server>>,
+    stack: String,
+) -> impl IntoResponse {
+    server.fix_ssr_stacktrace_impl(&stack).await
+}
+
+/// Parses the `:line:column` a stack frame location continues with,
+/// returning both and the length of the text they were parsed from.
+fn parse_position(text: &str) -> Option<(u32, u32, usize)> {
+    let number = |text: &str| {
+        let digits = text.len() - text.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        Some((text[..digits].parse::<u32>().ok()?, digits))
+    };
+
+    let (line, line_len) = number(text.strip_prefix(':')?)?;
+    let rest = &text[1 + line_len..];
+    let (column, column_len) = number(rest.strip_prefix(':')?)?;
+    Some((line, column, 2 + line_len + column_len))
+}
+
+/// Resolves the `.` and `..` components of `path` without touching the
+/// filesystem.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
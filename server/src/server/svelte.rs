@@ -0,0 +1,72 @@
+use super::node::NodeBridge;
+use anyhow::anyhow;
+use palladin_shared::{PalladinError, PalladinResult};
+use serde::Deserialize;
+use serde_json::json;
+use std::path::Path;
+
+/// Feature running the Svelte compiler in node.
+const WORKER: &str = include_str!("svelte_worker.mjs");
+
+/// Compiles `.svelte` components to JavaScript in node.
+///
+/// The compiler is resolved from the project's `node_modules`, so it matches
+/// the Svelte runtime the compiled components import.
+#[derive(Debug)]
+pub struct Svelte {
+    node: NodeBridge,
+}
+
+/// A compiled component.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CompiledComponent {
+    pub js: String,
+    /// The component's styles, extracted from the JavaScript
+    pub css: Option<String>,
+}
+
+impl Svelte {
+    /// Loads the Svelte compiler of the project in `root` in node.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if node cannot be started or Svelte isn't installed.
+    pub fn spawn(root: &Path) -> PalladinResult<Self> {
+        let node = NodeBridge::spawn("Svelte", WORKER, json!({ "root": root.to_string_lossy() }))?;
+        Ok(Self { node })
+    }
+
+    /// Compiles the component `source`, read from `path`. With `dev`, the
+    /// output keeps component state across hot updates where the compiler
+    /// supports it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the component doesn't compile or node exited.
+    pub fn compile(
+        &self,
+        path: &Path,
+        source: &str,
+        dev: bool,
+    ) -> PalladinResult<CompiledComponent> {
+        let result = self.node.call(
+            "compile",
+            json!({ "filename": path.to_string_lossy(), "source": source, "dev": dev }),
+        )?;
+
+        serde_json::from_value(result).map_err(|err| {
+            PalladinError::Build(anyhow!(
+                "Svelte returned no component for {}: {err}",
+                path.display()
+            ))
+        })
+    }
+}
+
+/// Checks if Svelte is installed in the project in `root`.
+pub fn find_svelte(root: &Path) -> bool {
+    root.join("node_modules")
+        .join("svelte")
+        .join("package.json")
+        .is_file()
+}
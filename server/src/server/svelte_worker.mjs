@@ -0,0 +1,31 @@
+// Palladin feature compiling Svelte components in the node host.
+
+import { createRequire } from "node:module";
+import { resolve } from "node:path";
+import { pathToFileURL } from "node:url";
+
+export default async function setup({ root }) {
+  // The compiler comes from the project, so it matches the runtime it imports
+  const require = createRequire(resolve(root, "package.json"));
+  const compiler = await import(
+    pathToFileURL(require.resolve("svelte/compiler")).href
+  );
+  const major = Number.parseInt(compiler.VERSION, 10);
+
+  return {
+    async compile({ filename, source, dev }) {
+      const options = { filename, generate: "client", css: "external", dev };
+      // Svelte 5 generates the code keeping component state across updates
+      // itself, older versions reject the option
+      if (major >= 5) {
+        options.hmr = dev;
+      }
+
+      const result = compiler.compile(source, options);
+      for (const warning of result.warnings) {
+        console.error(`[svelte] ${filename}: ${warning.message}`);
+      }
+      return { js: result.js.code, css: result.css?.code || null };
+    },
+  };
+}
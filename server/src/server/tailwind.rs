@@ -0,0 +1,150 @@
+use super::PostCss;
+use super::node::NodeBridge;
+use anyhow::anyhow;
+use palladin_shared::{PalladinError, PalladinResult};
+use parking_lot::RwLock;
+use serde_json::json;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Feature running the Tailwind generator in node.
+const WORKER: &str = include_str!("tailwind_worker.mjs");
+
+/// Extensions of the files Tailwind scans for class names.
+const CONTENT_EXTENSIONS: &[&str] = &[
+    "html", "js", "jsx", "mjs", "cjs", "ts", "tsx", "mts", "cts", "vue", "svelte", "astro", "md",
+    "mdx",
+];
+
+/// Generates the Tailwind CSS of stylesheets using its directives in node, so
+/// no separate Tailwind watcher has to run next to the server.
+///
+/// Tailwind 4 is used through `@tailwindcss/postcss`, Tailwind 3 through
+/// `tailwindcss`, both resolved from the project's `node_modules`. The
+/// stylesheets it generated are remembered, so they can be regenerated and
+/// pushed to browsers when a content file changes.
+#[derive(Debug)]
+pub struct Tailwind {
+    node: NodeBridge,
+    /// Stylesheets using Tailwind directives seen so far
+    stylesheets: RwLock<BTreeSet<PathBuf>>,
+}
+
+impl Tailwind {
+    /// Loads the Tailwind of the project in `root` in node.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if node cannot be started or Tailwind isn't
+    /// installed.
+    pub fn spawn(root: &Path) -> PalladinResult<Self> {
+        let node = NodeBridge::spawn(
+            "Tailwind",
+            WORKER,
+            json!({ "root": root.to_string_lossy() }),
+        )?;
+        Ok(Self {
+            node,
+            stylesheets: RwLock::default(),
+        })
+    }
+
+    /// Returns `css`, read from `path`, with the Tailwind directives expanded
+    /// for the classes currently used by the content files.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if Tailwind fails or node exited.
+    pub fn process(&self, path: &Path, css: &str) -> PalladinResult<String> {
+        self.track(path);
+        let result = self.node.call(
+            "process",
+            json!({ "from": path.to_string_lossy(), "css": css }),
+        )?;
+
+        result.as_str().map(str::to_string).ok_or_else(|| {
+            PalladinError::Build(anyhow!("Tailwind returned no CSS for {}", path.display()))
+        })
+    }
+
+    /// Returns the stylesheets using Tailwind directives seen so far.
+    pub fn stylesheets(&self) -> Vec<PathBuf> {
+        self.stylesheets.read().iter().cloned().collect()
+    }
+
+    fn track(&self, path: &Path) {
+        if !self.stylesheets.read().contains(path) {
+            self.stylesheets.write().insert(path.to_path_buf());
+        }
+    }
+}
+
+/// Checks if Tailwind is installed in the project in `root`.
+pub fn find_tailwind(root: &Path) -> bool {
+    ["tailwindcss", "@tailwindcss/postcss"].iter().any(|name| {
+        root.join("node_modules")
+            .join(name)
+            .join("package.json")
+            .is_file()
+    })
+}
+
+/// Checks if the stylesheet `css` uses Tailwind directives, either
+/// `@tailwind` or an import of `tailwindcss`.
+pub(crate) fn uses_tailwind(css: &str) -> bool {
+    css.contains("@tailwind ")
+        || css.contains("@import \"tailwindcss")
+        || css.contains("@import 'tailwindcss")
+}
+
+/// Checks if a change to `path` can change the classes Tailwind generates.
+pub(crate) fn is_content_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| CONTENT_EXTENSIONS.contains(&ext))
+}
+
+/// Runs the stylesheet `css`, read from `path`, through the configured
+/// tools.
+///
+/// A PostCSS config takes precedence and is expected to include Tailwind
+/// itself when the project uses it, its Tailwind stylesheets are still
+/// tracked for regeneration. Both run in node, which is waited on from a
+/// blocking thread.
+pub(crate) async fn process_stylesheet(
+    postcss: Option<Arc<PostCss>>,
+    tailwind: Option<Arc<Tailwind>>,
+    path: &Path,
+    css: &str,
+) -> PalladinResult<String> {
+    if postcss.is_none() && tailwind.is_none() {
+        return Ok(css.to_string());
+    }
+
+    let (path, css) = (path.to_path_buf(), css.to_string());
+    tokio::task::spawn_blocking(move || {
+        process_stylesheet_blocking(postcss.as_deref(), tailwind.as_deref(), &path, &css)
+    })
+    .await
+    .map_err(|err| PalladinError::Build(err.into()))?
+}
+
+fn process_stylesheet_blocking(
+    postcss: Option<&PostCss>,
+    tailwind: Option<&Tailwind>,
+    path: &Path,
+    css: &str,
+) -> PalladinResult<String> {
+    let tailwind = tailwind.filter(|_| uses_tailwind(css));
+    match (postcss, tailwind) {
+        (Some(postcss), tailwind) => {
+            if let Some(tailwind) = tailwind {
+                tailwind.track(path);
+            }
+            postcss.process(path, css)
+        }
+        (None, Some(tailwind)) => tailwind.process(path, css),
+        (None, None) => Ok(css.to_string()),
+    }
+}
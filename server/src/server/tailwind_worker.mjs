@@ -0,0 +1,39 @@
+// Palladin feature generating Tailwind CSS in the node host.
+
+import { createRequire } from "node:module";
+import { resolve } from "node:path";
+import { pathToFileURL } from "node:url";
+
+export default async function setup({ root }) {
+  // Tailwind and PostCSS come from the project, not from palladin
+  const require = createRequire(resolve(root, "package.json"));
+
+  async function load(specifier) {
+    const module = await import(pathToFileURL(require.resolve(specifier)).href);
+    return module.default ?? module;
+  }
+
+  // Tailwind 4 ships its PostCSS plugin separately, Tailwind 3 is one itself
+  async function loadTailwind() {
+    try {
+      return await load("@tailwindcss/postcss");
+    } catch {
+      return await load("tailwindcss");
+    }
+  }
+
+  const postcss = await load("postcss");
+  const tailwind = await loadTailwind();
+
+  return {
+    // Content files are rescanned on every call, so classes added since the
+    // last one are picked up
+    async process({ from, css }) {
+      const result = await postcss([tailwind()]).process(css, { from, map: false });
+      for (const warning of result.warnings()) {
+        console.error(`[tailwind] ${from}: ${warning.toString()}`);
+      }
+      return result.css;
+    },
+  };
+}
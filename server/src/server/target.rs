@@ -0,0 +1,117 @@
+use crate::messages::Message;
+use log::warn;
+use serde_json::Value;
+use std::path::Path;
+
+/// Browsers and runtimes the bundler can lower syntax for, by their
+/// browserslist names.
+const ENGINES: &[(&str, &str)] = &[
+    ("chrome", "chrome"),
+    ("and_chr", "chrome"),
+    ("edge", "edge"),
+    ("firefox", "firefox"),
+    ("ff", "firefox"),
+    ("and_ff", "firefox"),
+    ("safari", "safari"),
+    ("ios_saf", "ios"),
+    ("ios", "ios"),
+    ("opera", "opera"),
+    ("samsung", "samsung"),
+    ("node", "node"),
+    ("ie", "ie"),
+];
+
+/// Reads the browserslist queries of `root` from the `browserslist` field of
+/// package.json or `.browserslistrc` and turns them into transpile targets,
+/// e.g. `chrome >= 80` into `chrome80`.
+///
+/// Queries that don't name a browser version, like `defaults` or `> 0.5%`,
+/// can't be resolved without usage data and are skipped with a warning.
+pub fn browserslist_targets(root: &Path) -> Vec<String> {
+    let queries = read_browserslist(root);
+    let mut targets = Vec::new();
+    let mut skipped = Vec::new();
+
+    for query in &queries {
+        match browserslist_target(query) {
+            Some(target) => targets.push(target),
+            None => skipped.push(query.as_str()),
+        }
+    }
+
+    if !skipped.is_empty() {
+        warn!(
+            "{}",
+            Message::SkippedBrowserslistQueries { queries: &skipped }
+        );
+    }
+    targets
+}
+
+fn read_browserslist(root: &Path) -> Vec<String> {
+    let from_manifest = fs_err::read_to_string(root.join("package.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+        .and_then(|manifest| {
+            let field = manifest.get("browserslist")?;
+            // Environments map to their own queries, production is what gets bundled
+            let field = field.get("production").unwrap_or(field);
+            Some(match field {
+                Value::String(query) => split_queries(query),
+                Value::Array(queries) => queries
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .flat_map(split_queries)
+                    .collect(),
+                _ => Vec::new(),
+            })
+        });
+    if let Some(queries) = from_manifest {
+        return queries;
+    }
+
+    let Ok(rc) = fs_err::read_to_string(root.join(".browserslistrc")) else {
+        return Vec::new();
+    };
+    let mut queries = Vec::new();
+    let mut section = None;
+    for line in rc.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = Some(name.trim().to_string());
+        } else if !line.is_empty() && section.as_deref().is_none_or(|s| s == "production") {
+            queries.extend(split_queries(line));
+        }
+    }
+    queries
+}
+
+fn split_queries(queries: &str) -> Vec<String> {
+    queries
+        .split([',', '\n'])
+        .map(str::trim)
+        .filter(|query| !query.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Turns a query naming a browser version, like `safari 14` or
+/// `chrome >= 80`, into a transpile target.
+fn browserslist_target(query: &str) -> Option<String> {
+    let query = query.to_ascii_lowercase();
+    let mut parts = query.split_whitespace();
+    let browser = parts.next()?;
+    let version = match (parts.next()?, parts.next()) {
+        (">=", Some(version)) => version,
+        (version, None) => version,
+        _ => return None,
+    };
+
+    let (_, engine) = ENGINES.iter().find(|(name, _)| *name == browser)?;
+    // Ranges like `15.2-15.3` start at their lowest version
+    let version = version.split('-').next()?;
+    version
+        .split('.')
+        .all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()))
+        .then(|| format!("{engine}{version}"))
+}
@@ -0,0 +1,182 @@
+use crate::messages::Message;
+use crate::server::errors::{HttpResponse, REVALIDATE};
+use crate::server::{Server, ServerConfig, listener};
+use anyhow::anyhow;
+use axum::Router;
+use axum::routing::get;
+use log::{info, warn};
+use palladin_shared::{PalladinError, PalladinResult};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// A project served under its own path prefix by [`serve_tenants`], e.g. a
+/// git worktree of one branch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tenant {
+    /// Path prefix the project is served under, without slashes
+    pub name: String,
+    pub root: PathBuf,
+}
+
+/// Parses `dir` or `name=dir`, naming the tenant after the directory when
+/// no name is given.
+impl FromStr for Tenant {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, root) = match s.split_once('=') {
+            Some((name, root)) => (Some(name.trim().to_string()), PathBuf::from(root.trim())),
+            None => (None, PathBuf::from(s.trim())),
+        };
+
+        let name = name.or_else(|| {
+            root.file_name()
+                .map(|name| name.to_string_lossy().to_string())
+        });
+        match name {
+            Some(name) if is_valid_name(&name) && !root.as_os_str().is_empty() => {
+                Ok(Self { name, root })
+            }
+            _ => Err(Message::InvalidTenant { tenant: s }.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Tenant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.name, self.root.display())
+    }
+}
+
+/// Returns a tenant for every worktree of the git repository `dir` belongs
+/// to, named after its checked out branch, `/` replaced with `-`.
+///
+/// # Errors
+///
+/// Returns an error if git fails, e.g. when `dir` isn't in a repository.
+pub fn git_worktrees(dir: &Path) -> PalladinResult<Vec<Tenant>> {
+    let output = Command::new("git")
+        .args(["worktree", "list", "--porcelain"])
+        .current_dir(dir)
+        .output()
+        .map_err(|err| PalladinError::Build(anyhow!("{}", Message::GitFailed { error: &err })))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(PalladinError::Build(anyhow!(
+            "{}",
+            Message::WorktreeListFailed {
+                error: stderr.trim()
+            }
+        )));
+    }
+
+    // Worktrees are blocks of `key value` lines separated by blank lines
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut tenants: Vec<Tenant> = Vec::new();
+    for block in stdout.split("\n\n") {
+        let mut root = None;
+        let mut branch = None;
+        for line in block.lines() {
+            match line.split_once(' ') {
+                Some(("worktree", path)) => root = Some(PathBuf::from(path)),
+                Some(("branch", name)) => {
+                    branch = Some(name.trim_start_matches("refs/heads/").replace('/', "-"));
+                }
+                _ => {}
+            }
+        }
+
+        let Some(root) = root else {
+            continue;
+        };
+        // Detached worktrees are named after their directory
+        let name = branch
+            .or_else(|| root.file_name().map(|n| n.to_string_lossy().to_string()))
+            .filter(|name| is_valid_name(name));
+        match name {
+            Some(name) if !tenants.iter().any(|tenant| tenant.name == name) => {
+                tenants.push(Tenant { name, root })
+            }
+            _ => warn!("{}", Message::SkippedWorktree { root: &root }),
+        }
+    }
+
+    Ok(tenants)
+}
+
+/// Serves several projects from one process on `address`, each under
+/// `/<name>/` with its own bundler, caches, watcher and HMR channel. The
+/// root lists the projects.
+///
+/// Every config is given the base path of its tenant, which its router is
+/// nested under, so the URLs of its pages, its HMR client and the updates it
+/// sends stay within the tenant.
+///
+/// # Errors
+///
+/// Returns an error if a server cannot be created or the address cannot be
+/// bound.
+pub async fn serve_tenants(address: &str, tenants: Vec<(String, ServerConfig)>) -> PalladinResult {
+    let mut app = Router::new();
+    let mut names = Vec::new();
+
+    for (name, config) in tenants {
+        let config = config.with_base(format!("/{name}/"));
+        let server = Arc::new(Server::new(config)?);
+        info!(
+            "{}",
+            Message::ServingTenant {
+                root: server.context().root(),
+                address,
+                name: &name
+            }
+        );
+
+        app = app.nest_service(&format!("/{name}"), server.into_router());
+        names.push(name);
+    }
+
+    let index = tenant_index(&names);
+    let app = app.route(
+        "/",
+        get(move || {
+            let index = index.clone();
+            async move {
+                HttpResponse::ok(index)
+                    .content_type("text/html")
+                    .cache_control(REVALIDATE)
+                    .finish()
+            }
+        }),
+    );
+
+    let tcp = listener::bind(address).await?;
+    axum::serve(tcp, app).await?;
+    Ok(())
+}
+
+/// Checks if `name` can be used as a path segment as-is.
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with("__")
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        && name != "."
+        && name != ".."
+}
+
+/// Returns the page linking to every tenant.
+fn tenant_index(names: &[String]) -> String {
+    let links: String = names
+        .iter()
+        .map(|name| format!("<li><a href=\"/{name}/\">{name}</a></li>\n"))
+        .collect();
+    format!(
+        "<!doctype html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Palladin</title>\n\
+         </head>\n<body>\n<ul>\n{links}</ul>\n</body>\n</html>\n"
+    )
+}
@@ -0,0 +1,68 @@
+use parking_lot::Mutex;
+use std::io::{self, BufRead};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{OnceLock, mpsc};
+
+type LineHandler = Box<dyn Fn(&str) + Send>;
+
+/// Lines entered on the terminal, read by a single thread so key bindings
+/// and prompts don't fight over the stdin lock.
+struct Terminal {
+    /// Waiting prompt, gets the next line instead of the handlers
+    prompt: Mutex<Option<mpsc::Sender<String>>>,
+    handlers: Mutex<Vec<LineHandler>>,
+    closed: AtomicBool,
+}
+
+static TERMINAL: OnceLock<Terminal> = OnceLock::new();
+
+/// Returns the terminal, starting the thread reading stdin on first use.
+fn terminal() -> &'static Terminal {
+    TERMINAL.get_or_init(|| {
+        std::thread::spawn(|| {
+            let terminal = terminal();
+            for line in io::stdin().lock().lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if let Some(prompt) = terminal.prompt.lock().take() {
+                    let _ = prompt.send(line);
+                    continue;
+                }
+                for handler in terminal.handlers.lock().iter() {
+                    handler(&line);
+                }
+            }
+            terminal.closed.store(true, Ordering::SeqCst);
+            // Wakes up a prompt waiting for a line that never comes
+            terminal.prompt.lock().take();
+        });
+        Terminal {
+            prompt: Mutex::new(None),
+            handlers: Mutex::new(Vec::new()),
+            closed: AtomicBool::new(false),
+        }
+    })
+}
+
+/// Calls `handler` with every line entered on the terminal, except the ones
+/// answering a prompt.
+pub fn on_terminal_line(handler: impl Fn(&str) + Send + 'static) {
+    terminal().handlers.lock().push(Box::new(handler));
+}
+
+/// Waits for the next line entered on the terminal, without its line ending.
+///
+/// # Errors
+///
+/// Returns an error if stdin was closed.
+pub(crate) fn read_line() -> io::Result<String> {
+    let terminal = terminal();
+    let (tx, rx) = mpsc::channel();
+    *terminal.prompt.lock() = Some(tx);
+    if terminal.closed.load(Ordering::SeqCst) {
+        terminal.prompt.lock().take();
+    }
+    rx.recv()
+        .map_err(|_| io::Error::new(io::ErrorKind::UnexpectedEof, "stdin was closed"))
+}
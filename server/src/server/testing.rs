@@ -0,0 +1,63 @@
+use crate::server::Server;
+use crate::server::hmr::HmrMessage;
+use axum::Router;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::{RecvError, TryRecvError};
+
+impl Server {
+    /// Returns the dev server as a service handling requests in-process,
+    /// e.g. with `tower::ServiceExt::oneshot`, without binding a socket.
+    ///
+    /// The returned probe receives every message broadcast to HMR clients
+    /// from now on, so tests can assert on reloads without a websocket. Like
+    /// [`Server::into_router`], the bundler and file watcher are started on
+    /// the current tokio runtime.
+    pub fn into_service(self: Arc<Self>) -> (Router, HmrProbe) {
+        let probe = HmrProbe {
+            rx: self.hmr_tx.subscribe(),
+        };
+        (self.into_router(), probe)
+    }
+}
+
+/// Receives the messages the server broadcasts to all HMR clients.
+///
+/// Patches are sent to each client on its own and don't show up here.
+#[derive(Debug)]
+pub struct HmrProbe {
+    rx: broadcast::Receiver<HmrMessage>,
+}
+
+impl HmrProbe {
+    /// Waits for the next message, `None` if none is broadcast within
+    /// `timeout`.
+    pub async fn next(&mut self, timeout: Duration) -> Option<HmrMessage> {
+        tokio::time::timeout(timeout, async {
+            loop {
+                match self.rx.recv().await {
+                    Ok(message) => return Some(message),
+                    // Messages the probe fell behind on are skipped
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        })
+        .await
+        .ok()
+        .flatten()
+    }
+
+    /// Returns the messages broadcast since the last call, without waiting.
+    pub fn drain(&mut self) -> Vec<HmrMessage> {
+        let mut messages = Vec::new();
+        loop {
+            match self.rx.try_recv() {
+                Ok(message) => messages.push(message),
+                Err(TryRecvError::Lagged(_)) => continue,
+                Err(TryRecvError::Empty | TryRecvError::Closed) => return messages,
+            }
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use super::PackageManager;
+use crate::messages::Message;
+use log::warn;
+use std::process::Command;
+
+/// Oldest node release the JS plugin bridge runs on.
+pub const MIN_NODE_VERSION: (u32, u32) = (18, 0);
+
+/// An external tool a feature shells out to.
+#[derive(Debug, Clone)]
+pub struct ToolRequirement {
+    /// Executable name, e.g. `node`
+    pub tool: String,
+    /// Oldest compatible `(major, minor)` version, any version when `None`
+    pub min_version: Option<(u32, u32)>,
+    /// The feature needing the tool, e.g. `--js-plugin`
+    pub needed_for: String,
+    /// How to install the tool
+    pub install_hint: String,
+}
+
+impl ToolRequirement {
+    /// Node, as needed by the JS plugin bridge.
+    pub fn node(needed_for: impl Into<String>) -> Self {
+        Self {
+            tool: "node".to_string(),
+            min_version: Some(MIN_NODE_VERSION),
+            needed_for: needed_for.into(),
+            install_hint: format!(
+                "install Node.js {} or newer from https://nodejs.org",
+                MIN_NODE_VERSION.0
+            ),
+        }
+    }
+
+    /// The package manager `manager`.
+    pub fn package_manager(manager: PackageManager, needed_for: impl Into<String>) -> Self {
+        let install_hint = match manager {
+            PackageManager::Npm => "npm ships with Node.js, install it from https://nodejs.org",
+            PackageManager::Pnpm | PackageManager::Yarn => {
+                "run `corepack enable`, or install it globally with npm"
+            }
+            PackageManager::Bun => "install it from https://bun.sh",
+        };
+
+        Self {
+            tool: manager.to_string(),
+            min_version: None,
+            needed_for: needed_for.into(),
+            install_hint: install_hint.to_string(),
+        }
+    }
+}
+
+/// Outcome of checking the required tools.
+#[derive(Debug, Clone, Default)]
+pub struct ToolchainReport {
+    /// Tools that are missing or too old, along with why
+    pub unusable: Vec<(ToolRequirement, String)>,
+}
+
+impl ToolchainReport {
+    /// Checks if `tool` was found with a compatible version.
+    pub fn is_usable(&self, tool: &str) -> bool {
+        !self.unusable.iter().any(|(req, _)| req.tool == tool)
+    }
+}
+
+/// Checks that every required tool exists with a compatible version and
+/// logs a single warning listing those that don't, with install hints.
+pub fn check_toolchain(requirements: &[ToolRequirement]) -> ToolchainReport {
+    let unusable: Vec<_> = requirements
+        .iter()
+        .filter_map(|req| check(req).err().map(|problem| (req.clone(), problem)))
+        .collect();
+
+    if !unusable.is_empty() {
+        warn!("{}", Message::UnusableTools { tools: &unusable });
+    }
+
+    ToolchainReport { unusable }
+}
+
+fn check(req: &ToolRequirement) -> Result<(), String> {
+    // Windows installs npm, pnpm and yarn as .cmd shims
+    let program = if cfg!(windows) && req.tool != "node" {
+        format!("{}.cmd", req.tool)
+    } else {
+        req.tool.clone()
+    };
+
+    let output = Command::new(program)
+        .arg("--version")
+        .output()
+        .map_err(|_| "not found".to_string())?;
+    if !output.status.success() {
+        return Err("`--version` failed".to_string());
+    }
+
+    let Some(min) = req.min_version else {
+        return Ok(());
+    };
+    let version = String::from_utf8_lossy(&output.stdout);
+    match parse_version(&version) {
+        Some(found) if found >= min => Ok(()),
+        Some((major, minor)) => Err(format!(
+            "found {major}.{minor}, {}.{} or newer is required",
+            min.0, min.1
+        )),
+        None => Err(format!("unrecognized version `{}`", version.trim())),
+    }
+}
+
+/// Parses the `(major, minor)` version out of `--version` output like
+/// `v20.11.1`.
+fn parse_version(output: &str) -> Option<(u32, u32)> {
+    let version = output.trim().trim_start_matches('v');
+    let mut parts = version.split(['.', '-']);
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next().and_then(|p| p.parse().ok()).unwrap_or(0);
+    Some((major, minor))
+}
@@ -0,0 +1,48 @@
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use palladin_shared::{PalladinError, PalladinResult};
+
+/// Controls how unused code is removed from the bundle.
+#[derive(Debug, Clone)]
+pub struct TreeshakeConfig {
+    /// Disables tree shaking entirely when `false`
+    pub enabled: bool,
+    /// Whether the `sideEffects` field of dependencies' package.json is respected
+    pub respect_package_side_effects: bool,
+    /// Globs of modules that have no side effects, e.g. `**/src/utils/**`
+    pub side_effect_free: Vec<String>,
+    /// Whether `/* @__PURE__ */` and `/* #__NO_SIDE_EFFECTS__ */` annotations are respected
+    pub annotations: bool,
+    /// Functions whose calls can be dropped when their result is unused
+    pub pure_functions: Vec<String>,
+}
+
+impl TreeshakeConfig {
+    /// Compiles `side_effect_free` into a glob set matched against module ids.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if one of the globs is invalid.
+    pub fn side_effect_free_globs(&self) -> PalladinResult<GlobSet> {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in &self.side_effect_free {
+            let glob = Glob::new(pattern).map_err(|err| PalladinError::Build(err.into()))?;
+            builder.add(glob);
+        }
+
+        builder
+            .build()
+            .map_err(|err| PalladinError::Build(err.into()))
+    }
+}
+
+impl Default for TreeshakeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            respect_package_side_effects: true,
+            side_effect_free: Vec::new(),
+            annotations: true,
+            pure_functions: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,434 @@
+use palladin_shared::PalladinError::FileNotFound;
+use palladin_shared::{PalladinResult, canonicalize_with_strip};
+use parking_lot::RwLock;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Debug;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+/// Number of in-memory changes buffered for slow subscribers.
+const CHANGE_CAPACITY: usize = 256;
+
+/// Size of a tar header and of the blocks file contents are padded to.
+const TAR_BLOCK: usize = 512;
+
+/// File access of the server core, so projects can be served from memory or
+/// an archive, and unsaved editor buffers can shadow files on disk.
+///
+/// Paths are absolute. Set the filesystem with
+/// [`ServerConfig::with_vfs`](super::ServerConfig::with_vfs), [`RealFs`] is
+/// used by default.
+pub trait Vfs: Debug + Send + Sync + 'static {
+    /// Reads the contents of the file at `path`.
+    fn read(&self, path: &Path) -> PalladinResult<Vec<u8>>;
+
+    /// Reads the contents of the file at `path` as UTF-8.
+    fn read_to_string(&self, path: &Path) -> PalladinResult<String> {
+        Ok(String::from_utf8(self.read(path)?)?)
+    }
+
+    /// Returns the size in bytes of the file at `path`.
+    fn file_size(&self, path: &Path) -> PalladinResult<u64> {
+        Ok(self.read(path)?.len() as u64)
+    }
+
+    /// Checks if `path` is a file.
+    fn is_file(&self, path: &Path) -> bool;
+
+    /// Checks if `path` is a directory.
+    fn is_dir(&self, path: &Path) -> bool;
+
+    /// Checks if `path` is a file or a directory.
+    fn exists(&self, path: &Path) -> bool {
+        self.is_file(path) || self.is_dir(path)
+    }
+
+    /// Returns the canonical form of `path`.
+    fn canonicalize(&self, path: &Path) -> PalladinResult<PathBuf>;
+
+    /// Returns the paths of the entries of the directory at `path`.
+    fn read_dir(&self, path: &Path) -> PalladinResult<Vec<PathBuf>>;
+
+    /// Creates the directory at `path` and its missing parents.
+    fn create_dir_all(&self, path: &Path) -> PalladinResult;
+
+    /// Checks if `path` is held in memory rather than on disk, in which case
+    /// the bundler loads it through the filesystem instead of reading it
+    /// itself.
+    fn is_virtual(&self, path: &Path) -> bool {
+        let _ = path;
+        false
+    }
+
+    /// Returns a receiver of the paths changed in memory, which the watcher
+    /// handles like changes on disk.
+    fn subscribe(&self) -> Option<broadcast::Receiver<PathBuf>> {
+        None
+    }
+}
+
+/// The filesystem of the operating system.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RealFs;
+
+impl Vfs for RealFs {
+    fn read(&self, path: &Path) -> PalladinResult<Vec<u8>> {
+        Ok(fs_err::read(path)?)
+    }
+
+    fn file_size(&self, path: &Path) -> PalladinResult<u64> {
+        Ok(fs_err::metadata(path)?.len())
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        path.is_file()
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+
+    fn canonicalize(&self, path: &Path) -> PalladinResult<PathBuf> {
+        canonicalize_with_strip(path)
+    }
+
+    fn read_dir(&self, path: &Path) -> PalladinResult<Vec<PathBuf>> {
+        fs_err::read_dir(path)?
+            .map(|entry| Ok(entry?.path()))
+            .collect()
+    }
+
+    fn create_dir_all(&self, path: &Path) -> PalladinResult {
+        palladin_shared::create_dir_all(path)
+    }
+}
+
+/// A filesystem held entirely in memory, e.g. for tests or projects loaded
+/// from an archive.
+///
+/// Paths are normalized without touching the disk, `.` and `..` segments
+/// included. Directories exist implicitly for every file they contain.
+#[derive(Debug)]
+pub struct MemoryFs {
+    files: RwLock<BTreeMap<PathBuf, Arc<[u8]>>>,
+    /// Directories created explicitly, possibly empty
+    dirs: RwLock<BTreeSet<PathBuf>>,
+    changes: broadcast::Sender<PathBuf>,
+}
+
+impl Default for MemoryFs {
+    fn default() -> Self {
+        Self {
+            files: RwLock::new(BTreeMap::new()),
+            dirs: RwLock::new(BTreeSet::new()),
+            changes: broadcast::channel(CHANGE_CAPACITY).0,
+        }
+    }
+}
+
+impl MemoryFs {
+    /// Creates an empty filesystem.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a filesystem holding the regular files of the tar archive
+    /// `archive`, placed under `root`.
+    ///
+    /// Only ustar and plain v7 headers are read, extended pax headers and
+    /// links are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the archive is truncated or a header is invalid.
+    pub fn from_tar(root: &Path, archive: &[u8]) -> PalladinResult<Self> {
+        let fs = Self::new();
+        fs.dirs.write().insert(normalize(root));
+
+        let mut offset = 0;
+        while offset + TAR_BLOCK <= archive.len() {
+            let header = &archive[offset..offset + TAR_BLOCK];
+            // The archive ends with zeroed blocks
+            if header.iter().all(|&byte| byte == 0) {
+                break;
+            }
+
+            let size = tar_size(&header[124..136])
+                .ok_or_else(|| FileNotFound(format!("invalid tar header at byte {offset}")))?;
+            let start = offset + TAR_BLOCK;
+            let end = start + size;
+            if end > archive.len() {
+                return Err(FileNotFound(format!(
+                    "truncated tar archive at byte {start}"
+                )));
+            }
+
+            let name = tar_str(&header[0..100]);
+            let name = match tar_str(&header[345..500]) {
+                prefix if header[257..262] == *b"ustar" && !prefix.is_empty() => {
+                    format!("{prefix}/{name}")
+                }
+                _ => name,
+            };
+            let path = root.join(name.trim_start_matches("./"));
+
+            match header[156] {
+                b'0' | 0 => fs.insert(&path, archive[start..end].into()),
+                b'5' => {
+                    fs.dirs.write().insert(normalize(&path));
+                }
+                _ => {}
+            }
+
+            offset = start + size.div_ceil(TAR_BLOCK) * TAR_BLOCK;
+        }
+
+        Ok(fs)
+    }
+
+    /// Writes the file at `path`, replacing its previous contents.
+    pub fn write(&self, path: &Path, contents: impl Into<Vec<u8>>) {
+        let path = normalize(path);
+        self.insert(&path, contents.into().into());
+        let _ = self.changes.send(path);
+    }
+
+    /// Removes the file at `path`, returning whether it existed.
+    pub fn remove(&self, path: &Path) -> bool {
+        let path = normalize(path);
+        let removed = self.files.write().remove(&path).is_some();
+        if removed {
+            let _ = self.changes.send(path);
+        }
+        removed
+    }
+
+    /// Returns the paths of every file.
+    pub fn paths(&self) -> Vec<PathBuf> {
+        self.files.read().keys().cloned().collect()
+    }
+
+    fn insert(&self, path: &Path, contents: Arc<[u8]>) {
+        self.files.write().insert(normalize(path), contents);
+    }
+}
+
+impl Vfs for MemoryFs {
+    fn read(&self, path: &Path) -> PalladinResult<Vec<u8>> {
+        self.files
+            .read()
+            .get(&normalize(path))
+            .map(|contents| contents.to_vec())
+            .ok_or_else(|| FileNotFound(path.display().to_string()))
+    }
+
+    fn file_size(&self, path: &Path) -> PalladinResult<u64> {
+        self.files
+            .read()
+            .get(&normalize(path))
+            .map(|contents| contents.len() as u64)
+            .ok_or_else(|| FileNotFound(path.display().to_string()))
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        self.files.read().contains_key(&normalize(path))
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        let path = normalize(path);
+        self.dirs.read().iter().any(|dir| dir.starts_with(&path))
+            || self
+                .files
+                .read()
+                .keys()
+                .any(|file| file != &path && file.starts_with(&path))
+    }
+
+    fn canonicalize(&self, path: &Path) -> PalladinResult<PathBuf> {
+        let normalized = normalize(path);
+        if self.exists(&normalized) {
+            Ok(normalized)
+        } else {
+            Err(FileNotFound(path.display().to_string()))
+        }
+    }
+
+    fn read_dir(&self, path: &Path) -> PalladinResult<Vec<PathBuf>> {
+        let dir = normalize(path);
+        if !self.is_dir(&dir) {
+            return Err(FileNotFound(path.display().to_string()));
+        }
+
+        let files = self.files.read();
+        let dirs = self.dirs.read();
+        let entries: BTreeSet<PathBuf> = files
+            .keys()
+            .chain(dirs.iter())
+            .filter_map(|path| {
+                let child = path.strip_prefix(&dir).ok()?.components().next()?;
+                Some(dir.join(child))
+            })
+            .collect();
+        Ok(entries.into_iter().collect())
+    }
+
+    fn create_dir_all(&self, path: &Path) -> PalladinResult {
+        self.dirs.write().insert(normalize(path));
+        Ok(())
+    }
+
+    fn is_virtual(&self, path: &Path) -> bool {
+        self.is_file(path)
+    }
+
+    fn subscribe(&self) -> Option<broadcast::Receiver<PathBuf>> {
+        Some(self.changes.subscribe())
+    }
+}
+
+/// Files held in memory on top of another filesystem, e.g. the unsaved
+/// buffers of an editor over the project on disk.
+///
+/// Files written to the overlay shadow the ones of the base filesystem until
+/// they're removed again, directories are those of both.
+#[derive(Debug)]
+pub struct OverlayFs {
+    base: Arc<dyn Vfs>,
+    overlay: MemoryFs,
+}
+
+impl OverlayFs {
+    /// Creates an empty overlay over `base`.
+    pub fn new(base: Arc<dyn Vfs>) -> Self {
+        Self {
+            base,
+            overlay: MemoryFs::new(),
+        }
+    }
+
+    /// Shadows the file at `path` with `contents`.
+    pub fn write(&self, path: &Path, contents: impl Into<Vec<u8>>) {
+        self.overlay.write(&self.overlay_path(path), contents);
+    }
+
+    /// Drops the shadowing contents of the file at `path`, returning whether
+    /// there were any.
+    pub fn remove(&self, path: &Path) -> bool {
+        self.overlay.remove(&self.overlay_path(path))
+    }
+
+    /// Returns the paths of the shadowed files.
+    pub fn paths(&self) -> Vec<PathBuf> {
+        self.overlay.paths()
+    }
+
+    /// Returns the path `path` is stored under, canonicalized through the
+    /// base filesystem when its directory exists there.
+    fn overlay_path(&self, path: &Path) -> PathBuf {
+        let path = normalize(path);
+        let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+            return path;
+        };
+        self.base
+            .canonicalize(parent)
+            .map(|parent| parent.join(name))
+            .unwrap_or(path)
+    }
+}
+
+impl Vfs for OverlayFs {
+    fn read(&self, path: &Path) -> PalladinResult<Vec<u8>> {
+        match self.overlay.read(path) {
+            Ok(contents) => Ok(contents),
+            Err(_) => self.base.read(path),
+        }
+    }
+
+    fn file_size(&self, path: &Path) -> PalladinResult<u64> {
+        match self.overlay.file_size(path) {
+            Ok(size) => Ok(size),
+            Err(_) => self.base.file_size(path),
+        }
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        self.overlay.is_file(path) || self.base.is_file(path)
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        self.overlay.is_dir(path) || self.base.is_dir(path)
+    }
+
+    fn canonicalize(&self, path: &Path) -> PalladinResult<PathBuf> {
+        self.base.canonicalize(path).or_else(|err| {
+            let path = self.overlay_path(path);
+            if self.overlay.is_file(&path) {
+                Ok(path)
+            } else {
+                Err(err)
+            }
+        })
+    }
+
+    fn read_dir(&self, path: &Path) -> PalladinResult<Vec<PathBuf>> {
+        let mut entries: BTreeSet<PathBuf> = self
+            .overlay
+            .read_dir(path)
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
+        match self.base.read_dir(path) {
+            Ok(base) => entries.extend(base),
+            Err(err) if entries.is_empty() => return Err(err),
+            Err(_) => {}
+        }
+        Ok(entries.into_iter().collect())
+    }
+
+    fn create_dir_all(&self, path: &Path) -> PalladinResult {
+        self.base.create_dir_all(path)
+    }
+
+    fn is_virtual(&self, path: &Path) -> bool {
+        self.overlay.is_file(path) || self.base.is_virtual(path)
+    }
+
+    fn subscribe(&self) -> Option<broadcast::Receiver<PathBuf>> {
+        self.overlay.subscribe()
+    }
+}
+
+/// Resolves `.` and `..` segments of `path` without touching the disk.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Reads a NUL terminated string field of a tar header.
+fn tar_str(field: &[u8]) -> String {
+    let end = field
+        .iter()
+        .position(|&byte| byte == 0)
+        .unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).into_owned()
+}
+
+/// Reads the octal size field of a tar header.
+fn tar_size(field: &[u8]) -> Option<usize> {
+    let digits = tar_str(field);
+    let digits = digits.trim_matches(|c: char| c == ' ' || c == '\0');
+    if digits.is_empty() {
+        return Some(0);
+    }
+    usize::from_str_radix(digits, 8).ok()
+}
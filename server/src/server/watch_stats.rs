@@ -0,0 +1,125 @@
+use crate::server::Server;
+use crate::server::errors::HttpResponse;
+use axum::extract::State;
+use axum::http::Response;
+use axum::response::IntoResponse;
+use palladin_shared::{PalladinError, PalladinResult};
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Number of recent events kept for `/__palladin/watcher`.
+const RECENT_EVENTS: usize = 200;
+
+/// What the file watcher watches and did with the events it received, to
+/// find out why a change didn't trigger a rebuild.
+#[derive(Debug, Default)]
+pub(crate) struct WatchStats {
+    state: Mutex<WatchState>,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct WatchState {
+    /// Directories watched including their subdirectories
+    roots: Vec<PathBuf>,
+    ignored: IgnoredPatterns,
+    counters: WatchCounters,
+    /// The latest events, oldest first
+    recent: VecDeque<WatchEvent>,
+}
+
+/// Where the paths the watcher ignores come from.
+#[derive(Debug, Default, Serialize)]
+pub(crate) struct IgnoredPatterns {
+    /// Ignored subtrees, e.g. the build directory and `node_modules`
+    pub directories: Vec<PathBuf>,
+    /// Globs configured with `--watch-ignore`
+    pub globs: Vec<String>,
+    /// Ignore files read from the project root
+    pub ignore_files: Vec<PathBuf>,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct WatchCounters {
+    events: u64,
+    accepted: u64,
+    filtered: u64,
+    /// Batches of accepted changes that were handled
+    batches: u64,
+    rebuilds: u64,
+    reloads: u64,
+}
+
+/// A path reported by a filesystem event.
+#[derive(Debug, Serialize)]
+struct WatchEvent {
+    path: PathBuf,
+    kind: &'static str,
+    accepted: bool,
+    /// Why the event was filtered out
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<&'static str>,
+    /// Milliseconds since the Unix epoch
+    at: u64,
+}
+
+impl WatchStats {
+    /// Replaces the watched roots and ignored patterns reported.
+    pub fn set_watched(&self, roots: Vec<PathBuf>, ignored: IgnoredPatterns) {
+        let mut state = self.state.lock();
+        state.roots = roots;
+        state.ignored = ignored;
+    }
+
+    /// Records an event for `path`, filtered out for `reason` if given.
+    pub fn record(&self, path: PathBuf, kind: &'static str, reason: Option<&'static str>) {
+        let mut state = self.state.lock();
+        state.counters.events += 1;
+        match reason {
+            Some(_) => state.counters.filtered += 1,
+            None => state.counters.accepted += 1,
+        }
+
+        if state.recent.len() == RECENT_EVENTS {
+            state.recent.pop_front();
+        }
+        state.recent.push_back(WatchEvent {
+            path,
+            kind,
+            accepted: reason.is_none(),
+            reason,
+            at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or_default(),
+        });
+    }
+
+    /// Records a handled batch of changes and what it led to.
+    pub fn record_batch(&self, rebuild: bool, reload: bool) {
+        let mut state = self.state.lock();
+        let counters = &mut state.counters;
+        counters.batches += 1;
+        counters.rebuilds += u64::from(rebuild);
+        counters.reloads += u64::from(reload && !rebuild);
+    }
+}
+
+pub async fn serve_watcher_handler(State(server): State<Arc<Server>>) -> impl IntoResponse {
+    Server::serve_watcher_impl(&server)
+}
+
+impl Server {
+    fn serve_watcher_impl(&self) -> PalladinResult<Response<String>> {
+        let body = serde_json::to_string_pretty(&*self.watch_stats.state.lock())
+            .map_err(|e| PalladinError::Build(e.into()))?;
+
+        Ok(HttpResponse::ok(body)
+            .content_type("application/json")
+            .cache_control("no-cache")
+            .finish())
+    }
+}
@@ -0,0 +1,737 @@
+use crate::messages::Message;
+use crate::rolldown::is_asset;
+use crate::server::hmr::HmrMessage;
+use crate::server::module_deps::Invalidation;
+use crate::server::scan::{WatchPlan, scan};
+use crate::server::watch_stats::{IgnoredPatterns, WatchStats};
+use crate::server::{Context, Dependency, HmrAction, Server, is_content_file, source_aliases};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use log::{debug, error, info, warn};
+use notify::event::{ModifyKind, RenameMode};
+use notify::{Config, Event, EventKind, PollWatcher, RecursiveMode, Watcher};
+use palladin_shared::{PalladinError, PalladinResult};
+use serde_json::json;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+
+/// Default quiet period after the last event before a batch is handled.
+pub const DEFAULT_DEBOUNCE_MS: u64 = 100;
+
+/// Default interval between scans when polling.
+pub const DEFAULT_POLL_INTERVAL_MS: u64 = 1000;
+
+/// Default number of entries above which a directory isn't watched.
+pub const DEFAULT_MAX_DIR_ENTRIES: usize = 10_000;
+
+/// Ignore files read from the project root, later ones take precedence.
+const IGNORE_FILES: &[&str] = &[".gitignore", ".palladinignore"];
+
+/// Directories that are never watched, even without an ignore file.
+const ALWAYS_IGNORED: &[&str] = &[".git", "node_modules"];
+
+/// Options controlling which changes the file watcher reacts to.
+#[derive(Debug, Clone)]
+pub struct WatchOptions {
+    /// Globs relative to the root whose changes are ignored, e.g. `coverage/**`
+    pub ignored: Vec<String>,
+    /// Also watch packages symlinked into `node_modules`, e.g. by `pnpm link`
+    pub follow_symlinks: bool,
+    /// Additional directories to watch, such as workspace sibling packages
+    pub roots: Vec<PathBuf>,
+    /// Milliseconds without new events before a batch of changes is handled,
+    /// by palladin's watcher and the dev engine's alike
+    pub debounce_ms: u64,
+    /// Scan for changes every given milliseconds instead of relying on OS
+    /// events, which don't cross Docker, NFS or WSL2 mounts
+    pub poll_interval_ms: Option<u64>,
+    /// Subtrees relative to the root that are neither watched nor served,
+    /// e.g. large fixture or data directories
+    pub excluded: Vec<PathBuf>,
+    /// Directories with more entries than this are skipped when watching
+    pub max_dir_entries: usize,
+}
+
+impl Default for WatchOptions {
+    fn default() -> Self {
+        Self {
+            ignored: Vec::new(),
+            follow_symlinks: false,
+            roots: Vec::new(),
+            debounce_ms: DEFAULT_DEBOUNCE_MS,
+            poll_interval_ms: None,
+            excluded: Vec::new(),
+            max_dir_entries: DEFAULT_MAX_DIR_ENTRIES,
+        }
+    }
+}
+
+/// Watches the project root and forwards filesystem events to the server.
+pub struct FileWatcher {
+    watcher: Box<dyn Watcher + Send>,
+    /// Canonical path prefixes whose events are ignored
+    ignored: Vec<PathBuf>,
+    /// Patterns from the project's ignore files
+    gitignore: Gitignore,
+    /// User configured globs, matched against root-relative paths
+    globs: GlobSet,
+    root: PathBuf,
+    /// Directories watched including their subdirectories
+    recursive: Vec<PathBuf>,
+}
+
+impl FileWatcher {
+    /// Creates a watcher and returns it together with the receiving end of its
+    /// event channel.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the platform watcher cannot be created or an
+    /// ignore glob is invalid.
+    pub fn new(ctx: &Context) -> PalladinResult<(Self, UnboundedReceiver<Event>)> {
+        let (tx, rx) = unbounded_channel();
+        let watcher: Box<dyn Watcher + Send> = match ctx.config().watch.poll_interval_ms {
+            Some(interval) => {
+                let config = Config::default().with_poll_interval(Duration::from_millis(interval));
+                Box::new(PollWatcher::new(event_handler(tx), config)?)
+            }
+            None => Box::new(notify::recommended_watcher(event_handler(tx))?),
+        };
+
+        let mut ignored = vec![ctx.build_dir().clone()];
+        ignored.extend(ALWAYS_IGNORED.iter().map(|dir| ctx.root().join(dir)));
+        ignored.extend(
+            ctx.config()
+                .watch
+                .excluded
+                .iter()
+                .map(|dir| ctx.root().join(dir)),
+        );
+
+        Ok((
+            Self {
+                watcher,
+                ignored,
+                gitignore: load_ignore_files(ctx.root()),
+                globs: build_globs(&ctx.config().watch.ignored)?,
+                root: ctx.root().clone(),
+                recursive: Vec::new(),
+            },
+            rx,
+        ))
+    }
+
+    /// Starts watching `path` recursively.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the path cannot be watched.
+    pub fn watch(&mut self, path: &Path) -> PalladinResult {
+        self.watcher.watch(path, RecursiveMode::Recursive)?;
+        self.recursive.push(path.to_path_buf());
+        Ok(())
+    }
+
+    /// Sets up the watches planned by a startup scan.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a directory cannot be watched.
+    pub fn apply_plan(&mut self, plan: WatchPlan) -> PalladinResult {
+        for (path, mode) in plan.watches {
+            match mode {
+                RecursiveMode::Recursive => self.watch(&path)?,
+                RecursiveMode::NonRecursive => self.watcher.watch(&path, mode)?,
+            }
+        }
+
+        // Keep them from being watched once they change
+        self.ignored.extend(plan.oversized);
+        Ok(())
+    }
+
+    /// Starts watching directories created inside a directory that is only
+    /// watched non-recursively.
+    fn watch_new_dirs(&mut self, changes: &ChangeSet) {
+        for path in &changes.changed {
+            let covered = self.recursive.iter().any(|dir| path.starts_with(dir));
+            if covered || !path.is_dir() {
+                continue;
+            }
+
+            if let Err(err) = self.watch(path) {
+                warn!("{}", Message::CannotWatch { path, error: &err });
+            }
+        }
+    }
+
+    /// Starts watching a directory outside the project root, skipping its own
+    /// `node_modules`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the path cannot be watched.
+    pub fn watch_external(&mut self, path: &Path) -> PalladinResult {
+        self.watch(path)?;
+        self.ignored.push(path.join("node_modules"));
+        Ok(())
+    }
+
+    /// Checks if events for `path` should be ignored.
+    pub fn is_ignored_path(&self, path: &Path) -> bool {
+        self.ignore_reason(path).is_some()
+    }
+
+    /// Returns why events for `path` are ignored, `None` if they aren't.
+    fn ignore_reason(&self, path: &Path) -> Option<&'static str> {
+        if self.ignored.iter().any(|ignored| path.starts_with(ignored)) {
+            return Some("ignored directory");
+        }
+
+        if let Ok(relative) = path.strip_prefix(&self.root)
+            && self.globs.is_match(relative)
+        {
+            return Some("ignore glob");
+        }
+
+        // Matching panics for paths outside the ignore root
+        let ignored = path.starts_with(self.gitignore.path())
+            && self
+                .gitignore
+                .matched_path_or_any_parents(path, path.is_dir())
+                .is_ignore();
+        ignored.then_some("ignore file")
+    }
+
+    /// Reports what is watched and ignored to `stats`, with `globs` being
+    /// the configured ignore globs.
+    fn report(&self, stats: &WatchStats, globs: &[String]) {
+        let ignore_files = IGNORE_FILES
+            .iter()
+            .map(|name| self.root.join(name))
+            .filter(|path| path.is_file())
+            .collect();
+
+        stats.set_watched(
+            self.recursive.clone(),
+            IgnoredPatterns {
+                directories: self.ignored.clone(),
+                globs: globs.to_vec(),
+                ignore_files,
+            },
+        );
+    }
+}
+
+fn build_globs(patterns: &[String]) -> PalladinResult<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = Glob::new(pattern)
+            .map_err(|err| PalladinError::Watcher(format!("invalid ignore glob: {err}")))?;
+        builder.add(glob);
+    }
+
+    builder
+        .build()
+        .map_err(|err| PalladinError::Watcher(err.to_string()))
+}
+
+/// Returns the canonical targets of packages symlinked into `root/node_modules`,
+/// including scoped ones.
+///
+/// Links into `node_modules` itself, like pnpm's `.pnpm` store, are regular
+/// installs and skipped.
+fn linked_packages(root: &Path) -> Vec<PathBuf> {
+    let node_modules = root.join("node_modules");
+    let Ok(entries) = fs_err::read_dir(&node_modules) else {
+        return Vec::new();
+    };
+
+    let mut candidates = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let is_scope = entry.file_name().to_string_lossy().starts_with('@');
+        match fs_err::read_dir(&path) {
+            Ok(scoped) if is_scope => candidates.extend(scoped.flatten().map(|e| e.path())),
+            _ => candidates.push(path),
+        }
+    }
+
+    let node_modules = node_modules.canonicalize().unwrap_or(node_modules);
+    let mut linked: Vec<PathBuf> = candidates
+        .into_iter()
+        .filter(|path| path.is_symlink())
+        .filter_map(|path| path.canonicalize().ok())
+        .filter(|target| target.is_dir() && !target.starts_with(&node_modules))
+        .collect();
+    linked.sort();
+    linked.dedup();
+    linked
+}
+
+/// Reads the ignore files in `root`, skipping the ones that are missing or invalid.
+fn load_ignore_files(root: &Path) -> Gitignore {
+    let mut builder = GitignoreBuilder::new(root);
+    for name in IGNORE_FILES {
+        let path = root.join(name);
+        if !path.is_file() {
+            continue;
+        }
+
+        if let Some(err) = builder.add(&path) {
+            warn!(
+                "{}",
+                Message::IgnoreFileUnreadable {
+                    path: &path,
+                    error: &err
+                }
+            );
+        }
+    }
+
+    builder.build().unwrap_or_else(|err| {
+        warn!("{}", Message::InvalidIgnorePatterns { error: &err });
+        Gitignore::empty()
+    })
+}
+
+fn event_handler(tx: UnboundedSender<Event>) -> impl Fn(notify::Result<Event>) + Send + 'static {
+    move |result| match result {
+        Ok(event) => {
+            let _ = tx.send(event);
+        }
+        Err(err) => warn!("{}", Message::WatcherError { error: &err }),
+    }
+}
+
+/// Returns the name `kind` is reported under in the watcher statistics.
+fn event_kind_name(kind: &EventKind) -> &'static str {
+    match kind {
+        EventKind::Create(_) => "create",
+        EventKind::Modify(ModifyKind::Name(_)) => "rename",
+        EventKind::Modify(_) => "modify",
+        EventKind::Remove(_) => "remove",
+        EventKind::Access(_) => "access",
+        EventKind::Any | EventKind::Other => "other",
+    }
+}
+
+/// Paths touched by a batch of filesystem events.
+#[derive(Debug, Default)]
+struct ChangeSet {
+    changed: HashSet<PathBuf>,
+    removed: HashSet<PathBuf>,
+    /// Whether the changes were made in memory, out of the dev engine's sight
+    in_memory: bool,
+}
+
+impl ChangeSet {
+    fn add(&mut self, event: Event, watcher: &FileWatcher, stats: &WatchStats) {
+        let kind = event_kind_name(&event.kind);
+        let handled = matches!(
+            event.kind,
+            EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+        );
+        let paths: Vec<PathBuf> = event
+            .paths
+            .into_iter()
+            .filter(|path| {
+                let reason = match handled {
+                    true => watcher.ignore_reason(path),
+                    false => Some("event kind not handled"),
+                };
+                stats.record(path.clone(), kind, reason);
+                reason.is_none()
+            })
+            .collect();
+
+        match event.kind {
+            EventKind::Modify(ModifyKind::Name(RenameMode::From)) | EventKind::Remove(_) => {
+                paths.into_iter().for_each(|path| self.mark_removed(path));
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => {
+                // Paths are reported as [from, to]
+                let mut paths = paths.into_iter();
+                if let Some(from) = paths.next() {
+                    self.mark_removed(from);
+                }
+                paths.for_each(|to| self.mark_changed(to));
+            }
+            EventKind::Modify(ModifyKind::Name(_)) => {
+                // The backend doesn't say which side of the rename this is
+                for path in paths {
+                    if path.exists() {
+                        self.mark_changed(path);
+                    } else {
+                        self.mark_removed(path);
+                    }
+                }
+            }
+            EventKind::Create(_) | EventKind::Modify(_) => {
+                paths.into_iter().for_each(|path| self.mark_changed(path));
+            }
+            _ => {}
+        }
+    }
+
+    /// Adds a path changed in memory, which is removed when it no longer
+    /// exists.
+    fn add_in_memory(&mut self, path: PathBuf, exists: bool, stats: &WatchStats) {
+        stats.record(path.clone(), "in-memory", None);
+        self.in_memory = true;
+        if exists {
+            self.mark_changed(path);
+        } else {
+            self.mark_removed(path);
+        }
+    }
+
+    fn mark_changed(&mut self, path: PathBuf) {
+        self.removed.remove(&path);
+        self.changed.insert(path);
+    }
+
+    fn mark_removed(&mut self, path: PathBuf) {
+        self.changed.remove(&path);
+        self.removed.insert(path);
+    }
+
+    fn is_empty(&self) -> bool {
+        self.changed.is_empty() && self.removed.is_empty()
+    }
+}
+
+impl Server {
+    /// Watches the project root, extra watch roots and, when enabled, linked
+    /// packages for changes until the watcher shuts down.
+    ///
+    /// Bundled modules are rebuilt by the dev engine itself; this loop covers
+    /// files served straight from disk and notifies clients about deletions.
+    pub(crate) async fn watch_files(self: Arc<Self>) -> PalladinResult {
+        if let Some(changes) = self.ctx.vfs().subscribe() {
+            tokio::spawn(Arc::clone(&self).watch_in_memory(changes));
+        }
+        let invalidations = self.ctx.module_dependencies().subscribe();
+        tokio::spawn(Arc::clone(&self).watch_invalidations(invalidations));
+        // Projects held entirely in memory have nothing to watch on disk
+        if !self.ctx.root().is_dir() {
+            return Ok(());
+        }
+
+        let (watcher, mut rx) = FileWatcher::new(&self.ctx)?;
+        let options = &self.config().watch;
+
+        // Walk the project first, so heavy subtrees can be left out and large
+        // roots report progress instead of stalling silently
+        let root = self.ctx.root().clone();
+        let max_entries = options.max_dir_entries;
+        let (mut watcher, plan) = tokio::task::spawn_blocking(move || {
+            let plan = scan(&root, max_entries, |path| watcher.is_ignored_path(path));
+            (watcher, plan)
+        })
+        .await
+        .map_err(|err| PalladinError::Watcher(err.to_string()))?;
+        watcher.apply_plan(plan)?;
+        let globs = &options.ignored;
+
+        let mut external: Vec<PathBuf> = options
+            .roots
+            .iter()
+            .map(|path| self.ctx.root().join(path))
+            .collect();
+        if options.follow_symlinks {
+            external.extend(linked_packages(self.ctx.root()));
+        }
+        if self.config().workspace_sources {
+            let aliases = source_aliases(self.ctx.root(), &self.config().source_entries);
+            external.extend(aliases.iter().map(|alias| alias.source_dir().to_path_buf()));
+        }
+
+        for path in external {
+            match watcher.watch_external(&path) {
+                Ok(()) => info!("{}", Message::Watching { path: &path }),
+                Err(err) => warn!(
+                    "{}",
+                    Message::CannotWatch {
+                        path: &path,
+                        error: &err
+                    }
+                ),
+            }
+        }
+        watcher.report(&self.watch_stats, globs);
+
+        let debounce = Duration::from_millis(options.debounce_ms);
+        while let Some(event) = rx.recv().await {
+            let mut changes = ChangeSet::default();
+            changes.add(event, &watcher, &self.watch_stats);
+
+            // Every event restarts the window, so a burst of saves is
+            // handled as a single batch
+            while let Ok(Some(event)) = tokio::time::timeout(debounce, rx.recv()).await {
+                changes.add(event, &watcher, &self.watch_stats);
+            }
+
+            if !changes.is_empty() {
+                watcher.watch_new_dirs(&changes);
+                watcher.report(&self.watch_stats, globs);
+                self.apply_changes(changes).await;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Handles the files changed in memory by the configured
+    /// [`Vfs`](crate::server::Vfs) like changes on disk.
+    async fn watch_in_memory(self: Arc<Self>, mut rx: broadcast::Receiver<PathBuf>) {
+        let debounce = Duration::from_millis(self.config().watch.debounce_ms);
+        let vfs = self.ctx.vfs();
+
+        loop {
+            let path = match rx.recv().await {
+                Ok(path) => path,
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("{}", Message::MissedBufferChanges { count: skipped });
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+
+            let mut changes = ChangeSet::default();
+            changes.add_in_memory(path.clone(), vfs.exists(&path), &self.watch_stats);
+            while let Ok(Ok(path)) = tokio::time::timeout(debounce, rx.recv()).await {
+                changes.add_in_memory(path.clone(), vfs.exists(&path), &self.watch_stats);
+            }
+            self.apply_changes(changes).await;
+        }
+    }
+
+    /// Rebuilds the modules plugins invalidated, e.g. after an env variable
+    /// they're generated from changed.
+    async fn watch_invalidations(self: Arc<Self>, mut rx: broadcast::Receiver<Invalidation>) {
+        let dependencies = self.ctx.module_dependencies();
+
+        loop {
+            let ids = match rx.recv().await {
+                Ok(Invalidation::Dependency(dependency)) => dependencies.dependents(&dependency),
+                Ok(Invalidation::Module(id)) => vec![id],
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("{}", Message::MissedInvalidations { count: skipped });
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+
+            if !ids.is_empty() {
+                debug!("modules invalidated: {ids:?}");
+                self.invalidate_modules(ids).await;
+            }
+        }
+    }
+
+    async fn apply_changes(&self, changes: ChangeSet) {
+        // Server only modules aren't part of the client build
+        self.forget_ssr_modules(changes.changed.iter().chain(&changes.removed));
+
+        if let Some(mocks) = &self.config().mocks
+            && changes
+                .changed
+                .iter()
+                .chain(&changes.removed)
+                .any(|path| path.starts_with(mocks.dir()))
+        {
+            self.load_mocks();
+        }
+
+        if let Some(postcss) = &self.config().postcss
+            && changes.changed.iter().chain(&changes.removed).any(|path| {
+                path.extension().is_some_and(|ext| ext == "css") || is_content_file(path)
+            })
+        {
+            postcss.clear_cache();
+        }
+
+        let graph = self.module_graph();
+        let mut reload = false;
+        let mut rebuild = false;
+
+        if !changes.removed.is_empty() {
+            for path in &changes.removed {
+                let was_served = self.files.write().remove(path).is_some();
+                if graph.contains_module(path) {
+                    rebuild = true;
+                } else if was_served {
+                    reload = true;
+                }
+            }
+
+            let paths: Vec<String> = changes
+                .removed
+                .iter()
+                .filter_map(|path| self.ctx.url_path(path))
+                .map(|path| self.ctx.base_url(&path))
+                .collect();
+
+            debug!("files removed: {paths:?}");
+            let _ = self.hmr_tx.send(HmrMessage::Prune { paths });
+        }
+
+        // The dev engine may not pick up modules living outside the root,
+        // e.g. linked packages, nor the ones changed in memory
+        let mut invalidated: Vec<String> = changes
+            .changed
+            .iter()
+            .filter(|path| {
+                (changes.in_memory || !path.starts_with(self.ctx.root()))
+                    && graph.contains_module(path)
+            })
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect();
+
+        rebuild |= self.update_tailwind_stylesheets(&changes);
+
+        // Modules plugins generate from the changed files
+        let dependencies = self.ctx.module_dependencies();
+        for path in changes.changed.iter().chain(&changes.removed) {
+            for id in dependencies.dependents(&Dependency::File(path.clone())) {
+                if !invalidated.contains(&id) {
+                    invalidated.push(id);
+                }
+            }
+        }
+
+        // Plugins can take over changes, e.g. by sending a custom event
+        let plugins = &self.config().plugins;
+        let unhandled: Vec<&PathBuf> = changes
+            .changed
+            .iter()
+            .filter(|path| !plugins.iter().any(|p| p.handle_hot_update(path, self)))
+            .collect();
+
+        // Bundled modules are up to the dev engine, the policy decides about
+        // everything else. Imported assets are served from where they are,
+        // so pages still hold the old file
+        let mut assets = Vec::new();
+        for path in unhandled {
+            let Ok(relative) = path.strip_prefix(self.ctx.root()) else {
+                continue;
+            };
+            if graph.contains_module(path) && !is_asset(path) {
+                continue;
+            }
+
+            let url = self
+                .ctx
+                .base_url(&self.ctx.url_path(path).unwrap_or_default());
+            match self.config().hmr.action_for(relative) {
+                HmrAction::Refresh => assets.push(url),
+                HmrAction::Event(event) => {
+                    let _ = self.hmr_tx.send(HmrMessage::Custom {
+                        event: event.clone(),
+                        data: json!({ "path": url }),
+                    });
+                }
+                HmrAction::Reload => reload |= self.files.read().contains_key(path),
+                HmrAction::Ignore => {}
+            }
+        }
+        if !assets.is_empty() && !reload {
+            debug!("assets changed: {assets:?}");
+            let _ = self.hmr_tx.send(HmrMessage::AssetUpdate { paths: assets });
+        }
+
+        self.watch_stats
+            .record_batch(rebuild || !invalidated.is_empty(), reload);
+        if rebuild {
+            // Clients are reloaded once the new output is processed
+            debug!("bundled module removed, rebuilding");
+            self.rebuild().await;
+        } else if !invalidated.is_empty() {
+            debug!("modules invalidated: {invalidated:?}");
+            self.invalidate_modules(invalidated).await;
+        } else if reload {
+            debug!("served file changed, reloading clients");
+            let _ = self.hmr_tx.send(HmrMessage::FullReload);
+        }
+    }
+
+    /// Pushes the Tailwind stylesheets served from disk to clients again when
+    /// content files changed, returning whether bundled ones need a rebuild.
+    fn update_tailwind_stylesheets(&self, changes: &ChangeSet) -> bool {
+        let Some(tailwind) = &self.config().tailwind else {
+            return false;
+        };
+        if !changes.changed.iter().any(|path| is_content_file(path)) {
+            return false;
+        }
+
+        let graph = self.module_graph();
+        let (bundled, served): (Vec<PathBuf>, Vec<PathBuf>) = tailwind
+            .stylesheets()
+            .into_iter()
+            .partition(|path| graph.contains_module(path));
+
+        let paths: Vec<String> = {
+            let files = self.files.read();
+            served
+                .iter()
+                .filter(|path| files.contains_key(*path))
+                .filter_map(|path| self.ctx.url_path(path))
+                .map(|path| self.ctx.base_url(&path))
+                .collect()
+        };
+        if !paths.is_empty() {
+            debug!("tailwind stylesheets updated: {paths:?}");
+            let _ = self.hmr_tx.send(HmrMessage::CssUpdate { paths });
+        }
+
+        !bundled.is_empty()
+    }
+
+    /// Tells the dev engine the modules `ids` changed, for changes it cannot
+    /// see on disk, and sends clients the updates it computed for them.
+    pub(crate) async fn invalidate_modules(&self, ids: Vec<String>) {
+        let Some(engine) = self.engine() else {
+            return;
+        };
+
+        for id in ids {
+            match engine.invalidate(id.clone(), None).await {
+                Ok(updates) => self.deliver_hmr_updates(updates),
+                Err(err) => error!(
+                    "{}",
+                    Message::InvalidationFailed {
+                        id: &id,
+                        error: &err
+                    }
+                ),
+            }
+        }
+
+        // Pages reloaded by the engine load the new output
+        if let Err(err) = engine.ensure_latest_build_output().await {
+            error!("{}", Message::RebuildFailed { error: &err });
+        }
+        self.apply_pending_reloads();
+    }
+
+    /// Asks the dev engine to bring its output up to date, reloading every
+    /// client once it's served.
+    async fn rebuild(&self) {
+        let Some(engine) = self.engine() else {
+            return;
+        };
+
+        self.pending_reloads.add_all();
+
+        if let Err(err) = engine.ensure_latest_build_output().await {
+            error!("{}", Message::RebuildFailed { error: &err });
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use serde_json::Value;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Source entries tried, in order, for a workspace package that neither has
+/// a configured one nor a `source` field in its `package.json`.
+const CONVENTIONAL_SOURCE_ENTRIES: &[&str] = &[
+    "src/index.ts",
+    "src/index.tsx",
+    "src/index.mts",
+    "src/index.js",
+    "src/index.jsx",
+    "src/main.ts",
+    "src/main.js",
+];
+
+/// The source entry of a workspace package, overriding the detected one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceEntry {
+    /// Name of the package, e.g. `@acme/ui`
+    pub package: String,
+    /// Entry module, relative to the package directory
+    pub path: PathBuf,
+}
+
+/// Parses `name=path`.
+impl FromStr for SourceEntry {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((package, path)) if !package.trim().is_empty() && !path.trim().is_empty() => {
+                Ok(Self {
+                    package: package.trim().to_string(),
+                    path: PathBuf::from(path.trim()),
+                })
+            }
+            _ => Err(format!("invalid source entry '{s}', expected NAME=PATH")),
+        }
+    }
+}
+
+impl fmt::Display for SourceEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.package, self.path.display())
+    }
+}
+
+/// A workspace package imported from its sources.
+#[derive(Debug, Clone)]
+pub(crate) struct SourceAlias {
+    pub package: String,
+    /// The module imports of the package resolve to
+    pub entry: PathBuf,
+}
+
+impl SourceAlias {
+    /// Returns the directory holding the package's sources.
+    pub fn source_dir(&self) -> &Path {
+        self.entry.parent().unwrap_or(&self.entry)
+    }
+}
+
+/// Finds the source entries of the packages of the workspace `root` belongs
+/// to, with `overrides` taking precedence over the `source` field of their
+/// `package.json` and [`CONVENTIONAL_SOURCE_ENTRIES`].
+///
+/// Packages without sources are left out, so they resolve to their build
+/// output as usual.
+pub(crate) fn source_aliases(root: &Path, overrides: &[SourceEntry]) -> Vec<SourceAlias> {
+    workspace_packages(root)
+        .into_iter()
+        .filter_map(|dir| {
+            let manifest = read_manifest(&dir)?;
+            let package = manifest.get("name")?.as_str()?.to_string();
+
+            let configured = overrides
+                .iter()
+                .find(|entry| entry.package == package)
+                .map(|entry| entry.path.to_string_lossy().to_string());
+            let declared = manifest
+                .get("source")
+                .and_then(Value::as_str)
+                .map(str::to_string);
+            let entry = configured
+                .or(declared)
+                .map(|entry| dir.join(entry))
+                .filter(|entry| entry.is_file())
+                .or_else(|| {
+                    CONVENTIONAL_SOURCE_ENTRIES
+                        .iter()
+                        .map(|candidate| dir.join(candidate))
+                        .find(|entry| entry.is_file())
+                })?;
+
+            Some(SourceAlias { package, entry })
+        })
+        .collect()
+}
+
+pub(crate) fn read_manifest(dir: &Path) -> Option<Value> {
+    let content = fs_err::read_to_string(dir.join("package.json")).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Finds the packages of the workspace `root` belongs to, other than `root`
+/// itself.
+pub(crate) fn workspace_packages(root: &Path) -> Vec<PathBuf> {
+    let Some((workspace, patterns)) = root.ancestors().skip(1).find_map(|dir| {
+        let patterns = workspace_patterns(dir)?;
+        Some((dir, patterns))
+    }) else {
+        return Vec::new();
+    };
+
+    patterns
+        .iter()
+        .flat_map(|pattern| expand_pattern(workspace, pattern))
+        .filter(|dir| dir != root)
+        .collect()
+}
+
+/// Reads the package globs from `package.json` `workspaces` or
+/// `pnpm-workspace.yaml` in `dir`.
+fn workspace_patterns(dir: &Path) -> Option<Vec<String>> {
+    if let Some(workspaces) = read_manifest(dir).and_then(|m| m.get("workspaces").cloned()) {
+        // Either a list or yarn's `{ "packages": [...] }`
+        let list = workspaces.get("packages").unwrap_or(&workspaces);
+        return Some(
+            list.as_array()?
+                .iter()
+                .filter_map(|p| p.as_str().map(str::to_string))
+                .collect(),
+        );
+    }
+
+    let yaml = fs_err::read_to_string(dir.join("pnpm-workspace.yaml")).ok()?;
+    Some(
+        yaml.lines()
+            .filter_map(|line| line.trim().strip_prefix("- "))
+            .map(|item| item.trim().trim_matches(['\'', '"']).to_string())
+            .filter(|item| !item.starts_with('!'))
+            .collect(),
+    )
+}
+
+/// Expands a workspace glob, supporting a trailing `*` or `**`.
+fn expand_pattern(workspace: &Path, pattern: &str) -> Vec<PathBuf> {
+    let Some(parent) = pattern
+        .strip_suffix("/**")
+        .or_else(|| pattern.strip_suffix("/*"))
+    else {
+        let dir = workspace.join(pattern);
+        return if dir.is_dir() { vec![dir] } else { Vec::new() };
+    };
+
+    let Ok(entries) = fs_err::read_dir(workspace.join(parent)) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.join("package.json").is_file())
+        .collect()
+}
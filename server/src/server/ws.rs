@@ -0,0 +1,273 @@
+use crate::messages;
+use crate::server::Server;
+use crate::server::errors::HttpResponse;
+use crate::server::hmr::{
+    ClientMessage, HMR_CAPABILITIES, HMR_CLIENT, HMR_PROTOCOL_VERSION, HmrMessage, PALLADIN_VERSION,
+};
+use axum::body::Bytes;
+use axum::extract::ws::{CloseFrame, Message, Utf8Bytes, WebSocket, WebSocketUpgrade, close_code};
+use axum::extract::{Query, State};
+use axum::response::IntoResponse;
+use log::{debug, warn};
+use palladin_shared::PalladinError;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::time::{Instant, interval};
+
+/// How often a ping frame is sent to each client.
+const PING_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Connections that haven't answered a ping for this long are closed.
+const PONG_TIMEOUT: Duration = Duration::from_secs(45);
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HmrQuery {
+    /// Server id the client was connected to before it lost the connection
+    server_id: Option<String>,
+    /// Protocol version of the client, missing for clients predating versioning
+    protocol: Option<u32>,
+    /// Version of palladin that served the client, missing for clients
+    /// predating versioned client URLs
+    version: Option<String>,
+    /// Comma separated optional capabilities the client supports
+    capabilities: Option<String>,
+    /// Build the client's page was served for
+    build: Option<u64>,
+}
+
+impl HmrQuery {
+    fn capabilities(&self) -> Vec<String> {
+        self.capabilities
+            .as_deref()
+            .unwrap_or("")
+            .split(',')
+            .map(str::trim)
+            .filter(|c| !c.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+pub async fn hmr_ws_handler(
+    ws: WebSocketUpgrade,
+    State(server): State<Arc<Server>>,
+    Query(query): Query<HmrQuery>,
+) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| handle_socket(socket, server, query))
+}
+
+pub async fn serve_hmr_client_handler() -> impl IntoResponse {
+    HttpResponse::ok(HMR_CLIENT)
+        .content_type("application/javascript")
+        .cache_control("no-cache")
+        .finish()
+}
+
+/// State of the HMR server, fetched by the debugging panel of the client.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HmrStatus<'a> {
+    server_id: &'a str,
+    version: &'a str,
+    /// Build served to pages loaded now
+    build_id: u64,
+    protocol: u32,
+    capabilities: &'a [&'a str],
+    /// Number of connected clients
+    clients: usize,
+}
+
+pub async fn serve_hmr_status_handler(State(server): State<Arc<Server>>) -> impl IntoResponse {
+    let status = HmrStatus {
+        server_id: server.server_id(),
+        version: PALLADIN_VERSION,
+        build_id: server.build_id(),
+        protocol: HMR_PROTOCOL_VERSION,
+        capabilities: HMR_CAPABILITIES,
+        clients: server.hmr_client_count(),
+    };
+    serde_json::to_string(&status)
+        .map_err(|e| PalladinError::Build(e.into()))
+        .map(|body| {
+            HttpResponse::ok(body)
+                .content_type("application/json")
+                .cache_control("no-cache")
+                .finish()
+        })
+}
+
+async fn handle_socket(mut socket: WebSocket, server: Arc<Server>, query: HmrQuery) {
+    let mut rx = server.hmr_tx.subscribe();
+    let (client, mut targeted) = ClientGuard::register(&server);
+    let capabilities = query.capabilities();
+
+    // Messages of another protocol version can't be trusted to be understood
+    // either way. Reloading fetches the page with the client of this server,
+    // closing stops clients that don't understand the reload either.
+    if query.protocol != Some(HMR_PROTOCOL_VERSION) {
+        debug!(
+            "HMR client {} speaks protocol {:?}, server speaks {HMR_PROTOCOL_VERSION}",
+            client.id, query.protocol
+        );
+        let _ = send_message(&mut socket, &HmrMessage::FullReload).await;
+        let _ = socket
+            .send(Message::Close(Some(CloseFrame {
+                code: close_code::PROTOCOL,
+                reason: Utf8Bytes::from_static("unsupported HMR protocol version"),
+            })))
+            .await;
+        return;
+    }
+
+    let connected = HmrMessage::Connected {
+        server_id: server.server_id().to_string(),
+        client_id: client.id.clone(),
+        version: PALLADIN_VERSION.to_string(),
+        protocol: HMR_PROTOCOL_VERSION,
+        capabilities: HMR_CAPABILITIES.iter().map(|c| c.to_string()).collect(),
+    };
+    if send_message(&mut socket, &connected).await.is_err() {
+        return;
+    }
+
+    // The client was served by a previous server process, so whatever it
+    // has loaded may be stale.
+    if query.server_id.is_some_and(|id| id != server.server_id()) {
+        debug!("HMR client reconnected after a server restart");
+        if send_message(&mut socket, &HmrMessage::FullReload)
+            .await
+            .is_err()
+        {
+            return;
+        }
+    }
+
+    // The page was cached while another version of palladin served it, so
+    // its client may not understand this server. Reloading fetches the page
+    // again, with the client of this version. Clients not sending a version
+    // are left alone, reloading them would never stop.
+    if let Some(version) = query.version.as_deref()
+        && version != PALLADIN_VERSION
+    {
+        debug!(
+            "HMR client {} was served by palladin {version}, reloading it",
+            client.id
+        );
+        if send_message(&mut socket, &HmrMessage::FullReload)
+            .await
+            .is_err()
+        {
+            return;
+        }
+    }
+
+    // A rebuild finished between serving the page and opening the socket, so
+    // the reload sent for it was missed.
+    if query.build.is_some_and(|build| build != server.build_id()) {
+        debug!("HMR client connected with a stale build");
+        if send_message(&mut socket, &HmrMessage::FullReload)
+            .await
+            .is_err()
+        {
+            return;
+        }
+    }
+
+    let mut heartbeat = interval(PING_INTERVAL);
+    let mut last_pong = Instant::now();
+
+    loop {
+        tokio::select! {
+            message = rx.recv() => {
+                let message = match message {
+                    Ok(message) => message,
+                    // Missed messages may have been patches or reloads, so
+                    // the page can't be trusted to be current anymore
+                    Err(RecvError::Lagged(missed)) => {
+                        debug!("HMR client {} missed {missed} messages, reloading it", client.id);
+                        HmrMessage::FullReload
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+
+                if let Some(message) = message.negotiate(&capabilities)
+                    && send_message(&mut socket, &message).await.is_err()
+                {
+                    break;
+                }
+            }
+            Some(message) = targeted.recv() => {
+                if let Some(message) = message.negotiate(&capabilities)
+                    && send_message(&mut socket, &message).await.is_err()
+                {
+                    break;
+                }
+            }
+            _ = heartbeat.tick() => {
+                if last_pong.elapsed() > PONG_TIMEOUT {
+                    debug!("closing stale HMR connection");
+                    let _ = socket.send(Message::Close(None)).await;
+                    break;
+                }
+
+                if socket.send(Message::Ping(Bytes::new())).await.is_err() {
+                    break;
+                }
+            }
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Pong(_))) => last_pong = Instant::now(),
+                Some(Ok(Message::Text(text))) => {
+                    match serde_json::from_str::<ClientMessage>(&text) {
+                        Ok(message) => server.handle_client_message(&client.id, message),
+                        Err(err) => warn!(
+                            "{}",
+                            messages::Message::InvalidClientFrame {
+                                client: &client.id,
+                                error: &err
+                            }
+                        ),
+                    }
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+/// Keeps the client registry in sync with the lifetime of a socket.
+struct ClientGuard<'a> {
+    server: &'a Server,
+    id: String,
+}
+
+impl<'a> ClientGuard<'a> {
+    fn register(server: &'a Server) -> (Self, UnboundedReceiver<HmrMessage>) {
+        let (id, rx) = server.register_hmr_client();
+        debug!(
+            "HMR client {id} connected ({} connected)",
+            server.hmr_client_count()
+        );
+        (Self { server, id }, rx)
+    }
+}
+
+impl Drop for ClientGuard<'_> {
+    fn drop(&mut self) {
+        self.server.unregister_hmr_client(&self.id);
+        debug!(
+            "HMR client {} disconnected ({} connected)",
+            self.id,
+            self.server.hmr_client_count()
+        );
+    }
+}
+
+async fn send_message(socket: &mut WebSocket, message: &HmrMessage) -> Result<(), axum::Error> {
+    let payload = serde_json::to_string(message).map_err(axum::Error::new)?;
+    socket.send(Message::Text(payload.into())).await
+}
@@ -0,0 +1,103 @@
+//! Compiles a single module through the bundler pipeline without a project,
+//! e.g. for `palladin transform < input.tsx > output.js`.
+
+use crate::rolldown::create_transform_bundler;
+use crate::server::{Minify, PalladinPlugin};
+use palladin_shared::{PalladinError, PalladinResult};
+use rolldown_common::{ModuleType, Output};
+use std::sync::Arc;
+
+/// How the transformed source is parsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Loader {
+    Js,
+    Jsx,
+    Ts,
+    Tsx,
+    Json,
+}
+
+impl Loader {
+    pub(crate) fn module_type(&self) -> ModuleType {
+        match self {
+            Loader::Js => ModuleType::Js,
+            Loader::Jsx => ModuleType::Jsx,
+            Loader::Ts => ModuleType::Ts,
+            Loader::Tsx => ModuleType::Tsx,
+            Loader::Json => ModuleType::Json,
+        }
+    }
+}
+
+// Loaders are named after the file extension they handle
+palladin_shared::named_enum!(Loader, "loader" {
+    Js => "js",
+    Jsx => "jsx",
+    Ts => "ts",
+    Tsx => "tsx",
+    Json => "json",
+});
+
+/// Options for [`transform`].
+#[derive(Debug, Clone)]
+pub struct TransformOptions {
+    pub loader: Loader,
+    pub minify: Minify,
+    /// Syntax lowering targets (e.g., "es2017", "chrome80")
+    pub target: Vec<String>,
+    /// Also generate a source map of the output
+    pub sourcemap: bool,
+    /// Plugins whose `load` and `transform` hooks run on the module
+    pub plugins: Vec<Arc<dyn PalladinPlugin>>,
+}
+
+impl TransformOptions {
+    /// Creates options parsing the source with `loader` and leaving the rest
+    /// as it is.
+    pub fn new(loader: Loader) -> Self {
+        Self {
+            loader,
+            minify: Minify::Off,
+            target: Vec::new(),
+            sourcemap: false,
+            plugins: Vec::new(),
+        }
+    }
+}
+
+/// The compiled module.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct TransformOutput {
+    pub code: String,
+    /// Source map of `code` as JSON, when requested. The code doesn't
+    /// reference it, so it can be written wherever it's needed.
+    pub map: Option<String>,
+}
+
+/// Compiles `source` like the bundler compiles a module of a project,
+/// leaving its imports as they are.
+///
+/// # Errors
+///
+/// Returns an error if the source cannot be parsed or a plugin fails.
+pub async fn transform(
+    source: String,
+    options: &TransformOptions,
+) -> PalladinResult<TransformOutput> {
+    let mut bundler = create_transform_bundler(source, options).build()?;
+    let output = bundler.generate().await;
+    bundler.close().await?;
+
+    output?
+        .assets
+        .into_iter()
+        .find_map(|asset| match asset {
+            Output::Chunk(chunk) if chunk.is_entry => Some(TransformOutput {
+                code: chunk.code.to_string(),
+                map: chunk.map.as_ref().map(|map| map.to_json_string()),
+            }),
+            _ => None,
+        })
+        .ok_or_else(|| PalladinError::Build(anyhow::anyhow!("the module produced no output")))
+}
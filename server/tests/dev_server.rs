@@ -0,0 +1,147 @@
+//! Drives the dev server in-process through the service returned by
+//! `Server::into_service`, asserting on the messages sent to HMR clients.
+
+use axum::Router;
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use palladin_server::prelude::*;
+use palladin_server::server::{HmrProbe, Server};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tower::ServiceExt;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Creates an empty project named `name` in the temporary directory.
+fn project(name: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("palladin-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    std::fs::write(root.join("index.html"), "<!DOCTYPE html><html></html>").unwrap();
+    std::fs::write(root.join("src/main.ts"), "console.log('hello');\n").unwrap();
+    root
+}
+
+async fn service(root: PathBuf) -> (Router, HmrProbe) {
+    let entrypoint = Entrypoint::from(root.join("src/main.ts"));
+    let config = ServerConfig::new()
+        .with_root(root)
+        .with_entrypoints(vec![entrypoint])
+        .with_hot_typing(true);
+    let (router, probe) = Arc::new(Server::new(config).unwrap()).into_service();
+    wait_for_watcher(&router).await;
+    (router, probe)
+}
+
+/// Waits until the file watcher reports the directories it watches, so
+/// changes made from now on are picked up.
+async fn wait_for_watcher(router: &Router) {
+    let watching = async {
+        loop {
+            let request = Request::get("/__palladin/watcher")
+                .body(Body::empty())
+                .unwrap();
+            let response = router.clone().oneshot(request).await.unwrap();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let stats: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            if stats["roots"]
+                .as_array()
+                .is_some_and(|roots| !roots.is_empty())
+            {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    };
+    tokio::time::timeout(TIMEOUT, watching)
+        .await
+        .expect("the file watcher didn't start");
+}
+
+/// Writes `contents` into the editor buffer of the root-relative `path`.
+async fn write_buffer(router: &Router, path: &str, contents: &str) -> StatusCode {
+    let request = Request::put(format!("/__buffers/{path}"))
+        .body(Body::from(contents.to_string()))
+        .unwrap();
+    router.clone().oneshot(request).await.unwrap().status()
+}
+
+/// Waits for the first message `matches` accepts, skipping the others.
+async fn next_matching(
+    probe: &mut HmrProbe,
+    matches: impl Fn(&HmrMessage) -> bool,
+) -> Option<HmrMessage> {
+    while let Some(message) = probe.next(TIMEOUT).await {
+        if matches(&message) {
+            return Some(message);
+        }
+    }
+    None
+}
+
+#[tokio::test]
+async fn hmr_status_is_served() {
+    let (router, _probe) = service(project("status")).await;
+
+    let request = Request::get("/__palladin/hmr").body(Body::empty()).unwrap();
+    let response = router.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let status: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(status["clients"], 0);
+}
+
+#[tokio::test]
+async fn restarts_from_other_origins_are_rejected() {
+    let (router, _probe) = service(project("restart")).await;
+
+    let request = Request::post("/__palladin/restart")
+        .header("host", "localhost:8080")
+        .header("origin", "http://attacker.example")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn changed_images_are_refreshed_in_place() {
+    let (router, mut probe) = service(project("images")).await;
+
+    let status = write_buffer(&router, "logo.svg", "<svg></svg>").await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let message = next_matching(&mut probe, |message| {
+        matches!(message, HmrMessage::AssetUpdate { .. })
+    })
+    .await;
+    let Some(HmrMessage::AssetUpdate { paths }) = message else {
+        panic!("no asset update was sent");
+    };
+    assert_eq!(paths, vec!["/logo.svg".to_string()]);
+}
+
+#[tokio::test]
+async fn changed_markdown_is_sent_as_an_event() {
+    let (router, mut probe) = service(project("markdown")).await;
+
+    let status = write_buffer(&router, "docs/intro.md", "# Intro\n").await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let message = next_matching(&mut probe, |message| {
+        matches!(message, HmrMessage::Custom { .. })
+    })
+    .await;
+    let Some(HmrMessage::Custom { event, data }) = message else {
+        panic!("no custom event was sent");
+    };
+    assert_eq!(event, "palladin:markdown");
+    assert_eq!(data["path"], "/docs/intro.md");
+}
@@ -0,0 +1,48 @@
+//! Pins the interface of the prelude the way embedders use it. A change that
+//! breaks this file breaks embedders too, so it has to wait for a major
+//! release.
+
+use palladin_server::prelude::*;
+use std::path::PathBuf;
+
+#[test]
+fn server_config_is_built_with_builders() {
+    let config = ServerConfig::new()
+        .with_root(PathBuf::from("app"))
+        .with_entrypoints(vec![Entrypoint::from(PathBuf::from("src/main.ts"))])
+        .with_framework(Some(Framework::React))
+        .with_minify(Minify::Off)
+        .with_watch(WatchOptions::default());
+
+    assert_eq!(config.root, PathBuf::from("app"));
+    assert_eq!(config.minify, Minify::Off);
+}
+
+#[test]
+fn preview_options_are_built_with_builders() {
+    let runtime_config = RuntimeConfig::new("/config.json", PathBuf::from("config.json"));
+    let options = PreviewOptions::new(PathBuf::from("dist"), "127.0.0.1:4173")
+        .with_runtime_config(Some(runtime_config))
+        .with_security_headers(true)
+        .with_url_policy(None);
+
+    assert_eq!(options.address, "127.0.0.1:4173");
+    assert!(options.security_headers);
+    assert_eq!(
+        options.runtime_config.map(|config| config.url_path),
+        Some("/config.json".to_string())
+    );
+
+    // Only checks that the entry points keep taking the options
+    let _ = |options: PreviewOptions| preview(options);
+    let _ = |config: ServerConfig| build(config, BuildOptions::default());
+}
+
+#[test]
+fn non_exhaustive_enums_are_matched_with_a_wildcard() {
+    let name = match Severity::Warning {
+        Severity::Warning => "warning",
+        _ => "other",
+    };
+    assert_eq!(name, "warning");
+}
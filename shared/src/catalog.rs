@@ -0,0 +1,81 @@
+use parking_lot::RwLock;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Locale messages are printed in, set once at startup.
+static LOCALE: OnceLock<Locale> = OnceLock::new();
+
+/// Directory paths in `ids` messages are printed relative to.
+static MESSAGE_ROOT: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Language of the messages palladin prints.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Locale {
+    #[default]
+    En,
+    /// Stable message ids followed by their arguments, e.g.
+    /// `dev.running address=127.0.0.1:8080`, for snapshot tests that
+    /// shouldn't break when wording changes
+    Ids,
+}
+
+crate::named_enum!(Locale, "locale" {
+    En => "en",
+    Ids => "ids",
+});
+
+/// Sets the locale messages are printed in. Only the first call has an
+/// effect.
+pub fn set_locale(locale: Locale) {
+    let _ = LOCALE.set(locale);
+}
+
+/// Sets the project root, which paths below it are printed relative to in
+/// the `ids` locale, so snapshots don't depend on where the project is.
+pub fn set_message_root(root: &Path) {
+    let root = fs_err::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+    *MESSAGE_ROOT.write() = Some(root);
+}
+
+/// A message with an id that doesn't change with its wording, displayed
+/// with [`write_message`] in the locale set at startup.
+pub trait CatalogMessage {
+    /// Returns the id of the message, e.g. `build.wrote`.
+    fn id(&self) -> &'static str;
+
+    /// Returns the arguments of the message as `(name, value)` pairs, with
+    /// paths formatted by [`message_path`].
+    fn args(&self) -> Vec<(&'static str, String)>;
+
+    /// Writes the message in English.
+    fn fmt_en(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result;
+}
+
+/// Writes `message` in the locale set at startup, for the `Display`
+/// implementation of catalogs.
+pub fn write_message(message: &impl CatalogMessage, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match LOCALE.get().copied().unwrap_or_default() {
+        Locale::En => message.fmt_en(f),
+        Locale::Ids => {
+            f.write_str(message.id())?;
+            for (name, value) in message.args() {
+                write!(f, " {name}={value}")?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Returns `path` as an argument of a message, relative to the project root
+/// when it's below it.
+pub fn message_path(path: &Path) -> String {
+    let root = MESSAGE_ROOT.read();
+    root.as_deref()
+        .and_then(|root| path.strip_prefix(root).ok())
+        .map(|relative| match relative.as_os_str().is_empty() {
+            true => ".".to_string(),
+            false => relative.display().to_string(),
+        })
+        .unwrap_or_else(|| path.display().to_string())
+}
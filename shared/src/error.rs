@@ -1,8 +1,10 @@
 pub use anyhow::*;
 use rolldown_error::BatchedBuildDiagnostic;
+use std::fmt;
 use thiserror::*;
 
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum PalladinError {
     #[error("I/O Error: {0}")]
     IoError(#[from] std::io::Error),
@@ -17,7 +19,7 @@ pub enum PalladinError {
     NotifyError(#[from] notify::Error),
 
     #[error("Rolldown Error: {0}")]
-    RolldownError(#[from] BatchedBuildDiagnostic),
+    RolldownError(BuildDiagnostics),
 
     #[error("Build error: {0}")]
     Build(#[from] anyhow::Error),
@@ -30,10 +32,48 @@ pub enum PalladinError {
 
     #[error("Service communication error: {0}")]
     ServiceCommunication(String),
+
+    #[error("Invalid address: {0}")]
+    InvalidAddress(String),
 }
 
 pub type PalladinResult<T = ()> = Result<T, PalladinError>;
 
+/// Errors reported by the bundler, rendered the way they are printed.
+///
+/// Wraps rolldown's diagnostics so their type isn't part of the public API.
+#[derive(Debug, Clone)]
+pub struct BuildDiagnostics {
+    message: String,
+}
+
+impl BuildDiagnostics {
+    /// Returns the rendered diagnostics.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for BuildDiagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl From<BatchedBuildDiagnostic> for PalladinError {
+    fn from(diagnostics: BatchedBuildDiagnostic) -> Self {
+        PalladinError::RolldownError(BuildDiagnostics {
+            message: diagnostics.to_string(),
+        })
+    }
+}
+
+/// Extension of the responses built by [`PalladinError::response`], telling
+/// them apart from error statuses a handler answered with on purpose, e.g.
+/// so only they're replaced by custom error pages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorResponse;
+
 impl PalladinError {
     pub fn response(&self) -> axum::http::Response<String> {
         use axum::http::{Response, StatusCode};
@@ -46,14 +86,15 @@ impl PalladinError {
             _ => (self.to_string(), StatusCode::INTERNAL_SERVER_ERROR),
         };
 
-        Response::builder()
-            .status(code)
-            .body(message)
-            .unwrap_or_else(|_| {
-                Response::builder()
-                    .status(StatusCode::INTERNAL_SERVER_ERROR)
-                    .body("Internal Server Error".to_string())
-                    .unwrap()
-            })
+        let mut response = Response::new(message);
+        *response.status_mut() = code;
+        response.extensions_mut().insert(ErrorResponse);
+        response
+    }
+}
+
+impl axum::response::IntoResponse for PalladinError {
+    fn into_response(self) -> axum::response::Response {
+        self.response().into_response()
     }
 }
@@ -1,5 +1,12 @@
+mod catalog;
 mod error;
+mod logger;
+mod named_enum;
 mod path;
+mod time;
 
+pub use catalog::*;
 pub use error::*;
+pub use logger::*;
 pub use path::*;
+pub use time::*;
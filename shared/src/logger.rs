@@ -0,0 +1,170 @@
+use crate::UtcDateTime;
+use std::io::{self, IsTerminal};
+use std::time::Instant;
+
+/// Width targets are padded to when none is configured, fitting `[server]`.
+pub const DEFAULT_LOG_TARGET_WIDTH: usize = 8;
+
+/// Time log lines start with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogTime {
+    #[default]
+    None,
+    /// Time since the logger was created, e.g. `+1.204s`
+    Elapsed,
+    /// Wall clock time in UTC, e.g. `14:03:27.512`
+    Clock,
+}
+
+crate::named_enum!(LogTime, "log time" {
+    None => "none",
+    Elapsed => "elapsed",
+    Clock => "clock",
+});
+
+/// Formats log records into colored lines: an optional time, the target
+/// padded so messages line up, and the message with its continuation lines
+/// indented below its first.
+#[derive(Debug, Clone)]
+pub struct LogFormat {
+    pub time: LogTime,
+    /// Width `[target]` is padded to, longer targets aren't cut
+    pub target_width: usize,
+    /// Whether lines contain ANSI colors, off for plain text
+    pub colors: bool,
+    start: Instant,
+}
+
+impl Default for LogFormat {
+    fn default() -> Self {
+        Self::new(LogTime::None, DEFAULT_LOG_TARGET_WIDTH)
+    }
+}
+
+impl LogFormat {
+    pub fn new(time: LogTime, target_width: usize) -> Self {
+        Self {
+            time,
+            target_width,
+            colors: true,
+            start: Instant::now(),
+        }
+    }
+
+    /// Returns the format with colors turned on or off.
+    #[must_use]
+    #[inline(always)]
+    pub fn with_colors(mut self, colors: bool) -> Self {
+        self.colors = colors;
+        self
+    }
+
+    /// Returns the line `message` of `target` is logged as at `level`.
+    pub fn format(&self, level: log::Level, target: &str, message: &str) -> String {
+        let color = match level {
+            _ if !self.colors => "",
+            log::Level::Info => "\x1b[36m",  // Cyan
+            log::Level::Warn => "\x1b[33m",  // Yellow
+            log::Level::Error => "\x1b[31m", // Red
+            log::Level::Debug => "\x1b[35m", // Magenta
+            log::Level::Trace => "\x1b[37m", // White
+        };
+        let (reset, bold, dim) = match self.colors {
+            true => ("\x1b[0m", "\x1b[1m", "\x1b[2m"),
+            false => ("", "", ""),
+        };
+
+        let time = match self.time {
+            LogTime::None => String::new(),
+            LogTime::Elapsed => {
+                let elapsed = format!("+{:.3}s", self.start.elapsed().as_secs_f64());
+                format!("{elapsed:>9} ")
+            }
+            LogTime::Clock => {
+                let now = UtcDateTime::now();
+                format!(
+                    "{:02}:{:02}:{:02}.{:03} ",
+                    now.hour, now.minute, now.second, now.millis
+                )
+            }
+        };
+        let target = format!("[{target}]");
+        let width = target.len().max(self.target_width);
+        let padding = " ".repeat(width - target.len());
+
+        // Continuation lines start below the first line of the message
+        let indent = " ".repeat(time.len() + width + 1);
+        let mut lines = message.lines();
+        let mut line = format!(
+            "{dim}{time}{reset}{color}{bold}{target}{reset}{padding} {}",
+            lines.next().unwrap_or_default()
+        );
+        for next in lines {
+            line.push('\n');
+            if !next.is_empty() {
+                line.push_str(&indent);
+                line.push_str(next);
+            }
+        }
+        line
+    }
+}
+
+/// Logger printing the records of palladin, and warnings and errors of
+/// everything else, to stderr, so stdout only carries the output of commands.
+#[derive(Debug)]
+pub struct ColoredLogger {
+    format: LogFormat,
+}
+
+impl Default for ColoredLogger {
+    fn default() -> Self {
+        Self::new(LogFormat::default())
+    }
+}
+
+impl ColoredLogger {
+    /// Returns a logger writing lines in `format`, without colors when stderr
+    /// isn't a terminal, e.g. when it's redirected to a file.
+    pub fn new(format: LogFormat) -> Self {
+        let colors = format.colors && io::stderr().is_terminal();
+        Self {
+            format: format.with_colors(colors),
+        }
+    }
+
+    /// Installs the logger for the rest of the process, logging records up
+    /// to `level`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a logger is installed already.
+    pub fn init(self, level: log::LevelFilter) -> Result<(), log::SetLoggerError> {
+        log::set_logger(Box::leak(Box::new(self)))?;
+        log::set_max_level(level);
+        Ok(())
+    }
+}
+
+impl log::Log for ColoredLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        let target = metadata.target();
+
+        target.starts_with("palladin") || target == "server" || metadata.level() <= log::Level::Warn
+    }
+
+    fn log(&self, record: &log::Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let message = record.args().to_string();
+        eprintln!(
+            "{}",
+            self.format
+                .format(record.level(), record.target(), &message)
+        );
+    }
+
+    fn flush(&self) {}
+}
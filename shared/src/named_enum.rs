@@ -0,0 +1,55 @@
+/// Names the variants of a fieldless enum the way they're written on the
+/// command line and in config files, implementing `name()`, `Display` and a
+/// case-insensitive `FromStr` that lists every name when none matches.
+///
+/// `$what` names the value in the error, e.g. `unknown minify mode `fast`,
+/// expected one of: false, true, whitespace-only`.
+///
+/// ```ignore
+/// named_enum!(Minify, "minify mode" {
+///     Off => "false",
+///     On => "true",
+///     WhitespaceOnly => "whitespace-only",
+/// });
+/// ```
+#[macro_export]
+macro_rules! named_enum {
+    ($enum:ident, $what:literal { $($variant:ident => $name:literal),+ $(,)? }) => {
+        impl $enum {
+            const ALL: &'static [$enum] = &[$($enum::$variant),+];
+
+            /// Returns the name the value is written as.
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $($enum::$variant => $name,)+
+                }
+            }
+        }
+
+        impl ::std::fmt::Display for $enum {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.write_str(self.name())
+            }
+        }
+
+        impl ::std::str::FromStr for $enum {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let s = s.to_ascii_lowercase();
+                $enum::ALL
+                    .iter()
+                    .copied()
+                    .find(|value| value.name() == s)
+                    .ok_or_else(|| {
+                        let names: Vec<_> = $enum::ALL.iter().map($enum::name).collect();
+                        format!(
+                            "unknown {} `{s}`, expected one of: {}",
+                            $what,
+                            names.join(", ")
+                        )
+                    })
+            }
+        }
+    };
+}